@@ -16,7 +16,7 @@ _hx() {
 		COMPREPLY=($(compgen -W "$languages" -- $2))
 		;;
 	*)
-		COMPREPLY=($(compgen -fd -W "-h --help --tutor -V --version -v -vv -vvv --health -g --grammar --vsplit --hsplit -c --config --log" -- $2))
+		COMPREPLY=($(compgen -fd -W "-h --help --tutor -V --version -v -vv -vvv --health -g --grammar --vsplit --hsplit -c --config --log -e --execute" -- $2))
 		;;
 	esac
 } && complete -o filenames -F _hx hx
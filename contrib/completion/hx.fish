@@ -13,3 +13,4 @@ complete -c hx -l vsplit -d "Splits all given files vertically into different wi
 complete -c hx -l hsplit -d "Splits all given files horizontally into different windows"
 complete -c hx -s c -l config -r -d "Specifies a file to use for completion"
 complete -c hx -l log -r -d "Specifies a file to write log data into"
+complete -c hx -s e -l execute -x -d "Runs a typable command after startup"
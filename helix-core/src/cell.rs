@@ -0,0 +1,81 @@
+//! Code cells of scripts, delimited by marker comments like `# %%` as used by Jupytext,
+//! VS Code and Spyder.
+
+use std::borrow::Cow;
+
+use ropey::RopeSlice;
+
+use crate::movement::Direction;
+
+/// Whether `line` is a cell marker: the comment token followed by `%%`.
+pub fn is_cell_marker(line: RopeSlice, comment_token: &str) -> bool {
+    let line: Cow<str> = line.into();
+    line.trim_start()
+        .strip_prefix(comment_token)
+        .map_or(false, |rest| rest.trim_start().starts_with("%%"))
+}
+
+/// Returns the lines of the cell containing `line`, from its marker (or the start of the text)
+/// up to the next marker.
+pub fn cell_lines(text: RopeSlice, line: usize, comment_token: &str) -> std::ops::Range<usize> {
+    let start = (0..=line)
+        .rev()
+        .find(|&line| is_cell_marker(text.line(line), comment_token))
+        .unwrap_or(0);
+    let end = (line + 1..text.len_lines())
+        .find(|&line| is_cell_marker(text.line(line), comment_token))
+        .unwrap_or_else(|| text.len_lines());
+    start..end
+}
+
+/// Returns the line of the `count`th cell marker after or before `line`, or the last one found
+/// if there are fewer.
+pub fn find_cell_marker(
+    text: RopeSlice,
+    line: usize,
+    comment_token: &str,
+    direction: Direction,
+    count: usize,
+) -> Option<usize> {
+    let is_marker = |line: &usize| is_cell_marker(text.line(*line), comment_token);
+    match direction {
+        Direction::Forward => (line + 1..text.len_lines())
+            .filter(is_marker)
+            .take(count)
+            .last(),
+        Direction::Backward => (0..line).rev().filter(is_marker).take(count).last(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Rope;
+
+    #[test]
+    fn cells() {
+        let text = Rope::from("import os\n# %% load\nx = 1\n\n  #%%\ny = 2\n");
+        let text = text.slice(..);
+        assert!(is_cell_marker(text.line(1), "#"));
+        assert!(is_cell_marker(text.line(4), "#"));
+        assert!(!is_cell_marker(text.line(1), "//"));
+
+        assert_eq!(cell_lines(text, 0, "#"), 0..1);
+        assert_eq!(cell_lines(text, 3, "#"), 1..4);
+        assert_eq!(cell_lines(text, 5, "#"), 4..7);
+
+        assert_eq!(
+            find_cell_marker(text, 0, "#", Direction::Forward, 1),
+            Some(1)
+        );
+        assert_eq!(
+            find_cell_marker(text, 0, "#", Direction::Forward, 5),
+            Some(4)
+        );
+        assert_eq!(find_cell_marker(text, 4, "#", Direction::Forward, 1), None);
+        assert_eq!(
+            find_cell_marker(text, 5, "#", Direction::Backward, 1),
+            Some(4)
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Structural paths of data files, like `package.dependencies.serde` or `items[0].name`,
+//! computed from the syntax trees of their JSON, YAML and TOML grammars.
+
+use ropey::RopeSlice;
+
+use crate::tree_sitter::Node;
+
+/// Languages whose syntax trees structural paths are computed from.
+pub const LANGUAGES: &[&str] = &["json", "yaml", "toml"];
+
+/// Key-value pairs of objects, mappings and tables.
+const PAIR_KINDS: &[&str] = &["pair", "block_mapping_pair", "flow_pair"];
+/// TOML tables with their key in a `[header]`.
+const TABLE_KINDS: &[&str] = &["table", "table_array_element"];
+/// Arrays and sequences, whose items are indexed.
+const ARRAY_KINDS: &[&str] = &["array", "flow_sequence", "block_sequence"];
+
+fn key_text(text: RopeSlice, key: Node) -> String {
+    let key: String = text.byte_slice(key.start_byte()..key.end_byte()).into();
+    key.trim().trim_matches(&['"', '\''][..]).to_string()
+}
+
+/// The segment of the path `node` adds: the key of a pair or table, or the index of an item.
+fn segment(text: RopeSlice, node: Node) -> Option<String> {
+    let kind = node.kind();
+    if PAIR_KINDS.contains(&kind) {
+        let key = node
+            .child_by_field_name("key")
+            .or_else(|| node.named_child(0))?;
+        return Some(key_text(text, key));
+    }
+    if TABLE_KINDS.contains(&kind) {
+        let table_key = |node: Node| {
+            let key = node
+                .named_child(0)
+                .filter(|key| key.kind().ends_with("key"))?;
+            Some(key_text(text, key))
+        };
+        let key = table_key(node)?;
+        if kind != "table_array_element" {
+            return Some(key);
+        }
+        // `[[key]]` tables are the items of the array `key`.
+        let mut index = 0;
+        let mut sibling = node.prev_named_sibling();
+        while let Some(previous) = sibling {
+            if previous.kind() == kind && table_key(previous).as_ref() == Some(&key) {
+                index += 1;
+            }
+            sibling = previous.prev_named_sibling();
+        }
+        return Some(format!("{}[{}]", key, index));
+    }
+
+    let parent = node.parent()?;
+    if !node.is_named() || kind == "comment" || !ARRAY_KINDS.contains(&parent.kind()) {
+        return None;
+    }
+    let mut cursor = parent.walk();
+    let index = parent
+        .named_children(&mut cursor)
+        .filter(|item| item.kind() != "comment")
+        .position(|item| item.id() == node.id())?;
+    Some(format!("[{}]", index))
+}
+
+/// Returns the path of `node`, joining the keys of its ancestors with dots.
+pub fn node_path(text: RopeSlice, node: Node) -> String {
+    let mut segments = Vec::new();
+    let mut node = Some(node);
+    while let Some(n) = node {
+        segments.extend(segment(text, n));
+        node = n.parent();
+    }
+
+    let mut path = String::new();
+    for segment in segments.iter().rev() {
+        if !path.is_empty() && !segment.starts_with('[') {
+            path.push('.');
+        }
+        path.push_str(segment);
+    }
+    path
+}
+
+/// Returns the path of the char index `pos`.
+pub fn path_at(text: RopeSlice, root: Node, pos: usize) -> String {
+    let byte = text.char_to_byte(pos.min(text.len_chars()));
+    root.descendant_for_byte_range(byte, byte)
+        .map(|node| node_path(text, node))
+        .unwrap_or_default()
+}
+
+/// Finds the pair, table or item at `path`, returning the char index it starts at.
+pub fn find_path(text: RopeSlice, root: Node, path: &str) -> Option<usize> {
+    fn find<'a>(text: RopeSlice, node: Node<'a>, path: &str) -> Option<Node<'a>> {
+        if segment(text, node).is_some() && node_path(text, node) == path {
+            return Some(node);
+        }
+        let mut cursor = node.walk();
+        let children: Vec<_> = node.named_children(&mut cursor).collect();
+        children
+            .into_iter()
+            .find_map(|child| find(text, child, path))
+    }
+
+    find(text, root, path.trim()).map(|node| text.byte_to_char(node.start_byte()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{tree_sitter::Parser, Rope};
+    use helix_loader::grammar::get_language;
+
+    fn paths(language: &str, source: &str, cases: &[(&str, &str)]) {
+        let text = Rope::from(source);
+        let mut parser = Parser::new();
+        parser
+            .set_language(get_language(language).unwrap())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let root = tree.root_node();
+
+        for (needle, path) in cases {
+            let pos = text.byte_to_char(source.find(needle).unwrap());
+            assert_eq!(path_at(text.slice(..), root, pos), *path, "at {:?}", needle);
+        }
+    }
+
+    #[test]
+    fn json_paths() {
+        paths(
+            "json",
+            r#"{"a": {"b": [1, {"c": 2}]}, "d": true}"#,
+            &[("1,", "a.b[0]"), ("2}", "a.b[1].c"), ("true", "d")],
+        );
+    }
+
+    #[test]
+    fn yaml_paths() {
+        paths(
+            "yaml",
+            "a:\n  b:\n    - x\n    - c: 2\nd: true\n",
+            &[("x", "a.b[0]"), ("2", "a.b[1].c"), ("true", "d")],
+        );
+    }
+
+    #[test]
+    fn toml_paths() {
+        let source = r#"[package]
+name = "x"
+
+[[bin]]
+name = "a"
+
+[[bin]]
+name = "b"
+path = ["src", "main.rs"]
+
+[dependencies]
+serde = { version = "1" }
+"#;
+        paths(
+            "toml",
+            source,
+            &[
+                ("\"x\"", "package.name"),
+                ("\"a\"", "bin[0].name"),
+                ("\"b\"", "bin[1].name"),
+                ("\"main.rs\"", "bin[1].path[1]"),
+                ("\"1\"", "dependencies.serde.version"),
+            ],
+        );
+    }
+
+    #[test]
+    fn find_paths() {
+        let source = "[[bin]]\nname = \"a\"\n\n[[bin]]\nname = \"b\"\n";
+        let text = Rope::from(source);
+        let mut parser = Parser::new();
+        parser.set_language(get_language("toml").unwrap()).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let root = tree.root_node();
+
+        let pos = |needle| Some(text.byte_to_char(source.find(needle).unwrap()));
+        assert_eq!(
+            find_path(text.slice(..), root, "bin[1]"),
+            pos("[[bin]]\nname = \"b\"")
+        );
+        assert_eq!(
+            find_path(text.slice(..), root, "bin[1].name"),
+            pos("name = \"b\"")
+        );
+        assert_eq!(find_path(text.slice(..), root, "bin[2]"), None);
+    }
+}
@@ -2,7 +2,7 @@
 use std::time::Instant;
 
 use imara_diff::intern::InternedInput;
-use imara_diff::Algorithm;
+use imara_diff::{Algorithm, UnifiedDiffBuilder};
 use ropey::RopeSlice;
 
 use crate::{ChangeSet, Rope, Tendril, Transaction};
@@ -178,6 +178,19 @@ pub fn compare_ropes(before: &Rope, after: &Rope) -> Transaction {
     res
 }
 
+/// Returns the hunks of a unified diff of the lines of `before` and `after`, without the file
+/// headers.
+pub fn unified_diff(before: &Rope, after: &Rope) -> String {
+    let before = before.to_string();
+    let after = after.to_string();
+    let input = InternedInput::new(before.as_str(), after.as_str());
+    imara_diff::diff(
+        Algorithm::Histogram,
+        &input,
+        UnifiedDiffBuilder::new(&input),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -218,4 +231,14 @@ fn new_file() {
     fn deleted_file() {
         test_identity("foo", "");
     }
+
+    #[test]
+    fn unified() {
+        let before = Rope::from("a\nb\nc\n");
+        assert_eq!(unified_diff(&before, &before), "");
+        assert_eq!(
+            unified_diff(&before, &Rope::from("a\nB\nc\nd\n")),
+            "@@ -1,3 +1,4 @@\n a\n-b\n+B\n c\n+d\n"
+        );
+    }
 }
@@ -0,0 +1,136 @@
+//! Parsing of the requests of `.http` files, as used by REST clients: requests are separated by
+//! `###` lines and consist of a request line, headers and an optional body after a blank line.
+//! Variables defined with `@name = value` lines are substituted for `{{name}}`.
+
+use std::borrow::Cow;
+
+use ropey::RopeSlice;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+fn is_separator(line: &str) -> bool {
+    line.trim_start().starts_with("###")
+}
+
+fn is_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with('#') || line.starts_with("//")
+}
+
+/// Parses a `@name = value` variable definition.
+fn variable(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = line.trim().strip_prefix('@')?.split_once('=')?;
+    Some((name.trim(), value.trim()))
+}
+
+fn substitute(text: &str, variables: &[(&str, &str)]) -> String {
+    variables
+        .iter()
+        .fold(text.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{{{}}}}}", name), value)
+        })
+}
+
+/// Returns the request the line `line` of `text` is part of.
+pub fn request_at(text: RopeSlice, line: usize) -> Option<Request> {
+    let lines: Vec<String> = text
+        .lines()
+        .map(|line| {
+            let line: Cow<str> = line.into();
+            line.trim_end_matches(&['\r', '\n'][..]).to_string()
+        })
+        .collect();
+    let line = line.min(lines.len().saturating_sub(1));
+    let variables: Vec<_> = lines.iter().filter_map(|line| variable(line)).collect();
+
+    let start = (0..=line)
+        .rev()
+        .find(|&line| is_separator(&lines[line]))
+        .map_or(0, |line| line + 1);
+    let end = (line + 1..lines.len())
+        .find(|&line| is_separator(&lines[line]))
+        .unwrap_or(lines.len());
+    let mut request = lines[start..end]
+        .iter()
+        .skip_while(|line| line.trim().is_empty() || is_comment(line) || variable(line).is_some());
+
+    let request_line = substitute(request.next()?, &variables);
+    let mut parts = request_line.split_whitespace();
+    let (method, url) = match (parts.next()?, parts.next()) {
+        (method, Some(url)) if method.chars().all(|ch| ch.is_ascii_uppercase()) => (method, url),
+        (url, None) => ("GET", url),
+        _ => return None,
+    };
+
+    let mut headers = Vec::new();
+    for line in request.by_ref() {
+        if line.trim().is_empty() {
+            break;
+        }
+        if is_comment(line) {
+            continue;
+        }
+        let (name, value) = line.split_once(':')?;
+        headers.push((
+            name.trim().to_string(),
+            substitute(value.trim(), &variables),
+        ));
+    }
+
+    let body: Vec<_> = request.map(String::as_str).collect();
+    Some(Request {
+        method: method.to_string(),
+        url: url.to_string(),
+        headers,
+        body: substitute(body.join("\n").trim_end(), &variables),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Rope;
+
+    #[test]
+    fn requests() {
+        let text = Rope::from(
+            "@host = localhost:8080\n\
+             \n\
+             ### list\n\
+             # a comment\n\
+             https://{{host}}/users\n\
+             \n\
+             ###\n\
+             POST http://{{host}}/users HTTP/1.1\n\
+             Content-Type: application/json\n\
+             // Authorization: none\n\
+             \n\
+             {\"name\": \"{{host}}\"}\n\
+             \n",
+        );
+        let text = text.slice(..);
+
+        let request = request_at(text, 3).unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.url, "https://localhost:8080/users");
+        assert!(request.headers.is_empty());
+        assert_eq!(request.body, "");
+
+        let request = request_at(text, 11).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.url, "http://localhost:8080/users");
+        assert_eq!(
+            request.headers,
+            [("Content-Type".to_string(), "application/json".to_string())]
+        );
+        assert_eq!(request.body, "{\"name\": \"localhost:8080\"}");
+
+        assert_eq!(request_at(text, 0), None);
+    }
+}
@@ -199,6 +199,57 @@ pub fn indent_level_for_line(line: RopeSlice, tab_width: usize, indent_width: us
     len / indent_width
 }
 
+/// Width in columns of the leading whitespace of `line`.
+fn indent_width_of(line: &str, tab_width: usize) -> usize {
+    let mut len = 0;
+    for ch in line.chars() {
+        match ch {
+            '\t' => len += tab_width_at(len, tab_width as u16),
+            ' ' => len += 1,
+            _ => break,
+        }
+    }
+    len
+}
+
+/// Re-indents the lines of `block` so its least indented lines start with `indent`, keeping the
+/// indentation of the other lines relative to them in the given indent style. Blank lines are
+/// emptied.
+pub fn reindent_block(
+    block: &str,
+    indent: &str,
+    indent_style: &IndentStyle,
+    tab_width: usize,
+) -> String {
+    let is_blank = |line: &str| line.trim().is_empty();
+    let base = block
+        .split_inclusive('\n')
+        .filter(|line| !is_blank(line))
+        .map(|line| indent_width_of(line, tab_width))
+        .min()
+        .unwrap_or(0);
+
+    let mut reindented = String::with_capacity(block.len());
+    for line in block.split_inclusive('\n') {
+        let content = line.trim_start_matches([' ', '\t']);
+        if is_blank(line) {
+            reindented.push_str(content);
+            continue;
+        }
+        let relative = indent_width_of(line, tab_width) - base;
+        reindented.push_str(indent);
+        match indent_style {
+            IndentStyle::Tabs => {
+                reindented.extend(std::iter::repeat('\t').take(relative / tab_width));
+                reindented.extend(std::iter::repeat(' ').take(relative % tab_width));
+            }
+            IndentStyle::Spaces(_) => reindented.extend(std::iter::repeat(' ').take(relative)),
+        }
+        reindented.push_str(content);
+    }
+    reindented
+}
+
 /// Computes for node and all ancestors whether they are the first node on their line.
 /// The first entry in the return value represents the root node, the last one the node itself
 fn get_first_in_line(mut node: Node, new_line_byte_pos: Option<usize>) -> Vec<bool> {
@@ -797,4 +848,21 @@ fn test_indent_level() {
             3
         );
     }
+
+    #[test]
+    fn test_reindent_block() {
+        let block = "    if x {\n        y();\n  \n    }\n";
+        assert_eq!(
+            reindent_block(block, "\t\t", &IndentStyle::Tabs, 4),
+            "\t\tif x {\n\t\t\ty();\n\n\t\t}\n"
+        );
+        assert_eq!(
+            reindent_block(block, "", &IndentStyle::Spaces(2), 4),
+            "if x {\n    y();\n\n}\n"
+        );
+        assert_eq!(
+            reindent_block("\tx\r\n", "  ", &IndentStyle::Spaces(2), 4),
+            "  x\r\n"
+        );
+    }
 }
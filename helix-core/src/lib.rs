@@ -1,18 +1,23 @@
 pub use encoding_rs as encoding;
 
 pub mod auto_pairs;
+pub mod cell;
 pub mod chars;
 pub mod comment;
 pub mod config;
+pub mod data_path;
 pub mod diagnostic;
 pub mod diff;
 pub mod doc_formatter;
 pub mod graphemes;
 pub mod history;
+pub mod http;
 pub mod increment;
 pub mod indent;
 pub mod line_ending;
+pub mod link;
 pub mod macros;
+pub mod markup;
 pub mod match_brackets;
 pub mod movement;
 pub mod object;
@@ -24,10 +29,12 @@
 pub mod shellwords;
 pub mod surround;
 pub mod syntax;
+pub mod tags;
 pub mod test;
 pub mod text_annotations;
 pub mod textobject;
 mod transaction;
+pub mod trigram;
 pub mod wrap;
 
 pub mod unicode {
@@ -0,0 +1,100 @@
+//! Detection of URLs and file paths in plain text, used to follow links in documents whose
+//! language server doesn't provide them.
+
+use std::{borrow::Cow, ops::Range};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use ropey::RopeSlice;
+
+/// A URL (`https://helix-editor.com`, `file:///etc/hosts`, `mailto:user@host`) or a file path
+/// starting with `/`, `./`, `../` or `~/`, preceded by the start of the line, whitespace or an
+/// opening delimiter.
+static LINK_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?:^|[\s(\[{<"'`=])((?:[a-zA-Z][a-zA-Z0-9+.-]*://|mailto:)[^\s<>"'`]+|(?:~|\.{1,2})?/[^\s<>"'`():,;]+)"#,
+    )
+    .unwrap()
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// Char range of the link in the text.
+    pub range: Range<usize>,
+    pub target: String,
+}
+
+/// Finds the links in the lines `lines` of `text`.
+pub fn find_links(text: RopeSlice, lines: Range<usize>) -> Vec<Link> {
+    let end = lines.end.min(text.len_lines());
+    let mut links = Vec::new();
+    for line_idx in lines.start.min(end)..end {
+        let line = text.line(line_idx);
+        let line_start = text.line_to_char(line_idx);
+        let content: Cow<str> = line.into();
+        for captures in LINK_REGEX.captures_iter(&content) {
+            let link = captures.get(1).unwrap();
+            // Punctuation following a link in prose is not part of it.
+            let target = link
+                .as_str()
+                .trim_end_matches(&['.', ',', ';', ':', '!', '?', ')', ']', '}', '>'][..]);
+            if !target.chars().any(char::is_alphanumeric) {
+                continue;
+            }
+            let start = line_start + line.byte_to_char(link.start());
+            links.push(Link {
+                range: start..start + target.chars().count(),
+                target: target.to_string(),
+            });
+        }
+    }
+    links
+}
+
+/// Returns the link containing the char index `pos`.
+pub fn link_at(text: RopeSlice, pos: usize) -> Option<Link> {
+    let line = text.char_to_line(pos.min(text.len_chars()));
+    find_links(text, line..line + 1)
+        .into_iter()
+        .find(|link| link.range.contains(&pos))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Rope;
+
+    fn targets(text: &str) -> Vec<String> {
+        let text = Rope::from(text);
+        find_links(text.slice(..), 0..text.len_lines())
+            .into_iter()
+            .map(|link| link.target)
+            .collect()
+    }
+
+    #[test]
+    fn urls_and_paths() {
+        assert_eq!(
+            targets("see https://helix-editor.com/news. and (./src/lib.rs)\n~/notes.md /"),
+            [
+                "https://helix-editor.com/news",
+                "./src/lib.rs",
+                "~/notes.md"
+            ]
+        );
+        assert_eq!(targets("a/b and // comment"), Vec::<String>::new());
+        assert_eq!(
+            targets("<mailto:user@host> file:///etc/hosts"),
+            ["mailto:user@host", "file:///etc/hosts"]
+        );
+    }
+
+    #[test]
+    fn link_ranges() {
+        let text = Rope::from("first\nrëad /tmp/x now\n");
+        let link = link_at(text.slice(..), 12).unwrap();
+        assert_eq!(link.target, "/tmp/x");
+        assert_eq!(text.slice(link.range).to_string(), "/tmp/x");
+        assert_eq!(link_at(text.slice(..), 18), None);
+    }
+}
@@ -0,0 +1,179 @@
+//! Editing helpers for the lists, task lists and block quotes of markup languages like
+//! Markdown.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use ropey::RopeSlice;
+
+use crate::{syntax::Syntax, tree_sitter::Node};
+
+/// Indentation and block quote markers, followed by an optional list marker and checkbox.
+static PREFIX_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^([ \t]*(?:>[ \t]?)*)(?:(?:([-+*])|(\d{1,9})([.)]))([ \t]+)(\[[ xX]\][ \t]+)?)?")
+        .unwrap()
+});
+
+/// Node kinds of the markup grammars whose lines continue with the same prefix.
+const CONTINUED_KINDS: &[&str] = &["list_item", "block_quote"];
+
+/// Returns the prefix starting the line after `line`, continuing its list item or block quote:
+/// ordered lists are numbered on and checkboxes are unchecked. Returns `None` if the line has
+/// no prefix or nothing follows it.
+pub fn line_continuation(line: &str) -> Option<String> {
+    let captures = PREFIX_REGEX.captures(line)?;
+    let prefix = captures.get(0)?;
+    let quotes = captures.get(1)?.as_str();
+    if !quotes.contains('>') && prefix.as_str() == quotes {
+        return None;
+    }
+    if line[prefix.end()..].trim().is_empty() {
+        return None;
+    }
+
+    let mut continuation = quotes.to_string();
+    if let Some(bullet) = captures.get(2) {
+        continuation.push_str(bullet.as_str());
+    } else if let Some(number) = captures.get(3) {
+        let number: u64 = number.as_str().parse().ok()?;
+        continuation.push_str(&(number + 1).to_string());
+        continuation.push_str(captures.get(4)?.as_str());
+    }
+    if let Some(spacing) = captures.get(5) {
+        continuation.push_str(spacing.as_str());
+    }
+    if captures.get(6).is_some() {
+        continuation.push_str("[ ] ");
+    }
+    Some(continuation)
+}
+
+/// Whether the line `line` is part of a list item or block quote of a markup grammar.
+pub fn in_continued_block(syntax: &Syntax, text: RopeSlice, line: usize) -> bool {
+    let line_start = text.line_to_char(line);
+    let first_char = text
+        .line(line)
+        .chars()
+        .position(|ch| !ch.is_whitespace())
+        .unwrap_or(0);
+    let byte = text.char_to_byte(line_start + first_char);
+    let mut node = syntax
+        .tree()
+        .root_node()
+        .descendant_for_byte_range(byte, byte);
+    while let Some(n) = node {
+        if CONTINUED_KINDS.contains(&n.kind()) {
+            return true;
+        }
+        node = n.parent();
+    }
+    false
+}
+
+/// Returns the change toggling the checkbox of the list item `line`, as a char range of the
+/// line and its replacement. List items without a checkbox get an unchecked one.
+pub fn toggle_checkbox(line: &str) -> Option<(usize, usize, &'static str)> {
+    let captures = PREFIX_REGEX.captures(line)?;
+    let spacing = captures.get(5)?;
+    let char_idx = |byte_idx: usize| line[..byte_idx].chars().count();
+    match captures.get(6) {
+        Some(checkbox) => {
+            let inner = char_idx(checkbox.start()) + 1;
+            let checked = checkbox.as_str()[1..2].eq_ignore_ascii_case("x");
+            Some((inner, inner + 1, if checked { " " } else { "x" }))
+        }
+        None => {
+            let end = char_idx(spacing.end());
+            Some((end, end, "[ ] "))
+        }
+    }
+}
+
+/// Returns the changes renumbering the items of the ordered list around `pos` from the number
+/// of its first item, as char ranges of the numbers and their replacements.
+pub fn renumber_list(syntax: &Syntax, text: RopeSlice, pos: usize) -> Vec<(usize, usize, String)> {
+    let byte = text.char_to_byte(pos);
+    let mut node = syntax
+        .tree()
+        .root_node()
+        .descendant_for_byte_range(byte, byte);
+    // The innermost ordered list.
+    let list = loop {
+        match node {
+            Some(n) if n.kind() == "list" && list_number(text, n).is_some() => break n,
+            Some(n) => node = n.parent(),
+            None => return Vec::new(),
+        }
+    };
+
+    let mut cursor = list.walk();
+    let items: Vec<Node> = list
+        .named_children(&mut cursor)
+        .filter(|item| item.kind() == "list_item")
+        .collect();
+    let mut number = match items.first().and_then(|&item| item_number(text, item)) {
+        Some((_, _, number)) => number,
+        None => return Vec::new(),
+    };
+    let mut changes = Vec::new();
+    for item in items {
+        if let Some((start, end, current)) = item_number(text, item) {
+            if current != number {
+                changes.push((start, end, number.to_string()));
+            }
+        }
+        number += 1;
+    }
+    changes
+}
+
+/// The number of the first item of `list`, if it is an ordered list.
+fn list_number(text: RopeSlice, list: Node) -> Option<u64> {
+    item_number(text, list.named_child(0)?).map(|(_, _, number)| number)
+}
+
+/// The char range and value of the number of the ordered list item `item`.
+fn item_number(text: RopeSlice, item: Node) -> Option<(usize, usize, u64)> {
+    let marker = item.named_child(0)?;
+    if !matches!(marker.kind(), "list_marker_dot" | "list_marker_parenthesis") {
+        return None;
+    }
+    let start = text.byte_to_char(marker.start_byte());
+    let marker_text: String = text
+        .byte_slice(marker.start_byte()..marker.end_byte())
+        .into();
+    let leading = marker_text.len() - marker_text.trim_start().len();
+    let digits: String = marker_text
+        .trim_start()
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    let number = digits.parse().ok()?;
+    Some((start + leading, start + leading + digits.len(), number))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn continuation() {
+        assert_eq!(line_continuation("- item\n").as_deref(), Some("- "));
+        assert_eq!(line_continuation("  * item").as_deref(), Some("  * "));
+        assert_eq!(line_continuation("9. item").as_deref(), Some("10. "));
+        assert_eq!(line_continuation("1)  item").as_deref(), Some("2)  "));
+        assert_eq!(line_continuation("- [x] done").as_deref(), Some("- [ ] "));
+        assert_eq!(line_continuation("> quote").as_deref(), Some("> "));
+        assert_eq!(line_continuation("> - item").as_deref(), Some("> - "));
+        assert_eq!(line_continuation("- "), None);
+        assert_eq!(line_continuation("  text"), None);
+        assert_eq!(line_continuation("-item"), None);
+    }
+
+    #[test]
+    fn checkbox() {
+        assert_eq!(toggle_checkbox("- [ ] todo"), Some((3, 4, "x")));
+        assert_eq!(toggle_checkbox("  1. [X] done"), Some((6, 7, " ")));
+        assert_eq!(toggle_checkbox("- item"), Some((2, 2, "[ ] ")));
+        assert_eq!(toggle_checkbox("text"), None);
+    }
+}
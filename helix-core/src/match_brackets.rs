@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use tree_sitter::Node;
 
 use crate::{Rope, Syntax};
@@ -13,6 +15,24 @@
 
 // limit matching pairs to only ( ) { } [ ] < > ' ' " "
 
+/// Keywords closing a block opened by another keyword, like `do ... end` or `if ... fi`.
+const CLOSING_KEYWORDS: &[&str] = &[
+    "end",
+    "fi",
+    "done",
+    "esac",
+    "endif",
+    "endfor",
+    "endwhile",
+    "endfunction",
+];
+
+/// Node kinds of the opening and closing tags of elements, like `<div> ... </div>`.
+const TAG_PAIRS: &[(&str, &str)] = &[
+    ("start_tag", "end_tag"),
+    ("jsx_opening_element", "jsx_closing_element"),
+];
+
 // Returns the position of the matching bracket under cursor.
 //
 // If the cursor is one the opening bracket, the position of
@@ -43,6 +63,63 @@ pub fn find_matching_bracket_fuzzy(syntax: &Syntax, doc: &Rope, pos: usize) -> O
     find_pair(syntax, doc, pos, true)
 }
 
+// Returns the char range of the delimiter matching the one under the cursor.
+//
+// Delimiters are the multi-character tokens enclosing a syntax node: keywords
+// such as `do` and `end`, or the opening and closing tags of an element. If
+// the cursor is on the opening delimiter, the range of the closing one is
+// returned and the other way around.
+//
+// If the cursor is not on a delimiter, `None` is returned.
+#[must_use]
+pub fn find_matching_delimiter(syntax: &Syntax, doc: &Rope, pos: usize) -> Option<Range<usize>> {
+    if pos >= doc.len_chars() {
+        return None;
+    }
+    let byte_pos = doc.char_to_byte(pos);
+    let mut node = syntax
+        .tree()
+        .root_node()
+        .descendant_for_byte_range(byte_pos, byte_pos)?;
+
+    loop {
+        let parent = node.parent()?;
+        if let Some((open, close)) = delimiters(&parent) {
+            let matching = if node == open {
+                Some(close)
+            } else if node == close {
+                Some(open)
+            } else {
+                None
+            };
+            if let Some(matching) = matching {
+                return Some(
+                    doc.byte_to_char(matching.start_byte())..doc.byte_to_char(matching.end_byte()),
+                );
+            }
+        }
+        node = parent;
+    }
+}
+
+/// The first and last child of `node` if they delimit it.
+fn delimiters<'a>(node: &Node<'a>) -> Option<(Node<'a>, Node<'a>)> {
+    let count = node.child_count();
+    if count < 2 {
+        return None;
+    }
+    let open = node.child(0)?;
+    let close = node.child(count - 1)?;
+
+    let is_keyword_pair = !open.is_named()
+        && !close.is_named()
+        && open.kind() != close.kind()
+        && CLOSING_KEYWORDS.contains(&close.kind());
+    let is_tag_pair = TAG_PAIRS.contains(&(open.kind(), close.kind()));
+
+    (is_keyword_pair || is_tag_pair).then_some((open, close))
+}
+
 fn find_pair(syntax: &Syntax, doc: &Rope, pos: usize, traverse_parents: bool) -> Option<usize> {
     let tree = syntax.tree();
     let pos = doc.char_to_byte(pos);
@@ -90,3 +167,56 @@ fn surrounding_bytes(doc: &Rope, node: &Node) -> Option<(usize, usize)> {
 
     Some((start_byte, end_byte))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::syntax::{Configuration, HighlightConfiguration, Loader};
+    use helix_loader::grammar::get_language;
+    use std::sync::Arc;
+
+    fn matching_delimiter(language: &str, source: &str, needle: &str) -> Option<String> {
+        let text = Rope::from(source);
+        let loader = Loader::new(Configuration { language: vec![] });
+        let config =
+            HighlightConfiguration::new(get_language(language).unwrap(), "", "", "").unwrap();
+        let syntax = Syntax::new(&text, Arc::new(config), Arc::new(loader));
+
+        let pos = text.byte_to_char(source.find(needle).unwrap());
+        find_matching_delimiter(&syntax, &text, pos).map(|range| text.slice(range).to_string())
+    }
+
+    #[test]
+    fn match_keyword_delimiters() {
+        let source = "def foo\n  bar\nend\n";
+        assert_eq!(
+            matching_delimiter("ruby", source, "def").as_deref(),
+            Some("end")
+        );
+        assert_eq!(
+            matching_delimiter("ruby", source, "nd").as_deref(),
+            Some("def")
+        );
+        assert_eq!(matching_delimiter("ruby", source, "foo"), None);
+
+        let source = "if true; then\n  echo\nfi\n";
+        assert_eq!(
+            matching_delimiter("bash", source, "if").as_deref(),
+            Some("fi")
+        );
+    }
+
+    #[test]
+    fn match_tag_delimiters() {
+        let source = "<div><p>text</p></div>";
+        assert_eq!(
+            matching_delimiter("html", source, "div").as_deref(),
+            Some("</div>")
+        );
+        assert_eq!(
+            matching_delimiter("html", source, "/p").as_deref(),
+            Some("<p>")
+        );
+        assert_eq!(matching_delimiter("html", source, "text"), None);
+    }
+}
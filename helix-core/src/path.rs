@@ -30,6 +30,17 @@ pub fn expand_tilde(path: &Path) -> PathBuf {
     path.to_path_buf()
 }
 
+/// Expands the tilde `~` and the environment variables written as `${NAME}` in `path`, see
+/// [`expand_tilde`] and [`helix_loader::expand_vars`]. Paths referencing variables which
+/// aren't set are left unchanged.
+pub fn expand(path: &Path) -> PathBuf {
+    let path = match path.to_str().map(helix_loader::expand_vars) {
+        Some(Ok(expanded)) => PathBuf::from(expanded.as_ref()),
+        _ => path.to_path_buf(),
+    };
+    expand_tilde(&path)
+}
+
 /// Normalize a path, removing things like `.` and `..`.
 ///
 /// CAUTION: This does not resolve symlinks (unlike
@@ -86,7 +97,7 @@ pub fn get_normalized_path(path: &Path) -> PathBuf {
 /// This function is used instead of `std::fs::canonicalize` because we don't want to verify
 /// here if the path exists, just normalize it's components.
 pub fn get_canonicalized_path(path: &Path) -> std::io::Result<PathBuf> {
-    let path = expand_tilde(path);
+    let path = expand(path);
     let path = if path.is_relative() {
         std::env::current_dir().map(|current_dir| current_dir.join(path))?
     } else {
@@ -162,3 +173,29 @@ pub fn get_truncated_path<P: AsRef<Path>>(path: P) -> PathBuf {
     ret.push(file);
     ret
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        std::env::set_var("HELIX_TEST_EXPAND", "value");
+        let expand = |path| expand(Path::new(path));
+        assert_eq!(expand("${HELIX_TEST_EXPAND}/a"), Path::new("value/a"));
+        assert_eq!(expand("a/${HELIX_TEST_EXPAND}b"), Path::new("a/valueb"));
+        assert_eq!(
+            expand("$HELIX_TEST_EXPAND/a"),
+            Path::new("$HELIX_TEST_EXPAND/a")
+        );
+        assert_eq!(
+            expand("${HELIX_TEST_UNSET}/${HELIX_TEST_EXPAND}"),
+            Path::new("${HELIX_TEST_UNSET}/${HELIX_TEST_EXPAND}")
+        );
+        assert_eq!(
+            expand("a/${HELIX_TEST_EXPAND"),
+            Path::new("a/${HELIX_TEST_EXPAND")
+        );
+        assert_eq!(expand("a$/b"), Path::new("a$/b"));
+    }
+}
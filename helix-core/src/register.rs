@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+
+/// The maximum number of yanks kept in the yank ring.
+const YANK_RING_CAPACITY: usize = 20;
 
 #[derive(Debug)]
 pub struct Register {
@@ -35,10 +38,12 @@ pub fn push(&mut self, value: String) {
     }
 }
 
-/// Currently just wraps a `HashMap` of `Register`s
+/// Wraps a `HashMap` of `Register`s and keeps a ring of the values recently yanked to them.
 #[derive(Debug, Default)]
 pub struct Registers {
     inner: HashMap<char, Register>,
+    /// Recently yanked values, the most recent first.
+    yank_ring: VecDeque<Vec<String>>,
 }
 
 impl Registers {
@@ -57,6 +62,17 @@ pub fn write(&mut self, name: char, values: Vec<String>) {
         }
     }
 
+    /// Writes yanked or deleted `values` to the register `name` and records them in the yank
+    /// ring.
+    pub fn yank(&mut self, name: char, values: Vec<String>) {
+        if name != '_' && !values.is_empty() {
+            self.yank_ring.retain(|yanked| *yanked != values);
+            self.yank_ring.push_front(values.clone());
+            self.yank_ring.truncate(YANK_RING_CAPACITY);
+        }
+        self.write(name, values);
+    }
+
     pub fn push(&mut self, name: char, value: String) {
         if name != '_' {
             if let Some(r) = self.inner.get_mut(&name) {
@@ -78,4 +94,33 @@ pub fn last(&self, name: char) -> Option<&String> {
     pub fn inner(&self) -> &HashMap<char, Register> {
         &self.inner
     }
+
+    /// The values recently yanked to the registers, the most recent first.
+    pub fn yank_ring(&self) -> impl ExactSizeIterator<Item = &[String]> {
+        self.yank_ring.iter().map(Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn yank_ring() {
+        let mut registers = Registers::default();
+        registers.yank('"', vec!["one".to_string()]);
+        registers.yank('a', vec!["two".to_string(), "three".to_string()]);
+        registers.write('q', vec!["<esc>xi".to_string()]);
+        registers.yank('_', vec!["discarded".to_string()]);
+        registers.yank('"', vec!["one".to_string()]);
+
+        let ring: Vec<_> = registers.yank_ring().collect();
+        assert_eq!(
+            ring,
+            [
+                vec!["one".to_string()],
+                vec!["two".to_string(), "three".to_string()]
+            ]
+        );
+    }
 }
@@ -455,13 +455,31 @@ pub fn map(self, changes: &ChangeSet) -> Self {
             return self;
         }
 
-        Self::new(
-            self.ranges
-                .into_iter()
-                .map(|range| range.map(changes))
-                .collect(),
-            self.primary_index,
-        )
+        // Map the ranges in a single pass over the changes: their starts and ends are sorted
+        // since the selection is normalized.
+        let mut ranges = self.ranges;
+        let positions = ranges.iter_mut().flat_map(|range| {
+            use std::cmp::Ordering;
+            // Same as `Range::map`.
+            range.old_visual_position = None;
+            match range.anchor.cmp(&range.head) {
+                Ordering::Equal => [
+                    (&mut range.anchor, Assoc::After),
+                    (&mut range.head, Assoc::After),
+                ],
+                Ordering::Less => [
+                    (&mut range.anchor, Assoc::After),
+                    (&mut range.head, Assoc::Before),
+                ],
+                Ordering::Greater => [
+                    (&mut range.head, Assoc::After),
+                    (&mut range.anchor, Assoc::Before),
+                ],
+            }
+        });
+        changes.update_positions(positions);
+
+        Self::new(ranges, self.primary_index)
     }
 
     pub fn ranges(&self) -> &[Range] {
@@ -677,10 +695,24 @@ pub fn keep_or_remove_matches(
     selection: &Selection,
     regex: &crate::regex::Regex,
     remove: bool,
+) -> Option<Selection> {
+    keep_or_remove(
+        selection,
+        |range| regex.is_match(&range.fragment(text)),
+        remove,
+    )
+}
+
+/// Keeps the ranges of the selection matching `predicate`, or removes them if `remove` is set.
+/// Returns `None` if no range would be left.
+pub fn keep_or_remove(
+    selection: &Selection,
+    predicate: impl Fn(&Range) -> bool,
+    remove: bool,
 ) -> Option<Selection> {
     let result: SmallVec<_> = selection
         .iter()
-        .filter(|range| regex.is_match(&range.fragment(text)) ^ remove)
+        .filter(|range| predicate(range) ^ remove)
         .copied()
         .collect();
 
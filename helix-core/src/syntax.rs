@@ -59,6 +59,14 @@ fn default_timeout() -> u64 {
     20
 }
 
+fn default_max_restarts() -> usize {
+    3
+}
+
+fn default_restart_delay() -> u64 {
+    1000
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Configuration {
     pub language: Vec<LanguageConfiguration>,
@@ -84,6 +92,8 @@ pub struct LanguageConfiguration {
     pub comment_token: Option<String>,
     pub text_width: Option<usize>,
     pub soft_wrap: Option<SoftWrap>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion: Option<CompletionConfig>,
 
     #[serde(default, skip_serializing, deserialize_with = "deserialize_lsp_config")]
     pub config: Option<serde_json::Value>,
@@ -94,6 +104,13 @@ pub struct LanguageConfiguration {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub formatter: Option<FormatterConfiguration>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_runner: Option<TestRunnerConfiguration>,
+
+    /// Shell commands run with `:run` and `:build`.
+    #[serde(default)]
+    pub commands: LanguageCommands,
+
     #[serde(default)]
     pub diagnostic_severity: Severity,
 
@@ -106,6 +123,9 @@ pub struct LanguageConfiguration {
     //
     #[serde(skip)]
     pub(crate) highlight_config: OnceCell<Option<Arc<HighlightConfiguration>>>,
+    /// Why the grammar couldn't be loaded, if it couldn't.
+    #[serde(skip)]
+    grammar_error: OnceCell<String>,
     // tags_config OnceCell<> https://github.com/tree-sitter/tree-sitter/pull/583
     #[serde(skip_serializing_if = "Option::is_none")]
     pub language_server: Option<LanguageServerConfiguration>,
@@ -116,6 +136,8 @@ pub struct LanguageConfiguration {
     pub(crate) indent_query: OnceCell<Option<Query>>,
     #[serde(skip)]
     pub(crate) textobject_query: OnceCell<Option<TextObjectQuery>>,
+    #[serde(skip)]
+    pub(crate) tags_query: OnceCell<Option<Query>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub debugger: Option<DebugAdapterConfig>,
 
@@ -214,9 +236,35 @@ pub struct LanguageServerConfiguration {
     pub args: Vec<String>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub environment: HashMap<String, String>,
+    /// Directories like `.venv/bin`, looked up from the directory of the document up to the
+    /// workspace. The server command is searched in the ones found before the `PATH`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bin_dirs: Vec<String>,
     #[serde(default = "default_timeout")]
     pub timeout: u64,
     pub language_id: Option<String>,
+    #[serde(default)]
+    pub start: LanguageServerStart,
+    /// How many times the server is restarted after exiting unexpectedly.
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: usize,
+    /// Delay in milliseconds before restarting the server after it exited unexpectedly,
+    /// doubled with each restart.
+    #[serde(default = "default_restart_delay")]
+    pub restart_delay: u64,
+}
+
+/// When to start the language server of a language.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LanguageServerStart {
+    /// When the first document of the language is opened.
+    #[default]
+    Open,
+    /// Once the editor is idle after the first document of the language is opened.
+    Idle,
+    /// On the first action requiring the language server.
+    Demand,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -228,6 +276,33 @@ pub struct FormatterConfiguration {
     pub args: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TestRunnerConfiguration {
+    /// Shell command running a single test. `%{test}` expands to the name of the test, along
+    /// with the placeholders of `:run-shell-command` like `%{filename}`.
+    pub command: String,
+    /// Shell command running all the tests of a file at once. `%{tests}` expands to the names
+    /// of the tests separated by spaces. Without it, `command` is run for each test.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_command: Option<String>,
+    /// Matches the names of the failed tests in the output of `file-command`, in the `test`
+    /// capture group. Without it, the tests all fail when the command fails.
+    #[serde(default, skip_serializing, deserialize_with = "deserialize_regex")]
+    pub failure_regex: Option<Regex>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct LanguageCommands {
+    /// Placeholders like `%{filename}` are expanded as in `:run-shell-command`.
+    pub run: Option<String>,
+    pub build: Option<String>,
+    pub test: Option<String>,
+    /// Command the code cells run with `:cell-run` are piped to.
+    pub repl: Option<String>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct AdvancedCompletion {
@@ -481,7 +556,8 @@ fn initialize_highlight(&self, scopes: &[String]) -> Option<Arc<HighlightConfigu
                         "Failed to load tree-sitter parser for language {:?}: {}",
                         self.language_id,
                         err
-                    )
+                    );
+                    let _ = self.grammar_error.set(err.to_string());
                 })
                 .ok()?;
             let config = HighlightConfiguration::new(
@@ -514,6 +590,11 @@ pub fn is_highlight_initialized(&self) -> bool {
         self.highlight_config.get().is_some()
     }
 
+    /// The error from loading the grammar, once [`Self::highlight_config`] tried and failed.
+    pub fn grammar_error(&self) -> Option<&str> {
+        self.grammar_error.get().map(String::as_str)
+    }
+
     pub fn indent_query(&self) -> Option<&Query> {
         self.indent_query
             .get_or_init(|| self.load_query("indents.scm"))
@@ -529,6 +610,14 @@ pub fn textobject_query(&self) -> Option<&TextObjectQuery> {
             .as_ref()
     }
 
+    /// Query from `tags.scm` locating definitions. Requires the highlight configuration to be
+    /// initialized, see [`LanguageConfiguration::highlight_config`].
+    pub fn tags_query(&self) -> Option<&Query> {
+        self.tags_query
+            .get_or_init(|| self.load_query("tags.scm"))
+            .as_ref()
+    }
+
     pub fn scope(&self) -> &str {
         &self.scope
     }
@@ -551,6 +640,24 @@ fn load_query(&self, kind: &str) -> Option<Query> {
             .ok()
     }
 }
+/// Per-language overrides of when completion is triggered.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct CompletionConfig {
+    /// Characters which trigger completion when typed, replacing the trigger characters
+    /// advertised by the language server.
+    pub trigger_characters: Option<Vec<char>>,
+    /// Minimum length of the word under the cursor to trigger completion when idle.
+    /// Overrides `editor.completion-trigger-len`.
+    pub trigger_len: Option<u8>,
+    /// Idle time in milliseconds after which completion is triggered.
+    /// Overrides `editor.idle-timeout`.
+    pub idle_timeout: Option<u64>,
+    /// Insert the candidate directly when a manually requested completion only has one.
+    /// Defaults to false.
+    pub auto_insert_single: bool,
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
 pub struct SoftWrap {
@@ -0,0 +1,151 @@
+//! A ctags-like index of definitions built from tree-sitter `tags.scm` queries, used to
+//! navigate code in languages or projects without a language server.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::QueryCursor;
+
+use crate::{
+    syntax::{Loader, RopeProvider, Syntax},
+    Rope,
+};
+
+/// Files larger than this are not indexed.
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tag {
+    pub name: String,
+    /// The kind of definition, e.g. `function` for a `@definition.function` capture.
+    pub kind: String,
+    pub path: PathBuf,
+    /// 0-indexed line of the name of the definition.
+    pub line: usize,
+}
+
+/// Returns the definitions in `text`, using the language configured for `path`.
+pub fn document_tags(text: &Rope, path: &Path, loader: &Arc<Loader>) -> Vec<Tag> {
+    let config = match loader.language_config_for_file_name(path) {
+        Some(config) => config,
+        None => return Vec::new(),
+    };
+    // The highlight configuration loads the grammar the tags query is compiled for.
+    let highlight_config = match config.highlight_config(&loader.scopes()) {
+        Some(highlight_config) => highlight_config,
+        None => return Vec::new(),
+    };
+    let (query, name_index) = match config
+        .tags_query()
+        .and_then(|query| Some((query, query.capture_index_for_name("name")?)))
+    {
+        Some(query) => query,
+        None => return Vec::new(),
+    };
+
+    let syntax = Syntax::new(text, highlight_config, loader.clone());
+    let slice = text.slice(..);
+    let mut cursor = QueryCursor::new();
+    cursor
+        .matches(query, syntax.tree().root_node(), RopeProvider(slice))
+        .filter_map(|mat| {
+            let kind = mat.captures.iter().find_map(|capture| {
+                query.capture_names()[capture.index as usize].strip_prefix("definition.")
+            })?;
+            let name = mat
+                .captures
+                .iter()
+                .find(|capture| capture.index == name_index)?
+                .node;
+            Some(Tag {
+                name: slice.byte_slice(name.byte_range()).to_string(),
+                kind: kind.to_string(),
+                path: path.to_path_buf(),
+                line: name.start_position().row,
+            })
+        })
+        .collect()
+}
+
+/// Definitions of a set of files, updated when the files change.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TagIndex {
+    files: HashMap<PathBuf, (SystemTime, Vec<Tag>)>,
+    /// Whether every file of the workspace was indexed during this session, after which
+    /// only the files that are saved need to be indexed again.
+    #[serde(skip)]
+    complete: bool,
+    /// Whether the index changed since it was loaded or last saved.
+    #[serde(skip)]
+    unsaved: bool,
+}
+
+impl TagIndex {
+    /// Indexes `path` unless it wasn't modified since it was last indexed. Returns whether
+    /// the index changed.
+    pub fn update_file(&mut self, path: &Path, loader: &Arc<Loader>) -> bool {
+        let metadata = match std::fs::metadata(path) {
+            Ok(metadata) if metadata.len() <= MAX_FILE_SIZE => metadata,
+            _ => {
+                let removed = self.files.remove(path).is_some();
+                self.unsaved |= removed;
+                return removed;
+            }
+        };
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        if matches!(self.files.get(path), Some((indexed, _)) if *indexed == modified) {
+            return false;
+        }
+
+        let tags = match std::fs::read_to_string(path) {
+            Ok(text) => document_tags(&Rope::from(text), path, loader),
+            // Binary or unreadable files have no tags.
+            Err(_) => Vec::new(),
+        };
+        self.files.insert(path.to_path_buf(), (modified, tags));
+        self.unsaved = true;
+        true
+    }
+
+    /// Removes the files for which `keep` returns false from the index. Returns whether the
+    /// index changed.
+    pub fn retain_files(&mut self, mut keep: impl FnMut(&Path) -> bool) -> bool {
+        let len = self.files.len();
+        self.files.retain(|path, _| keep(path));
+        let changed = self.files.len() != len;
+        self.unsaved |= changed;
+        changed
+    }
+
+    /// Returns whether the index changed since it was loaded or this was last called.
+    pub fn take_unsaved(&mut self) -> bool {
+        std::mem::take(&mut self.unsaved)
+    }
+
+    /// Returns whether every file of the workspace was indexed during this session.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    pub fn mark_complete(&mut self) {
+        self.complete = true;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn tags(&self) -> impl Iterator<Item = &Tag> {
+        self.files.values().flat_map(|(_, tags)| tags)
+    }
+
+    /// Returns the definitions named `name`.
+    pub fn find<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Tag> {
+        self.tags().filter(move |tag| tag.name == name)
+    }
+}
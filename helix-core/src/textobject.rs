@@ -288,6 +288,59 @@ pub fn textobject_treesitter(
     get_range().unwrap_or(range)
 }
 
+/// Returns the text objects named `object_name` within the given range, in the order of the
+/// text. Objects nested in another matching object are skipped. The `textobject` captures are
+/// used if the query defines them, the other kind of captures otherwise.
+/// `slice_tree` is the tree-sitter node corresponding to given text slice.
+pub fn textobjects_treesitter_in_range(
+    slice: RopeSlice,
+    range: Range,
+    textobject: TextObject,
+    object_name: &str,
+    slice_tree: Node,
+    lang_config: &LanguageConfiguration,
+) -> Vec<Range> {
+    let fallback = match textobject {
+        TextObject::Inside => TextObject::Around,
+        _ => TextObject::Inside,
+    };
+    let capture_names = [
+        format!("{}.{}", object_name, textobject),
+        format!("{}.{}", object_name, fallback),
+    ];
+    let capture_names = [capture_names[0].as_str(), capture_names[1].as_str()];
+
+    let start_byte = slice.char_to_byte(range.from());
+    let end_byte = slice.char_to_byte(range.to());
+    let mut cursor = QueryCursor::new();
+    let mut nodes: Vec<_> = match lang_config
+        .textobject_query()
+        .and_then(|query| query.capture_nodes_any(&capture_names, slice_tree, slice, &mut cursor))
+    {
+        Some(nodes) => nodes
+            .map(|node| node.byte_range())
+            .filter(|node| start_byte <= node.start && node.end <= end_byte && !node.is_empty())
+            .collect(),
+        None => return Vec::new(),
+    };
+    // Outer nodes first, so nested nodes can be skipped.
+    nodes.sort_unstable_by_key(|node| (node.start, std::cmp::Reverse(node.end)));
+
+    let mut ranges: Vec<Range> = Vec::new();
+    let mut last_end = 0;
+    for node in nodes {
+        if node.start < last_end {
+            continue;
+        }
+        last_end = node.end;
+        ranges.push(Range::new(
+            slice.byte_to_char(node.start),
+            slice.byte_to_char(node.end),
+        ));
+    }
+    ranges
+}
+
 #[cfg(test)]
 mod test {
     use super::TextObject::*;
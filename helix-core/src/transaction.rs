@@ -126,7 +126,7 @@ pub fn compose(self, other: Self) -> Self {
             return self;
         }
 
-        let len = self.changes.len();
+        let len = self.changes.len().max(other.changes.len());
 
         let mut changes_a = self.changes.into_iter();
         let mut changes_b = other.changes.into_iter();
@@ -134,7 +134,7 @@ pub fn compose(self, other: Self) -> Self {
         let mut head_a = changes_a.next();
         let mut head_b = changes_b.next();
 
-        let mut changes = Self::with_capacity(len); // TODO: max(a, b), shrink_to_fit() afterwards
+        let mut changes = Self::with_capacity(len);
 
         loop {
             use std::cmp::Ordering;
@@ -332,25 +332,75 @@ pub fn is_empty(&self) -> bool {
     /// range, or at that point. `After` will move it forward, placing it at the end of such
     /// insertions.
     pub fn map_pos(&self, pos: usize, assoc: Assoc) -> usize {
+        PositionMapper::new(self).map(pos, assoc)
+    }
+
+    /// Map several positions through the changes in place, like [`ChangeSet::map_pos`].
+    ///
+    /// Sorted positions are mapped in a single pass over the changes, which makes mapping the
+    /// positions of a large number of changes linear rather than quadratic. Unsorted positions
+    /// are still mapped correctly, but each position that goes back restarts the pass.
+    pub fn update_positions<'a>(&self, positions: impl Iterator<Item = (&'a mut usize, Assoc)>) {
+        let mut mapper = PositionMapper::new(self);
+        for (pos, assoc) in positions {
+            *pos = mapper.map(*pos, assoc);
+        }
+    }
+
+    pub fn changes_iter(&self) -> ChangeIterator {
+        ChangeIterator::new(self)
+    }
+}
+
+/// Maps positions through a [`ChangeSet`], resuming from the change the last position was
+/// found in.
+struct PositionMapper<'a> {
+    changes: &'a [Operation],
+    /// Index of the first change not passed yet, and its start in the old and new document.
+    index: usize,
+    old_pos: usize,
+    new_pos: usize,
+    /// The last position mapped: the changes passed for it are passed for any later position.
+    last: usize,
+}
+
+impl<'a> PositionMapper<'a> {
+    fn new(changes: &'a ChangeSet) -> Self {
+        Self {
+            changes: &changes.changes,
+            index: 0,
+            old_pos: 0,
+            new_pos: 0,
+            last: 0,
+        }
+    }
+
+    fn map(&mut self, pos: usize, assoc: Assoc) -> usize {
         use Operation::*;
-        let mut old_pos = 0;
-        let mut new_pos = 0;
 
-        let mut iter = self.changes.iter().peekable();
+        if pos < self.last {
+            self.index = 0;
+            self.old_pos = 0;
+            self.new_pos = 0;
+        }
+        self.last = pos;
 
-        while let Some(change) = iter.next() {
+        while let Some(change) = self.changes.get(self.index) {
+            let old_pos = self.old_pos;
+            let new_pos = self.new_pos;
             let len = match change {
                 Delete(i) | Retain(i) => *i,
                 Insert(_) => 0,
             };
             let mut old_end = old_pos + len;
+            let mut consumed = 1;
 
             match change {
                 Retain(_) => {
                     if old_end > pos {
                         return new_pos + (pos - old_pos);
                     }
-                    new_pos += len;
+                    self.new_pos += len;
                 }
                 Delete(_) => {
                     // in range
@@ -362,8 +412,8 @@ pub fn map_pos(&self, pos: usize, assoc: Assoc) -> usize {
                     let ins = s.chars().count();
 
                     // a subsequent delete means a replace, consume it
-                    if let Some(Delete(len)) = iter.peek() {
-                        iter.next();
+                    if let Some(Delete(len)) = self.changes.get(self.index + 1) {
+                        consumed = 2;
 
                         old_end = old_pos + len;
                         // in range of replaced text
@@ -389,23 +439,20 @@ pub fn map_pos(&self, pos: usize, assoc: Assoc) -> usize {
                         }
                     }
 
-                    new_pos += ins;
+                    self.new_pos += ins;
                 }
             }
-            old_pos = old_end;
+            self.index += consumed;
+            self.old_pos = old_end;
         }
 
-        if pos > old_pos {
+        if pos > self.old_pos {
             panic!(
                 "Position {} is out of range for changeset len {}!",
-                pos, old_pos
+                pos, self.old_pos
             )
         }
-        new_pos
-    }
-
-    pub fn changes_iter(&self) -> ChangeIterator {
-        ChangeIterator::new(self)
+        self.new_pos
     }
 }
 
@@ -754,6 +801,37 @@ fn map_pos() {
         assert_eq!(cs.map_pos(2, Assoc::After), 2);
     }
 
+    #[test]
+    fn update_positions() {
+        let doc = Rope::from("hello world!\ntest 123");
+        let transaction = Transaction::change(
+            &doc,
+            vec![
+                (0, 0, Some("> ".into())),
+                (6, 11, Some("void".into())),
+                (12, 17, None),
+            ]
+            .into_iter(),
+        );
+        let changes = transaction.changes();
+
+        let assocs = [Assoc::Before, Assoc::After];
+        // sorted, repeated and unsorted positions all map like `map_pos`
+        let positions: Vec<_> = [0, 0, 3, 6, 8, 11, 12, 15, 17, 21, 4, 0, 21, 12]
+            .into_iter()
+            .zip(assocs.into_iter().cycle())
+            .collect();
+        let mut mapped: Vec<_> = positions.iter().map(|(pos, _)| *pos).collect();
+        changes.update_positions(
+            mapped
+                .iter_mut()
+                .zip(positions.iter().map(|(_, assoc)| *assoc)),
+        );
+        for ((pos, assoc), mapped) in positions.into_iter().zip(mapped) {
+            assert_eq!(mapped, changes.map_pos(pos, assoc), "{pos} {assoc:?}");
+        }
+    }
+
     #[test]
     fn transaction_change() {
         let mut doc = Rope::from("hello world!\ntest 123");
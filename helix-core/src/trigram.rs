@@ -0,0 +1,249 @@
+//! A trigram index over the contents of a set of files, used to narrow down the files a
+//! regex search has to scan.
+//!
+//! Every file is indexed by the set of (ASCII lowercased) byte trigrams it contains. A query
+//! extracts the literal runs a regex requires to match and only the files containing all of
+//! their trigrams are candidates. Lowercasing makes the index usable for case insensitive
+//! searches at the cost of some false positives, the candidates still have to be searched.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use regex_syntax::hir::{Hir, HirKind, Literal, RepetitionKind, RepetitionRange};
+use serde::{Deserialize, Serialize};
+
+/// Files larger than this are not indexed and are always candidates.
+const MAX_FILE_SIZE: u64 = 4 * 1024 * 1024;
+
+fn trigram(bytes: [u8; 3]) -> u32 {
+    u32::from_le_bytes([
+        bytes[0].to_ascii_lowercase(),
+        bytes[1].to_ascii_lowercase(),
+        bytes[2].to_ascii_lowercase(),
+        0,
+    ])
+}
+
+/// Returns the sorted, deduplicated trigrams of `bytes`.
+fn trigrams(bytes: &[u8]) -> Vec<u32> {
+    let set: BTreeSet<_> = bytes
+        .windows(3)
+        .map(|window| trigram([window[0], window[1], window[2]]))
+        .collect();
+    set.into_iter().collect()
+}
+
+/// Returns the literal runs any match of `regex` must contain, or `None` if it doesn't parse.
+/// The extraction is conservative: everything that isn't a required literal (alternations,
+/// classes, optional or repeated parts) ends the current run.
+pub fn required_literals(regex: &str) -> Option<Vec<String>> {
+    let hir = regex_syntax::Parser::new().parse(regex).ok()?;
+    let mut literals = Vec::new();
+    let mut current = String::new();
+    collect_literals(&hir, &mut literals, &mut current);
+    end_run(&mut literals, &mut current);
+    Some(literals)
+}
+
+fn end_run(literals: &mut Vec<String>, current: &mut String) {
+    if !current.is_empty() {
+        literals.push(std::mem::take(current));
+    }
+}
+
+fn collect_literals(hir: &Hir, literals: &mut Vec<String>, current: &mut String) {
+    match hir.kind() {
+        HirKind::Literal(Literal::Unicode(ch)) => current.push(*ch),
+        HirKind::Literal(Literal::Byte(byte)) if byte.is_ascii() => current.push(*byte as char),
+        // Zero-width assertions don't separate the characters around them.
+        HirKind::Empty | HirKind::Anchor(_) | HirKind::WordBoundary(_) => (),
+        HirKind::Group(group) => collect_literals(&group.hir, literals, current),
+        HirKind::Concat(hirs) => {
+            for hir in hirs {
+                collect_literals(hir, literals, current);
+            }
+        }
+        HirKind::Repetition(repetition) => {
+            let min = match &repetition.kind {
+                RepetitionKind::ZeroOrOne | RepetitionKind::ZeroOrMore => 0,
+                RepetitionKind::OneOrMore => 1,
+                RepetitionKind::Range(
+                    RepetitionRange::Exactly(min)
+                    | RepetitionRange::AtLeast(min)
+                    | RepetitionRange::Bounded(min, _),
+                ) => *min,
+            };
+            // The first repetition continues the current run, what follows it may be
+            // another repetition.
+            if min > 0 {
+                collect_literals(&repetition.hir, literals, current);
+            }
+            end_run(literals, current);
+        }
+        HirKind::Literal(_) | HirKind::Class(_) | HirKind::Alternation(_) => {
+            end_run(literals, current)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileEntry {
+    modified: SystemTime,
+    /// Sorted trigrams of the file, `None` for files too large to be indexed.
+    trigrams: Option<Vec<u32>>,
+}
+
+/// Trigram index of a set of files, updated when the files change.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TrigramIndex {
+    files: HashMap<PathBuf, FileEntry>,
+    /// Whether the index changed since it was loaded or last saved.
+    #[serde(skip)]
+    unsaved: bool,
+}
+
+impl TrigramIndex {
+    /// Indexes `path` unless it wasn't modified since it was last indexed. Returns whether
+    /// the index changed.
+    pub fn update_file(&mut self, path: &Path) -> bool {
+        let metadata = match std::fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(_) => return self.remove_file(path),
+        };
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        if matches!(self.files.get(path), Some(entry) if entry.modified == modified) {
+            return false;
+        }
+
+        let trigrams = if metadata.len() <= MAX_FILE_SIZE {
+            match std::fs::read(path) {
+                Ok(bytes) => Some(trigrams(&bytes)),
+                Err(_) => return self.remove_file(path),
+            }
+        } else {
+            None
+        };
+        self.files
+            .insert(path.to_path_buf(), FileEntry { modified, trigrams });
+        self.unsaved = true;
+        true
+    }
+
+    fn remove_file(&mut self, path: &Path) -> bool {
+        let removed = self.files.remove(path).is_some();
+        self.unsaved |= removed;
+        removed
+    }
+
+    /// Removes the files for which `keep` returns false from the index. Returns whether the
+    /// index changed.
+    pub fn retain_files(&mut self, mut keep: impl FnMut(&Path) -> bool) -> bool {
+        let len = self.files.len();
+        self.files.retain(|path, _| keep(path));
+        let changed = self.files.len() != len;
+        self.unsaved |= changed;
+        changed
+    }
+
+    /// Returns whether the index changed since it was loaded or this was last called.
+    pub fn take_unsaved(&mut self) -> bool {
+        std::mem::take(&mut self.unsaved)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Returns the indexed files that can't contain a match for `regex`, along with their
+    /// modification time when they were indexed. A file modified since then has to be
+    /// searched anyway.
+    pub fn non_matching(&self, regex: &str) -> HashMap<PathBuf, SystemTime> {
+        let required: BTreeSet<u32> = match required_literals(regex) {
+            Some(literals) => literals
+                .iter()
+                .flat_map(|literal| trigrams(literal.as_bytes()))
+                // Only ASCII is lowercased, case insensitive matches of other characters
+                // could have any trigram.
+                .filter(|trigram| trigram.to_le_bytes().iter().all(u8::is_ascii))
+                .collect(),
+            None => return HashMap::new(),
+        };
+        if required.is_empty() {
+            return HashMap::new();
+        }
+
+        self.files
+            .iter()
+            .filter(|(_, entry)| match &entry.trigrams {
+                Some(trigrams) => required
+                    .iter()
+                    .any(|trigram| trigrams.binary_search(trigram).is_err()),
+                None => false,
+            })
+            .map(|(path, entry)| (path.clone(), entry.modified))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_literals() {
+        let literals = |regex| required_literals(regex).unwrap();
+        assert_eq!(literals("hello"), ["hello"]);
+        assert_eq!(literals("fn \\w+\\(foo"), ["fn ", "(foo"]);
+        assert_eq!(literals("colou?r"), ["colo", "r"]);
+        assert_eq!(literals("ab+c"), ["ab", "c"]);
+        assert_eq!(literals("foo[a-z]bar"), ["foo", "bar"]);
+        assert_eq!(literals("foo[]x]bar"), ["foo", "bar"]);
+        assert_eq!(literals("pre(fix)?post"), ["pre", "post"]);
+        assert_eq!(literals("pre(fix)post"), ["prefixpost"]);
+        assert_eq!(literals("a{2,3}bc"), ["a", "bc"]);
+        assert_eq!(literals("x{0,3}bc"), ["bc"]);
+        assert_eq!(literals("^start.*end$"), ["start", "end"]);
+        assert_eq!(literals("foo(a|b)bar"), ["foo", "bar"]);
+        assert_eq!(literals("\\bword\\b"), ["word"]);
+        assert!(literals("foo|bar").is_empty());
+        assert!(literals("(?i)foo").is_empty());
+        assert!(literals(".*").is_empty());
+        assert_eq!(required_literals("foo("), None);
+    }
+
+    #[test]
+    fn test_non_matching() {
+        let dir = std::env::temp_dir().join(format!("helix-trigram-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let foo = dir.join("foo.txt");
+        let bar = dir.join("bar.txt");
+        std::fs::write(&foo, "fn main() { println!(\"Hello\"); }").unwrap();
+        std::fs::write(&bar, "struct Bar;").unwrap();
+
+        let mut index = TrigramIndex::default();
+        assert!(index.update_file(&foo));
+        assert!(index.update_file(&bar));
+        assert!(!index.update_file(&foo));
+        assert!(index.take_unsaved());
+        assert!(!index.take_unsaved());
+
+        let non_matching = |index: &TrigramIndex, regex| {
+            let mut paths: Vec<_> = index.non_matching(regex).into_keys().collect();
+            paths.sort();
+            paths
+        };
+        assert_eq!(non_matching(&index, "hello"), [bar.clone()]);
+        assert_eq!(non_matching(&index, "struct \\w+;"), [foo.clone()]);
+        assert_eq!(non_matching(&index, "missing"), [bar.clone(), foo.clone()]);
+        assert!(non_matching(&index, "a|b").is_empty());
+        assert!(non_matching(&index, "fn").is_empty());
+
+        assert!(index.retain_files(|path| path != foo));
+        assert!(non_matching(&index, "struct").is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
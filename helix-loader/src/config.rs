@@ -17,7 +17,11 @@ pub fn user_lang_config() -> Result<toml::Value, toml::de::Error> {
     .map(|path| path.join("languages.toml"))
     .filter_map(|file| {
         std::fs::read_to_string(file)
-            .map(|config| toml::from_str(&config))
+            .map(|config| -> Result<toml::Value, toml::de::Error> {
+                let mut config = toml::from_str(&config)?;
+                crate::expand_toml_vars(&mut config)?;
+                Ok(config)
+            })
             .ok()
     })
     .collect::<Result<Vec<_>, _>>()?
@@ -1,12 +1,13 @@
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::time::SystemTime;
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
     process::Command,
-    sync::mpsc::channel,
+    sync::{mpsc::channel, Mutex},
 };
 use tree_sitter::Language;
 
@@ -59,6 +60,11 @@ pub enum GrammarSource {
 const BUILD_TARGET: &str = env!("BUILD_TARGET");
 const REMOTE_NAME: &str = "origin";
 
+/// Libraries of the grammars rebuilt by [rebuild_grammar] during this session, by grammar id.
+/// A loaded library can't be replaced or loaded again under the same path, so each rebuild is
+/// written to a fresh file which [get_language] loads instead of the installed one.
+static REBUILT_GRAMMARS: Lazy<Mutex<HashMap<String, PathBuf>>> = Lazy::new(Default::default);
+
 #[cfg(target_arch = "wasm32")]
 pub fn get_language(name: &str) -> Result<Language> {
     unimplemented!()
@@ -67,9 +73,12 @@ pub fn get_language(name: &str) -> Result<Language> {
 #[cfg(not(target_arch = "wasm32"))]
 pub fn get_language(name: &str) -> Result<Language> {
     use libloading::{Library, Symbol};
-    let mut rel_library_path = PathBuf::new().join("grammars").join(name);
-    rel_library_path.set_extension(DYLIB_EXTENSION);
-    let library_path = crate::runtime_file(&rel_library_path);
+    let rebuilt_path = REBUILT_GRAMMARS.lock().unwrap().get(name).cloned();
+    let library_path = rebuilt_path.unwrap_or_else(|| {
+        let mut rel_library_path = PathBuf::new().join("grammars").join(name);
+        rel_library_path.set_extension(DYLIB_EXTENSION);
+        crate::runtime_file(&rel_library_path)
+    });
 
     let library = unsafe { Library::new(&library_path) }
         .with_context(|| format!("Error opening dynamic library {:?}", library_path))?;
@@ -80,6 +89,23 @@ pub fn get_language(name: &str) -> Result<Language> {
             .with_context(|| format!("Failed to load symbol {}", language_fn_name))?;
         language_fn()
     };
+
+    // A grammar compiled against an incompatible tree-sitter ABI can't be used (and would be
+    // rejected by `Parser::set_language` anyway), so report it here with a hint to rebuild.
+    let version = language.version();
+    if !(tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION)
+        .contains(&version)
+    {
+        bail!(
+            "Grammar {:?} was built for tree-sitter ABI version {}, but version {}-{} is required. \
+             Rebuild it with ':grammar-rebuild'",
+            name,
+            version,
+            tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION,
+            tree_sitter::LANGUAGE_VERSION,
+        );
+    }
+
     std::mem::forget(library);
     Ok(language)
 }
@@ -150,7 +176,7 @@ pub fn build_grammars(target: Option<String>) -> Result<()> {
     let grammars = get_grammar_configs()?;
     println!("Building {} grammars", grammars.len());
     let results = run_parallel(grammars, move |grammar| {
-        build_grammar(grammar, target.as_deref())
+        build_grammar(grammar, target.as_deref(), false, None)
     });
 
     let mut errors = Vec::new();
@@ -187,6 +213,56 @@ pub fn build_grammars(target: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// Rebuilds the grammar `grammar_id` even if it's up to date, for example to replace a library
+/// built against an incompatible version of tree-sitter. The new library is used by
+/// [get_language] from then on and installed in place of the old one for later sessions.
+pub fn rebuild_grammar(grammar_id: &str) -> Result<()> {
+    let grammar = get_grammar_configs()?
+        .into_iter()
+        .find(|grammar| grammar.grammar_id == grammar_id)
+        .ok_or_else(|| anyhow!("Grammar {:?} is not configured", grammar_id))?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let mut library_path = std::env::temp_dir().join(format!(
+        "helix-{}-{}-{}",
+        grammar_id,
+        std::process::id(),
+        timestamp
+    ));
+    library_path.set_extension(DYLIB_EXTENSION);
+    build_grammar(grammar, None, true, Some(&library_path))?;
+
+    // Replace the installed library by renaming a copy over it: the old file stays valid for
+    // the processes which have it loaded. This fails on Windows while the library is in use.
+    let mut installed_path = crate::runtime_dirs()
+        .first()
+        .expect("No runtime directories provided") // guaranteed by post-condition
+        .join("grammars")
+        .join(grammar_id);
+    installed_path.set_extension(DYLIB_EXTENSION);
+    let staged_path = installed_path.with_extension("tmp");
+    if let Err(err) = fs::copy(&library_path, &staged_path)
+        .and_then(|_| fs::rename(&staged_path, &installed_path))
+    {
+        let _ = fs::remove_file(&staged_path);
+        log::warn!(
+            "Failed to install the rebuilt grammar {} to {:?}: {}",
+            grammar_id,
+            installed_path,
+            err
+        );
+    }
+
+    REBUILT_GRAMMARS
+        .lock()
+        .unwrap()
+        .insert(grammar_id.to_string(), library_path);
+    Ok(())
+}
+
 // Returns the set of grammar configurations the user requests.
 // Grammars are configured in the default and user `languages.toml` and are
 // merged. The `grammar_selection` key of the config is then used to filter
@@ -349,7 +425,12 @@ enum BuildStatus {
     Built { grammar_id: String },
 }
 
-fn build_grammar(grammar: GrammarConfiguration, target: Option<&str>) -> Result<BuildStatus> {
+fn build_grammar(
+    grammar: GrammarConfiguration,
+    target: Option<&str>,
+    force: bool,
+    library_path: Option<&Path>,
+) -> Result<BuildStatus> {
     let grammar_dir = if let GrammarSource::Local { path } = &grammar.source {
         PathBuf::from(&path)
     } else {
@@ -384,13 +465,15 @@ fn build_grammar(grammar: GrammarConfiguration, target: Option<&str>) -> Result<
     }
     .join("src");
 
-    build_tree_sitter_library(&path, grammar, target)
+    build_tree_sitter_library(&path, grammar, target, force, library_path)
 }
 
 fn build_tree_sitter_library(
     src_path: &Path,
     grammar: GrammarConfiguration,
     target: Option<&str>,
+    force: bool,
+    library_path: Option<&Path>,
 ) -> Result<BuildStatus> {
     let header_path = src_path;
     let parser_path = src_path.join("parser.c");
@@ -406,15 +489,22 @@ fn build_tree_sitter_library(
             None
         }
     };
-    let parser_lib_path = crate::runtime_dirs()
-        .first()
-        .expect("No runtime directories provided") // guaranteed by post-condition
-        .join("grammars");
-    let mut library_path = parser_lib_path.join(&grammar.grammar_id);
-    library_path.set_extension(DYLIB_EXTENSION);
+    let library_path = match library_path {
+        Some(library_path) => library_path.to_path_buf(),
+        None => {
+            let parser_lib_path = crate::runtime_dirs()
+                .first()
+                .expect("No runtime directories provided") // guaranteed by post-condition
+                .join("grammars");
+            let mut library_path = parser_lib_path.join(&grammar.grammar_id);
+            library_path.set_extension(DYLIB_EXTENSION);
+            library_path
+        }
+    };
 
-    let recompile = needs_recompile(&library_path, &parser_path, &scanner_path)
-        .context("Failed to compare source and binary timestamps")?;
+    let recompile = force
+        || needs_recompile(&library_path, &parser_path, &scanner_path)
+            .context("Failed to compare source and binary timestamps")?;
 
     if !recompile {
         return Ok(BuildStatus::AlreadyBuilt);
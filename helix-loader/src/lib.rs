@@ -2,7 +2,10 @@
 pub mod grammar;
 
 use etcetera::base_strategy::{choose_base_strategy, BaseStrategy};
-use std::path::{Path, PathBuf};
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
 
 pub const VERSION_AND_GIT_HASH: &str = env!("VERSION_AND_GIT_HASH");
 
@@ -51,6 +54,7 @@ fn prioritize_runtime_dirs() -> Vec<PathBuf> {
     rt_dirs.push(conf_rt_dir);
 
     if let Ok(dir) = std::env::var("HELIX_RUNTIME") {
+        let dir = expand_vars(&dir).map_or(dir.clone(), Cow::into_owned);
         rt_dirs.push(dir.into());
     }
 
@@ -121,6 +125,13 @@ pub fn cache_dir() -> PathBuf {
     path
 }
 
+pub fn state_dir() -> PathBuf {
+    let strategy = choose_base_strategy().expect("Unable to find the config directory!");
+    let mut path = strategy.state_dir().unwrap_or_else(|| strategy.data_dir());
+    path.push("helix");
+    path
+}
+
 pub fn config_file() -> PathBuf {
     CONFIG_FILE
         .get()
@@ -140,6 +151,76 @@ pub fn log_file() -> PathBuf {
     cache_dir().join("helix.log")
 }
 
+/// Expands the environment variables written as `${NAME}` in `input`, as well as a leading
+/// tilde `~` followed by a slash into the home directory. A lone tilde is left unchanged, it
+/// is a valid auto pair or key, and so are other forms like `${NAME:-default}`.
+///
+/// Returns an error naming the variable if one of them isn't defined.
+pub fn expand_vars(input: &str) -> Result<Cow<str>, String> {
+    let mut expanded = String::new();
+    let mut rest = input;
+
+    if input.starts_with("~/") {
+        if let Ok(home) = etcetera::home_dir() {
+            expanded.push_str(&home.to_string_lossy());
+            rest = &input[1..];
+        }
+    }
+    if expanded.is_empty() && !rest.contains("${") {
+        return Ok(Cow::Borrowed(input));
+    }
+
+    while let Some(start) = rest.find("${") {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let name = &rest[start + 2..end];
+        let is_name = !name.is_empty()
+            && name
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+        if is_name {
+            let value = std::env::var(name)
+                .map_err(|_| format!("undefined environment variable `{}` in `{}`", name, input))?;
+            expanded.push_str(&rest[..start]);
+            expanded.push_str(&value);
+        } else {
+            expanded.push_str(&rest[..=end]);
+        }
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    Ok(Cow::Owned(expanded))
+}
+
+/// Expands the variables of all the strings of `value` with [`expand_vars`].
+pub fn expand_toml_vars(value: &mut toml::Value) -> Result<(), toml::de::Error> {
+    use serde::de::Error;
+    use toml::Value;
+
+    match value {
+        Value::String(string) => {
+            if let Cow::Owned(expanded) = expand_vars(string).map_err(toml::de::Error::custom)? {
+                *string = expanded;
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                expand_toml_vars(value)?;
+            }
+        }
+        Value::Table(table) => {
+            for (_, value) in table.iter_mut() {
+                expand_toml_vars(value)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
 /// Merge two TOML documents, merging values from `right` onto `left`
 ///
 /// When an array exists in both `left` and `right`, `right`'s array is
@@ -209,6 +290,30 @@ fn get_name(v: &Value) -> Option<&str> {
     }
 }
 
+#[cfg(test)]
+mod expand_vars_tests {
+    use super::expand_vars;
+
+    #[test]
+    fn expands_env_vars() {
+        std::env::set_var("HELIX_TEST_VAR", "value");
+        assert_eq!(expand_vars("a/${HELIX_TEST_VAR}/b").unwrap(), "a/value/b");
+        assert_eq!(expand_vars("$HELIX_TEST_VAR").unwrap(), "$HELIX_TEST_VAR");
+        assert_eq!(expand_vars("a~/b").unwrap(), "a~/b");
+        assert_eq!(expand_vars("~").unwrap(), "~");
+        assert_eq!(expand_vars("${unclosed").unwrap(), "${unclosed");
+        assert_eq!(
+            expand_vars("${HELIX_TEST_VAR:-x}/${HELIX_TEST_VAR}").unwrap(),
+            "${HELIX_TEST_VAR:-x}/value"
+        );
+        assert_eq!(expand_vars("${}").unwrap(), "${}");
+        assert_eq!(
+            expand_vars("${HELIX_TEST_UNDEFINED}").unwrap_err(),
+            "undefined environment variable `HELIX_TEST_UNDEFINED` in `${HELIX_TEST_UNDEFINED}`"
+        );
+    }
+}
+
 #[cfg(test)]
 mod merge_toml_tests {
     use std::str;
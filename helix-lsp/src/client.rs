@@ -20,7 +20,10 @@
     atomic::{AtomicU64, Ordering},
     Arc,
 };
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 use tokio::{
     io::{BufReader, BufWriter},
     process::{Child, Command},
@@ -41,6 +44,61 @@ fn workspace_for_uri(uri: lsp::Url) -> WorkspaceFolder {
     }
 }
 
+/// Whether `uri` matches one of the filters of a file operation registered by the server.
+/// `is_dir` tells whether `uri` is a directory, for filters limited to files or folders.
+fn file_operation_matches(
+    options: &lsp::FileOperationRegistrationOptions,
+    uri: &lsp::Url,
+    is_dir: bool,
+) -> bool {
+    let path = match uri.to_file_path() {
+        Ok(path) => path,
+        Err(_) => return false,
+    };
+    options.filters.iter().any(|filter| {
+        if filter
+            .scheme
+            .as_ref()
+            .map_or(false, |scheme| scheme != uri.scheme())
+        {
+            return false;
+        }
+        match filter.pattern.matches {
+            Some(lsp::FileOperationPatternKind::File) if is_dir => return false,
+            Some(lsp::FileOperationPatternKind::Folder) if !is_dir => return false,
+            _ => (),
+        }
+        let ignore_case = filter
+            .pattern
+            .options
+            .as_ref()
+            .and_then(|options| options.ignore_case)
+            .unwrap_or(false);
+        match globset::GlobBuilder::new(&filter.pattern.glob)
+            .literal_separator(true)
+            .case_insensitive(ignore_case)
+            .build()
+        {
+            Ok(glob) => glob.compile_matcher().is_match(&path),
+            Err(err) => {
+                log::warn!(
+                    "Invalid file operation glob {}: {}",
+                    filter.pattern.glob,
+                    err
+                );
+                false
+            }
+        }
+    })
+}
+
+/// A glob registered by the server through `workspace/didChangeWatchedFiles`.
+#[derive(Debug)]
+struct FileWatcher {
+    glob: globset::GlobMatcher,
+    kind: lsp::WatchKind,
+}
+
 #[derive(Debug)]
 pub struct Client {
     id: usize,
@@ -48,15 +106,66 @@ pub struct Client {
     server_tx: UnboundedSender<Payload>,
     request_counter: AtomicU64,
     pub(crate) capabilities: OnceCell<lsp::ServerCapabilities>,
-    config: Option<Value>,
+    config: Mutex<Option<Value>>,
     root_path: std::path::PathBuf,
     root_uri: Option<lsp::Url>,
     workspace_folders: Mutex<Vec<lsp::WorkspaceFolder>>,
+    /// file watchers registered by the server, by registration id
+    file_watchers: Mutex<HashMap<String, Vec<FileWatcher>>>,
     initalize_notify: Arc<Notify>,
     /// workspace folders added while the server is still initalizing
     req_timeout: u64,
 }
 
+/// Returns the directories `bin_dirs` found in the directory of the document and its parents up
+/// to the workspace, closest first. Documents outside of the workspace have none.
+fn local_bin_dirs(
+    bin_dirs: &[String],
+    doc_path: Option<&PathBuf>,
+    workspace: &Path,
+) -> Vec<PathBuf> {
+    let dir = match doc_path.and_then(|path| path.parent()) {
+        Some(dir) if !bin_dirs.is_empty() && dir.starts_with(workspace) => dir,
+        _ => return Vec::new(),
+    };
+    dir.ancestors()
+        .take_while(|ancestor| ancestor.starts_with(workspace))
+        .flat_map(|ancestor| bin_dirs.iter().map(move |bin_dir| ancestor.join(bin_dir)))
+        .filter(|bin_dir| bin_dir.is_dir())
+        .collect()
+}
+
+/// Sends `$/cancelRequest` for the request `id` when dropped before the response arrived,
+/// like when the job waiting for the response is cancelled.
+struct CancelOnDrop {
+    server_tx: UnboundedSender<Payload>,
+    id: Option<jsonrpc::Id>,
+}
+
+impl CancelOnDrop {
+    fn disarm(mut self) {
+        self.id = None;
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        let id = match self.id.take() {
+            Some(id) => id,
+            None => return,
+        };
+        let mut params = serde_json::Map::new();
+        params.insert("id".to_string(), serde_json::json!(id));
+        let notification = jsonrpc::Notification {
+            jsonrpc: Some(jsonrpc::Version::V2),
+            method: <lsp::notification::Cancel as lsp::notification::Notification>::METHOD
+                .to_string(),
+            params: jsonrpc::Params::Map(params),
+        };
+        let _ = self.server_tx.send(Payload::Notification(notification));
+    }
+}
+
 impl Client {
     pub fn try_add_doc(
         self: &Arc<Self>,
@@ -166,6 +275,77 @@ fn add_workspace_folder(
         tokio::spawn(self.did_change_workspace(vec![workspace_for_uri(root_uri)], Vec::new()));
     }
 
+    /// The workspace folders capabilities of the server, if it supports several folders.
+    fn workspace_folders_capabilities(&self) -> Option<&lsp::WorkspaceFoldersServerCapabilities> {
+        self.capabilities
+            .get()?
+            .workspace
+            .as_ref()?
+            .workspace_folders
+            .as_ref()
+            .filter(|cap| cap.supported.unwrap_or(false))
+    }
+
+    /// Adds `root` to the workspace folders of the server, if it supports several and doesn't
+    /// have it yet. Waits for the server to be initialized to find out.
+    pub fn add_workspace_root(self: &Arc<Self>, root: &Path) {
+        let Ok(root_uri) = lsp::Url::from_file_path(root) else {
+            return;
+        };
+
+        let client = Arc::clone(self);
+        let add = move || {
+            let has_folder = client
+                .workspace_folders
+                .lock()
+                .iter()
+                .any(|workspace| workspace.uri == root_uri);
+            if let Some(caps) = client
+                .workspace_folders_capabilities()
+                .filter(|_| !has_folder)
+            {
+                client.add_workspace_folder(Some(root_uri), &caps.change_notifications);
+            }
+        };
+        if self.capabilities.get().is_some() {
+            add();
+        } else {
+            let initalize_notify = self.initalize_notify.clone();
+            tokio::spawn(async move {
+                initalize_notify.notified().await;
+                add();
+            });
+        }
+    }
+
+    /// Removes `root` from the workspace folders of the server, unless it's the root the
+    /// server was started in.
+    pub fn remove_workspace_root(&self, root: &Path) {
+        let Ok(root_uri) = lsp::Url::from_file_path(root) else {
+            return;
+        };
+        if self.root_uri.as_ref() == Some(&root_uri) {
+            return;
+        }
+
+        let mut workspace_folders = self.workspace_folders.lock();
+        let Some(index) = workspace_folders
+            .iter()
+            .position(|workspace| workspace.uri == root_uri)
+        else {
+            return;
+        };
+        let removed = workspace_folders.remove(index);
+        drop(workspace_folders);
+
+        match self.workspace_folders_capabilities() {
+            Some(caps) if caps.change_notifications != Some(OneOf::Left(false)) => {
+                tokio::spawn(self.did_change_workspace(Vec::new(), vec![removed]));
+            }
+            _ => (),
+        }
+    }
+
     #[allow(clippy::type_complexity)]
     #[allow(clippy::too_many_arguments)]
     pub fn start(
@@ -173,16 +353,43 @@ pub fn start(
         args: &[String],
         config: Option<Value>,
         server_environment: HashMap<String, String>,
+        bin_dirs: &[String],
         root_markers: &[String],
         manual_roots: &[PathBuf],
         id: usize,
         req_timeout: u64,
         doc_path: Option<&std::path::PathBuf>,
     ) -> Result<(Self, UnboundedReceiver<(usize, Call)>, Arc<Notify>)> {
-        // Resolve path to the binary
-        let cmd = which::which(cmd).map_err(|err| anyhow::anyhow!(err))?;
+        let (workspace, workspace_is_cwd) = find_workspace();
+        let workspace = path::get_normalized_path(&workspace);
+
+        // Project local binaries, like the ones of a virtualenv or `node_modules/.bin`, take
+        // precedence over the ones on the `PATH`.
+        let bin_dirs = local_bin_dirs(bin_dirs, doc_path, &workspace);
+        let mut command = if bin_dirs.is_empty() {
+            // Resolve path to the binary
+            Command::new(which::which(cmd).map_err(|err| anyhow::anyhow!(err))?)
+        } else {
+            let path = std::env::var_os("PATH").unwrap_or_default();
+            let path =
+                std::env::join_paths(bin_dirs.iter().cloned().chain(std::env::split_paths(&path)))
+                    .map_err(|err| anyhow::anyhow!(err))?;
+            let cwd = std::env::current_dir().unwrap_or_default();
+            let cmd = which::which_in(cmd, Some(&path), cwd).map_err(|err| anyhow::anyhow!(err))?;
+            let mut command = Command::new(cmd);
+            command.env("PATH", path);
+            // Activate the virtualenv the binaries belong to, so the server finds its packages.
+            if let Some(venv) = bin_dirs
+                .iter()
+                .filter_map(|dir| dir.parent())
+                .find(|dir| dir.join("pyvenv.cfg").is_file())
+            {
+                command.env("VIRTUAL_ENV", venv);
+            }
+            command
+        };
 
-        let process = Command::new(cmd)
+        let process = command
             .envs(server_environment)
             .args(args)
             .stdin(Stdio::piped())
@@ -201,8 +408,6 @@ pub fn start(
 
         let (server_rx, server_tx, initialize_notify) =
             Transport::start(reader, writer, stderr, id);
-        let (workspace, workspace_is_cwd) = find_workspace();
-        let workspace = path::get_normalized_path(&workspace);
         let root = find_lsp_workspace(
             doc_path
                 .and_then(|x| x.parent().and_then(|x| x.to_str()))
@@ -229,11 +434,12 @@ pub fn start(
             server_tx,
             request_counter: AtomicU64::new(0),
             capabilities: OnceCell::new(),
-            config,
+            config: Mutex::new(config),
             req_timeout,
             root_path,
             root_uri,
             workspace_folders: Mutex::new(workspace_folders),
+            file_watchers: Mutex::new(HashMap::new()),
             initalize_notify: initialize_notify.clone(),
         };
 
@@ -286,8 +492,19 @@ pub fn offset_encoding(&self) -> OffsetEncoding {
             .unwrap_or_default()
     }
 
-    pub fn config(&self) -> Option<&Value> {
-        self.config.as_ref()
+    pub fn config(&self) -> Option<Value> {
+        self.config.lock().clone()
+    }
+
+    /// Replaces the settings of this server, returning whether they changed. The caller is
+    /// responsible for pushing changed settings with [`Client::did_change_configuration`].
+    pub fn update_config(&self, config: Option<Value>) -> bool {
+        let mut current = self.config.lock();
+        if *current == config {
+            return false;
+        }
+        *current = config;
+        true
     }
 
     pub async fn workspace_folders(
@@ -341,10 +558,18 @@ fn call<R: lsp::request::Request>(
                     value: request,
                 })
                 .map_err(|e| Error::Other(e.into()))?;
+            let cancel = CancelOnDrop {
+                server_tx,
+                id: Some(id.clone()),
+            };
 
             // TODO: delay other calls until initialize success
-            timeout(Duration::from_secs(timeout_secs), rx.recv())
-                .await
+            let response = timeout(Duration::from_secs(timeout_secs), rx.recv()).await;
+            // Requests which timed out are cancelled too.
+            if response.is_ok() {
+                cancel.disarm();
+            }
+            response
                 .map_err(|_| Error::Timeout(id))? // return Timeout
                 .ok_or(Error::StreamClosed)?
         }
@@ -414,7 +639,8 @@ pub fn reply(
     // -------------------------------------------------------------------------------------------
 
     pub(crate) async fn initialize(&self, enable_snippets: bool) -> Result<lsp::InitializeResult> {
-        if let Some(config) = &self.config {
+        let config = self.config();
+        if let Some(config) = &config {
             log::info!("Using custom LSP config: {}", config);
         }
 
@@ -426,7 +652,7 @@ pub(crate) async fn initialize(&self, enable_snippets: bool) -> Result<lsp::Init
             // clients will prefer _uri if possible
             root_path: self.root_path.to_str().map(|path| path.to_owned()),
             root_uri: self.root_uri.clone(),
-            initialization_options: self.config.clone(),
+            initialization_options: config,
             capabilities: lsp::ClientCapabilities {
                 workspace: Some(lsp::WorkspaceClientCapabilities {
                     configuration: Some(true),
@@ -434,6 +660,10 @@ pub(crate) async fn initialize(&self, enable_snippets: bool) -> Result<lsp::Init
                         dynamic_registration: Some(false),
                     }),
                     workspace_folders: Some(true),
+                    did_change_watched_files: Some(lsp::DidChangeWatchedFilesClientCapabilities {
+                        dynamic_registration: Some(true),
+                        ..Default::default()
+                    }),
                     apply_edit: Some(true),
                     symbol: Some(lsp::WorkspaceSymbolClientCapabilities {
                         dynamic_registration: Some(false),
@@ -445,6 +675,15 @@ pub(crate) async fn initialize(&self, enable_snippets: bool) -> Result<lsp::Init
                     inlay_hint: Some(lsp::InlayHintWorkspaceClientCapabilities {
                         refresh_support: Some(false),
                     }),
+                    file_operations: Some(lsp::WorkspaceFileOperationsClientCapabilities {
+                        dynamic_registration: Some(false),
+                        did_create: Some(true),
+                        will_create: Some(false),
+                        did_rename: Some(true),
+                        will_rename: Some(true),
+                        did_delete: Some(true),
+                        will_delete: Some(false),
+                    }),
                     workspace_edit: Some(lsp::WorkspaceEditClientCapabilities {
                         document_changes: Some(true),
                         resource_operations: Some(vec![
@@ -532,6 +771,16 @@ pub(crate) async fn initialize(&self, enable_snippets: bool) -> Result<lsp::Init
                         dynamic_registration: Some(false),
                         resolve_support: None,
                     }),
+                    on_type_formatting: Some(lsp::DocumentOnTypeFormattingClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    linked_editing_range: Some(lsp::LinkedEditingRangeClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    document_link: Some(lsp::DocumentLinkClientCapabilities {
+                        dynamic_registration: Some(false),
+                        tooltip_support: Some(false),
+                    }),
                     ..Default::default()
                 }),
                 window: Some(lsp::WindowClientCapabilities {
@@ -602,6 +851,199 @@ pub fn did_change_workspace(
         })
     }
 
+    fn file_operations(&self) -> Option<&lsp::WorkspaceFileOperationsServerCapabilities> {
+        self.capabilities
+            .get()?
+            .workspace
+            .as_ref()?
+            .file_operations
+            .as_ref()
+    }
+
+    /// Whether the server registered the file operation `operation` for the file `uri`.
+    fn file_operation_registered(
+        &self,
+        operation: impl FnOnce(
+            &lsp::WorkspaceFileOperationsServerCapabilities,
+        ) -> Option<&lsp::FileOperationRegistrationOptions>,
+        uri: &lsp::Url,
+    ) -> Option<()> {
+        let options = operation(self.file_operations()?)?;
+        file_operation_matches(options, uri, false).then_some(())
+    }
+
+    /// Requests the edits to apply before renaming the file `old_uri` to `new_uri`, such as
+    /// updating its imports. Returns `None` if the server isn't interested in renames.
+    pub fn will_rename_file(
+        &self,
+        old_uri: &lsp::Url,
+        new_uri: &lsp::Url,
+    ) -> Option<impl Future<Output = Result<Option<lsp::WorkspaceEdit>>>> {
+        self.file_operation_registered(|operations| operations.will_rename.as_ref(), old_uri)?;
+
+        let params = lsp::RenameFilesParams {
+            files: vec![lsp::FileRename {
+                old_uri: old_uri.to_string(),
+                new_uri: new_uri.to_string(),
+            }],
+        };
+        let request = self.call::<lsp::request::WillRenameFiles>(params);
+
+        Some(async move {
+            let json = request.await?;
+            let response: Option<lsp::WorkspaceEdit> = serde_json::from_value(json)?;
+            Ok(response)
+        })
+    }
+
+    pub fn did_rename_file(
+        &self,
+        old_uri: &lsp::Url,
+        new_uri: &lsp::Url,
+    ) -> Option<impl Future<Output = Result<()>>> {
+        self.file_operation_registered(|operations| operations.did_rename.as_ref(), old_uri)?;
+
+        Some(
+            self.notify::<lsp::notification::DidRenameFiles>(lsp::RenameFilesParams {
+                files: vec![lsp::FileRename {
+                    old_uri: old_uri.to_string(),
+                    new_uri: new_uri.to_string(),
+                }],
+            }),
+        )
+    }
+
+    pub fn did_create_file(&self, uri: &lsp::Url) -> Option<impl Future<Output = Result<()>>> {
+        self.file_operation_registered(|operations| operations.did_create.as_ref(), uri)?;
+
+        Some(
+            self.notify::<lsp::notification::DidCreateFiles>(lsp::CreateFilesParams {
+                files: vec![lsp::FileCreate {
+                    uri: uri.to_string(),
+                }],
+            }),
+        )
+    }
+
+    pub fn did_delete_file(&self, uri: &lsp::Url) -> Option<impl Future<Output = Result<()>>> {
+        self.file_operation_registered(|operations| operations.did_delete.as_ref(), uri)?;
+
+        Some(
+            self.notify::<lsp::notification::DidDeleteFiles>(lsp::DeleteFilesParams {
+                files: vec![lsp::FileDelete {
+                    uri: uri.to_string(),
+                }],
+            }),
+        )
+    }
+
+    /// Handles a `client/registerCapability` request. Only file watchers are registered
+    /// dynamically, other registrations are ignored.
+    pub fn register_capabilities(&self, registrations: Vec<lsp::Registration>) {
+        use lsp::notification::Notification as _;
+
+        for registration in registrations {
+            if registration.method != lsp::notification::DidChangeWatchedFiles::METHOD {
+                log::warn!(
+                    "Ignoring dynamic registration of unsupported method {}",
+                    registration.method
+                );
+                continue;
+            }
+
+            let options = registration
+                .register_options
+                .map(serde_json::from_value::<lsp::DidChangeWatchedFilesRegistrationOptions>);
+            let options = match options {
+                Some(Ok(options)) => options,
+                _ => {
+                    log::warn!("Invalid file watcher registration {}", registration.id);
+                    continue;
+                }
+            };
+
+            let watchers = options
+                .watchers
+                .into_iter()
+                .filter_map(|watcher| self.file_watcher(watcher))
+                .collect();
+            self.file_watchers.lock().insert(registration.id, watchers);
+        }
+    }
+
+    /// Whether the server registered file watchers.
+    pub fn has_file_watchers(&self) -> bool {
+        !self.file_watchers.lock().is_empty()
+    }
+
+    /// Handles a `client/unregisterCapability` request.
+    pub fn unregister_capabilities(&self, unregistrations: Vec<lsp::Unregistration>) {
+        let mut file_watchers = self.file_watchers.lock();
+        for unregistration in unregistrations {
+            file_watchers.remove(&unregistration.id);
+        }
+    }
+
+    fn file_watcher(&self, watcher: lsp::FileSystemWatcher) -> Option<FileWatcher> {
+        let pattern = match watcher.glob_pattern {
+            lsp::GlobPattern::String(pattern) => pattern,
+            lsp::GlobPattern::Relative(relative) => {
+                let base = match relative.base_uri {
+                    OneOf::Left(folder) => folder.uri,
+                    OneOf::Right(uri) => uri,
+                };
+                let base = base.to_file_path().ok()?;
+                base.join(relative.pattern).to_str()?.to_owned()
+            }
+        };
+
+        let glob = globset::GlobBuilder::new(&pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|err| log::warn!("Invalid file watcher glob {}: {}", pattern, err))
+            .ok()?;
+
+        Some(FileWatcher {
+            glob: glob.compile_matcher(),
+            kind: watcher.kind.unwrap_or_else(lsp::WatchKind::all),
+        })
+    }
+
+    /// Sends a `workspace/didChangeWatchedFiles` notification if the server watches `path`.
+    /// Globs are matched against the absolute path as well as the path relative to the root.
+    pub fn did_change_watched_file(
+        &self,
+        path: &Path,
+        typ: lsp::FileChangeType,
+    ) -> Option<impl Future<Output = Result<()>>> {
+        let kind = match typ {
+            lsp::FileChangeType::CREATED => lsp::WatchKind::Create,
+            lsp::FileChangeType::DELETED => lsp::WatchKind::Delete,
+            _ => lsp::WatchKind::Change,
+        };
+        let relative_path = path.strip_prefix(&self.root_path).ok();
+        let watched = self
+            .file_watchers
+            .lock()
+            .values()
+            .flatten()
+            .filter(|watcher| watcher.kind.contains(kind))
+            .any(|watcher| {
+                watcher.glob.is_match(path)
+                    || relative_path.map_or(false, |path| watcher.glob.is_match(path))
+            });
+        if !watched {
+            return None;
+        }
+
+        let uri = lsp::Url::from_file_path(path).ok()?;
+        Some(self.notify::<lsp::notification::DidChangeWatchedFiles>(
+            lsp::DidChangeWatchedFilesParams {
+                changes: vec![lsp::FileEvent { uri, typ }],
+            },
+        ))
+    }
+
     // -------------------------------------------------------------------------------------------
     // Text document
     // -------------------------------------------------------------------------------------------
@@ -955,6 +1397,7 @@ pub fn text_document_formatting(
         // merge FormattingOptions with 'config.format'
         let config_format = self
             .config
+            .lock()
             .as_ref()
             .and_then(|cfg| cfg.get("format"))
             .and_then(|fmt| HashMap::<String, lsp::FormattingProperty>::deserialize(fmt).ok());
@@ -1016,6 +1459,41 @@ pub fn text_document_range_formatting(
         })
     }
 
+    pub fn text_document_on_type_formatting(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        position: lsp::Position,
+        ch: char,
+        options: lsp::FormattingOptions,
+    ) -> Option<impl Future<Output = Result<Value>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        // Return early if the server does not support on-type formatting or `ch` doesn't
+        // trigger it.
+        let provider = capabilities.document_on_type_formatting_provider.as_ref()?;
+        let is_trigger = |trigger: &String| trigger.chars().eq(std::iter::once(ch));
+        if !is_trigger(&provider.first_trigger_character)
+            && !provider
+                .more_trigger_character
+                .iter()
+                .flatten()
+                .any(is_trigger)
+        {
+            return None;
+        }
+
+        let params = lsp::DocumentOnTypeFormattingParams {
+            text_document_position: lsp::TextDocumentPositionParams {
+                text_document,
+                position,
+            },
+            ch: ch.to_string(),
+            options,
+        };
+
+        Some(self.call::<lsp::request::OnTypeFormatting>(params))
+    }
+
     pub fn text_document_document_highlight(
         &self,
         text_document: lsp::TextDocumentIdentifier,
@@ -1044,6 +1522,54 @@ pub fn text_document_document_highlight(
         Some(self.call::<lsp::request::DocumentHighlightRequest>(params))
     }
 
+    pub fn text_document_document_link(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        work_done_token: Option<lsp::ProgressToken>,
+    ) -> Option<impl Future<Output = Result<Value>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        // Return early if the server does not support document links.
+        capabilities.document_link_provider.as_ref()?;
+
+        let params = lsp::DocumentLinkParams {
+            text_document,
+            work_done_progress_params: lsp::WorkDoneProgressParams { work_done_token },
+            partial_result_params: lsp::PartialResultParams {
+                partial_result_token: None,
+            },
+        };
+
+        Some(self.call::<lsp::request::DocumentLinkRequest>(params))
+    }
+
+    pub fn text_document_linked_editing_range(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        position: lsp::Position,
+        work_done_token: Option<lsp::ProgressToken>,
+    ) -> Option<impl Future<Output = Result<Value>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        // Return early if the server does not support linked editing ranges.
+        match capabilities.linked_editing_range_provider {
+            Some(lsp::LinkedEditingRangeServerCapabilities::Simple(true))
+            | Some(lsp::LinkedEditingRangeServerCapabilities::Options(_))
+            | Some(lsp::LinkedEditingRangeServerCapabilities::RegistrationOptions(_)) => (),
+            _ => return None,
+        }
+
+        let params = lsp::LinkedEditingRangeParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document,
+                position,
+            },
+            work_done_progress_params: lsp::WorkDoneProgressParams { work_done_token },
+        };
+
+        Some(self.call::<lsp::request::LinkedEditingRange>(params))
+    }
+
     fn goto_request<
         T: lsp::request::Request<
             Params = lsp::GotoDefinitionParams,
@@ -1340,3 +1866,57 @@ pub fn command(&self, command: lsp::Command) -> Option<impl Future<Output = Resu
         Some(self.call::<lsp::request::ExecuteCommand>(params))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(
+        glob: &str,
+        matches: Option<lsp::FileOperationPatternKind>,
+    ) -> lsp::FileOperationFilter {
+        lsp::FileOperationFilter {
+            scheme: Some("file".to_string()),
+            pattern: lsp::FileOperationPattern {
+                glob: glob.to_string(),
+                matches,
+                options: None,
+            },
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_operation_matches() {
+        let options = lsp::FileOperationRegistrationOptions {
+            filters: vec![filter("**/*.rs", Some(lsp::FileOperationPatternKind::File))],
+        };
+        let rust_file = lsp::Url::parse("file:///project/src/main.rs").unwrap();
+        let toml_file = lsp::Url::parse("file:///project/Cargo.toml").unwrap();
+        assert!(file_operation_matches(&options, &rust_file, false));
+        assert!(!file_operation_matches(&options, &rust_file, true));
+        assert!(!file_operation_matches(&options, &toml_file, false));
+
+        let options = lsp::FileOperationRegistrationOptions {
+            filters: vec![filter(
+                "**/src",
+                Some(lsp::FileOperationPatternKind::Folder),
+            )],
+        };
+        let dir = lsp::Url::parse("file:///project/src").unwrap();
+        assert!(file_operation_matches(&options, &dir, true));
+        assert!(!file_operation_matches(&options, &dir, false));
+
+        let mut ignore_case = filter("**/*.RS", None);
+        ignore_case.pattern.options = Some(lsp::FileOperationPatternOptions {
+            ignore_case: Some(true),
+        });
+        let options = lsp::FileOperationRegistrationOptions {
+            filters: vec![ignore_case],
+        };
+        assert!(file_operation_matches(&options, &rust_file, false));
+
+        let options = lsp::FileOperationRegistrationOptions { filters: vec![] };
+        assert!(!file_operation_matches(&options, &rust_file, false));
+    }
+}
@@ -49,7 +49,7 @@ pub enum Error {
     Other(#[from] anyhow::Error),
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum OffsetEncoding {
     /// UTF-8 code units aka bytes
     Utf8,
@@ -546,6 +546,7 @@ pub enum MethodCall {
     WorkspaceFolders,
     WorkspaceConfiguration(lsp::ConfigurationParams),
     RegisterCapability(lsp::RegistrationParams),
+    UnregisterCapability(lsp::UnregistrationParams),
 }
 
 impl MethodCall {
@@ -569,6 +570,10 @@ pub fn parse(method: &str, params: jsonrpc::Params) -> Result<MethodCall> {
                 let params: lsp::RegistrationParams = params.parse()?;
                 Self::RegisterCapability(params)
             }
+            lsp::request::UnregisterCapability::METHOD => {
+                let params: lsp::UnregistrationParams = params.parse()?;
+                Self::UnregisterCapability(params)
+            }
             _ => {
                 return Err(Error::Unhandled);
             }
@@ -750,6 +755,13 @@ pub fn get(
         Ok(Some(client))
     }
 
+    /// Whether a language server of the language is running.
+    pub fn is_running(&self, language_config: &LanguageConfiguration) -> bool {
+        self.inner
+            .get(&language_config.scope)
+            .map_or(false, |clients| !clients.is_empty())
+    }
+
     pub fn iter_clients(&self) -> impl Iterator<Item = &Arc<Client>> {
         self.inner.values().flatten().map(|(_, client)| client)
     }
@@ -850,6 +862,7 @@ fn start_client(
         &ls_config.args,
         config.config.clone(),
         ls_config.environment.clone(),
+        &ls_config.bin_dirs,
         &config.roots,
         config.workspace_lsp_roots.as_deref().unwrap_or(root_dirs),
         id,
@@ -10,7 +10,9 @@
     align_view,
     document::DocumentSavedEventResult,
     editor::{ConfigEvent, EditorEvent},
+    global_jumplist::GlobalJumpList,
     graphics::Rect,
+    recent_files::RecentFiles,
     theme,
     tree::Layout,
     Align, Editor,
@@ -20,12 +22,14 @@
 
 use crate::{
     args::Args,
-    commands::apply_workspace_edit,
+    commands::{apply_workspace_edit, typed},
     compositor::{Compositor, Event},
     config::Config,
-    job::Jobs,
+    job::{self, Job, Jobs},
     keymap::Keymaps,
-    ui::{self, overlay::overlayed},
+    startup,
+    ui::{self, overlay::overlayed, PromptEvent},
+    workspace_index,
 };
 
 use log::{debug, error, warn};
@@ -44,8 +48,6 @@
 #[cfg(windows)]
 type Signals = futures_util::stream::Empty<()>;
 
-const LSP_DEADLINE: Duration = Duration::from_millis(16);
-
 #[cfg(not(feature = "integration"))]
 use tui::backend::CrosstermBackend;
 
@@ -76,6 +78,8 @@ pub struct Application {
     jobs: Jobs,
     lsp_progress: LspProgressMap,
     last_render: Instant,
+    /// Whether an event changed what's on screen since the last render.
+    render_pending: bool,
 }
 
 #[cfg(feature = "integration")]
@@ -111,11 +115,12 @@ pub fn new(
 
         use helix_view::editor::Action;
 
+        let started = Instant::now();
         let mut theme_parent_dirs = vec![helix_loader::config_dir()];
         theme_parent_dirs.extend(helix_loader::runtime_dirs().iter().cloned());
         let theme_loader = std::sync::Arc::new(theme::Loader::new(&theme_parent_dirs));
 
-        let true_color = config.editor.true_color || crate::true_color();
+        let color_support = crate::color_support(config.editor.true_color);
         let theme = config
             .theme
             .as_ref()
@@ -127,11 +132,16 @@ pub fn new(
                         e
                     })
                     .ok()
-                    .filter(|theme| (true_color || theme.is_16_color()))
+                    .map(|theme| theme.degrade(color_support))
             })
-            .unwrap_or_else(|| theme_loader.default_theme(true_color));
+            .unwrap_or_else(|| {
+                theme_loader.default_theme(color_support == theme::ColorSupport::TrueColor)
+            });
+        startup::record("load theme", started);
 
-        let syn_loader = std::sync::Arc::new(syntax::Loader::new(syn_loader_conf));
+        let syn_loader = startup::time("create syntax loader", || {
+            std::sync::Arc::new(syntax::Loader::new(syn_loader_conf))
+        });
 
         #[cfg(not(feature = "integration"))]
         let backend = CrosstermBackend::new(stdout(), &config.editor);
@@ -139,10 +149,11 @@ pub fn new(
         #[cfg(feature = "integration")]
         let backend = TestBackend::new(120, 150);
 
-        let terminal = Terminal::new(backend)?;
+        let terminal = startup::time("set up terminal", || Terminal::new(backend))?;
         let area = terminal.size().expect("couldn't get terminal size");
         let mut compositor = Compositor::new(area);
         let config = Arc::new(ArcSwap::from_pointee(config));
+        let started = Instant::now();
         let mut editor = Editor::new(
             area,
             theme_loader.clone(),
@@ -151,6 +162,15 @@ pub fn new(
                 &config.editor
             })),
         );
+        startup::record("create editor", started);
+        startup::time("load state", || {
+            if editor.config().persist_old_files {
+                editor.recent_files = RecentFiles::load();
+            }
+            if editor.config().persist_jumplist {
+                editor.global_jumps = GlobalJumpList::load();
+            }
+        });
 
         let keys = Box::new(Map::new(Arc::clone(&config), |config: &Config| {
             &config.keys
@@ -158,6 +178,8 @@ pub fn new(
         let editor_view = Box::new(ui::EditorView::new(Keymaps::new(keys)));
         compositor.push(editor_view);
 
+        // Opening documents loads their grammars and starts their language servers.
+        let started = Instant::now();
         if args.load_tutor {
             let path = helix_loader::runtime_file(Path::new("tutor"));
             editor.open(&path, Action::VerticalSplit)?;
@@ -196,12 +218,22 @@ pub fn new(
                         // NOTE: this isn't necessarily true anymore. If
                         // `--vsplit` or `--hsplit` are used, the file which is
                         // opened last is focused on.
-                        let view_id = editor.tree.focus;
-                        let doc = doc_mut!(editor, &doc_id);
-                        let pos = Selection::point(pos_at_coords(doc.text().slice(..), pos, true));
-                        doc.set_selection(view_id, pos);
+                        // Without an explicit position the last position of a recently
+                        // edited file is restored by `Editor::open`.
+                        if let Some(pos) = pos {
+                            let view_id = editor.tree.focus;
+                            let doc = doc_mut!(editor, &doc_id);
+                            let pos =
+                                Selection::point(pos_at_coords(doc.text().slice(..), pos, true));
+                            doc.set_selection(view_id, pos);
+                        }
                     }
                 }
+                if args.read_stdin {
+                    editor
+                        .new_file_from_stdin(Action::Load)
+                        .context("read from stdin")?;
+                }
                 editor.set_status(format!(
                     "Loaded {} file{}.",
                     nr_of_files,
@@ -212,7 +244,7 @@ pub fn new(
                 let (view, doc) = current!(editor);
                 align_view(doc, view, Align::Center);
             }
-        } else if stdin().is_tty() || cfg!(feature = "integration") {
+        } else if !args.read_stdin && (stdin().is_tty() || cfg!(feature = "integration")) {
             editor.new_file(Action::VerticalSplit);
         } else if cfg!(target_os = "macos") {
             // On Linux and Windows, we allow the output of a command to be piped into the new buffer.
@@ -220,12 +252,20 @@ pub fn new(
             //   https://github.com/crossterm-rs/crossterm/issues/500
             anyhow::bail!("Piping into helix-term is currently not supported on macOS");
         } else {
-            editor
-                .new_file_from_stdin(Action::VerticalSplit)
-                .unwrap_or_else(|_| editor.new_file(Action::VerticalSplit));
+            let doc_id = editor.new_file_from_stdin(Action::VerticalSplit);
+            // Reading stdin was requested explicitly with `-`, so a failure is an error
+            // rather than a reason to silently fall back to an empty scratch buffer.
+            if args.read_stdin {
+                doc_id.context("read from stdin")?;
+            } else if doc_id.is_err() {
+                editor.new_file(Action::VerticalSplit);
+            }
         }
 
+        startup::record("open files", started);
+
         editor.set_theme(theme);
+        workspace_index::spawn_indexer(&editor);
 
         #[cfg(windows)]
         let signals = futures_util::stream::empty();
@@ -233,7 +273,7 @@ pub fn new(
         let signals = Signals::new([signal::SIGTSTP, signal::SIGCONT, signal::SIGUSR1])
             .context("build signal handler")?;
 
-        let app = Self {
+        let mut app = Self {
             compositor,
             terminal,
             editor,
@@ -247,11 +287,32 @@ pub fn new(
             jobs: Jobs::new(),
             lsp_progress: LspProgressMap::new(),
             last_render: Instant::now(),
+            render_pending: false,
         };
 
+        app.run_startup_commands(&args.startup_commands);
+
         Ok(app)
     }
 
+    /// Runs the commands given with `+{command}` or `--execute` on the command line, in order.
+    /// Execution stops at the first command that fails and its error is shown in the statusline.
+    fn run_startup_commands(&mut self, commands: &[String]) {
+        let mut cx = crate::compositor::Context {
+            editor: &mut self.editor,
+            jobs: &mut self.jobs,
+            scroll: None,
+        };
+
+        for command in commands {
+            if let Err(err) = typed::execute_command_line(&mut cx, command, PromptEvent::Validate) {
+                cx.editor
+                    .set_error(format!("startup command '{}' failed: {}", command, err));
+                break;
+            }
+        }
+    }
+
     async fn render(&mut self) {
         let mut cx = crate::compositor::Context {
             editor: &mut self.editor,
@@ -263,6 +324,8 @@ async fn render(&mut self) {
         // to ensure that there are no tasks running that want to block rendering
         drop(cx.editor.redraw_handle.1.write().await);
         cx.editor.needs_redraw = false;
+        self.render_pending = false;
+        self.last_render = Instant::now();
         {
             // exhaust any leftover redraw notifications
             let notify = cx.editor.redraw_handle.0.notified();
@@ -292,8 +355,9 @@ pub async fn event_loop<S>(&mut self, input_stream: &mut S)
     where
         S: Stream<Item = crossterm::Result<crossterm::event::Event>> + Unpin,
     {
+        let started = Instant::now();
         self.render().await;
-        self.last_render = Instant::now();
+        startup::record("first render", started);
 
         loop {
             if !self.event_loop_until_idle(input_stream).await {
@@ -313,9 +377,16 @@ pub async fn event_loop_until_idle<S>(&mut self, input_stream: &mut S) -> bool
 
             use futures_util::StreamExt;
 
+            let next_frame = tokio::time::Instant::from_std(self.next_frame());
+
             tokio::select! {
                 biased;
 
+                // Render events coalesced during the last frame before handling more events,
+                // to keep the UI responsive under a burst of them.
+                _ = tokio::time::sleep_until(next_frame), if self.render_pending => {
+                    self.render().await;
+                }
                 Some(signal) = self.signals.next() => {
                     self.handle_signals(signal).await;
                 }
@@ -324,11 +395,11 @@ pub async fn event_loop_until_idle<S>(&mut self, input_stream: &mut S) -> bool
                 }
                 Some(callback) = self.jobs.futures.next() => {
                     self.jobs.handle_callback(&mut self.editor, &mut self.compositor, callback);
-                    self.render().await;
+                    self.request_render();
                 }
                 Some(callback) = self.jobs.wait_futures.next() => {
                     self.jobs.handle_callback(&mut self.editor, &mut self.compositor, callback);
-                    self.render().await;
+                    self.request_render();
                 }
                 event = self.editor.wait_event() => {
                     let _idle_handled = self.handle_editor_event(event).await;
@@ -342,6 +413,12 @@ pub async fn event_loop_until_idle<S>(&mut self, input_stream: &mut S) -> bool
                 }
             }
 
+            // Render right away unless the last render was less than a frame ago: events arriving
+            // faster than that are drawn together at the next frame.
+            if self.render_pending && Instant::now() >= self.next_frame() {
+                self.render().await;
+            }
+
             // for integration tests only, reset the idle timer after every
             // event to signal when test events are done processing
             #[cfg(feature = "integration")]
@@ -351,6 +428,17 @@ pub async fn event_loop_until_idle<S>(&mut self, input_stream: &mut S) -> bool
         }
     }
 
+    /// Renders once the current frame is over, see [`Application::next_frame`].
+    fn request_render(&mut self) {
+        self.render_pending = true;
+    }
+
+    /// When the next frame may be rendered, limiting renders to `max-fps` per second.
+    fn next_frame(&self) -> Instant {
+        let max_fps = self.editor.config().max_fps.max(1);
+        self.last_render + Duration::from_secs(1) / max_fps
+    }
+
     pub fn handle_config_events(&mut self, config_event: ConfigEvent) {
         match config_event {
             ConfigEvent::Refresh => self.refresh_config(),
@@ -388,12 +476,30 @@ fn refresh_language_config(&mut self) -> Result<(), Error> {
             document.detect_language(self.syn_loader.clone());
         }
 
+        // Push changed settings to the running servers so they don't need to be restarted.
+        for doc in self.editor.documents.values() {
+            let (language_server, language_config) =
+                match (doc.language_server(), doc.language_config()) {
+                    (Some(language_server), Some(language_config)) => {
+                        (language_server, language_config)
+                    }
+                    _ => continue,
+                };
+            if !language_server.update_config(language_config.config.clone())
+                || !language_server.is_initialized()
+            {
+                continue;
+            }
+            let settings = language_config.config.clone().unwrap_or_default();
+            tokio::spawn(language_server.did_change_configuration(settings));
+        }
+
         Ok(())
     }
 
     /// Refresh theme after config change
     fn refresh_theme(&mut self, config: &Config) -> Result<(), Error> {
-        let true_color = config.editor.true_color || crate::true_color();
+        let color_support = crate::color_support(config.editor.true_color);
         let theme = config
             .theme
             .as_ref()
@@ -405,9 +511,12 @@ fn refresh_theme(&mut self, config: &Config) -> Result<(), Error> {
                         e
                     })
                     .ok()
-                    .filter(|theme| (true_color || theme.is_16_color()))
+                    .map(|theme| theme.degrade(color_support))
             })
-            .unwrap_or_else(|| self.theme_loader.default_theme(true_color));
+            .unwrap_or_else(|| {
+                self.theme_loader
+                    .default_theme(color_support == theme::ColorSupport::TrueColor)
+            });
 
         self.editor.set_theme(theme);
         Ok(())
@@ -494,7 +603,7 @@ pub async fn handle_idle_timeout(&mut self) {
             scroll: None,
         };
         let should_render = self.compositor.handle_event(&Event::IdleTimeout, &mut cx);
-        if should_render || self.editor.needs_redraw {
+        if should_render || self.editor.needs_redraw || self.render_pending {
             self.render().await;
         }
     }
@@ -531,6 +640,11 @@ pub fn handle_document_write(&mut self, doc_save_event: DocumentSavedEventResult
         let lines = doc_save_event.text.len_lines();
         let bytes = doc_save_event.text.len_bytes();
 
+        let change = if doc.path() != Some(&doc_save_event.path) {
+            lsp::FileChangeType::CREATED
+        } else {
+            lsp::FileChangeType::CHANGED
+        };
         if doc.path() != Some(&doc_save_event.path) {
             if let Err(err) = doc.set_path(Some(&doc_save_event.path)) {
                 log::error!(
@@ -552,6 +666,9 @@ pub fn handle_document_write(&mut self, doc_save_event: DocumentSavedEventResult
             let _ = self.editor.refresh_language_server(id);
         }
 
+        self.editor.file_changed(&doc_save_event.path, change);
+        workspace_index::update_saved_file(&self.editor, &doc_save_event.path);
+
         // TODO: fix being overwritten by lsp
         self.editor.set_status(format!(
             "'{}' written, {}L {}B",
@@ -568,30 +685,28 @@ pub async fn handle_editor_event(&mut self, event: EditorEvent) -> bool {
         match event {
             EditorEvent::DocumentSaved(event) => {
                 self.handle_document_write(event);
-                self.render().await;
+                self.request_render();
             }
             EditorEvent::ConfigEvent(event) => {
                 self.handle_config_events(event);
-                self.render().await;
+                self.request_render();
             }
             EditorEvent::LanguageServerMessage((id, call)) => {
                 self.handle_language_server_message(call, id).await;
-                // limit render calls for fast language server messages
-                let last = self.editor.language_servers.incoming.is_empty();
-
-                if last || self.last_render.elapsed() > LSP_DEADLINE {
-                    self.render().await;
-                    self.last_render = Instant::now();
-                }
+                self.request_render();
             }
             EditorEvent::DebuggerEvent(payload) => {
                 let needs_render = self.editor.handle_debugger_message(payload).await;
                 if needs_render {
-                    self.render().await;
+                    self.request_render();
                 }
             }
+            EditorEvent::CommandOutput(output) => {
+                self.editor.append_command_output(output);
+                self.request_render();
+            }
             EditorEvent::IdleTimer => {
-                self.editor.clear_idle_timer();
+                self.editor.start_due_idle_tasks();
                 self.handle_idle_timeout().await;
 
                 #[cfg(feature = "integration")]
@@ -636,8 +751,57 @@ pub async fn handle_terminal_events(
         };
 
         if should_redraw && !self.editor.should_close() {
-            self.render().await;
+            self.request_render();
+        }
+    }
+
+    /// Restarts the language server of the language `scope` after it exited unexpectedly, with
+    /// a delay growing with each restart, until it was restarted `max-restarts` times.
+    fn restart_language_server(&mut self, scope: String) {
+        let language_config = match self.editor.syn_loader.language_config_for_scope(&scope) {
+            Some(language_config) => language_config,
+            None => return,
+        };
+        let server = match &language_config.language_server {
+            Some(server) => server,
+            None => return,
+        };
+        let restarts = self
+            .editor
+            .language_server_restarts
+            .get(&scope)
+            .copied()
+            .unwrap_or_default();
+        if restarts >= server.max_restarts {
+            self.editor.set_error(format!(
+                "Language server `{}` exited unexpectedly after {} restarts, use :lsp-restart to start it again",
+                server.command, restarts
+            ));
+            return;
         }
+
+        let delay = Duration::from_millis(server.restart_delay) * 2u32.pow(restarts.min(10) as u32);
+        self.editor
+            .language_server_restarts
+            .insert(scope.clone(), restarts + 1);
+        self.editor.set_error(format!(
+            "Language server `{}` exited unexpectedly, restarting it in {:.1}s",
+            server.command,
+            delay.as_secs_f64()
+        ));
+
+        let restart = async move {
+            tokio::time::sleep(delay).await;
+            let call: job::Callback =
+                job::Callback::Editor(Box::new(move |editor: &mut Editor| {
+                    editor.restart_language_servers(&scope);
+                }));
+            Ok(call)
+        };
+        self.jobs.add(
+            Job::with_callback(restart)
+                .named(format!("restart language server {}", server.command)),
+        );
     }
 
     pub async fn handle_language_server_message(
@@ -675,7 +839,7 @@ pub async fn handle_language_server_message(
                         // This might not be required by the spec but Neovim does this as well, so it's
                         // probably a good idea for compatibility.
                         if let Some(config) = language_server.config() {
-                            tokio::spawn(language_server.did_change_configuration(config.clone()));
+                            tokio::spawn(language_server.did_change_configuration(config));
                         }
 
                         let docs = self.editor.documents().filter(|doc| {
@@ -928,6 +1092,17 @@ pub async fn handle_language_server_message(
                         // do nothing
                     }
                     Notification::Exit => {
+                        // The editor removes the servers it stops or replaces from the registry:
+                        // one still there exited unexpectedly.
+                        let crashed = self.editor.language_servers.get_by_id(server_id).is_some();
+                        let scope = self
+                            .editor
+                            .documents()
+                            .find(|doc| {
+                                doc.language_server().map(|server| server.id()) == Some(server_id)
+                            })
+                            .and_then(|doc| Some(doc.language_config()?.scope.clone()));
+
                         self.editor.set_status("Language server exited");
 
                         // Clear any diagnostics for documents with this server open.
@@ -952,6 +1127,10 @@ pub async fn handle_language_server_message(
 
                         // Remove the language server from the registry.
                         self.editor.language_servers.remove_by_id(server_id);
+
+                        if let Some(scope) = scope.filter(|_| crashed) {
+                            self.restart_language_server(scope);
+                        }
                     }
                 }
             }
@@ -1024,11 +1203,11 @@ pub async fn handle_language_server_message(
                             .items
                             .iter()
                             .map(|item| {
-                                let mut config = match &item.scope_uri {
+                                let config = match &item.scope_uri {
                                     Some(scope) => {
                                         let path = scope.to_file_path().ok()?;
                                         let doc = self.editor.document_by_path(path)?;
-                                        doc.language_config()?.config.as_ref()?
+                                        doc.language_config()?.config.clone()?
                                     }
                                     None => self
                                         .editor
@@ -1037,24 +1216,45 @@ pub async fn handle_language_server_message(
                                         .unwrap()
                                         .config()?,
                                 };
-                                if let Some(section) = item.section.as_ref() {
-                                    for part in section.split('.') {
-                                        config = config.get(part)?;
-                                    }
+                                match item.section.as_ref() {
+                                    Some(section) => section
+                                        .split('.')
+                                        .try_fold(&config, |config, part| config.get(part))
+                                        .cloned(),
+                                    None => Some(config),
                                 }
-                                Some(config)
                             })
                             .collect();
                         Ok(json!(result))
                     }
-                    Ok(MethodCall::RegisterCapability(_params)) => {
-                        log::warn!("Ignoring a client/registerCapability request because dynamic capability registration is not enabled. Please report this upstream to the language server");
-                        // Language Servers based on the `vscode-languageserver-node` library often send
-                        // client/registerCapability even though we do not enable dynamic registration
-                        // for any capabilities. We should send a MethodNotFound JSONRPC error in this
-                        // case but that rejects the registration promise in the server which causes an
-                        // exit. So we work around this by ignoring the request and sending back an OK
-                        // response.
+                    Ok(MethodCall::RegisterCapability(params)) => {
+                        // Only file watchers are registered dynamically. Language Servers based on
+                        // the `vscode-languageserver-node` library often send client/registerCapability
+                        // for other capabilities even though we do not enable dynamic registration
+                        // for them. We should send a MethodNotFound JSONRPC error in this case but
+                        // that rejects the registration promise in the server which causes an exit.
+                        // So we work around this by ignoring those registrations and sending back an
+                        // OK response.
+                        let watches_files = match self.editor.language_servers.get_by_id(server_id)
+                        {
+                            Some(language_server) => {
+                                language_server.register_capabilities(params.registrations);
+                                language_server.has_file_watchers()
+                            }
+                            None => false,
+                        };
+                        if watches_files {
+                            self.editor.watch_workspace_files();
+                        }
+
+                        Ok(serde_json::Value::Null)
+                    }
+                    Ok(MethodCall::UnregisterCapability(params)) => {
+                        if let Some(language_server) =
+                            self.editor.language_servers.get_by_id(server_id)
+                        {
+                            language_server.unregister_capabilities(params.unregisterations);
+                        }
 
                         Ok(serde_json::Value::Null)
                     }
@@ -1076,7 +1276,9 @@ pub async fn handle_language_server_message(
 
     async fn claim_term(&mut self) -> std::io::Result<()> {
         let terminal_config = self.config.load().editor.clone().into();
-        self.terminal.claim(terminal_config)
+        self.terminal.claim(terminal_config)?;
+        self.editor.terminal_supports_images = self.terminal.backend().supports_images();
+        Ok(())
     }
 
     fn restore_term(&mut self) -> std::io::Result<()> {
@@ -1139,6 +1341,31 @@ pub async fn close(&mut self) -> Vec<anyhow::Error> {
             errs.push(err);
         }
 
+        if self.editor.config().persist_old_files {
+            let doc_ids: Vec<_> = self.editor.documents().map(|doc| doc.id()).collect();
+            for doc_id in doc_ids {
+                self.editor.record_recent_file(doc_id);
+            }
+            self.editor.recent_files.save();
+        }
+
+        if self.editor.config().persist_jumplist {
+            self.editor.global_jumps.save();
+        }
+
+        // The indexer holds the locks while it walks the workspace and saves the indexes
+        // itself once done, so don't wait for it.
+        if self.editor.config().search.index {
+            if let Ok(root) = std::env::current_dir() {
+                if let Some(mut trigram_index) = self.editor.trigram_index.try_lock() {
+                    workspace_index::save_trigrams(&root, &mut trigram_index);
+                }
+                if let Some(mut tag_index) = self.editor.tag_index.try_lock() {
+                    workspace_index::save_tags(&root, &mut tag_index);
+                }
+            }
+        }
+
         if self.editor.close_language_servers(None).await.is_err() {
             log::error!("Timed out waiting for language servers to shutdown");
             errs.push(anyhow::format_err!(
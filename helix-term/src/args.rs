@@ -16,7 +16,9 @@ pub struct Args {
     pub verbosity: u64,
     pub log_file: Option<PathBuf>,
     pub config_file: Option<PathBuf>,
-    pub files: Vec<(PathBuf, Position)>,
+    pub files: Vec<(PathBuf, Option<Position>)>,
+    pub read_stdin: bool,
+    pub startup_commands: Vec<String>,
 }
 
 impl Args {
@@ -29,6 +31,7 @@ pub fn parse_args() -> Result<Args> {
         while let Some(arg) = argv.next() {
             match arg.as_str() {
                 "--" => break, // stop parsing at this point treat the remaining as files
+                "-" => args.read_stdin = true,
                 "--version" => args.display_version = true,
                 "--help" => args.display_help = true,
                 "--tutor" => args.load_tutor = true,
@@ -59,6 +62,14 @@ pub fn parse_args() -> Result<Args> {
                     Some(path) => args.log_file = Some(path.into()),
                     None => anyhow::bail!("--log must specify a path to write"),
                 },
+                "-e" | "--execute" => match argv.next() {
+                    Some(command) => args.startup_commands.push(command),
+                    None => anyhow::bail!("--execute must specify a command to run"),
+                },
+                arg if arg.starts_with('+') => match &arg[1..] {
+                    "" => anyhow::bail!("+ must be followed by a line number or a command"),
+                    command => args.startup_commands.push(command.to_string()),
+                },
                 arg if arg.starts_with("--") => {
                     anyhow::bail!("unexpected double dash argument: {}", arg)
                 }
@@ -86,14 +97,15 @@ pub fn parse_args() -> Result<Args> {
     }
 }
 
-/// Parse arg into [`PathBuf`] and position.
-pub(crate) fn parse_file(s: &str) -> (PathBuf, Position) {
-    let def = || (PathBuf::from(s), Position::default());
+/// Parse arg into [`PathBuf`] and position, if one is given.
+pub(crate) fn parse_file(s: &str) -> (PathBuf, Option<Position>) {
+    let def = || (PathBuf::from(s), None);
     if Path::new(s).exists() {
         return def();
     }
     split_path_row_col(s)
         .or_else(|| split_path_row(s))
+        .map(|(path, pos)| (path, Some(pos)))
         .unwrap_or_else(def)
 }
 
@@ -1,5 +1,8 @@
 pub(crate) mod dap;
+pub(crate) mod http;
 pub(crate) mod lsp;
+pub(crate) mod tags;
+pub(crate) mod test_runner;
 pub(crate) mod typed;
 
 pub use dap::*;
@@ -10,14 +13,16 @@
 pub use typed::*;
 
 use helix_core::{
-    char_idx_at_visual_offset, comment,
+    cell, char_idx_at_visual_offset,
+    chars::char_is_word,
+    comment,
     doc_formatter::TextFormat,
     encoding, find_first_non_whitespace_char, find_workspace, graphemes,
     history::UndoKind,
     increment, indent,
     indent::IndentStyle,
     line_ending::{get_line_ending_of_str, line_end_char_index, str_is_line_ending},
-    match_brackets,
+    link, markup, match_brackets,
     movement::{self, move_vertically_visual, Direction},
     object, pos_at_coords,
     regex::{self, Regex, RegexBuilder},
@@ -32,11 +37,14 @@
 };
 use helix_view::{
     clipboard::ClipboardType,
-    document::{FormatterError, Mode, SCRATCH_BUFFER_NAME},
-    editor::{Action, Motion},
+    document::{FormatterError, Mode, SavePoint, SCRATCH_BUFFER_NAME},
+    editor::{Action, LastPaste, Motion},
+    global_jumplist::{GlobalJump, GlobalJumpList},
     info::Info,
-    input::KeyEvent,
+    input::{Event, KeyEvent},
     keyboard::KeyCode,
+    quickfix::{QuickfixEntry, QuickfixList},
+    recent_files::RecentFile,
     tree,
     view::View,
     Document, DocumentId, Editor, ViewId,
@@ -49,17 +57,18 @@
 
 use crate::{
     args,
-    compositor::{self, Component, Compositor},
-    filter_picker_entry,
+    compositor::{self, Component, Compositor, EventResult},
+    extra_walk_roots, filter_picker_entry,
     job::Callback,
     keymap::ReverseKeymap,
     ui::{
         self, editor::InsertEvent, lsp::SignatureHelp, overlay::overlayed, FilePicker, Picker,
         Popup, Prompt, PromptEvent,
     },
+    workspace_index,
 };
 
-use crate::job::{self, Jobs};
+use crate::job::{self, Job, Jobs};
 use futures_util::StreamExt;
 use std::{collections::HashMap, fmt, future::Future};
 use std::{collections::HashSet, num::NonZeroUsize};
@@ -67,6 +76,7 @@
 use std::{
     borrow::Cow,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use once_cell::sync::Lazy;
@@ -114,7 +124,9 @@ pub fn callback<T, F>(
         T: for<'de> serde::Deserialize<'de> + Send + 'static,
         F: FnOnce(&mut Editor, &mut Compositor, T) + Send + 'static,
     {
-        self.jobs.callback(make_job_callback(call, callback));
+        self.jobs.add(
+            Job::with_callback(make_job_callback(call, callback)).named("language server request"),
+        );
     }
 
     /// Returns 1 if no explicit count was provided
@@ -186,15 +198,20 @@ pub fn execute(&self, cx: &mut Context) {
         match &self {
             Self::Typable { name, args, doc: _ } => {
                 let args: Vec<Cow<str>> = args.iter().map(Cow::from).collect();
-                if let Some(command) = typed::TYPABLE_COMMAND_MAP.get(name.as_str()) {
-                    let mut cx = compositor::Context {
-                        editor: cx.editor,
-                        jobs: cx.jobs,
-                        scroll: None,
-                    };
-                    if let Err(e) = (command.fun)(&mut cx, &args[..], PromptEvent::Validate) {
-                        cx.editor.set_error(format!("{}", e));
+                let mut cx = compositor::Context {
+                    editor: cx.editor,
+                    jobs: cx.jobs,
+                    scroll: None,
+                };
+                let result = typed::expand_args(name, &args).and_then(|args| {
+                    match typed::TYPABLE_COMMAND_MAP.get(name.as_str()) {
+                        Some(command) => (command.fun)(&mut cx, &args[..], PromptEvent::Validate),
+                        // Not a builtin command, the keymap was checked against the aliases.
+                        None => typed::execute_alias(&mut cx, name, &args),
                     }
+                });
+                if let Err(e) = result {
+                    cx.editor.set_error(format!("{}", e));
                 }
             }
             Self::Static { fun, .. } => (fun)(cx),
@@ -267,6 +284,7 @@ pub fn doc(&self) -> &str {
         select_regex, "Select all regex matches inside selections",
         split_selection, "Split selections on regex matches",
         split_selection_on_newline, "Split selection on newlines",
+        split_selection_on_textobject, "Split selection on tree-sitter text objects",
         merge_consecutive_selections, "Merge consecutive selections",
         search, "Search for regex pattern",
         rsearch, "Reverse search for regex pattern",
@@ -277,6 +295,7 @@ pub fn doc(&self) -> &str {
         search_selection, "Use current selection as search pattern",
         make_search_word_bounded, "Modify current search to make it word bounded",
         global_search, "Global search in workspace folder",
+        todo_picker, "Open picker of the tagged comments like TODO in workspace folder",
         extend_line, "Select current line, if already selected, extend to another line based on the anchor",
         extend_line_below, "Select current line, if already selected, extend to next line",
         extend_line_above, "Select current line, if already selected, extend to previous line",
@@ -296,8 +315,15 @@ pub fn doc(&self) -> &str {
         file_picker_in_current_buffer_directory, "Open file picker at current buffers's directory",
         file_picker_in_current_directory, "Open file picker at current working directory",
         code_action, "Perform code action",
+        code_action_quickfix, "Perform quickfix code action",
+        code_action_refactor, "Perform refactor code action",
         buffer_picker, "Open buffer picker",
+        recent_files_picker, "Open recent files picker",
         jumplist_picker, "Open jumplist picker",
+        global_jumplist_picker, "Open global jumplist picker",
+        quickfix_picker, "Open quickfix list picker",
+        goto_next_quickfix, "Goto next quickfix list entry",
+        goto_prev_quickfix, "Goto previous quickfix list entry",
         symbol_picker, "Open symbol picker",
         select_references_to_symbol_under_cursor, "Select symbol references",
         workspace_symbol_picker, "Open workspace symbol picker",
@@ -312,6 +338,8 @@ pub fn doc(&self) -> &str {
         select_mode, "Enter selection extend mode",
         exit_select_mode, "Exit selection mode",
         goto_definition, "Goto definition",
+        goto_definition_hsplit, "Goto definition (hsplit)",
+        goto_definition_vsplit, "Goto definition (vsplit)",
         goto_declaration, "Goto declaration",
         add_newline_above, "Add newline above",
         add_newline_below, "Add newline below",
@@ -322,13 +350,22 @@ pub fn doc(&self) -> &str {
         goto_file, "Goto files in selection",
         goto_file_hsplit, "Goto files in selection (hsplit)",
         goto_file_vsplit, "Goto files in selection (vsplit)",
+        goto_link, "Follow link under cursor",
+        open_link, "Open link under cursor with the system's default application",
         goto_reference, "Goto references",
+        goto_reference_hsplit, "Goto references (hsplit)",
+        goto_reference_vsplit, "Goto references (vsplit)",
+        peek_definition, "Peek definition in a popup",
+        peek_reference, "Peek references in a popup",
         goto_window_top, "Goto window top",
         goto_window_center, "Goto window center",
         goto_window_bottom, "Goto window bottom",
         goto_last_accessed_file, "Goto last accessed file",
         goto_last_modified_file, "Goto last modified file",
         goto_last_modification, "Goto last modification",
+        goto_prev_change_location, "Goto previous location in the change list",
+        goto_next_change_location, "Goto next location in the change list",
+        goto_last_change_location, "Goto last change location in any buffer",
         goto_line, "Goto line",
         goto_last_line, "Goto last line",
         goto_first_diag, "Goto first diagnostic",
@@ -372,7 +409,12 @@ pub fn doc(&self) -> &str {
         replace_selections_with_clipboard, "Replace selections by clipboard content",
         replace_selections_with_primary_clipboard, "Replace selections by primary clipboard",
         paste_after, "Paste after selection",
+        cycle_paste, "Replace the last paste with an older yank",
+        yank_ring_picker, "Open yank ring picker",
+        jobs_picker, "Open picker of the running background jobs",
         paste_before, "Paste before selection",
+        paste_after_reindent, "Paste after selection, re-indenting the pasted lines",
+        paste_before_reindent, "Paste before selection, re-indenting the pasted lines",
         paste_clipboard_after, "Paste clipboard after selections",
         paste_clipboard_before, "Paste clipboard before selections",
         paste_primary_clipboard_after, "Paste primary clipboard after selections",
@@ -390,6 +432,8 @@ pub fn doc(&self) -> &str {
         completion, "Invoke completion popup",
         hover, "Show docs for item under cursor",
         toggle_comments, "Comment/uncomment selections",
+        toggle_checkbox, "Check/uncheck the task list items in selections",
+        renumber_list, "Renumber the ordered list around the cursor",
         rotate_selections_forward, "Rotate selections forward",
         rotate_selections_backward, "Rotate selections backward",
         rotate_selection_contents_forward, "Rotate selection contents forward",
@@ -401,6 +445,8 @@ pub fn doc(&self) -> &str {
         jump_forward, "Jump forward on jumplist",
         jump_backward, "Jump backward on jumplist",
         save_selection, "Save current selection to jumplist",
+        undo_selection, "Undo selection change",
+        redo_selection, "Redo selection change",
         jump_view_right, "Jump to right split",
         jump_view_left, "Jump to left split",
         jump_view_up, "Jump to split above",
@@ -426,7 +472,7 @@ pub fn doc(&self) -> &str {
         align_view_bottom, "Align view bottom",
         scroll_up, "Scroll view up",
         scroll_down, "Scroll view down",
-        match_brackets, "Goto matching bracket",
+        match_brackets, "Goto matching bracket, keyword or tag",
         surround_add, "Surround add",
         surround_replace, "Surround replace",
         surround_delete, "Surround delete",
@@ -442,6 +488,8 @@ pub fn doc(&self) -> &str {
         goto_prev_comment, "Goto previous comment",
         goto_next_test, "Goto next test",
         goto_prev_test, "Goto previous test",
+        goto_next_cell, "Goto next code cell",
+        goto_prev_cell, "Goto previous code cell",
         goto_next_paragraph, "Goto next paragraph",
         goto_prev_paragraph, "Goto previous paragraph",
         dap_launch, "Launch debug target",
@@ -472,6 +520,7 @@ pub fn doc(&self) -> &str {
         record_macro, "Record macro",
         replay_macro, "Replay macro",
         command_palette, "Open command palette",
+        theme_picker, "Open theme picker",
     );
 }
 
@@ -508,14 +557,16 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
             let args = typable_command
                 .map(|s| s.to_owned())
                 .collect::<Vec<String>>();
-            typed::TYPABLE_COMMAND_MAP
+            // Unknown names may be command aliases, which are only known once the whole
+            // config is loaded. The config checks them, see `Config::load`.
+            let name = typed::TYPABLE_COMMAND_MAP
                 .get(name)
-                .map(|cmd| MappableCommand::Typable {
-                    name: cmd.name.to_owned(),
-                    doc: format!(":{} {:?}", cmd.name, args),
-                    args,
-                })
-                .ok_or_else(|| anyhow!("No TypableCommand named '{}'", s))
+                .map_or(name, |cmd| cmd.name);
+            Ok(MappableCommand::Typable {
+                name: name.to_owned(),
+                doc: format!(":{} {:?}", name, args),
+                args,
+            })
         } else {
             MappableCommand::STATIC_COMMAND_LIST
                 .iter()
@@ -1089,7 +1140,7 @@ fn goto_file_start(cx: &mut Context) {
             .selection(view.id)
             .clone()
             .transform(|range| range.put_cursor(text, 0, cx.editor.mode == Mode::Select));
-        push_jump(view, doc);
+        push_jump(view, doc, &mut cx.editor.global_jumps);
         doc.set_selection(view.id, selection);
     }
 }
@@ -1102,7 +1153,7 @@ fn goto_file_end(cx: &mut Context) {
         .selection(view.id)
         .clone()
         .transform(|range| range.put_cursor(text, pos, cx.editor.mode == Mode::Select));
-    push_jump(view, doc);
+    push_jump(view, doc, &mut cx.editor.global_jumps);
     doc.set_selection(view.id, selection);
 }
 
@@ -1150,6 +1201,22 @@ fn goto_file_impl(cx: &mut Context, action: Action) {
                 .to_string(),
         );
     }
+
+    // Help pages link to each other with `|topic|`.
+    if doc.readonly {
+        if let [link] = &paths[..] {
+            if let Some(topic) = crate::help::link_target(link.trim()) {
+                let topic = topic.to_string();
+                cx.callback = Some(Box::new(move |compositor, cx| {
+                    if let Err(err) = crate::help::open(cx.editor, compositor, &topic) {
+                        cx.editor.set_error(err.to_string());
+                    }
+                }));
+                return;
+            }
+        }
+    }
+
     for sel in paths {
         let p = sel.trim();
         if !p.is_empty() {
@@ -1160,6 +1227,108 @@ fn goto_file_impl(cx: &mut Context, action: Action) {
     }
 }
 
+/// Returns the target of the link under the primary cursor, preferring the links reported by
+/// the language server over the URLs and paths found in the text.
+fn link_under_cursor(editor: &Editor) -> Option<String> {
+    let (view, doc) = current_ref!(editor);
+    let text = doc.text().slice(..);
+    let pos = doc.selection(view.id).primary().cursor(text);
+    doc.document_links()
+        .and_then(|links| links.iter().find(|link| link.range.contains(&pos)).cloned())
+        .or_else(|| link::link_at(text, pos))
+        .map(|link| link.target)
+}
+
+/// Resolves a link to a file. Relative paths are relative to the directory of the current
+/// document. Returns `None` for links to anything but files.
+fn link_path(editor: &Editor, target: &str) -> Option<PathBuf> {
+    if target.starts_with("file://") {
+        return helix_lsp::Url::parse(target).ok()?.to_file_path().ok();
+    }
+    if target.contains("://") || target.starts_with("mailto:") {
+        return None;
+    }
+    let path = helix_core::path::expand_tilde(Path::new(target));
+    if path.is_absolute() {
+        return Some(path);
+    }
+    let dir = doc!(editor)
+        .path()
+        .and_then(|path| path.parent())
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::current_dir().ok())?;
+    Some(helix_core::path::get_normalized_path(&dir.join(path)))
+}
+
+/// Opens `target` with the default application of the system, like the browser for URLs.
+fn open_external(editor: &mut Editor, target: &str) {
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    // Unlike `cmd /C start`, this doesn't interpret `&` and `^` in URLs.
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = std::process::Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    };
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let mut command = std::process::Command::new("xdg-open");
+
+    let child = command
+        .arg(target)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+    match child {
+        Ok(mut child) => {
+            // Reap the opener once it exits.
+            std::thread::spawn(move || child.wait());
+            editor.set_status(format!("Opened {}", target));
+        }
+        Err(err) => editor.set_error(format!("Failed to open {}: {}", target, err)),
+    }
+}
+
+/// Follows the link under the cursor: files are opened in the editor, anything else with the
+/// default application of the system.
+fn goto_link(cx: &mut Context) {
+    let target = match link_under_cursor(cx.editor) {
+        Some(target) => target,
+        None => {
+            cx.editor.set_error("No link under the cursor");
+            return;
+        }
+    };
+    match link_path(cx.editor, &target) {
+        Some(path) => {
+            let (view, doc) = current!(cx.editor);
+            push_jump(view, doc, &mut cx.editor.global_jumps);
+            if let Err(err) = cx.editor.open(&path, Action::Replace) {
+                cx.editor
+                    .set_error(format!("Open file {} failed: {}", path.display(), err));
+            }
+        }
+        None => open_external(cx.editor, &target),
+    }
+}
+
+/// Opens the link under the cursor with the default application of the system, even if it
+/// is a file.
+fn open_link(cx: &mut Context) {
+    let target = match link_under_cursor(cx.editor) {
+        Some(target) => target,
+        None => {
+            cx.editor.set_error("No link under the cursor");
+            return;
+        }
+    };
+    match link_path(cx.editor, &target) {
+        Some(path) => open_external(cx.editor, &path.to_string_lossy()),
+        None => open_external(cx.editor, &target),
+    }
+}
+
 fn extend_word_impl<F>(cx: &mut Context, extend_fn: F)
 where
     F: Fn(RopeSlice, Range, usize) -> Range,
@@ -1714,6 +1883,69 @@ fn split_selection_on_newline(cx: &mut Context) {
     doc.set_selection(view.id, selection);
 }
 
+fn split_selection_on_textobject(cx: &mut Context) {
+    cx.on_next_key(move |cx, event| {
+        cx.editor.autoinfo = None;
+        let object_name = match event.char() {
+            Some('t') => "class",
+            Some('f') => "function",
+            Some('a') => "parameter",
+            Some('c') => "comment",
+            Some('T') => "test",
+            _ => return,
+        };
+
+        let (view, doc) = current!(cx.editor);
+        let (lang_config, syntax) = match doc.language_config().zip(doc.syntax()) {
+            Some(t) => t,
+            None => {
+                cx.editor
+                    .set_status("Syntax-tree is not available in current buffer");
+                return;
+            }
+        };
+        let text = doc.text().slice(..);
+        let selection = doc.selection(view.id);
+
+        let mut ranges = SmallVec::with_capacity(selection.len());
+        let mut primary_index = 0;
+        for (i, range) in selection.iter().enumerate() {
+            if i == selection.primary_index() {
+                primary_index = ranges.len();
+            }
+            let objects = textobject::textobjects_treesitter_in_range(
+                text,
+                *range,
+                textobject::TextObject::Inside,
+                object_name,
+                syntax.tree().root_node(),
+                lang_config,
+            );
+            if objects.is_empty() {
+                ranges.push(*range);
+            } else {
+                ranges.extend(
+                    objects
+                        .into_iter()
+                        .map(|object| object.with_direction(range.direction())),
+                );
+            }
+        }
+
+        let selection = Selection::new(ranges, primary_index);
+        doc.set_selection(view.id, selection);
+    });
+
+    let help_text = [
+        ("t", "Type definition (tree-sitter)"),
+        ("f", "Function (tree-sitter)"),
+        ("a", "Argument/parameter (tree-sitter)"),
+        ("c", "Comment (tree-sitter)"),
+        ("T", "Test (tree-sitter)"),
+    ];
+    cx.editor.autoinfo = Some(Info::new("Split selection on", &help_text));
+}
+
 fn merge_consecutive_selections(cx: &mut Context) {
     let (view, doc) = current!(cx.editor);
     let selection = doc.selection(view.id).clone().merge_consecutive_ranges();
@@ -1970,18 +2202,21 @@ fn make_search_word_bounded(cx: &mut Context) {
 }
 
 fn global_search(cx: &mut Context) {
-    #[derive(Debug)]
+    #[derive(Debug, Clone)]
     struct FileResult {
         path: PathBuf,
         /// 0 indexed lines
         line_num: usize,
+        /// The trimmed matched line.
+        text: String,
     }
 
     impl FileResult {
-        fn new(path: &Path, line_num: usize) -> Self {
+        fn new(path: &Path, line_num: usize, text: &str) -> Self {
             Self {
                 path: path.to_path_buf(),
                 line_num,
+                text: text.trim().to_string(),
             }
         }
     }
@@ -2006,11 +2241,15 @@ fn format(&self, current_path: &Self::Data) -> Row {
     }
 
     let (all_matches_sx, all_matches_rx) = tokio::sync::mpsc::unbounded_channel::<FileResult>();
+    // The matches are streamed into the picker as they are found too.
+    let (picker_matches_sx, mut picker_matches_rx) =
+        tokio::sync::mpsc::unbounded_channel::<FileResult>();
     let config = cx.editor.config();
     let smart_case = config.search.smart_case;
     let file_picker_config = config.file_picker.clone();
 
     let reg = cx.register.unwrap_or('/');
+    let trigram_index = config.search.index.then(|| cx.editor.trigram_index.clone());
 
     let completions = search_completions(cx, Some(reg));
     ui::regex_prompt(
@@ -2024,11 +2263,19 @@ fn format(&self, current_path: &Self::Data) -> Row {
                 .map(|comp| (0.., std::borrow::Cow::Owned(comp.clone())))
                 .collect()
         },
-        move |_editor, regex, event| {
+        move |editor, regex, event| {
             if event != PromptEvent::Validate {
                 return;
             }
 
+            // Files the workspace index knows can't match, unless they changed since they
+            // were indexed. The index isn't waited for while it is being updated.
+            let non_matching = trigram_index
+                .as_ref()
+                .and_then(|index| Some(index.try_lock()?.non_matching(regex.as_str())))
+                .unwrap_or_default();
+            let non_matching = Arc::new(non_matching);
+
             if let Ok(matcher) = RegexMatcherBuilder::new()
                 .case_smart(smart_case)
                 .build(regex.as_str())
@@ -2044,24 +2291,56 @@ fn format(&self, current_path: &Self::Data) -> Row {
                     .canonicalize()
                     .unwrap_or_else(|_| search_root.clone());
 
-                WalkBuilder::new(search_root)
-                    .hidden(file_picker_config.hidden)
-                    .parents(file_picker_config.parents)
-                    .ignore(file_picker_config.ignore)
-                    .follow_links(file_picker_config.follow_symlinks)
-                    .git_ignore(file_picker_config.git_ignore)
-                    .git_global(file_picker_config.git_global)
-                    .git_exclude(file_picker_config.git_exclude)
-                    .max_depth(file_picker_config.max_depth)
-                    .filter_entry(move |entry| {
-                        filter_picker_entry(entry, &absolute_root, dedup_symlinks)
-                    })
-                    .build_parallel()
-                    .run(|| {
+                // Search the roots added to the workspace too.
+                let extra_roots: Vec<_> = extra_walk_roots(&search_root, &editor.workspace_roots)
+                    .into_iter()
+                    .cloned()
+                    .collect();
+                let mut absolute_roots = vec![absolute_root];
+                absolute_roots.extend(extra_roots.iter().cloned());
+
+                let file_picker_config = file_picker_config.clone();
+                let all_matches_sx = all_matches_sx.clone();
+                let picker_matches_sx = picker_matches_sx.clone();
+                let redraw = editor.redraw_handle.0.clone();
+                let trigram_index = trigram_index.clone();
+                // The search stops once the job collecting its matches is cancelled.
+                tokio::task::spawn_blocking(move || {
+                    let mut walk_builder = WalkBuilder::new(search_root);
+                    for root in extra_roots {
+                        walk_builder.add(root);
+                    }
+                    walk_builder
+                        .hidden(file_picker_config.hidden)
+                        .parents(file_picker_config.parents)
+                        .ignore(file_picker_config.ignore)
+                        .follow_links(file_picker_config.follow_symlinks)
+                        .git_ignore(file_picker_config.git_ignore)
+                        .git_global(file_picker_config.git_global)
+                        .git_exclude(file_picker_config.git_exclude)
+                        .max_depth(file_picker_config.max_depth)
+                        .filter_entry(move |entry| {
+                            absolute_roots
+                                .iter()
+                                .all(|root| filter_picker_entry(entry, root, dedup_symlinks))
+                        });
+
+                    // Files searched are brought up to date in the index after the search.
+                    let (searched_sx, searched_rx) = std::sync::mpsc::channel::<PathBuf>();
+
+                    walk_builder.build_parallel().run(|| {
                         let mut searcher = searcher.clone();
                         let matcher = matcher.clone();
                         let all_matches_sx = all_matches_sx.clone();
+                        let picker_matches_sx = picker_matches_sx.clone();
+                        let redraw = redraw.clone();
+                        let non_matching = non_matching.clone();
+                        let searched_sx = trigram_index.is_some().then(|| searched_sx.clone());
                         Box::new(move |entry: Result<DirEntry, ignore::Error>| -> WalkState {
+                            if all_matches_sx.is_closed() {
+                                return WalkState::Quit;
+                            }
+
                             let entry = match entry {
                                 Ok(entry) => entry,
                                 Err(_) => return WalkState::Continue,
@@ -2073,15 +2352,28 @@ fn format(&self, current_path: &Self::Data) -> Row {
                                 _ => return WalkState::Continue,
                             };
 
+                            if let Some(indexed) = non_matching.get(entry.path()) {
+                                let modified =
+                                    entry.metadata().ok().and_then(|meta| meta.modified().ok());
+                                if modified.as_ref() == Some(indexed) {
+                                    return WalkState::Continue;
+                                }
+                            }
+                            if let Some(searched_sx) = &searched_sx {
+                                let _ = searched_sx.send(entry.path().to_path_buf());
+                            }
+
                             let result = searcher.search_path(
                                 &matcher,
                                 entry.path(),
-                                sinks::UTF8(|line_num, _| {
-                                    all_matches_sx
-                                        .send(FileResult::new(entry.path(), line_num as usize - 1))
-                                        .unwrap();
-
-                                    Ok(true)
+                                sinks::UTF8(|line_num, line| {
+                                    let result =
+                                        FileResult::new(entry.path(), line_num as usize - 1, line);
+                                    // The picker may have been closed before the search ends.
+                                    if picker_matches_sx.send(result.clone()).is_ok() {
+                                        redraw.notify_one();
+                                    }
+                                    Ok(all_matches_sx.send(result).is_ok())
                                 }),
                             );
 
@@ -2095,6 +2387,17 @@ fn format(&self, current_path: &Self::Data) -> Row {
                             WalkState::Continue
                         })
                     });
+
+                    // Files skipped by the index are unchanged, only the searched ones may
+                    // need to be indexed again. The index is saved when the editor exits.
+                    drop(searched_sx);
+                    if let Some(trigram_index) = trigram_index {
+                        let mut trigram_index = trigram_index.lock();
+                        for path in searched_rx.try_iter() {
+                            trigram_index.update_file(&path);
+                        }
+                    }
+                });
             } else {
                 // Otherwise do nothing
                 // log::warn!("Global Search Invalid Pattern")
@@ -2104,20 +2407,20 @@ fn format(&self, current_path: &Self::Data) -> Row {
 
     let current_path = doc_mut!(cx.editor).path().cloned();
 
+    // The picker is shown as soon as the first match is found.
     let show_picker = async move {
-        let all_matches: Vec<FileResult> =
-            UnboundedReceiverStream::new(all_matches_rx).collect().await;
+        let first_match = picker_matches_rx.recv().await;
         let call: job::Callback = Callback::EditorCompositor(Box::new(
-            move |editor: &mut Editor, compositor: &mut Compositor| {
-                if all_matches.is_empty() {
-                    editor.set_status("No matches found");
-                    return;
-                }
+            move |_editor: &mut Editor, compositor: &mut Compositor| {
+                let first_match = match first_match {
+                    Some(first_match) => first_match,
+                    None => return,
+                };
 
                 let picker = FilePicker::new(
-                    all_matches,
+                    vec![first_match],
                     current_path,
-                    move |cx, FileResult { path, line_num }, action| {
+                    move |cx, FileResult { path, line_num, .. }, action| {
                         match cx.editor.open(path, action) {
                             Ok(_) => {}
                             Err(e) => {
@@ -2143,16 +2446,204 @@ fn format(&self, current_path: &Self::Data) -> Row {
                         doc.set_selection(view.id, Selection::single(start, end));
                         align_view(doc, view, Align::Center);
                     },
-                    |_editor, FileResult { path, line_num }| {
+                    |_editor, FileResult { path, line_num, .. }| {
                         Some((path.clone().into(), Some((*line_num, *line_num))))
                     },
-                );
+                )
+                .with_option_stream(picker_matches_rx);
                 compositor.push(Box::new(overlayed(picker)));
             },
         ));
         Ok(call)
     };
     cx.jobs.callback(show_picker);
+
+    let collect_matches = async move {
+        let all_matches: Vec<FileResult> =
+            UnboundedReceiverStream::new(all_matches_rx).collect().await;
+        let call: job::Callback = Callback::Editor(Box::new(move |editor: &mut Editor| {
+            if all_matches.is_empty() {
+                editor.set_status("No matches found");
+                return;
+            }
+
+            let entries = all_matches
+                .into_iter()
+                .map(|result| QuickfixEntry {
+                    path: result.path,
+                    line: result.line_num,
+                    column: 0,
+                    text: result.text,
+                    offset_encoding: None,
+                })
+                .collect();
+            editor.quickfix = QuickfixList::new("global search", entries);
+        }));
+        Ok(call)
+    };
+    cx.jobs
+        .add(Job::with_callback(collect_matches).named("global search"));
+}
+
+/// Collects the comments tagged with one of the `todo-tags` in the workspace into a picker,
+/// grouped by tag.
+fn todo_picker(cx: &mut Context) {
+    struct TodoItem {
+        /// Index of the tag in `todo-tags`.
+        tag: usize,
+        path: PathBuf,
+        /// 0 indexed line
+        line_num: usize,
+        text: String,
+    }
+
+    impl ui::menu::Item for TodoItem {
+        /// The tags.
+        type Data = Vec<String>;
+
+        fn format(&self, tags: &Self::Data) -> Row {
+            let path = helix_core::path::get_relative_path(&self.path);
+            format!(
+                "{} {}:{} {}",
+                tags[self.tag],
+                path.display(),
+                self.line_num + 1,
+                self.text
+            )
+            .into()
+        }
+    }
+
+    let config = cx.editor.config();
+    let tags = config.todo_tags.clone();
+    let file_picker_config = config.file_picker.clone();
+    if tags.is_empty() {
+        cx.editor.set_error("No todo-tags configured");
+        return;
+    }
+    let pattern = format!(
+        r"\b({})\b",
+        tags.iter()
+            .map(|tag| regex::escape(tag))
+            .collect::<Vec<_>>()
+            .join("|")
+    );
+    let (regex, matcher) = match (
+        Regex::new(&pattern),
+        RegexMatcherBuilder::new().build(&pattern),
+    ) {
+        (Ok(regex), Ok(matcher)) => (regex, matcher),
+        _ => {
+            cx.editor.set_error("Invalid todo-tags");
+            return;
+        }
+    };
+    let root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("./"));
+    let syn_loader = cx.editor.syn_loader.clone();
+
+    let show_picker = async move {
+        let (tags, items) = tokio::task::spawn_blocking(move || {
+            let mut searcher = SearcherBuilder::new()
+                .binary_detection(BinaryDetection::quit(b'\x00'))
+                .build();
+            let mut files: Vec<_> = workspace_index::workspace_files(&root, &file_picker_config)
+                .into_iter()
+                .collect();
+            files.sort();
+
+            let mut items = Vec::new();
+            for path in files {
+                // Only tags following the comment token of the file's language are collected,
+                // so that the tags mentioned in code and strings are left out.
+                let comment_token = match syn_loader
+                    .language_config_for_file_name(&path)
+                    .and_then(|config| config.comment_token.clone())
+                {
+                    Some(token) => token,
+                    None => continue,
+                };
+                let result = searcher.search_path(
+                    &matcher,
+                    &path,
+                    sinks::UTF8(|line_num, line| {
+                        let tag = regex.captures_iter(line).find_map(|captures| {
+                            let tag = captures.get(1)?;
+                            line[..tag.start()].contains(&comment_token).then(|| tag)
+                        });
+                        if let Some(tag) = tag {
+                            items.push(TodoItem {
+                                tag: tags.iter().position(|t| t == tag.as_str()).unwrap_or(0),
+                                path: path.clone(),
+                                line_num: line_num as usize - 1,
+                                text: line[tag.end()..]
+                                    .trim_start_matches(|ch: char| ch == ':' || ch.is_whitespace())
+                                    .trim_end()
+                                    .to_string(),
+                            });
+                        }
+                        Ok(true)
+                    }),
+                );
+                if let Err(err) = result {
+                    log::error!("Todo search error: {}, {}", path.display(), err);
+                }
+            }
+            // The sort is stable, so the items of a tag stay ordered by path and line.
+            items.sort_by_key(|item| item.tag);
+            (tags, items)
+        })
+        .await?;
+
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                editor.todo_counts = tags
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, tag)| {
+                        let count = items.iter().filter(|item| item.tag == i).count();
+                        (count > 0).then(|| (tag.clone(), count))
+                    })
+                    .collect();
+                if items.is_empty() {
+                    editor.set_status("No tagged comments found");
+                    return;
+                }
+                let counts: Vec<_> = editor
+                    .todo_counts
+                    .iter()
+                    .map(|(tag, count)| format!("{} {}", count, tag))
+                    .collect();
+                editor.set_status(counts.join(", "));
+
+                let picker = FilePicker::new(
+                    items,
+                    tags,
+                    |cx, TodoItem { path, line_num, .. }, action| {
+                        if let Err(e) = cx.editor.open(path, action) {
+                            cx.editor.set_error(format!(
+                                "Failed to open file '{}': {}",
+                                path.display(),
+                                e
+                            ));
+                            return;
+                        }
+                        let (view, doc) = current!(cx.editor);
+                        let text = doc.text();
+                        let pos = text.line_to_char((*line_num).min(text.len_lines() - 1));
+                        doc.set_selection(view.id, Selection::point(pos));
+                        align_view(doc, view, Align::Center);
+                    },
+                    |_editor, TodoItem { path, line_num, .. }| {
+                        Some((path.clone().into(), Some((*line_num, *line_num))))
+                    },
+                );
+                compositor.push(Box::new(overlayed(picker)));
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs
+        .add(Job::with_callback(show_picker).named("todo search"));
 }
 
 enum Extend {
@@ -2285,7 +2776,7 @@ fn delete_selection_impl(cx: &mut Context, op: Operation) {
         let text = doc.text().slice(..);
         let values: Vec<String> = selection.fragments(text).map(Cow::into_owned).collect();
         let reg_name = cx.register.unwrap_or('"');
-        cx.editor.registers.write(reg_name, values);
+        cx.editor.registers.yank(reg_name, values);
     };
 
     // then delete
@@ -2301,6 +2792,7 @@ fn delete_selection_impl(cx: &mut Context, op: Operation) {
         }
         Operation::Change => {
             enter_insert_mode(cx);
+            linked_editing_ranges(cx);
         }
     }
 }
@@ -2367,6 +2859,152 @@ fn enter_insert_mode(cx: &mut Context) {
     cx.editor.mode = Mode::Insert;
 }
 
+/// Node kinds of the opening and closing tags of HTML and JSX elements.
+const TAG_KINDS: &[&str] = &[
+    "start_tag",
+    "end_tag",
+    "jsx_opening_element",
+    "jsx_closing_element",
+];
+
+/// The names of the opening and closing tags of the element whose tag name is at `pos`, found
+/// with the tree-sitter grammar.
+fn tag_name_ranges(doc: &Document, pos: usize) -> Option<Vec<Range>> {
+    fn named_children<'a>(node: Node<'a>) -> impl Iterator<Item = Node<'a>> {
+        (0..node.named_child_count()).filter_map(move |i| node.named_child(i))
+    }
+
+    let text = doc.text().slice(..);
+    let root = doc.syntax()?.tree().root_node();
+    let is_tag_name = |node: &Node| {
+        node.is_named()
+            && node
+                .parent()
+                .map_or(false, |parent| TAG_KINDS.contains(&parent.kind()))
+    };
+
+    let byte = text.char_to_byte(pos);
+    // the cursor may also sit right after the name, when appending to it
+    let name = [byte, byte.saturating_sub(1)]
+        .into_iter()
+        .filter_map(|byte| root.descendant_for_byte_range(byte, byte))
+        .find(is_tag_name)?;
+    let tag = name.parent()?;
+    let element = tag.parent()?;
+
+    let other_name = named_children(element)
+        .filter(|other| other.id() != tag.id() && TAG_KINDS.contains(&other.kind()))
+        .find_map(|other| named_children(other).find(|node| node.kind() == name.kind()))?;
+
+    let range = |node: Node| {
+        Range::new(
+            text.byte_to_char(node.start_byte()),
+            text.byte_to_char(node.end_byte()),
+        )
+    };
+    Some(vec![range(name), range(other_name)])
+}
+
+/// Looks up the ranges edited together with the one under the cursor, asking the language
+/// server and falling back to the tree-sitter grammar.
+fn linked_editing_ranges(cx: &mut Context) {
+    use helix_lsp::{lsp, util::lsp_range_to_range};
+
+    if !cx.editor.config().linked_editing {
+        return;
+    }
+
+    let (view, doc) = current!(cx.editor);
+    let cursor = doc
+        .selection(view.id)
+        .primary()
+        .cursor(doc.text().slice(..));
+    let request = doc.language_server().and_then(|language_server| {
+        let offset_encoding = language_server.offset_encoding();
+        let future = language_server.text_document_linked_editing_range(
+            doc.identifier(),
+            doc.position(view.id, offset_encoding),
+            None,
+        )?;
+        Some((future, offset_encoding))
+    });
+    let (future, offset_encoding) = match request {
+        Some(request) => request,
+        None => {
+            if let Some(ranges) = tag_name_ranges(doc, cursor) {
+                doc.set_linked_editing_ranges(ranges);
+            }
+            return;
+        }
+    };
+
+    let (doc_id, version) = (doc.id(), doc.version());
+    cx.callback(
+        future,
+        move |editor, _compositor, response: Option<lsp::LinkedEditingRanges>| {
+            if editor.mode != Mode::Insert {
+                return;
+            }
+            let doc = match editor.documents.get_mut(&doc_id) {
+                Some(doc) if doc.version() == version => doc,
+                _ => return,
+            };
+            let ranges = response
+                .into_iter()
+                .flat_map(|response| response.ranges)
+                .filter_map(|range| lsp_range_to_range(doc.text(), range, offset_encoding))
+                .collect();
+            doc.set_linked_editing_ranges(ranges);
+        },
+    );
+}
+
+/// Mirrors the linked editing range containing the cursor into the other linked ranges, after
+/// typing in insert mode.
+pub fn sync_linked_editing_ranges(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let ranges = doc.linked_editing_ranges();
+    if ranges.len() < 2 {
+        return;
+    }
+
+    let text = doc.text();
+    let cursor = doc.selection(view.id).primary().cursor(text.slice(..));
+    let edited = match ranges
+        .iter()
+        .find(|range| (range.from()..=range.to()).contains(&cursor))
+    {
+        Some(range) => *range,
+        None => {
+            // the cursor left the linked ranges
+            doc.set_linked_editing_ranges(Vec::new());
+            return;
+        }
+    };
+
+    let name = text.slice(edited.from()..edited.to());
+    // stop linking once the edit goes beyond a name, e.g. when adding attributes to a tag
+    if name
+        .chars()
+        .any(|ch| !(ch.is_alphanumeric() || "-_.:".contains(ch)))
+    {
+        doc.set_linked_editing_ranges(Vec::new());
+        return;
+    }
+
+    let name = Tendril::from(name.to_string());
+    let changes: Vec<_> = ranges
+        .iter()
+        .filter(|range| **range != edited && text.slice(range.from()..range.to()) != name)
+        .map(|range| (range.from(), range.to(), Some(name.clone())))
+        .collect();
+    if changes.is_empty() {
+        return;
+    }
+    let transaction = Transaction::change(text, changes.into_iter());
+    doc.apply(&transaction, view.id);
+}
+
 // inserts at the start of each selection
 fn insert_mode(cx: &mut Context) {
     enter_insert_mode(cx);
@@ -2384,6 +3022,8 @@ fn insert_mode(cx: &mut Context) {
         .transform(|range| Range::new(range.to(), range.from()));
 
     doc.set_selection(view.id, selection);
+    // Look the linked ranges up once the cursor is where the insertion happens.
+    linked_editing_ranges(cx);
 }
 
 // inserts at the end of each selection
@@ -2416,11 +3056,12 @@ fn append_mode(cx: &mut Context) {
         )
     });
     doc.set_selection(view.id, selection);
+    linked_editing_ranges(cx);
 }
 
 fn file_picker(cx: &mut Context) {
     let root = find_workspace().0;
-    let picker = ui::file_picker(root, &cx.editor.config());
+    let picker = ui::workspace_file_picker(root, &cx.editor.workspace_roots, &cx.editor.config());
     cx.push_layer(Box::new(overlayed(picker)));
 }
 
@@ -2511,21 +3152,202 @@ fn format(&self, _data: &Self::Data) -> Row {
     cx.push_layer(Box::new(overlayed(picker)));
 }
 
-fn jumplist_picker(cx: &mut Context) {
-    struct JumpMeta {
-        id: DocumentId,
-        path: Option<PathBuf>,
-        selection: Selection,
-        text: String,
-        is_current: bool,
+fn recent_files_picker(cx: &mut Context) {
+    impl ui::menu::Item for RecentFile {
+        type Data = ();
+
+        fn format(&self, _data: &Self::Data) -> Row {
+            let path = helix_core::path::get_relative_path(&self.path);
+            Row::new([
+                path.to_string_lossy().into_owned(),
+                format!("{}", self.line + 1),
+            ])
+        }
     }
 
-    impl ui::menu::Item for JumpMeta {
+    let files: Vec<_> = cx
+        .editor
+        .recent_files
+        .iter()
+        .filter(|file| file.path.is_file())
+        .cloned()
+        .collect();
+    if files.is_empty() {
+        cx.editor.set_status("No recent files");
+        return;
+    }
+
+    let picker = FilePicker::new(
+        files,
+        (),
+        |cx, file, action| {
+            // The last cursor position is restored by `Editor::open`.
+            if let Err(err) = cx.editor.open(&file.path, action) {
+                let err = format!("unable to open \"{}\": {}", file.path.display(), err);
+                cx.editor.set_error(err);
+            }
+        },
+        |_editor, file| Some((file.path.clone().into(), Some((file.line, file.line)))),
+    );
+    cx.push_layer(Box::new(overlayed(picker)));
+}
+
+fn global_jumplist_picker(cx: &mut Context) {
+    impl ui::menu::Item for GlobalJump {
         type Data = ();
 
         fn format(&self, _data: &Self::Data) -> Row {
-            let path = self
-                .path
+            let path = helix_core::path::get_relative_path(&self.path);
+            Row::new([
+                path.to_string_lossy().into_owned(),
+                format!("{}", self.line + 1),
+                self.text.clone(),
+            ])
+        }
+    }
+
+    let jumps: Vec<_> = cx
+        .editor
+        .global_jumps
+        .iter()
+        .filter(|jump| jump.path.is_file())
+        .cloned()
+        .collect();
+    if jumps.is_empty() {
+        cx.editor.set_status("No jumps");
+        return;
+    }
+
+    let picker = FilePicker::new(
+        jumps,
+        (),
+        |cx, jump, action| {
+            let (view, doc) = current!(cx.editor);
+            push_jump(view, doc, &mut cx.editor.global_jumps);
+
+            if let Err(err) = cx.editor.open(&jump.path, action) {
+                let err = format!("unable to open \"{}\": {}", jump.path.display(), err);
+                cx.editor.set_error(err);
+                return;
+            }
+            let (view, doc) = current!(cx.editor);
+            let text = doc.text().slice(..);
+            let pos = pos_at_coords(text, jump.position(), true);
+            doc.set_selection(view.id, Selection::point(pos));
+            align_view(doc, view, Align::Center);
+        },
+        |_editor, jump| Some((jump.path.clone().into(), Some((jump.line, jump.line)))),
+    );
+    cx.push_layer(Box::new(overlayed(picker)));
+}
+
+/// Opens the file of the quickfix list `entry` and moves the cursor to it.
+pub(crate) fn jump_to_quickfix_entry(editor: &mut Editor, entry: &QuickfixEntry, action: Action) {
+    let (view, doc) = current!(editor);
+    push_jump(view, doc, &mut editor.global_jumps);
+
+    if let Err(err) = editor.open(&entry.path, action) {
+        let err = format!("unable to open \"{}\": {}", entry.path.display(), err);
+        editor.set_error(err);
+        return;
+    }
+    let (view, doc) = current!(editor);
+    let pos = entry.pos(doc.text());
+    doc.set_selection(view.id, Selection::point(pos));
+    align_view(doc, view, Align::Center);
+}
+
+/// Steps `count` entries through the quickfix list and jumps to the entry.
+pub(crate) fn goto_quickfix(editor: &mut Editor, direction: Direction, count: usize) {
+    let quickfix = &mut editor.quickfix;
+    let entry = match direction {
+        Direction::Forward => quickfix.next(count),
+        Direction::Backward => quickfix.prev(count),
+    };
+    let entry = match entry {
+        Some(entry) => entry.clone(),
+        None => {
+            editor.set_status("The quickfix list is empty");
+            return;
+        }
+    };
+    let status = format!(
+        "({}/{}) {}",
+        quickfix.index().unwrap_or_default() + 1,
+        quickfix.entries().len(),
+        entry.text
+    );
+    jump_to_quickfix_entry(editor, &entry, Action::Replace);
+    editor.set_status(status);
+}
+
+fn goto_next_quickfix(cx: &mut Context) {
+    let count = cx.count();
+    goto_quickfix(cx.editor, Direction::Forward, count);
+}
+
+fn goto_prev_quickfix(cx: &mut Context) {
+    let count = cx.count();
+    goto_quickfix(cx.editor, Direction::Backward, count);
+}
+
+/// Builds a picker over the entries of the quickfix list, which makes the picked entry the
+/// current one.
+pub(crate) fn quickfix_list_picker(editor: &Editor) -> Option<FilePicker<(usize, QuickfixEntry)>> {
+    impl ui::menu::Item for (usize, QuickfixEntry) {
+        /// Index of the current entry.
+        type Data = Option<usize>;
+
+        fn format(&self, current: &Self::Data) -> Row {
+            let (index, entry) = self;
+            let path = helix_core::path::get_relative_path(&entry.path);
+            let marker = if *current == Some(*index) { "*" } else { "" };
+            Row::new([
+                marker.to_string(),
+                format!("{}:{}", path.to_string_lossy(), entry.line + 1),
+                entry.text.clone(),
+            ])
+        }
+    }
+
+    let quickfix = &editor.quickfix;
+    if quickfix.entries().is_empty() {
+        return None;
+    }
+    let entries = quickfix.entries().iter().cloned().enumerate().collect();
+    Some(FilePicker::new(
+        entries,
+        quickfix.index(),
+        |cx, (index, entry), action| {
+            cx.editor.quickfix.select(*index);
+            jump_to_quickfix_entry(cx.editor, entry, action);
+        },
+        |_editor, (_, entry)| Some((entry.path.clone().into(), Some((entry.line, entry.line)))),
+    ))
+}
+
+fn quickfix_picker(cx: &mut Context) {
+    match quickfix_list_picker(cx.editor) {
+        Some(picker) => cx.push_layer(Box::new(overlayed(picker))),
+        None => cx.editor.set_status("The quickfix list is empty"),
+    }
+}
+
+fn jumplist_picker(cx: &mut Context) {
+    struct JumpMeta {
+        id: DocumentId,
+        path: Option<PathBuf>,
+        selection: Selection,
+        text: String,
+        is_current: bool,
+    }
+
+    impl ui::menu::Item for JumpMeta {
+        type Data = ();
+
+        fn format(&self, _data: &Self::Data) -> Row {
+            let path = self
+                .path
                 .as_deref()
                 .map(helix_core::path::get_relative_path);
             let path = match path.as_deref().and_then(Path::to_str) {
@@ -2593,10 +3415,14 @@ fn format(&self, _data: &Self::Data) -> Row {
     cx.push_layer(Box::new(overlayed(picker)));
 }
 
+/// Number of recently used commands floated to the top of the command palette.
+const RECENT_COMMANDS_LIMIT: usize = 10;
+
 impl ui::menu::Item for MappableCommand {
-    type Data = ReverseKeymap;
+    /// Bindings of the commands and the names of recently used commands.
+    type Data = (ReverseKeymap, Vec<String>);
 
-    fn format(&self, keymap: &Self::Data) -> Row {
+    fn format(&self, (keymap, _recent): &Self::Data) -> Row {
         let fmt_binding = |bindings: &Vec<Vec<KeyEvent>>| -> String {
             bindings.iter().fold(String::new(), |mut acc, bind| {
                 if !acc.is_empty() {
@@ -2620,6 +3446,19 @@ fn format(&self, keymap: &Self::Data) -> Row {
             },
         }
     }
+
+    fn filter_text(&self, _data: &Self::Data) -> Cow<str> {
+        // Match on the name and description but not on the bindings.
+        format!("{} {}", self.name(), self.doc()).into()
+    }
+
+    fn score_bonus(&self, (_keymap, recent): &Self::Data) -> i64 {
+        match recent.iter().position(|name| name == self.name()) {
+            // Large enough to rank recent commands above any other match.
+            Some(idx) => 1000 * (RECENT_COMMANDS_LIMIT - idx) as i64,
+            None => 0,
+        }
+    }
 }
 
 pub fn command_palette(cx: &mut Context) {
@@ -2638,7 +3477,42 @@ pub fn command_palette(cx: &mut Context) {
                 }
             }));
 
-            let picker = Picker::new(commands, keymap, move |cx, command, _action| {
+            // List the recently used commands first, the most recent one at the top.
+            let recent = cx.editor.recent_commands.clone();
+            commands.sort_by_key(|command| {
+                recent
+                    .iter()
+                    .position(|name| name == command.name())
+                    .unwrap_or(RECENT_COMMANDS_LIMIT)
+            });
+
+            let picker = Picker::new(commands, (keymap, recent), move |cx, command, _action| {
+                let recent = &mut cx.editor.recent_commands;
+                recent.retain(|name| name != command.name());
+                recent.insert(0, command.name().to_string());
+                recent.truncate(RECENT_COMMANDS_LIMIT);
+
+                // Typable commands that need arguments open the command prompt so they can be
+                // filled in.
+                if let MappableCommand::Typable { name, .. } = command {
+                    if typed::TYPABLE_COMMAND_MAP
+                        .get(name.as_str())
+                        .map_or(false, |command| command.args_required())
+                    {
+                        let line = format!("{} ", name);
+                        cx.jobs.callback(async move {
+                            let call: job::Callback = Callback::EditorCompositor(Box::new(
+                                move |editor: &mut Editor, compositor: &mut Compositor| {
+                                    let prompt = typed::command_prompt(editor, line);
+                                    compositor.push(Box::new(prompt))
+                                },
+                            ));
+                            Ok(call)
+                        });
+                        return;
+                    }
+                }
+
                 let mut ctx = Context {
                     register: None,
                     count: std::num::NonZeroUsize::new(1),
@@ -2669,6 +3543,35 @@ pub fn command_palette(cx: &mut Context) {
     ));
 }
 
+fn theme_picker(cx: &mut Context) {
+    let color_support = crate::color_support(cx.editor.config().true_color);
+    let load = move |editor: &Editor, name: &str| {
+        editor
+            .theme_loader
+            .load(name)
+            .map(|theme| theme.degrade(color_support))
+    };
+
+    let picker = Picker::new(
+        cx.editor.theme_loader.names(),
+        (),
+        move |cx, name, _action| match load(cx.editor, name) {
+            Ok(theme) => cx.editor.set_theme(theme),
+            Err(err) => cx
+                .editor
+                .set_error(format!("Could not load theme: {}", err)),
+        },
+    )
+    // Preview the selected theme, restoring the previous one if the picker is closed.
+    .on_selection_change(move |cx, name| {
+        if let Ok(theme) = load(cx.editor, name) {
+            cx.editor.set_theme_preview(theme);
+        }
+    })
+    .on_abort(|cx| cx.editor.unset_theme_preview());
+    cx.push_layer(Box::new(overlayed(picker)));
+}
+
 fn last_picker(cx: &mut Context) {
     // TODO: last picker does not seem to work well with buffer_picker
     cx.callback = Some(Box::new(|compositor, cx| {
@@ -2684,6 +3587,7 @@ fn last_picker(cx: &mut Context) {
 fn insert_at_line_start(cx: &mut Context) {
     goto_first_nonwhitespace(cx);
     enter_insert_mode(cx);
+    linked_editing_ranges(cx);
 }
 
 // A inserts at the end of each line with a selection
@@ -2698,6 +3602,7 @@ fn insert_at_line_end(cx: &mut Context) {
         Range::new(pos, pos)
     });
     doc.set_selection(view.id, selection);
+    linked_editing_ranges(cx);
 }
 
 // Creates an LspCallback that waits for formatting changes to be computed. When they're done,
@@ -2745,6 +3650,23 @@ async fn make_format_callback(
     Ok(call)
 }
 
+/// Writes the document without formatting it, for writes whose formatting was cancelled.
+fn write_unformatted_callback(
+    doc_id: DocumentId,
+    path: Option<PathBuf>,
+    force: bool,
+) -> job::Callback {
+    Callback::Editor(Box::new(move |editor| {
+        if !editor.documents.contains_key(&doc_id) {
+            return;
+        }
+        match editor.save(doc_id, path, force) {
+            Ok(()) => editor.set_status("Cancelled formatting, writing unformatted"),
+            Err(err) => editor.set_error(format!("Error saving: {}", err)),
+        }
+    }))
+}
+
 #[derive(PartialEq, Eq)]
 pub enum Open {
     Below,
@@ -2836,15 +3758,16 @@ fn normal_mode(cx: &mut Context) {
 }
 
 // Store a jump on the jumplist.
-fn push_jump(view: &mut View, doc: &Document) {
+fn push_jump(view: &mut View, doc: &Document, global_jumps: &mut GlobalJumpList) {
     let jump = (doc.id(), doc.selection(view.id).clone());
+    global_jumps.push(doc, &jump.1);
     view.jumps.push(jump);
 }
 
 fn goto_line(cx: &mut Context) {
     if cx.count.is_some() {
         let (view, doc) = current!(cx.editor);
-        push_jump(view, doc);
+        push_jump(view, doc, &mut cx.editor.global_jumps);
 
         goto_line_without_jumplist(cx.editor, cx.count);
     }
@@ -2886,7 +3809,7 @@ fn goto_last_line(cx: &mut Context) {
         .clone()
         .transform(|range| range.put_cursor(text, pos, cx.editor.mode == Mode::Select));
 
-    push_jump(view, doc);
+    push_jump(view, doc, &mut cx.editor.global_jumps);
     doc.set_selection(view.id, selection);
 }
 
@@ -2912,6 +3835,61 @@ fn goto_last_modification(cx: &mut Context) {
     }
 }
 
+fn goto_change_location(cx: &mut Context, pos: Option<usize>) {
+    let (view, doc) = current!(cx.editor);
+    let pos = match pos {
+        Some(pos) => pos.min(doc.text().len_chars()),
+        None => {
+            cx.editor.set_error("no more change locations");
+            return;
+        }
+    };
+    let text = doc.text().slice(..);
+    let selection = doc
+        .selection(view.id)
+        .clone()
+        .transform(|range| range.put_cursor(text, pos, cx.editor.mode == Mode::Select));
+    doc.set_selection(view.id, selection);
+    align_view(doc, view, Align::Center);
+}
+
+fn goto_prev_change_location(cx: &mut Context) {
+    let count = cx.count();
+    let pos = doc_mut!(cx.editor).change_list.backward(count);
+    goto_change_location(cx, pos);
+}
+
+fn goto_next_change_location(cx: &mut Context) {
+    let count = cx.count();
+    let pos = doc_mut!(cx.editor).change_list.forward(count);
+    goto_change_location(cx, pos);
+}
+
+/// Jumps to the location of the latest edit in any document.
+fn goto_last_change_location(cx: &mut Context) {
+    let last_changed = cx
+        .editor
+        .documents()
+        .filter_map(|doc| Some((doc.change_list.last_change()?, doc.id())))
+        .max()
+        .map(|(_, doc_id)| doc_id);
+    let doc_id = match last_changed {
+        Some(doc_id) => doc_id,
+        None => {
+            cx.editor.set_error("no changes");
+            return;
+        }
+    };
+
+    let (view, doc) = current!(cx.editor);
+    push_jump(view, doc, &mut cx.editor.global_jumps);
+    if doc.id() != doc_id {
+        cx.editor.switch(doc_id, Action::Replace);
+    }
+    let pos = doc_mut!(cx.editor).change_list.last();
+    goto_change_location(cx, pos);
+}
+
 fn goto_last_modified_file(cx: &mut Context) {
     let view = view!(cx.editor);
     let alternate_file = view
@@ -3139,16 +4117,21 @@ pub fn idle_completion(cx: &mut Context) {
         let text = doc.text().slice(..);
         let cursor = doc.selection(view.id).primary().cursor(text);
 
+        let trigger_len = doc
+            .language_config()
+            .and_then(|config| config.completion.as_ref()?.trigger_len)
+            .unwrap_or(config.completion_trigger_len);
+
         use helix_core::chars::char_is_word;
         let mut iter = text.chars_at(cursor);
         iter.reverse();
-        for _ in 0..config.completion_trigger_len {
+        for _ in 0..trigger_len {
             match iter.next() {
                 Some(c) if char_is_word(c) => {}
                 _ => return,
             }
         }
-        super::completion(cx);
+        super::completion_impl(cx, CompletionInvoked::Automatic);
     }
 
     fn language_server_completion(cx: &mut Context, ch: char) {
@@ -3158,6 +4141,20 @@ fn language_server_completion(cx: &mut Context, ch: char) {
         }
 
         use helix_lsp::lsp;
+        // Typing a separator inside a path lists the entries of its directory.
+        if std::path::is_separator(ch) && config.path_completion {
+            let (view, doc) = current_ref!(cx.editor);
+            let cursor = doc
+                .selection(view.id)
+                .primary()
+                .cursor(doc.text().slice(..));
+            if path_before_cursor(doc, cursor).is_some() {
+                cx.editor.clear_idle_timer();
+                super::completion_impl(cx, CompletionInvoked::Automatic);
+                return;
+            }
+        }
+
         // if ch matches completion char, trigger completion
         let doc = doc_mut!(cx.editor);
         let language_server = match doc.language_server() {
@@ -3165,18 +4162,26 @@ fn language_server_completion(cx: &mut Context, ch: char) {
             None => return,
         };
 
-        let capabilities = language_server.capabilities();
-
-        if let Some(lsp::CompletionOptions {
-            trigger_characters: Some(triggers),
-            ..
-        }) = &capabilities.completion_provider
+        let is_trigger = match doc
+            .language_config()
+            .and_then(|config| config.completion.as_ref()?.trigger_characters.as_ref())
         {
-            // TODO: what if trigger is multiple chars long
-            if triggers.iter().any(|trigger| trigger.contains(ch)) {
-                cx.editor.clear_idle_timer();
-                super::completion(cx);
-            }
+            Some(triggers) => triggers.contains(&ch),
+            None => match &language_server.capabilities().completion_provider {
+                Some(lsp::CompletionOptions {
+                    trigger_characters: Some(triggers),
+                    ..
+                }) => {
+                    // TODO: what if trigger is multiple chars long
+                    triggers.iter().any(|trigger| trigger.contains(ch))
+                }
+                _ => false,
+            },
+        };
+
+        if is_trigger {
+            cx.editor.clear_idle_timer();
+            super::completion_impl(cx, CompletionInvoked::Automatic);
         }
     }
 
@@ -3217,6 +4222,60 @@ fn signature_help(cx: &mut Context, ch: char) {
         }
     }
 
+    fn on_type_formatting(cx: &mut Context, ch: char) {
+        use helix_lsp::lsp;
+
+        if !cx.editor.config().lsp.format_on_type {
+            return;
+        }
+
+        let (view, doc) = current_ref!(cx.editor);
+        let language_server = match doc.language_server() {
+            Some(language_server) => language_server,
+            None => return,
+        };
+        let offset_encoding = language_server.offset_encoding();
+        let options = lsp::FormattingOptions {
+            tab_size: doc.tab_width() as u32,
+            insert_spaces: matches!(doc.indent_style, IndentStyle::Spaces(_)),
+            ..Default::default()
+        };
+        let future = match language_server.text_document_on_type_formatting(
+            doc.identifier(),
+            doc.position(view.id, offset_encoding),
+            ch,
+            options,
+        ) {
+            Some(future) => future,
+            None => return,
+        };
+
+        let (doc_id, view_id, version) = (doc.id(), view.id, doc.version());
+        cx.callback(
+            future,
+            move |editor, _compositor, edits: Option<Vec<lsp::TextEdit>>| {
+                let edits = match edits {
+                    Some(edits) if !edits.is_empty() => edits,
+                    _ => return,
+                };
+                // drop the edits if the document changed while the server was formatting
+                let doc = match editor.documents.get_mut(&doc_id) {
+                    Some(doc) if doc.version() == version => doc,
+                    _ => return,
+                };
+                if !editor.tree.contains(view_id) {
+                    return;
+                }
+                let transaction = helix_lsp::util::generate_transaction_from_edits(
+                    doc.text(),
+                    edits,
+                    offset_encoding,
+                );
+                doc.apply(&transaction, view_id);
+            },
+        );
+    }
+
     // The default insert hook: simply insert the character
     #[allow(clippy::unnecessary_wraps)] // need to use Option<> because of the Hook signature
     fn insert(doc: &Rope, selection: &Selection, ch: char) -> Option<Transaction> {
@@ -3248,7 +4307,11 @@ pub fn insert_char(cx: &mut Context, c: char) {
         // TODO: need a post insert hook too for certain triggers (autocomplete, signature help, etc)
         // this could also generically look at Transaction, but it's a bit annoying to look at
         // Operation instead of Change.
-        for hook in &[language_server_completion, signature_help] {
+        for hook in &[
+            language_server_completion,
+            signature_help,
+            on_type_formatting,
+        ] {
             hook(cx, c);
         }
     }
@@ -3305,16 +4368,27 @@ pub fn insert_newline(cx: &mut Context) {
 
                 (line_start, line_start, new_text.chars().count())
             } else {
-                let indent = indent::indent_for_newline(
-                    doc.language_config(),
-                    doc.syntax(),
-                    &doc.indent_style,
-                    doc.tab_width(),
-                    text,
-                    current_line,
-                    pos,
-                    current_line,
-                );
+                // Lists and block quotes of markup languages go on with the same prefix.
+                let continuation = doc
+                    .syntax()
+                    .filter(|syntax| markup::in_continued_block(syntax, text, current_line))
+                    .and_then(|_| {
+                        let line_start = text.line_to_char(current_line);
+                        markup::line_continuation(&Cow::from(text.slice(line_start..pos)))
+                    });
+                let indent = match continuation {
+                    Some(continuation) => continuation,
+                    None => indent::indent_for_newline(
+                        doc.language_config(),
+                        doc.syntax(),
+                        &doc.indent_style,
+                        doc.tab_width(),
+                        text,
+                        current_line,
+                        pos,
+                        current_line,
+                    ),
+                };
 
                 // If we are between pairs (such as brackets), we want to
                 // insert an additional line which is indented one level
@@ -3370,6 +4444,8 @@ pub fn insert_newline(cx: &mut Context) {
 
         let (view, doc) = current!(cx.editor);
         doc.apply(&transaction, view.id);
+
+        on_type_formatting(cx, '\n');
     }
 
     pub fn delete_char_backward(cx: &mut Context) {
@@ -3582,9 +4658,7 @@ fn yank(cx: &mut Context) {
         cx.register.unwrap_or('"')
     );
 
-    cx.editor
-        .registers
-        .write(cx.register.unwrap_or('"'), values);
+    cx.editor.registers.yank(cx.register.unwrap_or('"'), values);
 
     cx.editor.set_status(msg);
     exit_select_mode(cx);
@@ -3688,6 +4762,21 @@ fn paste_impl(
     action: Paste,
     count: usize,
     mode: Mode,
+    reindent: bool,
+) {
+    paste_apply(values, doc, view, action, count, mode, reindent);
+    doc.append_changes_to_history(view);
+}
+
+/// Pastes `values` without committing the change to the history.
+fn paste_apply(
+    values: &[String],
+    doc: &mut Document,
+    view: &mut View,
+    action: Paste,
+    count: usize,
+    mode: Mode,
+    reindent: bool,
 ) {
     if values.is_empty() {
         return;
@@ -3737,7 +4826,39 @@ fn paste_impl(
             (Paste::Cursor, _) => range.cursor(text.slice(..)),
         };
 
-        let value = values.next();
+        let mut value = values.next();
+        // Linewise pastes can be re-indented to fit the lines they are pasted between.
+        if reindent && linewise && !matches!(action, Paste::Cursor) {
+            if let Some(content) = &mut value {
+                let line = text.char_to_line(pos);
+                let line_end_index = if line == 0 {
+                    0
+                } else {
+                    line_end_char_index(&text.slice(..), line - 1)
+                };
+                // Without an indent query, follow the line the paste is anchored to.
+                let current_line = match action {
+                    Paste::Before => line,
+                    _ => line.saturating_sub(1),
+                };
+                let indent = indent::indent_for_newline(
+                    doc.language_config(),
+                    doc.syntax(),
+                    &doc.indent_style,
+                    doc.tab_width(),
+                    text.slice(..),
+                    line.saturating_sub(1),
+                    line_end_index,
+                    current_line,
+                );
+                *content = Tendril::from(indent::reindent_block(
+                    content,
+                    &indent,
+                    &doc.indent_style,
+                    doc.tab_width(),
+                ));
+            }
+        }
 
         let value_len = value
             .as_ref()
@@ -3757,17 +4878,51 @@ fn paste_impl(
     }
 
     doc.apply(&transaction, view.id);
-    doc.append_changes_to_history(view);
 }
 
 pub(crate) fn paste_bracketed_value(cx: &mut Context, contents: String) {
     let count = cx.count();
-    let paste = match cx.editor.mode {
-        Mode::Insert | Mode::Select => Paste::Cursor,
-        Mode::Normal => Paste::Before,
-    };
-    let (view, doc) = current!(cx.editor);
-    paste_impl(&[contents], doc, view, paste, count, cx.editor.mode);
+    let threshold = cx.editor.config().large_paste_threshold;
+    if threshold == 0 || contents.len() <= threshold {
+        paste_bracketed_value_impl(cx.editor, contents, count);
+        return;
+    }
+
+    // Inserting huge pastes can freeze the editor, keep them in a register until confirmed.
+    let lines = contents.lines().count();
+    cx.editor.registers.write('"', vec![contents.clone()]);
+    cx.editor.set_status(format!(
+        "Large paste ({} lines) stored in register \". Press y to insert it",
+        lines
+    ));
+    cx.on_next_key(move |cx, event| {
+        if event.char() == Some('y') {
+            paste_bracketed_value_impl(cx.editor, contents, count);
+            return;
+        }
+        // Any other key leaves the paste in the register and is handled as usual.
+        cx.callback = Some(Box::new(move |compositor, cx| {
+            let editor_view = compositor.find::<ui::EditorView>().unwrap();
+            match editor_view.handle_event(&Event::Key(event), cx) {
+                EventResult::Consumed(Some(callback)) | EventResult::Ignored(Some(callback)) => {
+                    callback(compositor, cx)
+                }
+                _ => (),
+            }
+        }));
+    });
+}
+
+fn paste_bracketed_value_impl(editor: &mut Editor, contents: String, count: usize) {
+    let mode = editor.mode;
+    let (view, doc) = current!(editor);
+    match mode {
+        // The paste is committed to the history with the rest of the insertion when leaving
+        // insert mode.
+        Mode::Insert => paste_apply(&[contents], doc, view, Paste::Cursor, count, mode, false),
+        Mode::Select => paste_impl(&[contents], doc, view, Paste::Cursor, count, mode, false),
+        Mode::Normal => paste_impl(&[contents], doc, view, Paste::Before, count, mode, false),
+    }
 }
 
 fn paste_clipboard_impl(
@@ -3776,10 +4931,11 @@ fn paste_clipboard_impl(
     clipboard_type: ClipboardType,
     count: usize,
 ) -> anyhow::Result<()> {
+    let reindent = editor.config().reindent_paste;
     let (view, doc) = current!(editor);
     match editor.clipboard_provider.get_contents(clipboard_type) {
         Ok(contents) => {
-            paste_impl(&[contents], doc, view, action, count, editor.mode);
+            paste_impl(&[contents], doc, view, action, count, editor.mode, reindent);
             Ok(())
         }
         Err(e) => Err(e.context("Couldn't get system clipboard contents")),
@@ -3892,26 +5048,179 @@ fn replace_selections_with_primary_clipboard(cx: &mut Context) {
 }
 
 fn paste(cx: &mut Context, pos: Paste) {
+    let reindent = cx.editor.config().reindent_paste;
+    paste_from_register(cx, pos, reindent);
+}
+
+fn paste_from_register(cx: &mut Context, pos: Paste, reindent: bool) {
     let count = cx.count();
     let reg_name = cx.register.unwrap_or('"');
-    let (view, doc) = current!(cx.editor);
-    let registers = &mut cx.editor.registers;
-
-    if let Some(values) = registers.read(reg_name) {
-        paste_impl(values, doc, view, pos, count, cx.editor.mode);
-    }
+    let values = match cx.editor.registers.read(reg_name) {
+        Some(values) => values.to_vec(),
+        None => return,
+    };
+    paste_values(cx.editor, &values, pos, count, reindent);
 }
 
-fn paste_after(cx: &mut Context) {
-    paste(cx, Paste::After)
+/// Pastes `values` and remembers the paste so it can be cycled through the yank ring.
+fn paste_values(editor: &mut Editor, values: &[String], pos: Paste, count: usize, reindent: bool) {
+    let mode = editor.mode;
+    let (view, doc) = current!(editor);
+    paste_impl(values, doc, view, pos, count, mode, reindent);
+
+    editor.last_paste = Some(LastPaste {
+        doc_id: doc.id(),
+        view_id: view.id,
+        version: doc.version(),
+        ring_index: editor
+            .registers
+            .yank_ring()
+            .position(|yanked| yanked == values),
+        repaste: Box::new(move |editor, values| {
+            let (view, doc) = current!(editor);
+            paste_impl(values, doc, view, pos, count, editor.mode, reindent);
+        }),
+    });
 }
 
-fn paste_before(cx: &mut Context) {
-    paste(cx, Paste::Before)
-}
+/// Replaces the text of the last paste with the `count`th older entry of the yank ring.
+fn cycle_paste(cx: &mut Context) {
+    let count = cx.count();
+    let last_paste = match cx.editor.last_paste.take() {
+        Some(last_paste) => last_paste,
+        None => {
+            cx.editor.set_error("Nothing was pasted");
+            return;
+        }
+    };
+    let (view, doc) = current!(cx.editor);
+    if view.id != last_paste.view_id
+        || doc.id() != last_paste.doc_id
+        || doc.version() != last_paste.version
+    {
+        cx.editor
+            .set_error("The text was changed since the last paste");
+        return;
+    }
 
-fn get_lines(doc: &Document, view_id: ViewId) -> Vec<usize> {
-    let mut lines = Vec::new();
+    let ring_len = cx.editor.registers.yank_ring().len().max(1);
+    let index = match last_paste.ring_index {
+        Some(index) => (index + count) % ring_len,
+        None => (count - 1) % ring_len,
+    };
+    let values = match cx.editor.registers.yank_ring().nth(index) {
+        Some(values) => values.to_vec(),
+        None => {
+            cx.editor.set_error("The yank ring is empty");
+            return;
+        }
+    };
+
+    if !doc.undo(view) {
+        return;
+    }
+    (last_paste.repaste)(cx.editor, &values);
+
+    let (view, doc) = current!(cx.editor);
+    cx.editor.last_paste = Some(LastPaste {
+        version: doc.version(),
+        ring_index: Some(index),
+        view_id: view.id,
+        ..last_paste
+    });
+    cx.editor
+        .set_status(format!("Pasted yank ring entry {}/{}", index + 1, ring_len));
+}
+
+fn jobs_picker(cx: &mut Context) {
+    impl ui::menu::Item for job::RunningJob {
+        type Data = ();
+
+        fn format(&self, _data: &Self::Data) -> Row {
+            Row::new([
+                self.name.clone(),
+                format!("{:.1}s", self.started.elapsed().as_secs_f64()),
+            ])
+        }
+    }
+
+    let running = cx.jobs.running();
+    if running.is_empty() {
+        cx.editor.set_status("No background jobs are running");
+        return;
+    }
+
+    // Picking a job cancels it.
+    let picker = Picker::new(running, (), |_cx, job, _action| job.cancel());
+    cx.push_layer(Box::new(overlayed(picker)));
+}
+
+fn yank_ring_picker(cx: &mut Context) {
+    struct YankRingEntry {
+        index: usize,
+        values: Vec<String>,
+    }
+
+    impl ui::menu::Item for YankRingEntry {
+        type Data = ();
+
+        fn format(&self, _data: &Self::Data) -> Row {
+            let preview = self
+                .values
+                .iter()
+                .map(|value| value.trim().lines().next().unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join(" | ");
+            Row::new([
+                format!("{}", self.index + 1),
+                format!("{}", self.values.len()),
+                preview,
+            ])
+        }
+    }
+
+    let entries: Vec<_> = cx
+        .editor
+        .registers
+        .yank_ring()
+        .enumerate()
+        .map(|(index, values)| YankRingEntry {
+            index,
+            values: values.to_vec(),
+        })
+        .collect();
+    if entries.is_empty() {
+        cx.editor.set_status("The yank ring is empty");
+        return;
+    }
+
+    let picker = Picker::new(entries, (), |cx, entry, _action| {
+        let reindent = cx.editor.config().reindent_paste;
+        paste_values(cx.editor, &entry.values, Paste::After, 1, reindent);
+        let (view, doc) = current!(cx.editor);
+        doc.append_changes_to_history(view);
+    });
+    cx.push_layer(Box::new(overlayed(picker)));
+}
+
+fn paste_after(cx: &mut Context) {
+    paste(cx, Paste::After)
+}
+
+fn paste_before(cx: &mut Context) {
+    paste(cx, Paste::Before)
+}
+
+fn paste_after_reindent(cx: &mut Context) {
+    paste_from_register(cx, Paste::After, true)
+}
+
+fn paste_before_reindent(cx: &mut Context) {
+    paste_from_register(cx, Paste::Before, true)
+}
+
+fn get_lines(doc: &Document, view_id: ViewId) -> Vec<usize> {
+    let mut lines = Vec::new();
 
     // Get all line numbers
     for range in doc.selection(view_id) {
@@ -4164,25 +5473,335 @@ fn remove_primary_selection(cx: &mut Context) {
     doc.set_selection(view.id, selection);
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionInvoked {
+    Manual,
+    Automatic,
+}
+
 pub fn completion(cx: &mut Context) {
-    use helix_lsp::{lsp, util::pos_to_lsp_pos};
+    completion_impl(cx, CompletionInvoked::Manual);
+}
+
+/// State of the document when completion was requested.
+struct CompletionTrigger {
+    doc: DocumentId,
+    view: ViewId,
+    savepoint: Arc<SavePoint>,
+    start_offset: usize,
+    trigger_offset: usize,
+    auto_insert_single: bool,
+}
+
+/// Words of `texts` and `tag_names` which may complete `prefix`.
+fn completion_words<'a>(
+    texts: &[Rope],
+    tag_names: impl Iterator<Item = &'a str>,
+    prefix: &str,
+    min_length: usize,
+) -> Vec<ui::CompletionItem> {
+    use helix_lsp::lsp;
+
+    let first = match prefix.chars().next() {
+        Some(first) => first.to_lowercase().next(),
+        None => return Vec::new(),
+    };
+    let is_candidate = |word: &str| {
+        word != prefix
+            && word.chars().count() >= min_length
+            && word.chars().next().and_then(|ch| ch.to_lowercase().next()) == first
+    };
 
-    let (view, doc) = current!(cx.editor);
+    let mut words = HashSet::new();
+    for text in texts {
+        for line in text.lines() {
+            let line = Cow::from(line);
+            words.extend(
+                line.split(|ch: char| !char_is_word(ch))
+                    .filter(|word| is_candidate(word))
+                    .map(str::to_string),
+            );
+        }
+    }
+    words.extend(
+        tag_names
+            .filter(|name| is_candidate(name))
+            .map(str::to_string),
+    );
 
-    let language_server = match doc.language_server() {
-        Some(language_server) => language_server,
-        None => return,
+    words
+        .into_iter()
+        .map(|word| ui::CompletionItem {
+            item: lsp::CompletionItem {
+                label: word,
+                kind: Some(lsp::CompletionItemKind::TEXT),
+                ..Default::default()
+            },
+            // Words aren't known to the language server.
+            resolved: true,
+        })
+        .collect()
+}
+
+/// Collects the words of the open documents, and optionally of the workspace tag index, which
+/// may complete `prefix`. The documents are scanned in the background, since that takes a while
+/// with many large documents open.
+fn word_completion_items(
+    editor: &Editor,
+    prefix: String,
+) -> Option<impl Future<Output = Vec<ui::CompletionItem>>> {
+    let config = editor.config();
+    if !config.word_completion.enable || prefix.is_empty() {
+        return None;
+    }
+    let min_length = config.word_completion.min_word_length.max(1);
+    // Cloning the texts is cheap, they share their chunks with the documents.
+    let texts: Vec<Rope> = editor.documents().map(|doc| doc.text().clone()).collect();
+    let tag_index = config
+        .word_completion
+        .workspace
+        .then(|| editor.tag_index.clone());
+
+    Some(async move {
+        tokio::task::spawn_blocking(move || {
+            // Don't wait for the index while it is being updated.
+            let index = tag_index.as_ref().and_then(|index| index.try_lock());
+            let tag_names = index
+                .iter()
+                .flat_map(|index| index.tags())
+                .map(|tag| tag.name.as_str());
+            completion_words(&texts, tag_names, &prefix, min_length)
+        })
+        .await
+        .unwrap_or_default()
+    })
+}
+
+/// Returns the start of the file name and the directory of the path typed before `cursor`,
+/// if the cursor is inside a string.
+fn path_before_cursor(doc: &Document, cursor: usize) -> Option<(usize, PathBuf)> {
+    let text = doc.text().slice(..);
+    let syntax = doc.syntax()?;
+    let byte = text.char_to_byte(cursor.saturating_sub(1));
+    let node = syntax
+        .tree()
+        .root_node()
+        .descendant_for_byte_range(byte, byte);
+    let in_string = std::iter::successors(node, |node| node.parent())
+        .any(|node| node.kind().contains("string"));
+    if !in_string {
+        return None;
+    }
+
+    let mut iter = text.chars_at(cursor);
+    iter.reverse();
+    let len = iter
+        .take_while(|ch| !ch.is_whitespace() && !"\"'`()[]{}<>,;=".contains(*ch))
+        .count();
+    let path: String = text.slice(cursor - len..cursor).into();
+    let separator = path.rfind(std::path::is_separator)?;
+    let file_name_len = path[separator + 1..].chars().count();
+
+    let dir = helix_core::path::expand(Path::new(&path[..=separator]));
+    let dir = if dir.is_relative() {
+        // Relative paths in a document usually refer to its siblings.
+        doc.path()
+            .and_then(|path| path.parent())
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::current_dir().ok())?
+            .join(dir)
+    } else {
+        dir
     };
+    Some((cursor - file_name_len, dir))
+}
+
+/// Entries of the directory of the path typed before `cursor` inside a string, along with
+/// the offset the completed file name starts at.
+fn path_completion_items(
+    editor: &Editor,
+    doc: &Document,
+    cursor: usize,
+) -> Option<(usize, Vec<ui::CompletionItem>)> {
+    use helix_lsp::lsp;
+
+    if !editor.config().path_completion {
+        return None;
+    }
+    let (start_offset, dir) = path_before_cursor(doc, cursor)?;
+    let show_hidden = doc.text().get_char(start_offset) == Some('.');
+
+    let items = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            if name.starts_with('.') && !show_hidden {
+                return None;
+            }
+            let is_dir = entry
+                .file_type()
+                .map_or(false, |file_type| file_type.is_dir());
+            let (label, kind) = if is_dir {
+                (format!("{}/", name), lsp::CompletionItemKind::FOLDER)
+            } else {
+                (name, lsp::CompletionItemKind::FILE)
+            };
+            Some(ui::CompletionItem {
+                item: lsp::CompletionItem {
+                    label,
+                    kind: Some(kind),
+                    ..Default::default()
+                },
+                resolved: true,
+            })
+        })
+        .collect();
+    Some((start_offset, items))
+}
+
+fn show_completion(
+    editor: &mut Editor,
+    compositor: &mut Compositor,
+    trigger: CompletionTrigger,
+    items: Vec<ui::CompletionItem>,
+    is_incomplete: bool,
+    offset_encoding: helix_lsp::OffsetEncoding,
+) {
+    let (view, doc) = current_ref!(editor);
+    // check if the completion request is stale.
+    //
+    // Completions are completed asynchrounsly and therefore the user could
+    //switch document/view or leave insert mode. In all of thoise cases the
+    // completion should be discarded
+    if editor.mode != Mode::Insert || view.id != trigger.view || doc.id() != trigger.doc {
+        return;
+    }
+
+    if items.is_empty() {
+        // editor.set_error("No completion available");
+        return;
+    }
+    let size = compositor.size();
+    let ui = compositor.find::<ui::EditorView>().unwrap();
+    let completion_area = ui.set_completion(
+        editor,
+        trigger.savepoint,
+        items,
+        offset_encoding,
+        trigger.start_offset,
+        trigger.trigger_offset,
+        is_incomplete,
+        size,
+    );
+    if trigger.auto_insert_single && ui.accept_sole_completion(editor) {
+        return;
+    }
+    let size = compositor.size();
+    let signature_help_area = compositor
+        .find_id::<Popup<SignatureHelp>>(SignatureHelp::ID)
+        .map(|signature_help| signature_help.area(size, editor));
+    // Delete the signature help popup if they intersect.
+    if matches!((completion_area, signature_help_area),(Some(a), Some(b)) if a.intersects(b)) {
+        compositor.remove(SignatureHelp::ID);
+    }
+}
+
+pub fn completion_impl(cx: &mut Context, invoked: CompletionInvoked) {
+    use helix_lsp::{lsp, util::pos_to_lsp_pos};
+
+    let (view, doc) = current_ref!(cx.editor);
+    let auto_insert_single = invoked == CompletionInvoked::Manual
+        && doc
+            .language_config()
+            .and_then(|config| config.completion.as_ref())
+            .map_or(false, |config| config.auto_insert_single);
 
-    let offset_encoding = language_server.offset_encoding();
     let text = doc.text().slice(..);
     let cursor = doc.selection(view.id).primary().cursor(text);
+    let trigger_offset = cursor;
 
-    let pos = pos_to_lsp_pos(doc.text(), cursor, offset_encoding);
+    // TODO: trigger_offset should be the cursor offset but we also need a starting offset from where we want to apply
+    // completion filtering. For example logger.te| should filter the initial suggestion list with "te".
 
-    let future = match language_server.completion(doc.identifier(), pos, None) {
-        Some(future) => future,
-        None => return,
+    let mut iter = text.chars_at(cursor);
+    iter.reverse();
+    let offset = iter.take_while(|ch| char_is_word(*ch)).count();
+    let start_offset = cursor.saturating_sub(offset);
+    let prefix: String = text.slice(start_offset..cursor).into();
+    // Inside a path, the entries of its directory are completed instead of words.
+    let (start_offset, path_items) = match path_completion_items(cx.editor, doc, cursor) {
+        Some((start_offset, path_items)) => (start_offset, Some(path_items)),
+        None => (start_offset, None),
+    };
+    let words = match path_items {
+        Some(_) => None,
+        None => word_completion_items(cx.editor, prefix),
+    };
+    let has_items = path_items.is_some() || words.is_some();
+    let word_items = async move {
+        match (path_items, words) {
+            (Some(path_items), _) => path_items,
+            (None, Some(words)) => words.await,
+            (None, None) => Vec::new(),
+        }
+    };
+
+    let (view, doc) = current!(cx.editor);
+    let trigger = CompletionTrigger {
+        doc: doc.id(),
+        view: view.id,
+        savepoint: doc.savepoint(view),
+        start_offset,
+        trigger_offset,
+        auto_insert_single,
+    };
+
+    let request = doc.language_server().and_then(|language_server| {
+        let offset_encoding = language_server.offset_encoding();
+        let pos = pos_to_lsp_pos(doc.text(), cursor, offset_encoding);
+        let future = language_server.completion(doc.identifier(), pos, None)?;
+        Some((future, offset_encoding))
+    });
+
+    // FIXME: The commands Context can only have a single callback
+    // which means it gets overwritten when executing keybindings
+    // with multiple commands or macros. This would mean that completion
+    // might be incorrectly applied when repeating the insertmode action
+    //
+    // TODO: to solve this either make cx.callback a Vec of callbacks or
+    // alternatively move `last_insert` to `helix_view::Editor`
+    let (future, offset_encoding) = match request {
+        Some(request) => request,
+        None => {
+            // Without a language server only the words are completed.
+            if !has_items {
+                return;
+            }
+            cx.callback = Some(Box::new(
+                move |compositor: &mut Compositor, _cx: &mut compositor::Context| {
+                    let ui = compositor.find::<ui::EditorView>().unwrap();
+                    ui.last_insert.1.push(InsertEvent::RequestCompletion);
+                },
+            ));
+            cx.jobs.callback(async move {
+                let word_items = word_items.await;
+                let call = Callback::EditorCompositor(Box::new(
+                    move |editor: &mut Editor, compositor: &mut Compositor| {
+                        show_completion(
+                            editor,
+                            compositor,
+                            trigger,
+                            word_items,
+                            false,
+                            helix_lsp::OffsetEncoding::default(),
+                        );
+                    },
+                ));
+                Ok(call)
+            });
+            return;
+        }
     };
 
     // setup a chanel that allows the request to be canceled
@@ -4203,28 +5822,6 @@ pub fn completion(cx: &mut Context) {
         }
     };
 
-    let trigger_offset = cursor;
-
-    // TODO: trigger_offset should be the cursor offset but we also need a starting offset from where we want to apply
-    // completion filtering. For example logger.te| should filter the initial suggestion list with "te".
-
-    use helix_core::chars;
-    let mut iter = text.chars_at(cursor);
-    iter.reverse();
-    let offset = iter.take_while(|ch| chars::char_is_word(*ch)).count();
-    let start_offset = cursor.saturating_sub(offset);
-    let savepoint = doc.savepoint(view);
-
-    let trigger_doc = doc.id();
-    let trigger_view = view.id;
-
-    // FIXME: The commands Context can only have a single callback
-    // which means it gets overwritten when executing keybindings
-    // with multiple commands or macros. This would mean that completion
-    // might be incorrectly applied when repeating the insertmode action
-    //
-    // TODO: to solve this either make cx.callback a Vec of callbacks or
-    // alternatively move `last_insert` to `helix_view::Editor`
     cx.callback = Some(Box::new(
         move |compositor: &mut Compositor, _cx: &mut compositor::Context| {
             let ui = compositor.find::<ui::EditorView>().unwrap();
@@ -4232,55 +5829,48 @@ pub fn completion(cx: &mut Context) {
         },
     ));
 
-    cx.callback(
-        future,
-        move |editor, compositor, response: Option<lsp::CompletionResponse>| {
-            let (view, doc) = current_ref!(editor);
-            // check if the completion request is stale.
-            //
-            // Completions are completed asynchrounsly and therefore the user could
-            //switch document/view or leave insert mode. In all of thoise cases the
-            // completion should be discarded
-            if editor.mode != Mode::Insert || view.id != trigger_view || doc.id() != trigger_doc {
-                return;
-            }
+    let callback = async move {
+        // The words are collected while waiting for the language server.
+        let (response, word_items) = tokio::join!(future, word_items);
+        let response: Option<lsp::CompletionResponse> = serde_json::from_value(response?)?;
+        let (items, is_incomplete) = match response {
+            Some(lsp::CompletionResponse::Array(items)) => (items, false),
+            Some(lsp::CompletionResponse::List(lsp::CompletionList {
+                is_incomplete,
+                items,
+            })) => (items, is_incomplete),
+            None => (Vec::new(), false),
+        };
 
-            let items = match response {
-                Some(lsp::CompletionResponse::Array(items)) => items,
-                // TODO: do something with is_incomplete
-                Some(lsp::CompletionResponse::List(lsp::CompletionList {
-                    is_incomplete: _is_incomplete,
+        let labels: HashSet<_> = items.iter().map(|item| item.label.clone()).collect();
+        let items = items
+            .into_iter()
+            .map(|item| ui::CompletionItem {
+                item,
+                resolved: false,
+            })
+            .chain(
+                word_items
+                    .into_iter()
+                    .filter(|word| !labels.contains(&word.item.label)),
+            )
+            .collect();
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                show_completion(
+                    editor,
+                    compositor,
+                    trigger,
                     items,
-                })) => items,
-                None => Vec::new(),
-            };
-
-            if items.is_empty() {
-                // editor.set_error("No completion available");
-                return;
-            }
-            let size = compositor.size();
-            let ui = compositor.find::<ui::EditorView>().unwrap();
-            let completion_area = ui.set_completion(
-                editor,
-                savepoint,
-                items,
-                offset_encoding,
-                start_offset,
-                trigger_offset,
-                size,
-            );
-            let size = compositor.size();
-            let signature_help_area = compositor
-                .find_id::<Popup<SignatureHelp>>(SignatureHelp::ID)
-                .map(|signature_help| signature_help.area(size, editor));
-            // Delete the signature help popup if they intersect.
-            if matches!((completion_area, signature_help_area),(Some(a), Some(b)) if a.intersects(b))
-            {
-                compositor.remove(SignatureHelp::ID);
-            }
-        },
-    );
+                    is_incomplete,
+                    offset_encoding,
+                );
+            },
+        ));
+        anyhow::Ok(call)
+    };
+    cx.jobs
+        .add(Job::with_callback(callback).named("language server request"));
 }
 
 // comments
@@ -4296,6 +5886,54 @@ fn toggle_comments(cx: &mut Context) {
     exit_select_mode(cx);
 }
 
+fn toggle_checkbox(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let mut lines: Vec<_> = doc
+        .selection(view.id)
+        .iter()
+        .flat_map(|range| {
+            let (start, end) = range.line_range(text);
+            start..=end
+        })
+        .collect();
+    lines.sort_unstable();
+    lines.dedup();
+
+    let changes = lines.into_iter().filter_map(|line| {
+        let line_start = text.line_to_char(line);
+        let (from, to, checkbox) = markup::toggle_checkbox(&Cow::from(text.line(line)))?;
+        Some((line_start + from, line_start + to, Some(checkbox.into())))
+    });
+    let transaction = Transaction::change(doc.text(), changes);
+    doc.apply(&transaction, view.id);
+    exit_select_mode(cx);
+}
+
+fn renumber_list(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let syntax = match doc.syntax() {
+        Some(syntax) => syntax,
+        None => {
+            cx.editor.set_error("Syntax information is not available");
+            return;
+        }
+    };
+    let text = doc.text().slice(..);
+    let pos = doc.selection(view.id).primary().cursor(text);
+    let changes = markup::renumber_list(syntax, text, pos);
+    if changes.is_empty() {
+        return;
+    }
+    let transaction = Transaction::change(
+        doc.text(),
+        changes
+            .into_iter()
+            .map(|(from, to, number)| (from, to, Some(number.into()))),
+    );
+    doc.apply(&transaction, view.id);
+}
+
 fn rotate_selections(cx: &mut Context, direction: Direction) {
     let count = cx.count();
     let (view, doc) = current!(cx.editor);
@@ -4441,8 +6079,11 @@ fn match_brackets(cx: &mut Context) {
     if let Some(syntax) = doc.syntax() {
         let text = doc.text().slice(..);
         let selection = doc.selection(view.id).clone().transform(|range| {
-            if let Some(pos) =
-                match_brackets::find_matching_bracket_fuzzy(syntax, doc.text(), range.cursor(text))
+            let cursor = range.cursor(text);
+            // Delimiters like `do ... end` are preferred to the brackets surrounding them.
+            if let Some(pos) = match_brackets::find_matching_delimiter(syntax, doc.text(), cursor)
+                .map(|delimiter| delimiter.start)
+                .or_else(|| match_brackets::find_matching_bracket_fuzzy(syntax, doc.text(), cursor))
             {
                 range.put_cursor(text, pos, cx.editor.mode == Mode::Select)
             } else {
@@ -4495,9 +6136,45 @@ fn jump_backward(cx: &mut Context) {
     };
 }
 
+fn undo_selection(cx: &mut Context) {
+    selection_history_impl(cx, true)
+}
+
+fn redo_selection(cx: &mut Context) {
+    selection_history_impl(cx, false)
+}
+
+fn selection_history_impl(cx: &mut Context, undo: bool) {
+    let count = cx.count();
+    let (view, doc) = current!(cx.editor);
+    let history = &mut view.selection_history;
+    let mut selection = doc.selection(view.id).clone();
+    let mut changed = false;
+    for _ in 0..count {
+        let next = if undo {
+            history.undo(doc.id(), selection.clone())
+        } else {
+            history.redo(doc.id(), selection.clone())
+        };
+        match next {
+            Some(next) => selection = next,
+            None => break,
+        }
+        changed = true;
+    }
+
+    if changed {
+        doc.set_selection(view.id, selection);
+    } else if undo {
+        cx.editor.set_status("Already at oldest selection");
+    } else {
+        cx.editor.set_status("Already at newest selection");
+    }
+}
+
 fn save_selection(cx: &mut Context) {
     let (view, doc) = current!(cx.editor);
-    push_jump(view, doc);
+    push_jump(view, doc, &mut cx.editor.global_jumps);
     cx.editor.set_status("Selection saved to jumplist");
 }
 
@@ -4748,6 +6425,41 @@ fn goto_prev_test(cx: &mut Context) {
     goto_ts_object_impl(cx, "test", Direction::Backward)
 }
 
+/// The comment token starting the code cell markers of `doc`, like `# %%`.
+fn cell_comment_token(doc: &Document) -> &str {
+    doc.language_config()
+        .and_then(|config| config.comment_token.as_deref())
+        .unwrap_or("#")
+}
+
+fn goto_cell_impl(cx: &mut Context, direction: Direction) {
+    let count = cx.count();
+    let motion = move |editor: &mut Editor| {
+        let (view, doc) = current!(editor);
+        let text = doc.text().slice(..);
+        let token = cell_comment_token(doc);
+        let extend = editor.mode == Mode::Select;
+        let selection = doc.selection(view.id).clone().transform(|range| {
+            let line = range.cursor_line(text);
+            match cell::find_cell_marker(text, line, token, direction, count) {
+                Some(line) => range.put_cursor(text, text.line_to_char(line), extend),
+                None => range,
+            }
+        });
+        doc.set_selection(view.id, selection);
+    };
+    motion(cx.editor);
+    cx.editor.last_motion = Some(Motion(Box::new(motion)));
+}
+
+fn goto_next_cell(cx: &mut Context) {
+    goto_cell_impl(cx, Direction::Forward)
+}
+
+fn goto_prev_cell(cx: &mut Context) {
+    goto_cell_impl(cx, Direction::Backward)
+}
+
 fn select_textobject_around(cx: &mut Context) {
     select_textobject(cx, textobject::TextObject::Around);
 }
@@ -4782,6 +6494,22 @@ fn select_textobject(cx: &mut Context, objtype: textobject::TextObject) {
                     )
                 };
 
+                let textobject_cell = |range: Range| -> Range {
+                    let token = cell_comment_token(doc);
+                    let lines = cell::cell_lines(text, range.cursor_line(text), token);
+                    // Inside a cell is its code, without the marker.
+                    let start = match objtype {
+                        textobject::TextObject::Inside
+                            if cell::is_cell_marker(text.line(lines.start), token) =>
+                        {
+                            lines.start + 1
+                        }
+                        _ => lines.start,
+                    };
+                    Range::new(text.line_to_char(start), text.line_to_char(lines.end))
+                        .with_direction(range.direction())
+                };
+
                 if ch == 'g' && doc.diff_handle().is_none() {
                     editor.set_status("Diff is not available in current buffer");
                     return;
@@ -4812,6 +6540,7 @@ fn select_textobject(cx: &mut Context, objtype: textobject::TextObject) {
                         'a' => textobject_treesitter("parameter", range),
                         'c' => textobject_treesitter("comment", range),
                         'T' => textobject_treesitter("test", range),
+                        'C' => textobject_cell(range),
                         'p' => textobject::textobject_paragraph(text, range, objtype, count),
                         'm' => textobject::textobject_pair_surround_closest(
                             text, range, objtype, count,
@@ -4845,6 +6574,7 @@ fn select_textobject(cx: &mut Context, objtype: textobject::TextObject) {
         ("a", "Argument/parameter (tree-sitter)"),
         ("c", "Comment (tree-sitter)"),
         ("T", "Test (tree-sitter)"),
+        ("C", "Code cell (`# %%` markers)"),
         ("m", "Closest surrounding pair"),
         (" ", "... or any character acting as a pair"),
     ];
@@ -5003,6 +6733,14 @@ fn shell_keep_pipe(cx: &mut Context) {
             if input.is_empty() {
                 return;
             }
+            let input = match typed::expand_placeholders(cx.editor, input, shell_escape) {
+                Ok(input) => input,
+                Err(err) => {
+                    cx.editor.set_error(err.to_string());
+                    return;
+                }
+            };
+            let input = input.as_str();
             let (view, doc) = current!(cx.editor);
             let selection = doc.selection(view.id);
 
@@ -5041,6 +6779,18 @@ fn shell_keep_pipe(cx: &mut Context) {
     );
 }
 
+/// Quotes `input` so the shell reads it back as a single word.
+fn shell_escape(input: Cow<str>) -> Cow<str> {
+    let is_safe = |ch: char| ch.is_ascii_alphanumeric() || "/._-+:,@=%".contains(ch);
+    if !input.is_empty() && input.chars().all(is_safe) {
+        input
+    } else if cfg!(unix) {
+        Cow::Owned(format!("'{}'", input.replace('\'', "'\\''")))
+    } else {
+        Cow::Owned(format!("\"{}\"", input.replace('"', "\"\"")))
+    }
+}
+
 fn shell_impl(shell: &[String], cmd: &str, input: Option<Rope>) -> anyhow::Result<(Tendril, bool)> {
     tokio::task::block_in_place(|| helix_lsp::block_on(shell_impl_async(shell, cmd, input)))
 }
@@ -5056,6 +6806,8 @@ async fn shell_impl_async(
 
     let mut process = Command::new(&shell[0]);
     process
+        // Cancelled jobs stop their command.
+        .kill_on_drop(true)
         .args(&shell[1..])
         .arg(cmd)
         .stdout(Stdio::piped())
@@ -5119,6 +6871,14 @@ fn shell(cx: &mut compositor::Context, cmd: &str, behavior: &ShellBehavior) {
         ShellBehavior::Replace | ShellBehavior::Ignore => true,
         ShellBehavior::Insert | ShellBehavior::Append => false,
     };
+    let cmd = match typed::expand_placeholders(cx.editor, cmd, shell_escape) {
+        Ok(cmd) => cmd,
+        Err(err) => {
+            cx.editor.set_error(err.to_string());
+            return;
+        }
+    };
+    let cmd = cmd.as_str();
 
     let config = cx.editor.config();
     let shell = &config.shell;
@@ -0,0 +1,117 @@
+//! Sending the requests of `.http` files with `curl`, showing the responses in scratch buffers.
+//! JSON bodies are shown in a buffer of their own next to the headers.
+
+use std::{borrow::Cow, process::Stdio};
+
+use anyhow::{ensure, Context as _};
+use helix_core::{http, Rope};
+use helix_view::{editor::Action, Document, Editor};
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    compositor,
+    job::{Callback, Job},
+    ui::PromptEvent,
+};
+
+/// Splits the output of `curl --include` into the status line and headers of the final
+/// response and its body, pretty-printed if it is JSON. Returns whether the body is JSON.
+fn format_response(output: &str) -> (String, String, bool) {
+    let output = output.replace("\r\n", "\n");
+    let mut response = output.as_str();
+    // Interim responses like `100 Continue` come first.
+    let (headers, body) = loop {
+        match response.split_once("\n\n") {
+            Some((_, rest)) if rest.starts_with("HTTP/") => response = rest,
+            Some((headers, body)) => break (headers, body),
+            None => break (response, ""),
+        }
+    };
+
+    let is_json = headers.lines().any(|line| {
+        line.split_once(':').map_or(false, |(name, value)| {
+            name.eq_ignore_ascii_case("content-type") && value.contains("json")
+        })
+    });
+    let json = is_json
+        .then(|| serde_json::from_str::<serde_json::Value>(body).ok())
+        .flatten()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok());
+    let body = json.as_deref().unwrap_or(body);
+    (
+        format!("{}\n", headers),
+        format!("{}\n", body.trim_end()),
+        is_json,
+    )
+}
+
+/// Sends the request under the cursor in the background.
+pub(super) fn http_send(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let (view, doc) = current_ref!(cx.editor);
+    let text = doc.text().slice(..);
+    let line = doc.selection(view.id).primary().cursor_line(text);
+    let request = http::request_at(text, line).context("no request under the cursor")?;
+
+    let mut command = tokio::process::Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--include"])
+        .args(["--request", &request.method]);
+    for (name, value) in &request.headers {
+        command.arg("--header").arg(format!("{}: {}", name, value));
+    }
+    if !request.body.is_empty() {
+        command.args(["--data-binary", "@-"]);
+    }
+    command
+        .arg(&request.url)
+        // Cancelled jobs stop the request.
+        .kill_on_drop(true)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let job_name = format!("{} {}", request.method, request.url);
+    let callback = async move {
+        let mut process = command.spawn().context("failed to run curl")?;
+        if let Some(mut stdin) = process.stdin.take() {
+            stdin.write_all(request.body.as_bytes()).await?;
+        }
+        let output = process.wait_with_output().await?;
+        ensure!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        let (headers, body, is_json) = format_response(&String::from_utf8_lossy(&output.stdout));
+
+        let call = Callback::Editor(Box::new(move |editor: &mut Editor| {
+            let status = headers.lines().next().unwrap_or_default().to_string();
+            // JSON bodies get a buffer of their own to be highlighted without the headers.
+            let response = if is_json {
+                headers
+            } else {
+                format!("{}\n{}", headers, body)
+            };
+            let doc = Document::from(Rope::from(response), None, editor.config.clone());
+            editor.new_file_from_document(Action::HorizontalSplit, doc);
+            if is_json {
+                let doc = Document::from(Rope::from(body), None, editor.config.clone());
+                let doc_id = editor.new_file_from_document(Action::VerticalSplit, doc);
+                let loader = editor.syn_loader.clone();
+                let _ = doc_mut!(editor, &doc_id).set_language_by_language_id("json", loader);
+            }
+            editor.set_status(status);
+        }));
+        Ok(call)
+    };
+    cx.jobs.add(Job::with_callback(callback).named(job_name));
+    Ok(())
+}
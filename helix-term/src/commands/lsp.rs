@@ -15,16 +15,20 @@
 
 use super::{align_view, push_jump, Align, Context, Editor, Open};
 
-use helix_core::{path, text_annotations::InlineAnnotation, Selection};
+use helix_core::{link::Link, path, text_annotations::InlineAnnotation, Selection};
 use helix_view::{
-    document::{DocumentInlayHints, DocumentInlayHintsId, Mode},
-    editor::Action,
+    document::{DocumentHighlights, DocumentInlayHints, DocumentInlayHintsId, DocumentLinks, Mode},
+    editor::{Action, Rename},
+    graphics::Image,
+    quickfix::{QuickfixEntry, QuickfixList},
     theme::Style,
     Document, View,
 };
 
 use crate::{
     compositor::{self, Compositor},
+    job,
+    trash::{self, Deletion},
     ui::{
         self, lsp::SignatureHelp, overlay::overlayed, DynamicPicker, FileLocation, FilePicker,
         Popup, PromptEvent,
@@ -46,7 +50,12 @@ macro_rules! language_server {
         match $doc.language_server() {
             Some(language_server) => language_server,
             None => {
-                $editor.set_status("Language server not active for current buffer");
+                let doc_id = $doc.id();
+                if $editor.start_deferred_language_server(doc_id) {
+                    $editor.set_status("Language server starting, try again once it is ready");
+                } else {
+                    $editor.set_status("Language server not active for current buffer");
+                }
                 return;
             }
         }
@@ -171,14 +180,14 @@ fn location_to_file_location(location: &lsp::Location) -> FileLocation {
 }
 
 // TODO: share with symbol picker(symbol.location)
-fn jump_to_location(
+pub fn jump_to_location(
     editor: &mut Editor,
     location: &lsp::Location,
     offset_encoding: OffsetEncoding,
     action: Action,
 ) {
     let (view, doc) = current!(editor);
-    push_jump(view, doc);
+    push_jump(view, doc, &mut editor.global_jumps);
 
     let path = match location.uri.to_file_path() {
         Ok(path) => path,
@@ -220,9 +229,15 @@ fn sym_picker(
         current_path.clone(),
         move |cx, symbol, action| {
             let (view, doc) = current!(cx.editor);
-            push_jump(view, doc);
+            push_jump(view, doc, &mut cx.editor.global_jumps);
 
-            if current_path.as_ref() != Some(&symbol.location.uri) {
+            let action = match action {
+                Action::Replace => cx.editor.config().goto_action.into(),
+                action => action,
+            };
+            if !matches!(action, Action::Replace)
+                || current_path.as_ref() != Some(&symbol.location.uri)
+            {
                 let uri = &symbol.location.uri;
                 let path = match uri.to_file_path() {
                     Ok(path) => path,
@@ -268,6 +283,7 @@ fn diag_picker(
     current_path: Option<lsp::Url>,
     format: DiagnosticsFormat,
     offset_encoding: OffsetEncoding,
+    quickfix: QuickfixList,
 ) -> FilePicker<PickerDiagnostic> {
     // TODO: drop current_path comparison and instead use workspace: bool flag?
 
@@ -294,9 +310,21 @@ fn diag_picker(
         flat_diag,
         (styles, format),
         move |cx, PickerDiagnostic { url, diag }, action| {
+            // Only picking a diagnostic replaces the quickfix list, browsing them doesn't.
+            let mut quickfix = quickfix.clone();
+            let index = quickfix.entries().iter().position(|entry| {
+                url.to_file_path().map_or(false, |path| entry.path == path)
+                    && entry.line == diag.range.start.line as usize
+                    && entry.column == diag.range.start.character as usize
+            });
+            if let Some(index) = index {
+                quickfix.select(index);
+            }
+            cx.editor.quickfix = quickfix;
+
             if current_path.as_ref() == Some(url) {
                 let (view, doc) = current!(cx.editor);
-                push_jump(view, doc);
+                push_jump(view, doc, &mut cx.editor.global_jumps);
             } else {
                 let path = url.to_file_path().unwrap();
                 cx.editor.open(&path, action).expect("editor.open failed");
@@ -381,15 +409,14 @@ fn nested_to_flat(
 pub fn workspace_symbol_picker(cx: &mut Context) {
     let doc = doc!(cx.editor);
     let current_url = doc.url();
-    let language_server = language_server!(cx.editor, doc);
+    let language_server = match doc.language_server() {
+        Some(language_server) => language_server,
+        None => return super::tags::symbol_picker(cx),
+    };
     let offset_encoding = language_server.offset_encoding();
     let future = match language_server.workspace_symbols("".to_string()) {
         Some(future) => future,
-        None => {
-            cx.editor
-                .set_error("Language server does not support workspace symbols");
-            return;
-        }
+        None => return super::tags::symbol_picker(cx),
     };
 
     cx.callback(
@@ -436,23 +463,87 @@ pub fn workspace_symbol_picker(cx: &mut Context) {
     )
 }
 
+/// Builds a quickfix list of `diagnostics`.
+fn diagnostics_quickfix(
+    title: &str,
+    diagnostics: &BTreeMap<lsp::Url, Vec<lsp::Diagnostic>>,
+    offset_encoding: OffsetEncoding,
+) -> QuickfixList {
+    let entries = diagnostics
+        .iter()
+        .filter_map(|(url, diagnostics)| Some((url.to_file_path().ok()?, diagnostics)))
+        .flat_map(|(path, diagnostics)| {
+            diagnostics.iter().map(move |diagnostic| QuickfixEntry {
+                path: path.clone(),
+                line: diagnostic.range.start.line as usize,
+                column: diagnostic.range.start.character as usize,
+                text: diagnostic
+                    .message
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                offset_encoding: Some(offset_encoding),
+            })
+        })
+        .collect();
+    QuickfixList::new(title, entries)
+}
+
+/// Fills the quickfix list with `locations`, described by their lines in open documents.
+fn set_locations_quickfix(
+    editor: &mut Editor,
+    title: &str,
+    locations: &[lsp::Location],
+    offset_encoding: OffsetEncoding,
+) {
+    let entries = locations
+        .iter()
+        .filter_map(|location| {
+            let path = location.uri.to_file_path().ok()?;
+            let line = location.range.start.line as usize;
+            let text = editor
+                .document_by_path(&path)
+                .map(|doc| doc.text())
+                .filter(|text| line < text.len_lines())
+                .map(|text| text.line(line).to_string().trim().to_string())
+                .unwrap_or_default();
+            Some(QuickfixEntry {
+                path,
+                line,
+                column: location.range.start.character as usize,
+                text,
+                offset_encoding: Some(offset_encoding),
+            })
+        })
+        .collect();
+    editor.quickfix = QuickfixList::new(title, entries);
+}
+
 pub fn diagnostics_picker(cx: &mut Context) {
     let doc = doc!(cx.editor);
     let language_server = language_server!(cx.editor, doc);
     if let Some(current_url) = doc.url() {
         let offset_encoding = language_server.offset_encoding();
+        let config = cx.editor.config();
         let diagnostics = cx
             .editor
             .diagnostics
             .get(&current_url)
+            .into_iter()
+            .flatten()
+            .filter(|diagnostic| config.diagnostic_filter.allows_lsp(diagnostic))
             .cloned()
-            .unwrap_or_default();
+            .collect();
+        let diagnostics: BTreeMap<_, _> = [(current_url.clone(), diagnostics)].into();
+        let quickfix = diagnostics_quickfix("diagnostics", &diagnostics, offset_encoding);
         let picker = diag_picker(
             cx,
-            [(current_url.clone(), diagnostics)].into(),
+            diagnostics,
             Some(current_url),
             DiagnosticsFormat::HideSourcePath,
             offset_encoding,
+            quickfix,
         );
         cx.push_layer(Box::new(overlayed(picker)));
     }
@@ -463,13 +554,28 @@ pub fn workspace_diagnostics_picker(cx: &mut Context) {
     let language_server = language_server!(cx.editor, doc);
     let current_url = doc.url();
     let offset_encoding = language_server.offset_encoding();
-    let diagnostics = cx.editor.diagnostics.clone();
+    let config = cx.editor.config();
+    let diagnostics = cx
+        .editor
+        .diagnostics
+        .iter()
+        .map(|(url, diagnostics)| {
+            let diagnostics = diagnostics
+                .iter()
+                .filter(|diagnostic| config.diagnostic_filter.allows_lsp(diagnostic))
+                .cloned()
+                .collect();
+            (url.clone(), diagnostics)
+        })
+        .collect();
+    let quickfix = diagnostics_quickfix("workspace diagnostics", &diagnostics, offset_encoding);
     let picker = diag_picker(
         cx,
         diagnostics,
         current_url,
         DiagnosticsFormat::ShowSourcePath,
         offset_encoding,
+        quickfix,
     );
     cx.push_layer(Box::new(overlayed(picker)));
 }
@@ -478,7 +584,11 @@ impl ui::menu::Item for lsp::CodeActionOrCommand {
     type Data = ();
     fn format(&self, _data: &Self::Data) -> Row {
         match self {
-            lsp::CodeActionOrCommand::CodeAction(action) => action.title.as_str().into(),
+            lsp::CodeActionOrCommand::CodeAction(action) => {
+                // show the kind next to the title to group the actions visually
+                let kind = action.kind.as_ref().map_or("", |kind| kind.as_str());
+                Row::new(vec![action.title.as_str(), kind])
+            }
             lsp::CodeActionOrCommand::Command(command) => command.title.as_str().into(),
         }
     }
@@ -531,6 +641,27 @@ fn action_prefered(action: &CodeActionOrCommand) -> bool {
     )
 }
 
+fn action_is_quickfix(action: &CodeActionOrCommand) -> bool {
+    action_category(action) == 0
+}
+
+/// Whether `action` is of `kind` or one of its sub-kinds, for example `refactor.extract` is a
+/// `refactor`.
+fn action_has_kind(action: &CodeActionOrCommand, kind: &lsp::CodeActionKind) -> bool {
+    match action {
+        CodeActionOrCommand::CodeAction(CodeAction {
+            kind: Some(action_kind),
+            ..
+        }) => {
+            let action_kind = action_kind.as_str();
+            action_kind
+                .strip_prefix(kind.as_str())
+                .map_or(false, |rest| rest.is_empty() || rest.starts_with('.'))
+        }
+        _ => false,
+    }
+}
+
 fn action_fixes_diagnostics(action: &CodeActionOrCommand) -> bool {
     matches!(
         action,
@@ -542,6 +673,20 @@ fn action_fixes_diagnostics(action: &CodeActionOrCommand) -> bool {
 }
 
 pub fn code_action(cx: &mut Context) {
+    code_action_impl(cx, None);
+}
+
+pub fn code_action_quickfix(cx: &mut Context) {
+    code_action_impl(cx, Some(lsp::CodeActionKind::QUICKFIX));
+}
+
+pub fn code_action_refactor(cx: &mut Context) {
+    code_action_impl(cx, Some(lsp::CodeActionKind::REFACTOR));
+}
+
+/// Requests the code actions for the primary selection, restricted to `kind` if given.
+fn code_action_impl(cx: &mut Context, kind: Option<lsp::CodeActionKind>) {
+    let auto_apply_quickfix = cx.editor.config().lsp.auto_apply_quickfix;
     let (view, doc) = current!(cx.editor);
 
     let language_server = language_server!(cx.editor, doc);
@@ -565,7 +710,7 @@ pub fn code_action(cx: &mut Context) {
                 })
                 .map(|diag| diagnostic_to_lsp_diagnostic(doc.text(), diag, offset_encoding))
                 .collect(),
-            only: None,
+            only: kind.clone().map(|kind| vec![kind]),
             trigger_kind: Some(CodeActionTriggerKind::INVOKED),
         },
     ) {
@@ -585,13 +730,16 @@ pub fn code_action(cx: &mut Context) {
                 None => return,
             };
 
-            // remove disabled code actions
+            // remove disabled code actions, and the actions of other kinds in case the server
+            // ignored `only`
             actions.retain(|action| {
                 matches!(
                     action,
                     CodeActionOrCommand::Command(_)
                         | CodeActionOrCommand::CodeAction(CodeAction { disabled: None, .. })
-                )
+                ) && kind
+                    .as_ref()
+                    .map_or(true, |kind| action_has_kind(action, kind))
             });
 
             if actions.is_empty() {
@@ -599,6 +747,18 @@ pub fn code_action(cx: &mut Context) {
                 return;
             }
 
+            if auto_apply_quickfix {
+                let mut preferred_quickfixes = actions
+                    .iter()
+                    .filter(|action| action_prefered(action) && action_is_quickfix(action));
+                if let (Some(action), None) =
+                    (preferred_quickfixes.next(), preferred_quickfixes.next())
+                {
+                    apply_code_action(editor, action, offset_encoding);
+                    return;
+                }
+            }
+
             // Sort codeactions into a useful order. This behaviour is only partially described in the LSP spec.
             // Many details are modeled after vscode because langauge servers are usually tested against it.
             // VScode sorts the codeaction two times:
@@ -608,8 +768,16 @@ pub fn code_action(cx: &mut Context) {
             // that is marked with `is_preffered` is shown first. The codeactions are then shown in seperate
             // submenus that only contain a certain category (see `action_category`) of actions.
             //
-            // Below this done in in a single sorting step
+            // Below this done in in a single sorting step, except that the preferred quickfixes
+            // are floated above all categories so the likely fix is a single keypress away.
             actions.sort_by(|action1, action2| {
+                let order = (action_prefered(action1) && action_is_quickfix(action1))
+                    .cmp(&(action_prefered(action2) && action_is_quickfix(action2)))
+                    .reverse();
+                if order != Ordering::Equal {
+                    return order;
+                }
+
                 // sort actions by category
                 let order = action_category(action1).cmp(&action_category(action2));
                 if order != Ordering::Equal {
@@ -641,26 +809,7 @@ pub fn code_action(cx: &mut Context) {
 
                 // always present here
                 let code_action = code_action.unwrap();
-
-                match code_action {
-                    lsp::CodeActionOrCommand::Command(command) => {
-                        log::debug!("code action command: {:?}", command);
-                        execute_lsp_command(editor, command.clone());
-                    }
-                    lsp::CodeActionOrCommand::CodeAction(code_action) => {
-                        log::debug!("code action: {:?}", code_action);
-                        if let Some(ref workspace_edit) = code_action.edit {
-                            log::debug!("edit: {:?}", workspace_edit);
-                            let _ = apply_workspace_edit(editor, offset_encoding, workspace_edit);
-                        }
-
-                        // if code action provides both edit and command first the edit
-                        // should be applied and then the command
-                        if let Some(command) = &code_action.command {
-                            execute_lsp_command(editor, command.clone());
-                        }
-                    }
-                }
+                apply_code_action(editor, code_action, offset_encoding);
             });
             picker.move_down(); // pre-select the first item
 
@@ -670,6 +819,32 @@ pub fn code_action(cx: &mut Context) {
     )
 }
 
+fn apply_code_action(
+    editor: &mut Editor,
+    code_action: &CodeActionOrCommand,
+    offset_encoding: OffsetEncoding,
+) {
+    match code_action {
+        lsp::CodeActionOrCommand::Command(command) => {
+            log::debug!("code action command: {:?}", command);
+            execute_lsp_command(editor, command.clone());
+        }
+        lsp::CodeActionOrCommand::CodeAction(code_action) => {
+            log::debug!("code action: {:?}", code_action);
+            if let Some(ref workspace_edit) = code_action.edit {
+                log::debug!("edit: {:?}", workspace_edit);
+                let _ = apply_workspace_edit(editor, offset_encoding, workspace_edit);
+            }
+
+            // if code action provides both edit and command first the edit
+            // should be applied and then the command
+            if let Some(command) = &code_action.command {
+                execute_lsp_command(editor, command.clone());
+            }
+        }
+    }
+}
+
 impl ui::menu::Item for lsp::Command {
     type Data = ();
     fn format(&self, _data: &Self::Data) -> Row {
@@ -700,7 +875,12 @@ pub fn execute_lsp_command(editor: &mut Editor, cmd: lsp::Command) {
     });
 }
 
-pub fn apply_document_resource_op(op: &lsp::ResourceOp) -> std::io::Result<()> {
+/// Applies a file operation of a workspace edit. Deleted files are moved to the trash unless
+/// `hard_delete` is set, the deletion is returned so it can be reported.
+pub fn apply_document_resource_op(
+    op: &lsp::ResourceOp,
+    hard_delete: bool,
+) -> std::io::Result<Option<Deletion>> {
     use lsp::ResourceOp;
     use std::fs;
     match op {
@@ -719,8 +899,9 @@ pub fn apply_document_resource_op(op: &lsp::ResourceOp) -> std::io::Result<()> {
                     }
                 }
 
-                fs::write(&path, [])
+                fs::write(&path, [])?;
             }
+            Ok(None)
         }
         ResourceOp::Delete(op) => {
             let path = op.uri.to_file_path().unwrap();
@@ -731,15 +912,17 @@ pub fn apply_document_resource_op(op: &lsp::ResourceOp) -> std::io::Result<()> {
                     .and_then(|options| options.recursive)
                     .unwrap_or(false);
 
-                if recursive {
-                    fs::remove_dir_all(&path)
-                } else {
-                    fs::remove_dir(&path)
+                if !recursive && fs::read_dir(&path)?.next().is_some() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        format!("directory {} is not empty", path.display()),
+                    ));
                 }
+                trash::delete(&path, hard_delete).map(Some)
             } else if path.is_file() {
-                fs::remove_file(&path)
+                trash::delete(&path, hard_delete).map(Some)
             } else {
-                Ok(())
+                Ok(None)
             }
         }
         ResourceOp::Rename(op) => {
@@ -748,13 +931,42 @@ pub fn apply_document_resource_op(op: &lsp::ResourceOp) -> std::io::Result<()> {
             let ignore_if_exists = op.options.as_ref().map_or(false, |options| {
                 !options.overwrite.unwrap_or(false) && options.ignore_if_exists.unwrap_or(false)
             });
-            if ignore_if_exists && to.exists() {
-                Ok(())
-            } else {
-                fs::rename(from, &to)
+            if !(ignore_if_exists && to.exists()) {
+                fs::rename(from, &to)?;
             }
+            Ok(None)
+        }
+    }
+}
+
+/// Points the document of a file renamed by a workspace edit to its new path. References to
+/// the old name aren't rewritten, the old file stem is put into the search register so they
+/// can be reviewed with global search.
+fn file_renamed(editor: &mut Editor, op: &lsp::RenameFile) {
+    let (from, to) = match (op.old_uri.to_file_path(), op.new_uri.to_file_path()) {
+        (Ok(from), Ok(to)) => (from, to),
+        _ => return,
+    };
+    if let Some(doc) = editor.document_by_path_mut(&from) {
+        if let Err(err) = doc.set_path(Some(&to)) {
+            log::error!("failed to update the path of {}: {}", from.display(), err);
         }
     }
+
+    let old_name = match from.file_stem() {
+        Some(stem) => stem.to_string_lossy().into_owned(),
+        None => return,
+    };
+    editor.registers.push(
+        '/',
+        format!("\\b{}\\b", helix_core::regex::escape(&old_name)),
+    );
+    editor.set_status(format!(
+        "Renamed {} to {}, search for references to '{}' with global search",
+        path::get_relative_path(&from).display(),
+        path::get_relative_path(&to).display(),
+        old_name
+    ));
 }
 
 #[derive(Debug)]
@@ -790,9 +1002,10 @@ pub fn apply_workspace_edit(
     offset_encoding: OffsetEncoding,
     workspace_edit: &lsp::WorkspaceEdit,
 ) -> Result<(), ApplyEditError> {
-    let mut apply_edits = |uri: &helix_lsp::Url,
-                           version: Option<i32>,
-                           text_edits: Vec<lsp::TextEdit>|
+    let apply_edits = |editor: &mut Editor,
+                       uri: &helix_lsp::Url,
+                       version: Option<i32>,
+                       text_edits: Vec<lsp::TextEdit>|
      -> Result<(), ApplyEditErrorKind> {
         let path = match uri.to_file_path() {
             Ok(path) => path,
@@ -866,6 +1079,7 @@ pub fn apply_workspace_edit(
                         .cloned()
                         .collect();
                     apply_edits(
+                        editor,
                         &document_edit.text_document.uri,
                         document_edit.text_document.version,
                         edits,
@@ -881,10 +1095,22 @@ pub fn apply_workspace_edit(
                 for (i, operation) in operations.iter().enumerate() {
                     match operation {
                         lsp::DocumentChangeOperation::Op(op) => {
-                            apply_document_resource_op(op).map_err(|io| ApplyEditError {
-                                kind: ApplyEditErrorKind::IoError(io),
-                                failed_change_idx: i,
-                            })?;
+                            let hard_delete = editor.config().hard_delete;
+                            let deletion =
+                                apply_document_resource_op(op, hard_delete).map_err(|io| {
+                                    ApplyEditError {
+                                        kind: ApplyEditErrorKind::IoError(io),
+                                        failed_change_idx: i,
+                                    }
+                                })?;
+                            if let (Some(deletion), lsp::ResourceOp::Delete(op)) = (deletion, op) {
+                                if let Ok(path) = op.uri.to_file_path() {
+                                    editor.set_status(deletion.describe(&path));
+                                }
+                            }
+                            if let lsp::ResourceOp::Rename(op) = op {
+                                file_renamed(editor, op);
+                            }
                         }
 
                         lsp::DocumentChangeOperation::Edit(document_edit) => {
@@ -900,6 +1126,7 @@ pub fn apply_workspace_edit(
                                 .cloned()
                                 .collect();
                             apply_edits(
+                                editor,
                                 &document_edit.text_document.uri,
                                 document_edit.text_document.version,
                                 edits,
@@ -921,7 +1148,7 @@ pub fn apply_workspace_edit(
         log::debug!("workspace changes: {:?}", changes);
         for (i, (uri, text_edits)) in changes.iter().enumerate() {
             let text_edits = text_edits.to_vec();
-            apply_edits(uri, None, text_edits).map_err(|kind| ApplyEditError {
+            apply_edits(editor, uri, None, text_edits).map_err(|kind| ApplyEditError {
                 kind,
                 failed_change_idx: i,
             })?;
@@ -931,17 +1158,20 @@ pub fn apply_workspace_edit(
     Ok(())
 }
 
+/// Jumps to the only location or opens a picker over `locations`. Both open the location with
+/// `default_action`, unless the picker requests a split explicitly.
 fn goto_impl(
     editor: &mut Editor,
     compositor: &mut Compositor,
     locations: Vec<lsp::Location>,
     offset_encoding: OffsetEncoding,
+    default_action: Action,
 ) {
     let cwdir = std::env::current_dir().unwrap_or_default();
 
     match locations.as_slice() {
         [location] => {
-            jump_to_location(editor, location, offset_encoding, Action::Replace);
+            jump_to_location(editor, location, offset_encoding, default_action);
         }
         [] => {
             editor.set_error("No definition found.");
@@ -951,6 +1181,10 @@ fn goto_impl(
                 locations,
                 cwdir,
                 move |cx, location, action| {
+                    let action = match action {
+                        Action::Replace => default_action,
+                        action => action,
+                    };
                     jump_to_location(cx.editor, location, offset_encoding, action)
                 },
                 move |_editor, location| Some(location_to_file_location(location)),
@@ -976,6 +1210,7 @@ fn to_locations(definitions: Option<lsp::GotoDefinitionResponse>) -> Vec<lsp::Lo
 }
 
 pub fn goto_declaration(cx: &mut Context) {
+    let action = cx.editor.config().goto_action.into();
     let (view, doc) = current!(cx.editor);
     let language_server = language_server!(cx.editor, doc);
     let offset_encoding = language_server.offset_encoding();
@@ -995,37 +1230,50 @@ pub fn goto_declaration(cx: &mut Context) {
         future,
         move |editor, compositor, response: Option<lsp::GotoDefinitionResponse>| {
             let items = to_locations(response);
-            goto_impl(editor, compositor, items, offset_encoding);
+            goto_impl(editor, compositor, items, offset_encoding, action);
         },
     );
 }
 
 pub fn goto_definition(cx: &mut Context) {
+    let action = cx.editor.config().goto_action.into();
+    goto_definition_impl(cx, action);
+}
+
+pub fn goto_definition_hsplit(cx: &mut Context) {
+    goto_definition_impl(cx, Action::HorizontalSplit);
+}
+
+pub fn goto_definition_vsplit(cx: &mut Context) {
+    goto_definition_impl(cx, Action::VerticalSplit);
+}
+
+fn goto_definition_impl(cx: &mut Context, action: Action) {
     let (view, doc) = current!(cx.editor);
-    let language_server = language_server!(cx.editor, doc);
+    let language_server = match doc.language_server() {
+        Some(language_server) => language_server,
+        None => return super::tags::goto_definition(cx, action),
+    };
     let offset_encoding = language_server.offset_encoding();
 
     let pos = doc.position(view.id, offset_encoding);
 
     let future = match language_server.goto_definition(doc.identifier(), pos, None) {
         Some(future) => future,
-        None => {
-            cx.editor
-                .set_error("Language server does not support goto-definition");
-            return;
-        }
+        None => return super::tags::goto_definition(cx, action),
     };
 
     cx.callback(
         future,
         move |editor, compositor, response: Option<lsp::GotoDefinitionResponse>| {
             let items = to_locations(response);
-            goto_impl(editor, compositor, items, offset_encoding);
+            goto_impl(editor, compositor, items, offset_encoding, action);
         },
     );
 }
 
 pub fn goto_type_definition(cx: &mut Context) {
+    let action = cx.editor.config().goto_action.into();
     let (view, doc) = current!(cx.editor);
     let language_server = language_server!(cx.editor, doc);
     let offset_encoding = language_server.offset_encoding();
@@ -1045,12 +1293,13 @@ pub fn goto_type_definition(cx: &mut Context) {
         future,
         move |editor, compositor, response: Option<lsp::GotoDefinitionResponse>| {
             let items = to_locations(response);
-            goto_impl(editor, compositor, items, offset_encoding);
+            goto_impl(editor, compositor, items, offset_encoding, action);
         },
     );
 }
 
 pub fn goto_implementation(cx: &mut Context) {
+    let action = cx.editor.config().goto_action.into();
     let (view, doc) = current!(cx.editor);
     let language_server = language_server!(cx.editor, doc);
     let offset_encoding = language_server.offset_encoding();
@@ -1070,12 +1319,25 @@ pub fn goto_implementation(cx: &mut Context) {
         future,
         move |editor, compositor, response: Option<lsp::GotoDefinitionResponse>| {
             let items = to_locations(response);
-            goto_impl(editor, compositor, items, offset_encoding);
+            goto_impl(editor, compositor, items, offset_encoding, action);
         },
     );
 }
 
 pub fn goto_reference(cx: &mut Context) {
+    let action = cx.editor.config().goto_action.into();
+    goto_reference_impl(cx, action);
+}
+
+pub fn goto_reference_hsplit(cx: &mut Context) {
+    goto_reference_impl(cx, Action::HorizontalSplit);
+}
+
+pub fn goto_reference_vsplit(cx: &mut Context) {
+    goto_reference_impl(cx, Action::VerticalSplit);
+}
+
+fn goto_reference_impl(cx: &mut Context, action: Action) {
     let (view, doc) = current!(cx.editor);
     let language_server = language_server!(cx.editor, doc);
     let offset_encoding = language_server.offset_encoding();
@@ -1095,7 +1357,88 @@ pub fn goto_reference(cx: &mut Context) {
         future,
         move |editor, compositor, response: Option<Vec<lsp::Location>>| {
             let items = response.unwrap_or_default();
-            goto_impl(editor, compositor, items, offset_encoding);
+            if items.len() > 1 {
+                set_locations_quickfix(editor, "references", &items, offset_encoding);
+            }
+            goto_impl(editor, compositor, items, offset_encoding, action);
+        },
+    );
+}
+
+fn peek_impl(
+    editor: &mut Editor,
+    compositor: &mut Compositor,
+    locations: Vec<lsp::Location>,
+    offset_encoding: OffsetEncoding,
+    not_found: &'static str,
+) {
+    if locations.is_empty() {
+        editor.set_error(not_found);
+        return;
+    }
+    let peek = ui::lsp::Peek::new(editor, locations, offset_encoding);
+    let popup = Popup::new(ui::lsp::Peek::ID, peek).auto_close(true);
+    compositor.replace_or_push(ui::lsp::Peek::ID, popup);
+}
+
+pub fn peek_definition(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let language_server = language_server!(cx.editor, doc);
+    let offset_encoding = language_server.offset_encoding();
+
+    let pos = doc.position(view.id, offset_encoding);
+
+    let future = match language_server.goto_definition(doc.identifier(), pos, None) {
+        Some(future) => future,
+        None => {
+            cx.editor
+                .set_error("Language server does not support goto-definition");
+            return;
+        }
+    };
+
+    cx.callback(
+        future,
+        move |editor, compositor, response: Option<lsp::GotoDefinitionResponse>| {
+            let items = to_locations(response);
+            peek_impl(
+                editor,
+                compositor,
+                items,
+                offset_encoding,
+                "No definition found.",
+            );
+        },
+    );
+}
+
+pub fn peek_reference(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let language_server = language_server!(cx.editor, doc);
+    let offset_encoding = language_server.offset_encoding();
+
+    let pos = doc.position(view.id, offset_encoding);
+
+    let future = match language_server.goto_reference(doc.identifier(), pos, None) {
+        Some(future) => future,
+        None => {
+            cx.editor
+                .set_error("Language server does not support goto-reference");
+            return;
+        }
+    };
+
+    cx.callback(
+        future,
+        move |editor, compositor, response: Option<Vec<lsp::Location>>| {
+            let items = response.unwrap_or_default();
+            peek_impl(
+                editor,
+                compositor,
+                items,
+                offset_encoding,
+                "No references found.",
+            );
         },
     );
 }
@@ -1245,7 +1588,30 @@ pub fn signature_help_impl(cx: &mut Context, invoked: SignatureHelpInvoked) {
     );
 }
 
+/// Loads the PNG image linked to under the cursor.
+fn image_under_cursor(editor: &Editor) -> Option<Image> {
+    let target = super::link_under_cursor(editor)?;
+    let path = super::link_path(editor, &target)?;
+    let is_png = path
+        .extension()
+        .map_or(false, |extension| extension.eq_ignore_ascii_case("png"));
+    if !is_png {
+        return None;
+    }
+    std::fs::read(path).ok().and_then(Image::from_png)
+}
+
 pub fn hover(cx: &mut Context) {
+    // Links to images are previewed instead of asking the language server.
+    if let Some(image) = image_under_cursor(cx.editor) {
+        let contents = ui::ImageView::new(image, cx.editor.supports_images());
+        let popup = Popup::new("hover", contents).auto_close(true);
+        cx.callback = Some(Box::new(move |compositor, _cx| {
+            compositor.replace_or_push("hover", popup);
+        }));
+        return;
+    }
+
     let (view, doc) = current!(cx.editor);
     let language_server = language_server!(cx.editor, doc);
     let offset_encoding = language_server.offset_encoding();
@@ -1302,6 +1668,66 @@ fn marked_string_to_markdown(contents: lsp::MarkedString) -> String {
     );
 }
 
+/// Returns the URIs of the documents changed by a workspace edit.
+fn workspace_edit_uris(edit: &lsp::WorkspaceEdit) -> Vec<&lsp::Url> {
+    let mut uris: Vec<_> = edit
+        .changes
+        .iter()
+        .flat_map(|changes| changes.keys())
+        .collect();
+    match &edit.document_changes {
+        Some(lsp::DocumentChanges::Edits(edits)) => {
+            uris.extend(edits.iter().map(|edit| &edit.text_document.uri))
+        }
+        Some(lsp::DocumentChanges::Operations(operations)) => {
+            uris.extend(operations.iter().filter_map(|operation| match operation {
+                lsp::DocumentChangeOperation::Edit(edit) => Some(&edit.text_document.uri),
+                lsp::DocumentChangeOperation::Op(_) => None,
+            }))
+        }
+        None => (),
+    }
+    uris.sort();
+    uris.dedup();
+    uris
+}
+
+/// Finds the symbols named `new_name` of a workspace symbol query in the documents changed by a
+/// rename, which the renamed symbol would collide with.
+fn rename_conflicts(
+    symbols: Option<lsp::WorkspaceSymbolResponse>,
+    new_name: &str,
+    edit: &lsp::WorkspaceEdit,
+) -> Vec<lsp::Location> {
+    let symbols: Vec<_> = match symbols {
+        Some(lsp::WorkspaceSymbolResponse::Flat(symbols)) => symbols
+            .into_iter()
+            .map(|symbol| (symbol.name, symbol.location))
+            .collect(),
+        Some(lsp::WorkspaceSymbolResponse::Nested(symbols)) => symbols
+            .into_iter()
+            .map(|symbol| {
+                let location = match symbol.location {
+                    lsp::OneOf::Left(location) => location,
+                    // Only the document of the symbol is known, point to its start.
+                    lsp::OneOf::Right(location) => {
+                        lsp::Location::new(location.uri, lsp::Range::default())
+                    }
+                };
+                (symbol.name, location)
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let uris = workspace_edit_uris(edit);
+    symbols
+        .into_iter()
+        .filter(|(name, location)| name == new_name && uris.contains(&&location.uri))
+        .map(|(_, location)| location)
+        .collect()
+}
+
 pub fn rename_symbol(cx: &mut Context) {
     fn get_prefill_from_word_boundary(editor: &Editor) -> String {
         let (view, doc) = current_ref!(editor);
@@ -1342,6 +1768,7 @@ fn get_prefill_from_lsp_response(
     }
 
     fn create_rename_prompt(editor: &Editor, prefill: String) -> Box<ui::Prompt> {
+        let old_name = prefill.clone();
         let prompt = ui::Prompt::new(
             "rename-to:".into(),
             None,
@@ -1366,12 +1793,93 @@ fn create_rename_prompt(editor: &Editor, prefill: String) -> Box<ui::Prompt> {
                             return;
                         }
                     };
-                match block_on(future) {
-                    Ok(edits) => {
-                        let _ = apply_workspace_edit(cx.editor, offset_encoding, &edits);
+                let edits = match block_on(future) {
+                    Ok(edits) => edits,
+                    Err(err) => {
+                        cx.editor.set_error(err.to_string());
+                        return;
                     }
-                    Err(err) => cx.editor.set_error(err.to_string()),
-                }
+                };
+
+                let old_name = old_name.clone();
+                let new_name = input.to_string();
+                let symbols = match language_server.workspace_symbols(new_name.clone()) {
+                    Some(future) => future,
+                    // Conflicts can't be looked up, rename right away.
+                    None => {
+                        apply_rename(cx.editor, offset_encoding, &edits, &old_name, &new_name);
+                        return;
+                    }
+                };
+
+                cx.jobs.callback(async move {
+                    // A failed query doesn't prevent the rename.
+                    let symbols = symbols
+                        .await
+                        .ok()
+                        .and_then(|response| serde_json::from_value(response).ok())
+                        .flatten();
+                    let conflicts = rename_conflicts(symbols, &new_name, &edits);
+                    let call: job::Callback = job::Callback::EditorCompositor(Box::new(
+                        move |editor: &mut Editor, compositor: &mut Compositor| {
+                            let first_conflict = match conflicts.first() {
+                                Some(location) => location,
+                                None => {
+                                    apply_rename(
+                                        editor,
+                                        offset_encoding,
+                                        &edits,
+                                        &old_name,
+                                        &new_name,
+                                    );
+                                    return;
+                                }
+                            };
+
+                            // Another symbol with the new name already exists in one of the
+                            // renamed files, ask before applying a rename that likely breaks
+                            // the code.
+                            let location = match first_conflict.uri.to_file_path() {
+                                Ok(path) => format!(
+                                    "{}:{}",
+                                    helix_core::path::get_relative_path(&path).display(),
+                                    first_conflict.range.start.line + 1
+                                ),
+                                Err(_) => first_conflict.uri.to_string(),
+                            };
+                            let message = match conflicts.len() {
+                                1 => format!("'{}' already exists at {}", new_name, location),
+                                n => format!(
+                                    "'{}' already exists at {} and {} other places",
+                                    new_name,
+                                    location,
+                                    n - 1
+                                ),
+                            };
+                            editor.set_error(message);
+                            let prompt = ui::Prompt::new(
+                                "rename anyway? (y/n):".into(),
+                                None,
+                                ui::completers::none,
+                                move |cx: &mut compositor::Context,
+                                      input: &str,
+                                      event: PromptEvent| {
+                                    if event == PromptEvent::Validate && input == "y" {
+                                        apply_rename(
+                                            cx.editor,
+                                            offset_encoding,
+                                            &edits,
+                                            &old_name,
+                                            &new_name,
+                                        );
+                                    }
+                                },
+                            );
+                            compositor.push(Box::new(prompt));
+                        },
+                    ));
+                    Ok(call)
+                });
             },
         )
         .with_line(prefill, editor);
@@ -1379,6 +1887,27 @@ fn create_rename_prompt(editor: &Editor, prefill: String) -> Box<ui::Prompt> {
         Box::new(prompt)
     }
 
+    /// Applies the edits of a rename and records it in the rename history of the editor.
+    fn apply_rename(
+        editor: &mut Editor,
+        offset_encoding: OffsetEncoding,
+        edits: &lsp::WorkspaceEdit,
+        old_name: &str,
+        new_name: &str,
+    ) {
+        if apply_workspace_edit(editor, offset_encoding, edits).is_ok() {
+            let files = workspace_edit_uris(edits)
+                .into_iter()
+                .filter_map(|uri| uri.to_file_path().ok())
+                .collect();
+            editor.renames.push(Rename {
+                old_name: old_name.to_string(),
+                new_name: new_name.to_string(),
+                files,
+            });
+        }
+    }
+
     let (view, doc) = current!(cx.editor);
     let language_server = language_server!(cx.editor, doc);
     let offset_encoding = language_server.offset_encoding();
@@ -1471,6 +2000,108 @@ pub fn select_references_to_symbol_under_cursor(cx: &mut Context) {
     );
 }
 
+/// Requests the occurrences of the symbol under the cursor of the focused view, unless they
+/// are still up to date.
+pub fn compute_document_highlights(editor: &mut Editor, jobs: &mut crate::job::Jobs) {
+    if !editor.config().lsp.display_document_highlights {
+        return;
+    }
+
+    let (view, doc) = current_ref!(editor);
+    if doc.document_highlights(view.id).is_some() {
+        return;
+    }
+    let language_server = match doc.language_server() {
+        Some(language_server) => language_server,
+        None => return,
+    };
+    let offset_encoding = language_server.offset_encoding();
+    let pos = doc.position(view.id, offset_encoding);
+    let future = match language_server.text_document_document_highlight(doc.identifier(), pos, None)
+    {
+        Some(future) => future,
+        None => return,
+    };
+
+    let view_id = view.id;
+    let doc_id = doc.id();
+    let version = doc.version();
+    let cursor = doc
+        .selection(view_id)
+        .primary()
+        .cursor(doc.text().slice(..));
+    let callback = super::make_job_callback(
+        future,
+        move |editor, _compositor, response: Option<Vec<lsp::DocumentHighlight>>| {
+            let doc = match editor.documents.get_mut(&doc_id) {
+                Some(doc) if doc.version() == version => doc,
+                _ => return,
+            };
+            let ranges = response
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|highlight| {
+                    let range = lsp_range_to_range(doc.text(), highlight.range, offset_encoding)?;
+                    let write = highlight.kind == Some(lsp::DocumentHighlightKind::WRITE);
+                    Some((range, write))
+                })
+                .collect();
+            doc.set_document_highlights(
+                view_id,
+                DocumentHighlights {
+                    version,
+                    cursor,
+                    ranges,
+                },
+            );
+        },
+    );
+    jobs.callback(callback);
+}
+
+/// Requests the links of the document of the focused view, unless they are still up to date.
+pub fn compute_document_links(editor: &mut Editor, jobs: &mut crate::job::Jobs) {
+    let doc = doc!(editor);
+    if doc.document_links().is_some() {
+        return;
+    }
+    let language_server = match doc.language_server() {
+        Some(language_server) => language_server,
+        None => return,
+    };
+    let offset_encoding = language_server.offset_encoding();
+    let future = match language_server.text_document_document_link(doc.identifier(), None) {
+        Some(future) => future,
+        None => return,
+    };
+
+    let doc_id = doc.id();
+    let version = doc.version();
+    let callback = super::make_job_callback(
+        future,
+        move |editor, _compositor, response: Option<Vec<lsp::DocumentLink>>| {
+            let doc = match editor.documents.get_mut(&doc_id) {
+                Some(doc) if doc.version() == version => doc,
+                _ => return,
+            };
+            // Links without a target would have to be resolved, which isn't supported.
+            let links = response
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|link| {
+                    let range = lsp_range_to_range(doc.text(), link.range, offset_encoding)?;
+                    Some(Link {
+                        range: range.from()..range.to(),
+                        target: link.target?.to_string(),
+                    })
+                })
+                .collect();
+            doc.set_document_links(DocumentLinks { version, links });
+        },
+    );
+    jobs.callback(callback);
+}
+
 pub fn compute_inlay_hints_for_all_views(editor: &mut Editor, jobs: &mut crate::job::Jobs) {
     if !editor.config().lsp.display_inlay_hints {
         return;
@@ -0,0 +1,155 @@
+//! Code navigation based on tree-sitter tag queries, used when no language server is
+//! available.
+
+use std::{borrow::Cow, path::PathBuf};
+
+use helix_core::{
+    tags::{Tag, TagIndex},
+    textobject::{textobject_word, TextObject},
+    Selection,
+};
+use helix_view::{align_view, editor::Action, Align, Editor};
+use tui::widgets::Row;
+
+use super::{push_jump, Context};
+use crate::{
+    compositor::Compositor,
+    job::Callback,
+    ui::{self, overlay::overlayed, FilePicker},
+    workspace_index,
+};
+
+impl ui::menu::Item for Tag {
+    /// Current working directory.
+    type Data = PathBuf;
+
+    fn format(&self, cwdir: &Self::Data) -> Row {
+        let path = self.path.strip_prefix(cwdir).unwrap_or(&self.path);
+        format!(
+            "{} ({}) {}:{}",
+            self.name,
+            self.kind,
+            path.display(),
+            self.line + 1
+        )
+        .into()
+    }
+}
+
+/// Builds the tag index in the background if it wasn't yet and then calls `f` with it.
+fn with_index(
+    cx: &mut Context,
+    f: impl FnOnce(&mut Editor, &mut Compositor, &TagIndex) + Send + 'static,
+) {
+    let index = cx.editor.tag_index.clone();
+    let loader = cx.editor.syn_loader.clone();
+    let config = cx.editor.config();
+    let file_picker_config = config.file_picker.clone();
+    let persist = config.search.index;
+    let root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("./"));
+
+    cx.jobs.callback(async move {
+        let index = tokio::task::spawn_blocking(move || {
+            let mut tags = index.lock();
+            // Saved files are indexed as they are written, so the workspace only has to be
+            // walked the first time.
+            if !tags.is_complete() {
+                let files = workspace_index::workspace_files(&root, &file_picker_config);
+                workspace_index::refresh_tags(&mut tags, &files, &loader);
+            }
+            if persist {
+                workspace_index::save_tags(&root, &mut tags);
+            }
+            drop(tags);
+            index
+        })
+        .await?;
+        let call = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                f(editor, compositor, &index.lock())
+            },
+        ));
+        Ok(call)
+    });
+}
+
+fn jump_to_tag(editor: &mut Editor, tag: &Tag, action: Action) {
+    let (view, doc) = current!(editor);
+    push_jump(view, doc, &mut editor.global_jumps);
+
+    if let Err(err) = editor.open(&tag.path, action) {
+        let err = format!("failed to open path: {:?}: {:?}", tag.path, err);
+        editor.set_error(err);
+        return;
+    }
+    let (view, doc) = current!(editor);
+    let text = doc.text();
+    let line = tag.line.min(text.len_lines() - 1);
+    let line_start = text.line_to_char(line);
+    let line_text: Cow<str> = text.line(line).into();
+    // Select the name of the definition, the file may have changed since it was indexed.
+    let selection = match line_text.find(&tag.name) {
+        Some(byte_idx) => {
+            let start = line_start + line_text[..byte_idx].chars().count();
+            Selection::single(start, start + tag.name.chars().count())
+        }
+        None => Selection::point(line_start),
+    };
+    doc.set_selection(view.id, selection);
+    align_view(doc, view, Align::Center);
+}
+
+/// A picker over `tags`, opening the accepted tag with `default_action` unless a split was
+/// requested explicitly.
+fn tag_picker(tags: Vec<Tag>, default_action: Action) -> FilePicker<Tag> {
+    let cwdir = std::env::current_dir().unwrap_or_default();
+    FilePicker::new(
+        tags,
+        cwdir,
+        move |cx, tag, action| {
+            let action = match action {
+                Action::Replace => default_action,
+                action => action,
+            };
+            jump_to_tag(cx.editor, tag, action)
+        },
+        |_editor, tag| Some((tag.path.clone().into(), Some((tag.line, tag.line)))),
+    )
+}
+
+/// Goes to the definitions of the word under the cursor found in the tag index.
+pub fn goto_definition(cx: &mut Context, action: Action) {
+    let (view, doc) = current_ref!(cx.editor);
+    let text = doc.text().slice(..);
+    let range = doc.selection(view.id).primary();
+    let word: String = textobject_word(text, range, TextObject::Inside, 1, false)
+        .fragment(text)
+        .into();
+    if word.trim().is_empty() {
+        cx.editor.set_error("No word under the cursor");
+        return;
+    }
+
+    with_index(cx, move |editor, compositor, index| {
+        let tags: Vec<_> = index.find(&word).cloned().collect();
+        match tags.as_slice() {
+            [] => editor.set_error(format!("No definition found for '{}'.", word)),
+            [tag] => jump_to_tag(editor, tag, action),
+            _ => compositor.push(Box::new(overlayed(tag_picker(tags, action)))),
+        }
+    });
+}
+
+/// Opens a picker with all definitions of the workspace found in the tag index.
+pub fn symbol_picker(cx: &mut Context) {
+    with_index(cx, |editor, compositor, index| {
+        let mut tags: Vec<_> = index.tags().cloned().collect();
+        if tags.is_empty() {
+            editor.set_error("No symbols found in the workspace");
+            return;
+        }
+        tags.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+        let action = editor.config().goto_action.into();
+        compositor.push(Box::new(overlayed(tag_picker(tags, action))));
+    });
+}
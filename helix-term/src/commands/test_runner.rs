@@ -0,0 +1,369 @@
+//! Running the tests of a document with the `test-runner` of its language. Tests are found
+//! with the `test.around` capture of the `textobjects.scm` query.
+
+use std::{borrow::Cow, collections::HashSet, path::PathBuf};
+
+use anyhow::{bail, Context as _};
+use helix_core::{regex::Regex, syntax::CapturedNode, tree_sitter::QueryCursor, Selection};
+use helix_view::{
+    align_view,
+    document::{TestResult, TestStatus},
+    editor::Action,
+    Align, Document, Editor,
+};
+use tui::widgets::Row;
+
+use super::{push_jump, shell_escape, shell_impl_async, typed::expand_placeholders};
+use crate::{
+    compositor::{self, Compositor},
+    job::{Callback, Job},
+    ui::{self, overlay::overlayed, FilePicker, Popup, PromptEvent},
+};
+
+/// A test found in a document.
+struct Test {
+    name: String,
+    /// Char range of the test.
+    range: std::ops::Range<usize>,
+}
+
+/// Finds the tests of `doc`, sorted by position.
+fn document_tests(doc: &Document) -> Vec<Test> {
+    let (syntax, query) = match (
+        doc.syntax(),
+        doc.language_config()
+            .and_then(|config| config.textobject_query()),
+    ) {
+        (Some(syntax), Some(query)) => (syntax, query),
+        _ => return Vec::new(),
+    };
+    let text = doc.text().slice(..);
+    let mut cursor = QueryCursor::new();
+    let nodes =
+        match query.capture_nodes("test.around", syntax.tree().root_node(), text, &mut cursor) {
+            Some(nodes) => nodes,
+            None => return Vec::new(),
+        };
+
+    let mut tests: Vec<_> = nodes
+        .filter_map(|captured| {
+            // Grouped captures end with the test itself, after its attributes or comments.
+            let node = match captured {
+                CapturedNode::Single(node) => node,
+                CapturedNode::Grouped(nodes) => *nodes.last()?,
+            };
+            let name = node.child_by_field_name("name")?;
+            Some(Test {
+                name: text.byte_slice(name.start_byte()..name.end_byte()).into(),
+                range: text.byte_to_char(node.start_byte())..text.byte_to_char(node.end_byte()),
+            })
+        })
+        .collect();
+    tests.sort_by_key(|test| test.range.start);
+    tests.dedup_by_key(|test| test.range.start);
+    tests
+}
+
+/// How the tests of a run are run.
+enum Run {
+    /// One command per test.
+    EachTest(Vec<String>),
+    /// A single command running all the tests.
+    AllTests {
+        command: String,
+        failure_regex: Option<Regex>,
+    },
+}
+
+/// Runs `command`, returning its status and output.
+async fn run_command(shell: &[String], command: &str) -> (TestStatus, String) {
+    match shell_impl_async(shell, command, None).await {
+        Ok((output, true)) => (TestStatus::Passed, output.to_string()),
+        Ok((output, false)) => (TestStatus::Failed, output.to_string()),
+        Err(err) => (TestStatus::Failed, err.to_string()),
+    }
+}
+
+/// The status of each of the tests named `names` after a run of all of them failed with
+/// `output`. Only the tests matched by `failure_regex` failed, unless it matches none of them,
+/// for example because the tests didn't compile.
+fn failed_tests(names: &[String], output: &str, failure_regex: Option<&Regex>) -> Vec<TestStatus> {
+    let failed: HashSet<_> = failure_regex
+        .into_iter()
+        .flat_map(|regex| regex.captures_iter(output))
+        .filter_map(|captures| Some(captures.name("test")?.as_str()))
+        .collect();
+    let any_matched = names.iter().any(|name| failed.contains(name.as_str()));
+    names
+        .iter()
+        .map(|name| {
+            if !any_matched || failed.contains(name.as_str()) {
+                TestStatus::Failed
+            } else {
+                TestStatus::Passed
+            }
+        })
+        .collect()
+}
+
+/// Runs `tests` of the current document in the background and records their results in the
+/// document. With `whole_file`, the tests are run at once with the `file-command` of the test
+/// runner if there is one.
+fn run_tests(
+    cx: &mut compositor::Context,
+    tests: Vec<Test>,
+    whole_file: bool,
+) -> anyhow::Result<()> {
+    let doc = doc!(cx.editor);
+    let runner = doc
+        .language_config()
+        .and_then(|config| config.test_runner.as_ref())
+        .context("no test runner configured for the language of the document")?;
+    let run = match &runner.file_command {
+        Some(file_command) if whole_file => {
+            let names: Vec<_> = tests
+                .iter()
+                .map(|test| shell_escape(Cow::from(test.name.as_str())))
+                .collect();
+            let command = file_command.replace("%{tests}", &names.join(" "));
+            Run::AllTests {
+                command: expand_placeholders(cx.editor, &command, shell_escape)?,
+                failure_regex: runner.failure_regex.clone(),
+            }
+        }
+        _ => {
+            let commands = tests
+                .iter()
+                .map(|test| {
+                    let command = runner
+                        .command
+                        .replace("%{test}", &shell_escape(Cow::from(test.name.as_str())));
+                    expand_placeholders(cx.editor, &command, shell_escape)
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            Run::EachTest(commands)
+        }
+    };
+
+    let doc_id = doc.id();
+    let doc = doc_mut!(cx.editor);
+    // Tests are told apart by their position, several tests may have the same name.
+    let indices: Vec<_> = tests
+        .iter()
+        .map(|test| {
+            doc.set_test_result(TestResult {
+                name: test.name.clone(),
+                pos: test.range.start,
+                status: TestStatus::Running,
+                output: String::new(),
+            })
+        })
+        .collect();
+
+    let shell = cx.editor.config().shell.clone();
+    let job_name = match tests.as_slice() {
+        [test] => format!("test {}", test.name),
+        tests => format!("{} tests", tests.len()),
+    };
+    let name = job_name.clone();
+    let names: Vec<_> = tests.into_iter().map(|test| test.name).collect();
+    let callback = async move {
+        let outcomes = match run {
+            Run::EachTest(commands) => {
+                let mut outcomes = Vec::with_capacity(commands.len());
+                for command in commands {
+                    outcomes.push(run_command(&shell, &command).await);
+                }
+                outcomes
+            }
+            Run::AllTests {
+                command,
+                failure_regex,
+            } => {
+                let (status, output) = run_command(&shell, &command).await;
+                let statuses = match status {
+                    TestStatus::Failed => failed_tests(&names, &output, failure_regex.as_ref()),
+                    status => vec![status; names.len()],
+                };
+                statuses
+                    .into_iter()
+                    .map(|status| (status, output.clone()))
+                    .collect()
+            }
+        };
+        let results: Vec<_> = names
+            .into_iter()
+            .zip(indices)
+            .zip(outcomes)
+            .map(|((name, index), (status, output))| (name, index, status, output))
+            .collect();
+
+        let call = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                let total = results.len();
+                let failed: Vec<_> = results
+                    .iter()
+                    .filter(|(_, _, status, _)| *status == TestStatus::Failed)
+                    .collect();
+                match failed.as_slice() {
+                    [] => editor.set_status(format!("{} passed", job_name)),
+                    // Show why a single test failed right away.
+                    [(name, _, _, output)] if total == 1 => {
+                        let contents = ui::Markdown::new(
+                            format!("```sh\n{}\n```", output),
+                            editor.syn_loader.clone(),
+                        );
+                        let popup = Popup::new("test-output", contents).position(Some(
+                            helix_core::Position::new(editor.cursor().0.unwrap_or_default().row, 2),
+                        ));
+                        compositor.replace_or_push("test-output", popup);
+                        editor.set_error(format!("test {} failed", name));
+                    }
+                    failed => editor.set_error(format!(
+                        "{} of {} tests failed, see :test-failures",
+                        failed.len(),
+                        total
+                    )),
+                }
+
+                let doc = match editor.documents.get_mut(&doc_id) {
+                    Some(doc) => doc,
+                    None => return,
+                };
+                for (_, index, status, output) in results {
+                    doc.finish_test(index, status, output);
+                }
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.add(Job::with_callback(callback).named(name));
+    Ok(())
+}
+
+pub(super) fn test_run_nearest(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let (view, doc) = current_ref!(cx.editor);
+    let cursor = doc
+        .selection(view.id)
+        .primary()
+        .cursor(doc.text().slice(..));
+    // The test around the cursor, or else the closest one before it.
+    let test = document_tests(doc)
+        .into_iter()
+        .take_while(|test| test.range.start <= cursor)
+        .last();
+    match test {
+        Some(test) => run_tests(cx, vec![test], false),
+        None => bail!("no test found before the cursor"),
+    }
+}
+
+pub(super) fn test_run_file(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let tests = document_tests(doc!(cx.editor));
+    if tests.is_empty() {
+        bail!("no test found in the document");
+    }
+    run_tests(cx, tests, true)
+}
+
+struct TestFailure {
+    name: String,
+    path: PathBuf,
+    /// 0-indexed line of the start of the test.
+    line: usize,
+}
+
+impl ui::menu::Item for TestFailure {
+    /// Current working directory.
+    type Data = PathBuf;
+
+    fn format(&self, cwdir: &Self::Data) -> Row {
+        let path = self.path.strip_prefix(cwdir).unwrap_or(&self.path);
+        format!("{} {}:{}", self.name, path.display(), self.line + 1).into()
+    }
+}
+
+fn jump_to_failure(editor: &mut Editor, failure: &TestFailure, action: Action) {
+    let (view, doc) = current!(editor);
+    push_jump(view, doc, &mut editor.global_jumps);
+
+    if let Err(err) = editor.open(&failure.path, action) {
+        let err = format!("failed to open path: {:?}: {:?}", failure.path, err);
+        editor.set_error(err);
+        return;
+    }
+    let (view, doc) = current!(editor);
+    let text = doc.text();
+    let pos = text.line_to_char(failure.line.min(text.len_lines() - 1));
+    doc.set_selection(view.id, Selection::point(pos));
+    align_view(doc, view, Align::Center);
+}
+
+/// Opens a picker over the tests which failed in their last run, in all documents.
+pub(super) fn test_failures(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let failures: Vec<_> = cx
+        .editor
+        .documents()
+        .filter_map(|doc| Some((doc.path()?, doc)))
+        .flat_map(|(path, doc)| {
+            let text = doc.text();
+            doc.test_results()
+                .iter()
+                .filter(|result| result.status == TestStatus::Failed)
+                .map(move |result| TestFailure {
+                    name: result.name.clone(),
+                    path: path.clone(),
+                    line: text.char_to_line(result.pos.min(text.len_chars())),
+                })
+        })
+        .collect();
+    if failures.is_empty() {
+        bail!("no failed tests");
+    }
+
+    cx.jobs.callback(async move {
+        let call = Callback::EditorCompositor(Box::new(
+            move |_editor: &mut Editor, compositor: &mut Compositor| {
+                let cwdir = std::env::current_dir().unwrap_or_default();
+                let picker = FilePicker::new(
+                    failures,
+                    cwdir,
+                    |cx, failure, action| jump_to_failure(cx.editor, failure, action),
+                    |_editor, failure| {
+                        Some((
+                            failure.path.clone().into(),
+                            Some((failure.line, failure.line)),
+                        ))
+                    },
+                );
+                compositor.push(Box::new(overlayed(picker)));
+            },
+        ));
+        Ok(call)
+    });
+    Ok(())
+}
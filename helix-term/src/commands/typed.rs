@@ -7,7 +7,7 @@
 
 use helix_core::{encoding, shellwords::Shellwords};
 use helix_view::document::DEFAULT_LANGUAGE_NAME;
-use helix_view::editor::{Action, CloseError, ConfigEvent};
+use helix_view::editor::{Action, CloseError, CommandOutput, ConfigEvent, GutterType, Repl};
 use serde_json::Value;
 use ui::completers::{self, Completer};
 
@@ -23,6 +23,10 @@ pub struct TypableCommand {
 }
 
 impl TypableCommand {
+    pub fn args_required(&self) -> bool {
+        self.signature.args_required
+    }
+
     fn completer_for_argument_number(&self, n: usize) -> &Completer {
         match self.signature.positional_args.get(n) {
             Some(completer) => completer,
@@ -38,6 +42,9 @@ pub struct CommandSignature {
 
     // All remaining arguments will use this completion method, if set.
     var_args: Completer,
+
+    // Whether the command does nothing useful without arguments.
+    args_required: bool,
 }
 
 impl CommandSignature {
@@ -45,6 +52,7 @@ const fn none() -> Self {
         Self {
             positional_args: &[],
             var_args: completers::none,
+            args_required: false,
         }
     }
 
@@ -52,6 +60,7 @@ const fn positional(completers: &'static [Completer]) -> Self {
         Self {
             positional_args: completers,
             var_args: completers::none,
+            args_required: false,
         }
     }
 
@@ -59,8 +68,16 @@ const fn all(completer: Completer) -> Self {
         Self {
             positional_args: &[],
             var_args: completer,
+            args_required: false,
         }
     }
+
+    /// Marks the arguments as required so that running the command from the command palette
+    /// prompts for them instead of running it without arguments.
+    const fn args_required(mut self) -> Self {
+        self.args_required = true;
+        self
+    }
 }
 
 fn quit(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) -> anyhow::Result<()> {
@@ -74,7 +91,10 @@ fn quit(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) ->
 
     // last view and we have unsaved changes
     if cx.editor.tree.views().count() == 1 {
-        buffers_remaining_impl(cx.editor)?
+        if let Err(err) = buffers_remaining_impl(cx.editor) {
+            push_unsaved_buffers_picker(cx, false);
+            return Err(err);
+        }
     }
 
     cx.block_try_flush_writes()?;
@@ -108,7 +128,7 @@ fn open(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) ->
     ensure!(!args.is_empty(), "wrong argument count");
     for arg in args {
         let (path, pos) = args::parse_file(arg);
-        let path = helix_core::path::expand_tilde(&path);
+        let path = helix_core::path::expand(&path);
         // If the path is a directory, open a file picker on that directory and update the status
         // message
         if let Ok(true) = std::fs::canonicalize(&path).map(|p| p.is_dir()) {
@@ -126,8 +146,10 @@ fn open(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) ->
             // Otherwise, just open the file
             let _ = cx.editor.open(&path, Action::Replace)?;
             let (view, doc) = current!(cx.editor);
-            let pos = Selection::point(pos_at_coords(doc.text().slice(..), pos, true));
-            doc.set_selection(view.id, pos);
+            if let Some(pos) = pos {
+                let pos = Selection::point(pos_at_coords(doc.text().slice(..), pos, true));
+                doc.set_selection(view.id, pos);
+            }
             // does not affect opening a buffer without pos
             align_view(doc, view, Align::Center);
         }
@@ -343,8 +365,14 @@ fn write_impl(
                 fmt,
                 Some((path.map(Into::into), force)),
             );
+            let on_cancel = write_unformatted_callback(doc.id(), path.map(Into::into), force);
 
-            jobs.add(Job::with_callback(callback).wait_before_exiting());
+            jobs.add(
+                Job::with_callback(callback)
+                    .wait_before_exiting()
+                    .named("format")
+                    .on_cancel(on_cancel),
+            );
         })
     } else {
         None
@@ -408,7 +436,7 @@ fn format(
     let (view, doc) = current!(cx.editor);
     if let Some(format) = doc.format() {
         let callback = make_format_callback(doc.id(), doc.version(), view.id, format, None);
-        cx.jobs.callback(callback);
+        cx.jobs.add(Job::with_callback(callback).named("format"));
     }
 
     Ok(())
@@ -455,6 +483,151 @@ fn set_indent_style(
     Ok(())
 }
 
+/// Sets or reports the line numbers of the current view, overriding the `line-number` setting.
+fn set_view_line_number(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let default = cx.editor.config().line_number;
+    let view = view_mut!(cx.editor);
+
+    // If no argument, report the line numbers of the view.
+    let arg = match args.first() {
+        Some(arg) => arg,
+        None => {
+            let status = match view.line_number {
+                Some(line_number) => line_number.to_string(),
+                None => format!("{} (default)", default),
+            };
+            cx.editor.set_status(status);
+            return Ok(());
+        }
+    };
+
+    view.line_number = match arg.to_lowercase().as_str() {
+        "default" => None,
+        arg => Some(arg.parse()?),
+    };
+    Ok(())
+}
+
+/// Sets or reports the gutters of the current view, overriding the `gutters` setting.
+fn set_view_gutters(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let view = view_mut!(cx.editor);
+
+    // If no argument, report the gutters of the view.
+    if args.is_empty() {
+        let gutters: Vec<_> = view.gutters().iter().map(GutterType::name).collect();
+        let mut status = gutters.join(" ");
+        if view.gutter_layout.is_none() {
+            status.push_str(" (default)");
+        }
+        cx.editor.set_status(status);
+        return Ok(());
+    }
+
+    view.gutter_layout = match args {
+        [arg] if arg == "default" => None,
+        [arg] if arg == "none" => Some(Vec::new()),
+        args => Some(
+            args.iter()
+                .map(|arg| arg.parse())
+                .collect::<anyhow::Result<_>>()?,
+        ),
+    };
+    Ok(())
+}
+
+fn startup_time(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let mut doc = Document::from(
+        Rope::from(crate::startup::report()),
+        None,
+        cx.editor.config.clone(),
+    );
+    doc.readonly = true;
+    cx.editor.new_file_from_document(Action::Replace, doc);
+    Ok(())
+}
+
+fn grammar_rebuild(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let grammar_id = |config: &helix_core::syntax::LanguageConfiguration| {
+        config
+            .grammar
+            .clone()
+            .unwrap_or_else(|| config.language_id.clone())
+    };
+    let grammar_id = match args.first() {
+        Some(language_id) => cx
+            .editor
+            .syn_loader
+            .language_config_for_language_id(language_id)
+            .map(|config| grammar_id(&*config))
+            .ok_or_else(|| anyhow!("Unknown language {:?}", language_id))?,
+        None => doc!(cx.editor)
+            .language_config()
+            .map(grammar_id)
+            .ok_or_else(|| anyhow!("The current buffer has no language"))?,
+    };
+    let name = format!("rebuild grammar {}", grammar_id);
+
+    cx.editor
+        .set_status(format!("Rebuilding grammar {}...", grammar_id));
+    let callback = async move {
+        let id = grammar_id.clone();
+        tokio::task::spawn_blocking(move || helix_loader::grammar::rebuild_grammar(&id)).await??;
+        let call: job::Callback = Callback::Editor(Box::new(move |editor: &mut Editor| {
+            // The loaded grammars are cached by the language configurations, so a new loader
+            // is needed for the open documents to load the rebuilt grammar.
+            let syntax_config = match helix_core::config::user_syntax_loader() {
+                Ok(syntax_config) => syntax_config,
+                Err(err) => {
+                    editor.set_error(format!("Failed to load language config: {}", err));
+                    return;
+                }
+            };
+            let loader = std::sync::Arc::new(helix_core::syntax::Loader::new(syntax_config));
+            loader.set_scopes(editor.theme.scopes().to_vec());
+            editor.syn_loader = loader.clone();
+            for document in editor.documents.values_mut() {
+                document.detect_language(loader.clone());
+            }
+            editor.set_status(format!("Rebuilt grammar {}", grammar_id));
+        }));
+        Ok(call)
+    };
+    cx.jobs.add(Job::with_callback(callback).named(name));
+    Ok(())
+}
+
 /// Sets or reports the current document's line ending setting.
 fn set_line_ending(
     cx: &mut compositor::Context,
@@ -626,7 +799,7 @@ pub fn write_all_impl(
     force: bool,
     write_scratch: bool,
 ) -> anyhow::Result<()> {
-    let mut errors: Vec<&'static str> = Vec::new();
+    let mut errors: Vec<String> = Vec::new();
     let auto_format = cx.editor.config().auto_format;
     let jobs = &mut cx.jobs;
     let current_view = view!(cx.editor);
@@ -642,7 +815,7 @@ pub fn write_all_impl(
             }
             if doc.path().is_none() {
                 if write_scratch {
-                    errors.push("cannot write a buffer without a filename\n");
+                    errors.push(format!("{}: buffer has no filename", doc.display_name()));
                 }
                 return None;
             }
@@ -671,7 +844,13 @@ pub fn write_all_impl(
                         fmt,
                         Some((None, force)),
                     );
-                    jobs.add(Job::with_callback(callback).wait_before_exiting());
+                    let on_cancel = write_unformatted_callback(doc.id(), None, force);
+                    jobs.add(
+                        Job::with_callback(callback)
+                            .wait_before_exiting()
+                            .named("format")
+                            .on_cancel(on_cancel),
+                    );
                 })
             } else {
                 None
@@ -687,11 +866,17 @@ pub fn write_all_impl(
 
     // manually call save for the rest of docs that don't have a formatter
     for id in saves {
-        cx.editor.save::<PathBuf>(id, None, force)?;
+        if let Err(err) = cx.editor.save::<PathBuf>(id, None, force) {
+            errors.push(format!("{}: {}", doc!(cx.editor, &id).display_name(), err));
+        }
     }
 
     if !errors.is_empty() && !force {
-        bail!("{:?}", errors);
+        bail!(
+            "failed to write {} buffer(s): {}",
+            errors.len(),
+            errors.join(", ")
+        );
     }
 
     Ok(())
@@ -709,70 +894,115 @@ fn write_all(
     write_all_impl(cx, false, true)
 }
 
-fn write_all_quit(
-    cx: &mut compositor::Context,
-    _args: &[Cow<str>],
-    event: PromptEvent,
-) -> anyhow::Result<()> {
-    if event != PromptEvent::Validate {
-        return Ok(());
-    }
-    write_all_impl(cx, false, true)?;
-    quit_all_impl(cx, false)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteRangeMode {
+    Create,
+    Overwrite,
+    Append,
 }
 
-fn force_write_all_quit(
+/// Writes the lines `first-last` given as second argument, or else the selections, of the
+/// current document to the file given as first argument.
+fn write_range_impl(
     cx: &mut compositor::Context,
-    _args: &[Cow<str>],
-    event: PromptEvent,
+    args: &[Cow<str>],
+    mode: WriteRangeMode,
 ) -> anyhow::Result<()> {
-    if event != PromptEvent::Validate {
-        return Ok(());
-    }
-    let _ = write_all_impl(cx, true, true);
-    quit_all_impl(cx, true)
-}
-
-fn quit_all_impl(cx: &mut compositor::Context, force: bool) -> anyhow::Result<()> {
-    cx.block_try_flush_writes()?;
-    if !force {
-        buffers_remaining_impl(cx.editor)?;
-    }
+    ensure!(
+        (1..=2).contains(&args.len()),
+        "expected a path and optionally a line range"
+    );
+    let path = helix_core::path::expand(Path::new(args[0].as_ref()));
 
-    // close all views
-    let views: Vec<_> = cx.editor.tree.views().map(|(view, _)| view.id).collect();
-    for view_id in views {
-        cx.editor.close(view_id);
+    let (view, doc) = current_ref!(cx.editor);
+    let text = doc.text().slice(..);
+    let mut content: String = match args.get(1) {
+        Some(range) => {
+            let (first, last) = range
+                .split_once(['-', ','])
+                .unwrap_or((range.as_ref(), range.as_ref()));
+            let line = |line: &str| -> anyhow::Result<usize> {
+                let line = line
+                    .trim()
+                    .parse::<usize>()
+                    .with_context(|| format!("invalid line range '{}'", range))?;
+                ensure!(
+                    (1..=text.len_lines()).contains(&line),
+                    "line {} is out of range",
+                    line
+                );
+                Ok(line - 1)
+            };
+            let (first, last) = (line(first)?, line(last)?);
+            ensure!(first <= last, "invalid line range '{}'", range);
+            text.slice(text.line_to_char(first)..text.line_to_char(last + 1))
+                .into()
+        }
+        None => doc
+            .selection(view.id)
+            .fragments(text)
+            .collect::<Vec<_>>()
+            .join(doc.line_ending.as_str()),
+    };
+    if !content.ends_with(doc.line_ending.as_str()) {
+        content.push_str(doc.line_ending.as_str());
     }
+    let (bytes, _, _) = doc.encoding().encode(&content);
 
+    let mut options = std::fs::OpenOptions::new();
+    match mode {
+        WriteRangeMode::Create => options.write(true).create_new(true),
+        WriteRangeMode::Overwrite => options.write(true).create(true).truncate(true),
+        WriteRangeMode::Append => options.append(true).create(true),
+    };
+    let mut file = options.open(&path).map_err(|err| match err.kind() {
+        std::io::ErrorKind::AlreadyExists => anyhow!(
+            "'{}' already exists, use :write-range! to overwrite it or :append-range",
+            path.display()
+        ),
+        _ => anyhow!("failed to open '{}': {}", path.display(), err),
+    })?;
+    std::io::Write::write_all(&mut file, &bytes)?;
+
+    let lines = content.lines().count();
+    let verb = match mode {
+        WriteRangeMode::Append => "appended to",
+        _ => "written to",
+    };
+    cx.editor.set_status(format!(
+        "{} line(s) {} '{}'",
+        lines,
+        verb,
+        helix_core::path::get_relative_path(&path).display()
+    ));
     Ok(())
 }
 
-fn quit_all(
+fn write_range(
     cx: &mut compositor::Context,
-    _args: &[Cow<str>],
+    args: &[Cow<str>],
     event: PromptEvent,
 ) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
         return Ok(());
     }
 
-    quit_all_impl(cx, false)
+    write_range_impl(cx, args, WriteRangeMode::Create)
 }
 
-fn force_quit_all(
+fn force_write_range(
     cx: &mut compositor::Context,
-    _args: &[Cow<str>],
+    args: &[Cow<str>],
     event: PromptEvent,
 ) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
         return Ok(());
     }
 
-    quit_all_impl(cx, true)
+    write_range_impl(cx, args, WriteRangeMode::Overwrite)
 }
 
-fn cquit(
+fn append_range(
     cx: &mut compositor::Context,
     args: &[Cow<str>],
     event: PromptEvent,
@@ -781,91 +1011,130 @@ fn cquit(
         return Ok(());
     }
 
-    let exit_code = args
-        .first()
-        .and_then(|code| code.parse::<i32>().ok())
-        .unwrap_or(1);
-
-    cx.editor.exit_code = exit_code;
-    quit_all_impl(cx, false)
+    write_range_impl(cx, args, WriteRangeMode::Append)
 }
 
-fn force_cquit(
-    cx: &mut compositor::Context,
-    args: &[Cow<str>],
-    event: PromptEvent,
-) -> anyhow::Result<()> {
-    if event != PromptEvent::Validate {
-        return Ok(());
-    }
-
-    let exit_code = args
-        .first()
-        .and_then(|code| code.parse::<i32>().ok())
-        .unwrap_or(1);
-    cx.editor.exit_code = exit_code;
+/// Resolves the destination of a file operation on `source`. A directory receives a file with
+/// the same name as `source`.
+fn file_operation_target(arg: &str, source: &Path) -> anyhow::Result<PathBuf> {
+    let path = helix_core::path::get_canonicalized_path(Path::new(arg))?;
+    let path = if path.is_dir() {
+        path.join(source.file_name().context("invalid file name")?)
+    } else {
+        path
+    };
+    ensure!(!path.exists(), "'{}' already exists", path.display());
+    Ok(path)
+}
 
-    quit_all_impl(cx, true)
+fn file_url(path: &Path) -> anyhow::Result<helix_lsp::Url> {
+    helix_lsp::Url::from_file_path(path)
+        .map_err(|_| anyhow!("invalid file path '{}'", path.display()))
 }
 
-fn theme(
-    cx: &mut compositor::Context,
-    args: &[Cow<str>],
-    event: PromptEvent,
+/// Moves the file of the document `doc_id` to `new_path` and updates the path of the document,
+/// notifying the language servers.
+fn move_document_file(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    new_path: &Path,
 ) -> anyhow::Result<()> {
-    let true_color = cx.editor.config.load().true_color || crate::true_color();
-    match event {
-        PromptEvent::Abort => {
-            cx.editor.unset_theme_preview();
-        }
-        PromptEvent::Update => {
-            if args.is_empty() {
-                // Ensures that a preview theme gets cleaned up if the user backspaces until the prompt is empty.
-                cx.editor.unset_theme_preview();
-            } else if let Some(theme_name) = args.first() {
-                if let Ok(theme) = cx.editor.theme_loader.load(theme_name) {
-                    if !(true_color || theme.is_16_color()) {
-                        bail!("Unsupported theme: theme requires true color support");
-                    }
-                    cx.editor.set_theme_preview(theme);
-                };
-            };
-        }
-        PromptEvent::Validate => {
-            if let Some(theme_name) = args.first() {
-                let theme = cx
-                    .editor
-                    .theme_loader
-                    .load(theme_name)
-                    .map_err(|err| anyhow::anyhow!("Could not load theme: {}", err))?;
-                if !(true_color || theme.is_16_color()) {
-                    bail!("Unsupported theme: theme requires true color support");
-                }
-                cx.editor.set_theme(theme);
-            } else {
-                let name = cx.editor.theme.name().to_string();
+    let doc = editor
+        .documents
+        .get_mut(&doc_id)
+        .context("buffer was closed")?;
+    let old_path = doc.path().context("buffer has no file")?.clone();
+    let (old_url, new_url) = (file_url(&old_path)?, file_url(new_path)?);
 
-                cx.editor.set_status(name);
-            }
+    if let Some(parent) = new_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    crate::trash::move_path(&old_path, new_path)?;
+
+    let old_identifier = doc.identifier();
+    doc.set_path(Some(new_path))?;
+    if let Some(language_server) = doc.language_server() {
+        let language_id = doc.language_id().map(ToOwned::to_owned).unwrap_or_default();
+        tokio::spawn(language_server.text_document_did_close(old_identifier));
+        tokio::spawn(language_server.text_document_did_open(
+            new_url.clone(),
+            doc.version(),
+            doc.text(),
+            language_id,
+        ));
+    }
+    for language_server in editor.language_servers.iter_clients() {
+        if let Some(notification) = language_server.did_rename_file(&old_url, &new_url) {
+            tokio::spawn(notification);
         }
-    };
+    }
+    editor.file_changed(&old_path, helix_lsp::lsp::FileChangeType::DELETED);
+    editor.file_changed(new_path, helix_lsp::lsp::FileChangeType::CREATED);
 
+    editor.set_status(format!(
+        "Moved '{}' to '{}'",
+        helix_core::path::get_relative_path(&old_path).display(),
+        helix_core::path::get_relative_path(new_path).display()
+    ));
     Ok(())
 }
 
-fn yank_main_selection_to_clipboard(
+fn rename_file(
     cx: &mut compositor::Context,
-    _args: &[Cow<str>],
+    args: &[Cow<str>],
     event: PromptEvent,
 ) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
         return Ok(());
     }
 
-    yank_main_selection_to_clipboard_impl(cx.editor, ClipboardType::Clipboard)
+    ensure!(args.len() == 1, "expected the new path of the file");
+    let doc = doc!(cx.editor);
+    let doc_id = doc.id();
+    let old_path = doc.path().context("buffer has no file")?;
+    let new_path = file_operation_target(&args[0], old_path)?;
+    let (old_url, new_url) = (file_url(old_path)?, file_url(&new_path)?);
+
+    // Language servers may update the references to the file, like imports, before it moves.
+    let requests: Vec<_> = cx
+        .editor
+        .language_servers
+        .iter_clients()
+        .filter_map(|language_server| {
+            let request = language_server.will_rename_file(&old_url, &new_url)?;
+            Some((request, language_server.offset_encoding()))
+        })
+        .collect();
+
+    let callback = async move {
+        let mut edits = Vec::new();
+        for (request, offset_encoding) in requests {
+            match request.await {
+                Ok(Some(edit)) => edits.push((edit, offset_encoding)),
+                Ok(None) => (),
+                Err(err) => log::error!("willRenameFiles request failed: {}", err),
+            }
+        }
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, _compositor: &mut Compositor| {
+                for (edit, offset_encoding) in edits {
+                    if let Err(err) = apply_workspace_edit(editor, offset_encoding, &edit) {
+                        log::error!("failed to apply the edits of a file rename: {:?}", err);
+                    }
+                }
+                if let Err(err) = move_document_file(editor, doc_id, &new_path) {
+                    editor.set_error(err.to_string());
+                }
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+
+    Ok(())
 }
 
-fn yank_joined_to_clipboard(
+fn delete_file(
     cx: &mut compositor::Context,
     args: &[Cow<str>],
     event: PromptEvent,
@@ -874,25 +1143,32 @@ fn yank_joined_to_clipboard(
         return Ok(());
     }
 
+    ensure!(args.is_empty(), ":delete-file takes no arguments");
     let doc = doc!(cx.editor);
-    let default_sep = Cow::Borrowed(doc.line_ending.as_str());
-    let separator = args.first().unwrap_or(&default_sep);
-    yank_joined_to_clipboard_impl(cx.editor, separator, ClipboardType::Clipboard)
-}
-
-fn yank_main_selection_to_primary_clipboard(
-    cx: &mut compositor::Context,
-    _args: &[Cow<str>],
-    event: PromptEvent,
-) -> anyhow::Result<()> {
-    if event != PromptEvent::Validate {
-        return Ok(());
+    ensure!(
+        !doc.is_modified(),
+        "buffer has unsaved changes, write or discard them first"
+    );
+    let doc_id = doc.id();
+    let path = doc.path().context("buffer has no file")?.clone();
+    let url = file_url(&path)?;
+
+    let deletion = crate::trash::delete(&path, cx.editor.config().hard_delete)?;
+    // Forcefully closing a document can't fail.
+    let _ = cx.editor.close_document(doc_id, true);
+    for language_server in cx.editor.language_servers.iter_clients() {
+        if let Some(notification) = language_server.did_delete_file(&url) {
+            tokio::spawn(notification);
+        }
     }
+    cx.editor
+        .file_changed(&path, helix_lsp::lsp::FileChangeType::DELETED);
 
-    yank_main_selection_to_clipboard_impl(cx.editor, ClipboardType::Selection)
+    cx.editor.set_status(deletion.describe(&path));
+    Ok(())
 }
 
-fn yank_joined_to_primary_clipboard(
+fn duplicate_file(
     cx: &mut compositor::Context,
     args: &[Cow<str>],
     event: PromptEvent,
@@ -901,13 +1177,31 @@ fn yank_joined_to_primary_clipboard(
         return Ok(());
     }
 
-    let doc = doc!(cx.editor);
-    let default_sep = Cow::Borrowed(doc.line_ending.as_str());
-    let separator = args.first().unwrap_or(&default_sep);
-    yank_joined_to_clipboard_impl(cx.editor, separator, ClipboardType::Selection)
+    ensure!(args.len() == 1, "expected the path of the copy");
+    let source = doc!(cx.editor)
+        .path()
+        .context("buffer has no file")?
+        .clone();
+    let target = file_operation_target(&args[0], &source)?;
+
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::copy(&source, &target)?;
+    let url = file_url(&target)?;
+    for language_server in cx.editor.language_servers.iter_clients() {
+        if let Some(notification) = language_server.did_create_file(&url) {
+            tokio::spawn(notification);
+        }
+    }
+    cx.editor
+        .file_changed(&target, helix_lsp::lsp::FileChangeType::CREATED);
+
+    cx.editor.open(&target, Action::Replace)?;
+    Ok(())
 }
 
-fn paste_clipboard_after(
+fn write_all_quit(
     cx: &mut compositor::Context,
     _args: &[Cow<str>],
     event: PromptEvent,
@@ -915,11 +1209,402 @@ fn paste_clipboard_after(
     if event != PromptEvent::Validate {
         return Ok(());
     }
-
-    paste_clipboard_impl(cx.editor, Paste::After, ClipboardType::Clipboard, 1)
+    write_all_impl(cx, false, true)?;
+    // Report the writes that failed instead of the buffers they left modified.
+    cx.block_try_flush_writes()?;
+    quit_all_impl(cx, false)
 }
 
-fn paste_clipboard_before(
+fn force_write_all_quit(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+    let _ = write_all_impl(cx, true, true);
+    quit_all_impl(cx, true)
+}
+
+fn quit_all_impl(cx: &mut compositor::Context, force: bool) -> anyhow::Result<()> {
+    cx.block_try_flush_writes()?;
+    if !force {
+        if let Err(err) = buffers_remaining_impl(cx.editor) {
+            push_unsaved_buffers_picker(cx, true);
+            return Err(err);
+        }
+    }
+
+    // close all views
+    let views: Vec<_> = cx.editor.tree.views().map(|(view, _)| view.id).collect();
+    for view_id in views {
+        cx.editor.close(view_id);
+    }
+
+    Ok(())
+}
+
+/// A modified buffer listed when quitting, with a summary of its changes to the saved file.
+struct UnsavedBuffer {
+    doc_id: DocumentId,
+    name: String,
+    changes: String,
+}
+
+impl ui::menu::Item for UnsavedBuffer {
+    type Data = ();
+
+    fn format(&self, _data: &Self::Data) -> Row {
+        Row::new([self.name.clone(), self.changes.clone()])
+    }
+}
+
+/// Pushes a picker of the modified buffers, offering to save, discard or diff the selected one.
+/// Once none remain the current view, or all of them if `quit_all` is set, are closed.
+fn push_unsaved_buffers_picker(cx: &mut compositor::Context, quit_all: bool) {
+    push_unsaved_buffers_picker_impl(cx, quit_all, HashSet::new())
+}
+
+/// Buffers in `saved` are being written and aren't offered anymore.
+fn push_unsaved_buffers_picker_impl(
+    cx: &mut compositor::Context,
+    quit_all: bool,
+    saved: HashSet<DocumentId>,
+) {
+    let callback = async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| match unsaved_buffers_picker(
+                editor, quit_all, saved,
+            ) {
+                Some(picker) => compositor.push(Box::new(overlayed(picker))),
+                None if quit_all => {
+                    let views: Vec<_> = editor.tree.views().map(|(view, _)| view.id).collect();
+                    for view_id in views {
+                        editor.close(view_id);
+                    }
+                }
+                None => editor.close(view!(editor).id),
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+}
+
+fn unsaved_buffers_picker(
+    editor: &Editor,
+    quit_all: bool,
+    saved: HashSet<DocumentId>,
+) -> Option<FilePicker<UnsavedBuffer>> {
+    let buffers: Vec<_> = editor
+        .documents()
+        .filter(|doc| doc.is_modified() && !saved.contains(&doc.id()))
+        .map(|doc| {
+            let changes = match saved_diff(doc) {
+                Ok(diff) => {
+                    let count =
+                        |prefix| diff.lines().filter(|line| line.starts_with(prefix)).count();
+                    format!("+{} -{}", count('+'), count('-'))
+                }
+                Err(_) => "new file".to_string(),
+            };
+            UnsavedBuffer {
+                doc_id: doc.id(),
+                name: doc.display_name().into_owned(),
+                changes,
+            }
+        })
+        .collect();
+    if buffers.is_empty() {
+        return None;
+    }
+
+    let picker = FilePicker::new(
+        buffers,
+        (),
+        move |cx, buffer, _action| {
+            let doc_id = buffer.doc_id;
+            let saved = saved.clone();
+            let prompt = ui::Prompt::new(
+                format!("{}: (s)ave, (d)iscard or di(f)f: ", buffer.name).into(),
+                None,
+                ui::completers::none,
+                move |cx, input: &str, event: PromptEvent| {
+                    if event != PromptEvent::Validate {
+                        return;
+                    }
+                    let mut saved = saved.clone();
+                    let result = match input {
+                        "s" => cx.editor.save::<PathBuf>(doc_id, None, false).map(|_| {
+                            saved.insert(doc_id);
+                        }),
+                        "d" => cx
+                            .editor
+                            .close_document(doc_id, true)
+                            .map_err(|err| match err {
+                                CloseError::SaveError(err) => err,
+                                CloseError::DoesNotExist => anyhow!("buffer was already closed"),
+                                CloseError::BufferModified(name) => anyhow!("{} is modified", name),
+                            }),
+                        "f" => {
+                            // Leave the user to review the changes, quitting is run again
+                            // afterwards.
+                            if let Err(err) = open_saved_diff(cx.editor, doc_id) {
+                                cx.editor.set_error(err.to_string());
+                            }
+                            return;
+                        }
+                        _ => Ok(()),
+                    };
+                    if let Err(err) = result {
+                        cx.editor.set_error(err.to_string());
+                    }
+                    push_unsaved_buffers_picker_impl(cx, quit_all, saved);
+                },
+            );
+            cx.jobs.callback(async move {
+                let call: job::Callback = Callback::EditorCompositor(Box::new(
+                    move |_editor: &mut Editor, compositor: &mut Compositor| {
+                        compositor.push(Box::new(prompt))
+                    },
+                ));
+                Ok(call)
+            });
+        },
+        |editor, buffer| {
+            let doc = editor.documents.get(&buffer.doc_id)?;
+            let &view_id = doc.selections().keys().next()?;
+            let line = doc
+                .selection(view_id)
+                .primary()
+                .cursor_line(doc.text().slice(..));
+            Some((buffer.doc_id.into(), Some((line, line))))
+        },
+    );
+    Some(picker)
+}
+
+/// Returns a unified diff of the saved file of `doc` to its contents.
+fn saved_diff(doc: &Document) -> anyhow::Result<String> {
+    let path = doc.path().context("buffer was never saved")?;
+    let (saved, _) =
+        helix_view::document::from_reader(&mut std::fs::File::open(path)?, Some(doc.encoding()))?;
+    Ok(helix_core::diff::unified_diff(&saved, doc.text()))
+}
+
+/// Opens the differences of the document `doc_id` to its saved file in a scratch buffer next
+/// to it.
+fn open_saved_diff(editor: &mut Editor, doc_id: DocumentId) -> anyhow::Result<()> {
+    let doc = editor.document(doc_id).context("buffer was closed")?;
+    let name = doc.display_name().into_owned();
+    let diff = saved_diff(doc)?;
+
+    editor.switch(doc_id, Action::Replace);
+    let text = Rope::from(format!("--- {0}\n+++ {0}\n{1}", name, diff));
+    let diff_doc = Document::from(text, None, editor.config.clone());
+    let diff_id = editor.new_file_from_document(Action::VerticalSplit, diff_doc);
+    let loader = editor.syn_loader.clone();
+    doc_mut!(editor, &diff_id).set_language_by_language_id("diff", loader)?;
+    Ok(())
+}
+
+fn quit_all(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    quit_all_impl(cx, false)
+}
+
+fn force_quit_all(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    quit_all_impl(cx, true)
+}
+
+fn cquit(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let exit_code = args
+        .first()
+        .and_then(|code| code.parse::<i32>().ok())
+        .unwrap_or(1);
+
+    cx.editor.exit_code = exit_code;
+    quit_all_impl(cx, false)
+}
+
+fn force_cquit(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let exit_code = args
+        .first()
+        .and_then(|code| code.parse::<i32>().ok())
+        .unwrap_or(1);
+    cx.editor.exit_code = exit_code;
+
+    quit_all_impl(cx, true)
+}
+
+fn theme(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    let color_support = crate::color_support(cx.editor.config.load().true_color);
+    match event {
+        PromptEvent::Abort => {
+            cx.editor.unset_theme_preview();
+        }
+        PromptEvent::Update => {
+            if args.is_empty() {
+                // Ensures that a preview theme gets cleaned up if the user backspaces until the prompt is empty.
+                cx.editor.unset_theme_preview();
+            } else if let Some(theme_name) = args.first() {
+                if let Ok(theme) = cx.editor.theme_loader.load(theme_name) {
+                    cx.editor.set_theme_preview(theme.degrade(color_support));
+                };
+            };
+        }
+        PromptEvent::Validate => {
+            if let Some(theme_name) = args.first() {
+                let theme = cx
+                    .editor
+                    .theme_loader
+                    .load(theme_name)
+                    .map_err(|err| anyhow::anyhow!("Could not load theme: {}", err))?;
+                cx.editor.set_theme(theme.degrade(color_support));
+            } else {
+                let name = cx.editor.theme.name().to_string();
+
+                cx.editor.set_status(name);
+            }
+        }
+    };
+
+    Ok(())
+}
+
+fn theme_set(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let (scope, style) = match args {
+        [scope, style @ ..] if !style.is_empty() => (scope, style.join(" ")),
+        _ => bail!("Bad arguments. Usage: `:theme-set scope style`"),
+    };
+
+    // Accept both TOML values like `{ fg = "red" }` and bare colors like `red`.
+    let value = toml::from_str::<toml::Value>(&format!("style = {}", style))
+        .ok()
+        .and_then(|table| table.get("style").cloned())
+        .unwrap_or(toml::Value::String(style));
+
+    let color_support = crate::color_support(cx.editor.config().true_color);
+    let mut theme = cx.editor.theme.clone();
+    theme
+        .set_style(scope, value)
+        .map_err(|err| anyhow!("Could not parse style: {}", err))?;
+    cx.editor.set_theme(theme.degrade(color_support));
+
+    Ok(())
+}
+
+fn yank_main_selection_to_clipboard(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    yank_main_selection_to_clipboard_impl(cx.editor, ClipboardType::Clipboard)
+}
+
+fn yank_joined_to_clipboard(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let doc = doc!(cx.editor);
+    let default_sep = Cow::Borrowed(doc.line_ending.as_str());
+    let separator = args.first().unwrap_or(&default_sep);
+    yank_joined_to_clipboard_impl(cx.editor, separator, ClipboardType::Clipboard)
+}
+
+fn yank_main_selection_to_primary_clipboard(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    yank_main_selection_to_clipboard_impl(cx.editor, ClipboardType::Selection)
+}
+
+fn yank_joined_to_primary_clipboard(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let doc = doc!(cx.editor);
+    let default_sep = Cow::Borrowed(doc.line_ending.as_str());
+    let separator = args.first().unwrap_or(&default_sep);
+    yank_joined_to_clipboard_impl(cx.editor, separator, ClipboardType::Selection)
+}
+
+fn paste_clipboard_after(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    paste_clipboard_impl(cx.editor, Paste::After, ClipboardType::Clipboard, 1)
+}
+
+fn paste_clipboard_before(
     cx: &mut compositor::Context,
     _args: &[Cow<str>],
     event: PromptEvent,
@@ -1025,7 +1710,7 @@ fn change_current_directory(
         return Ok(());
     }
 
-    let dir = helix_core::path::expand_tilde(
+    let dir = helix_core::path::expand(
         args.first()
             .context("target directory not provided")?
             .as_ref()
@@ -1044,6 +1729,57 @@ fn change_current_directory(
     Ok(())
 }
 
+fn workspace_add(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let root = match args.first() {
+        Some(root) => helix_core::path::get_canonicalized_path(Path::new(root.as_ref()))?,
+        None => {
+            let roots = std::iter::once(find_workspace().0)
+                .chain(cx.editor.workspace_roots.iter().cloned())
+                .map(|root| root.display().to_string())
+                .collect::<Vec<_>>();
+            cx.editor
+                .set_status(format!("Workspace roots: {}", roots.join(", ")));
+            return Ok(());
+        }
+    };
+    ensure!(root.is_dir(), "{} is not a directory", root.display());
+
+    cx.editor
+        .set_status(format!("Added {} to the workspace", root.display()));
+    cx.editor.add_workspace_root(root);
+    Ok(())
+}
+
+fn workspace_remove(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let root = args.first().context("workspace root not provided")?;
+    let root = helix_core::path::get_canonicalized_path(Path::new(root.as_ref()))?;
+    ensure!(
+        cx.editor.remove_workspace_root(&root),
+        "{} was not added to the workspace",
+        root.display()
+    );
+
+    cx.editor
+        .set_status(format!("Removed {} from the workspace", root.display()));
+    Ok(())
+}
+
 fn show_current_directory(
     cx: &mut compositor::Context,
     _args: &[Cow<str>],
@@ -1384,6 +2120,7 @@ fn lsp_restart(
         &editor_config.workspace_lsp_roots,
         editor_config.lsp.snippets,
     )?;
+    cx.editor.language_server_restarts.remove(&scope);
 
     // This collect is needed because refresh_language_server would need to re-borrow editor.
     let document_ids_to_refresh: Vec<DocumentId> = cx
@@ -1433,6 +2170,76 @@ fn lsp_stop(
     Ok(())
 }
 
+/// A file changed by a symbol rename, listed by `:rename-history`.
+struct RenamedFile {
+    old_name: String,
+    new_name: String,
+    path: PathBuf,
+}
+
+impl ui::menu::Item for RenamedFile {
+    type Data = ();
+
+    fn format(&self, _data: &Self::Data) -> Row {
+        Row::new([
+            format!("{} → {}", self.old_name, self.new_name),
+            helix_core::path::get_relative_path(&self.path)
+                .display()
+                .to_string(),
+        ])
+    }
+}
+
+fn rename_history(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let files: Vec<_> = cx
+        .editor
+        .renames
+        .iter()
+        .rev()
+        .flat_map(|rename| {
+            rename.files.iter().map(|path| RenamedFile {
+                old_name: rename.old_name.clone(),
+                new_name: rename.new_name.clone(),
+                path: path.clone(),
+            })
+        })
+        .collect();
+    ensure!(!files.is_empty(), "No symbols were renamed");
+
+    let callback = async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |_editor: &mut Editor, compositor: &mut Compositor| {
+                let picker = FilePicker::new(
+                    files,
+                    (),
+                    |cx, file, action| {
+                        if let Err(err) = cx.editor.open(&file.path, action) {
+                            cx.editor.set_error(format!(
+                                "Failed to open {}: {}",
+                                file.path.display(),
+                                err
+                            ));
+                        }
+                    },
+                    |_editor, file| Some((file.path.clone().into(), None)),
+                );
+                compositor.push(Box::new(overlayed(picker)))
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+    Ok(())
+}
+
 fn tree_sitter_scopes(
     cx: &mut compositor::Context,
     _args: &[Cow<str>],
@@ -1613,10 +2420,36 @@ fn tutor(
         return Ok(());
     }
 
-    let path = helix_loader::runtime_file(Path::new("tutor"));
-    cx.editor.open(&path, Action::Replace)?;
-    // Unset path to prevent accidentally saving to the original tutor file.
-    doc_mut!(cx.editor).set_path(None)?;
+    let path = helix_loader::runtime_file(Path::new("tutor"));
+    cx.editor.open(&path, Action::Replace)?;
+    // Unset path to prevent accidentally saving to the original tutor file.
+    doc_mut!(cx.editor).set_path(None)?;
+    Ok(())
+}
+
+fn help(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    ensure!(args.len() <= 1, ":help takes at most one argument");
+    let topic = args
+        .first()
+        .map_or("index", |topic| topic.as_ref())
+        .to_string();
+
+    let callback = async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                if let Err(err) = crate::help::open(editor, compositor, &topic) {
+                    editor.set_error(err.to_string());
+                }
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+
     Ok(())
 }
 
@@ -1671,6 +2504,7 @@ pub(super) fn goto_line_number(
                 .expect("update_goto_line_number_preview should always set last_selection");
 
             let (view, doc) = current!(cx.editor);
+            cx.editor.global_jumps.push(doc, &last_selection);
             view.jumps.push((doc.id(), last_selection));
         }
 
@@ -1684,6 +2518,92 @@ pub(super) fn goto_line_number(
     Ok(())
 }
 
+fn goto_data_path(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    ensure!(!args.is_empty(), "Path required");
+    let path = args.join(" ");
+    let (view, doc) = current!(cx.editor);
+    let syntax = doc
+        .syntax()
+        .context("the current buffer has no syntax tree")?;
+    let text = doc.text().slice(..);
+    let pos = helix_core::data_path::find_path(text, syntax.tree().root_node(), &path)
+        .with_context(|| format!("path not found: {}", path))?;
+
+    push_jump(view, doc, &mut cx.editor.global_jumps);
+    doc.set_selection(view.id, Selection::point(pos));
+    align_view(doc, view, Align::Center);
+    Ok(())
+}
+
+fn quickfix_count(args: &[Cow<str>]) -> anyhow::Result<usize> {
+    match args.first() {
+        Some(count) => count
+            .parse()
+            .with_context(|| format!("invalid count '{}'", count)),
+        None => Ok(1),
+    }
+}
+
+fn quickfix_next(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    goto_quickfix(cx.editor, Direction::Forward, quickfix_count(args)?);
+    Ok(())
+}
+
+fn quickfix_prev(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    goto_quickfix(cx.editor, Direction::Backward, quickfix_count(args)?);
+    Ok(())
+}
+
+fn quickfix_open(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    ensure!(
+        !cx.editor.quickfix.entries().is_empty(),
+        "The quickfix list is empty"
+    );
+    cx.jobs.callback(async move {
+        let call = job::Callback::EditorCompositor(Box::new(
+            |editor: &mut Editor, compositor: &mut Compositor| {
+                if let Some(picker) = quickfix_list_picker(editor) {
+                    compositor.push(Box::new(overlayed(picker)));
+                }
+            },
+        ));
+        Ok(call)
+    });
+    Ok(())
+}
+
 // Fetch the current value of a config option and output as status.
 fn get_option(
     cx: &mut compositor::Context,
@@ -1787,6 +2707,47 @@ fn toggle_option(
     Ok(())
 }
 
+/// Hide or show again the diagnostics of a source, like `clippy` or `cspell`.
+fn toggle_diagnostics_source(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    ensure!(
+        args.len() == 1,
+        "Bad arguments. Usage: `:diagnostics-toggle-source source`"
+    );
+    let source = &args[0];
+
+    let mut config = cx.editor.config().deref().clone();
+    let disabled_sources = &mut config.diagnostic_filter.disabled_sources;
+    match disabled_sources
+        .iter()
+        .position(|disabled| disabled == source)
+    {
+        Some(index) => {
+            disabled_sources.remove(index);
+            cx.editor
+                .set_status(format!("Showing diagnostics from '{}'", source));
+        }
+        None => {
+            disabled_sources.push(source.to_string());
+            cx.editor
+                .set_status(format!("Hiding diagnostics from '{}'", source));
+        }
+    }
+
+    cx.editor
+        .config_events
+        .0
+        .send(ConfigEvent::Update(Box::new(config)))?;
+    Ok(())
+}
+
 /// Change the language of the current buffer at runtime.
 fn language(
     cx: &mut compositor::Context,
@@ -1827,7 +2788,8 @@ fn sort(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) ->
         return Ok(());
     }
 
-    sort_impl(cx, args, false)
+    let options = SortOptions::parse(args)?;
+    transform_selections(cx, LinesOperation::Sort(options))
 }
 
 fn sort_reverse(
@@ -1839,36 +2801,183 @@ fn sort_reverse(
         return Ok(());
     }
 
-    sort_impl(cx, args, true)
+    let mut options = SortOptions::parse(args)?;
+    options.reverse = !options.reverse;
+    transform_selections(cx, LinesOperation::Sort(options))
+}
+
+fn uniq(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    transform_selections(cx, LinesOperation::Uniq)
 }
 
-fn sort_impl(
+fn reverse(
     cx: &mut compositor::Context,
     _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    transform_selections(cx, LinesOperation::Reverse)
+}
+
+#[derive(Default)]
+struct SortOptions {
+    numeric: bool,
+    ignore_case: bool,
     reverse: bool,
+    /// Sort by the first match of this regex, or its first capture group.
+    key: Option<Regex>,
+}
+
+impl SortOptions {
+    fn parse(args: &[Cow<str>]) -> anyhow::Result<Self> {
+        let mut options = Self::default();
+        // After `--`, an argument starting with `-` is the key rather than a flag.
+        let mut flags = true;
+        for arg in args {
+            match arg.as_ref() {
+                "--" if flags => flags = false,
+                "-n" | "--numeric" if flags => options.numeric = true,
+                "-i" | "--ignore-case" if flags => options.ignore_case = true,
+                "-r" | "--reverse" if flags => options.reverse = true,
+                flag if flags && flag.len() > 1 && flag.starts_with('-') => {
+                    bail!("unknown flag '{}'", flag)
+                }
+                key if options.key.is_none() => options.key = Some(Regex::new(key)?),
+                arg => bail!("unexpected argument '{}'", arg),
+            }
+        }
+        Ok(options)
+    }
+
+    fn key<'a>(&self, text: &'a str) -> &'a str {
+        let captures = match self.key.as_ref().and_then(|regex| regex.captures(text)) {
+            Some(captures) => captures,
+            None => return text,
+        };
+        captures
+            .get(1)
+            .or_else(|| captures.get(0))
+            .map_or(text, |key| key.as_str())
+    }
+
+    fn compare(&self, a: &str, b: &str) -> std::cmp::Ordering {
+        // Parses the number the key starts with.
+        fn number(key: &str) -> Option<f64> {
+            let key = key.trim_start();
+            let len = key
+                .char_indices()
+                .take_while(|&(i, ch)| {
+                    ch.is_ascii_digit() || ch == '.' || (i == 0 && (ch == '-' || ch == '+'))
+                })
+                .count();
+            key[..len].parse().ok()
+        }
+
+        let (a, b) = (self.key(a), self.key(b));
+        let ordering = if self.numeric {
+            // Keys without numbers come first.
+            match (number(a), number(b)) {
+                (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(std::cmp::Ordering::Equal),
+                (x, y) => x.is_some().cmp(&y.is_some()),
+            }
+            .then_with(|| a.cmp(b))
+        } else if self.ignore_case {
+            a.to_lowercase().cmp(&b.to_lowercase())
+        } else {
+            a.cmp(b)
+        };
+        if self.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+enum LinesOperation {
+    Sort(SortOptions),
+    Reverse,
+    Uniq,
+}
+
+impl LinesOperation {
+    /// Applies the operation to `items`, returning `None` for the removed ones.
+    fn apply(&self, mut items: Vec<String>) -> Vec<Option<String>> {
+        match self {
+            Self::Sort(options) => {
+                items.sort_by(|a, b| options.compare(a, b));
+                items.into_iter().map(Some).collect()
+            }
+            Self::Reverse => items.into_iter().rev().map(Some).collect(),
+            Self::Uniq => {
+                let mut seen = HashSet::new();
+                items
+                    .into_iter()
+                    .map(|item| seen.insert(item.clone()).then_some(item))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Applies `operation` to the lines of each selection spanning several lines, or else to the
+/// selections among each other, like multiple cursors on different lines.
+fn transform_selections(
+    cx: &mut compositor::Context,
+    operation: LinesOperation,
 ) -> anyhow::Result<()> {
     let scrolloff = cx.editor.config().scrolloff;
     let (view, doc) = current!(cx.editor);
     let text = doc.text().slice(..);
+    let line_ending = doc.line_ending.as_str();
 
     let selection = doc.selection(view.id);
-
-    let mut fragments: Vec<_> = selection
-        .slices(text)
-        .map(|fragment| fragment.chunks().collect())
-        .collect();
-
-    fragments.sort_by(match reverse {
-        true => |a: &Tendril, b: &Tendril| b.cmp(a),
-        false => |a: &Tendril, b: &Tendril| a.cmp(b),
+    let fragments: Vec<String> = selection.fragments(text).map(String::from).collect();
+    let multiline = fragments.iter().any(|fragment| {
+        let fragment = fragment.strip_suffix('\n').unwrap_or(fragment);
+        fragment.contains('\n')
     });
 
+    let replacements: Vec<String> = if multiline {
+        fragments
+            .iter()
+            .map(|fragment| {
+                let body = fragment
+                    .strip_suffix('\n')
+                    .map(|body| body.strip_suffix('\r').unwrap_or(body));
+                let lines = body.unwrap_or(fragment).lines().map(String::from).collect();
+                let mut lines: Vec<_> = operation.apply(lines).into_iter().flatten().collect();
+                if body.is_some() {
+                    lines.push(String::new());
+                }
+                lines.join(line_ending)
+            })
+            .collect()
+    } else {
+        operation
+            .apply(fragments)
+            .into_iter()
+            .map(Option::unwrap_or_default)
+            .collect()
+    };
+
     let transaction = Transaction::change(
         doc.text(),
         selection
             .into_iter()
-            .zip(fragments)
-            .map(|(s, fragment)| (s.from(), s.to(), Some(fragment))),
+            .zip(replacements)
+            .map(|(range, text)| (range.from(), range.to(), Some(text.into()))),
     );
 
     doc.apply(&transaction, view.id);
@@ -1878,6 +2987,66 @@ fn sort_impl(
     Ok(())
 }
 
+/// Formats `number` in `radix`, left-padding its digits with zeros to `width`.
+fn format_radix(number: i64, radix: u32, width: usize) -> String {
+    let mut digits = Vec::new();
+    let mut rest = number.unsigned_abs();
+    loop {
+        digits.push(std::char::from_digit((rest % radix as u64) as u32, radix).unwrap());
+        rest /= radix as u64;
+        if rest == 0 {
+            break;
+        }
+    }
+    digits.resize(digits.len().max(width), '0');
+    if number < 0 {
+        digits.push('-');
+    }
+    digits.into_iter().rev().collect()
+}
+
+fn insert_sequence(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let arg = |idx: usize, default: i64| -> anyhow::Result<i64> {
+        match args.get(idx) {
+            Some(arg) => arg
+                .parse()
+                .with_context(|| format!("invalid number '{}'", arg)),
+            None => Ok(default),
+        }
+    };
+    let start = arg(0, 1)?;
+    let step = arg(1, 1)?;
+    let width = arg(2, 0)?;
+    let radix = arg(3, 10)?;
+    ensure!(width >= 0, "invalid width {}", width);
+    ensure!((2..=36).contains(&radix), "radix must be between 2 and 36");
+
+    let scrolloff = cx.editor.config().scrolloff;
+    let (view, doc) = current!(cx.editor);
+    let selection = doc.selection(view.id);
+    let mut number = Some(start);
+    let changes = selection.iter().map(|range| {
+        let text = number.map(|number| format_radix(number, radix as u32, width as usize));
+        number = number.and_then(|number| number.checked_add(step));
+        (range.from(), range.from(), text.map(Tendril::from))
+    });
+    let transaction = Transaction::change(doc.text(), changes);
+
+    doc.apply(&transaction, view.id);
+    doc.append_changes_to_history(view);
+    view.ensure_cursor_in_view(doc, scrolloff);
+
+    Ok(())
+}
+
 fn reflow(
     cx: &mut compositor::Context,
     args: &[Cow<str>],
@@ -1919,6 +3088,95 @@ fn reflow(
     Ok(())
 }
 
+fn keep_or_remove_selections_by_predicate(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    remove: bool,
+) -> anyhow::Result<()> {
+    let tab_width = cx.editor.config().tab_width.max(1);
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let lines = |range: &Range| {
+        let (start, end) = range.line_range(text);
+        start..=end
+    };
+
+    let selection = doc.selection(view.id);
+    let selection = match args {
+        [predicate] if predicate == "blank" => selection::keep_or_remove(
+            selection,
+            |range| lines(range).all(|line| text.line(line).chars().all(char::is_whitespace)),
+            remove,
+        ),
+        [predicate, width] if predicate == "longer" => {
+            let width: usize = width
+                .parse()
+                .map_err(|_| anyhow!("invalid width '{}'", width))?;
+            selection::keep_or_remove(
+                selection,
+                |range| {
+                    lines(range).any(|line| {
+                        let line_end = line_end_char_index(&text, line);
+                        helix_core::visual_coords_at_pos(text, line_end, tab_width).col > width
+                    })
+                },
+                remove,
+            )
+        }
+        [predicate, scope] if predicate == "scope" => {
+            let syntax = doc
+                .syntax()
+                .ok_or_else(|| anyhow!("Syntax-tree is not available in current buffer"))?;
+            selection::keep_or_remove(
+                selection,
+                |range| {
+                    let from = text.char_to_byte(range.from());
+                    let to = text.char_to_byte(range.to());
+                    let node = syntax
+                        .tree()
+                        .root_node()
+                        .descendant_for_byte_range(from, to);
+                    std::iter::successors(node, |node| node.parent())
+                        .any(|node| node.kind().contains(&**scope))
+                },
+                remove,
+            )
+        }
+        _ => bail!("expected one of: blank, longer <width>, scope <name>"),
+    };
+
+    match selection {
+        Some(selection) => doc.set_selection(view.id, selection),
+        None if remove => bail!("all selections would be removed"),
+        None => bail!("no selection matches"),
+    }
+    Ok(())
+}
+
+fn keep_selections_matching(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    keep_or_remove_selections_by_predicate(cx, args, false)
+}
+
+fn remove_selections_matching(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    keep_or_remove_selections_by_predicate(cx, args, true)
+}
+
 fn tree_sitter_subtree(
     cx: &mut compositor::Context,
     _args: &[Cow<str>],
@@ -2026,48 +3284,262 @@ fn append_output(
     }
 
     ensure!(!args.is_empty(), "Shell command required");
-    shell(cx, &args.join(" "), &ShellBehavior::Append);
+    shell(cx, &args.join(" "), &ShellBehavior::Append);
+    Ok(())
+}
+
+fn insert_output(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    ensure!(!args.is_empty(), "Shell command required");
+    shell(cx, &args.join(" "), &ShellBehavior::Insert);
+    Ok(())
+}
+
+fn pipe_to(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    pipe_impl(cx, args, event, &ShellBehavior::Ignore)
+}
+
+fn pipe(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) -> anyhow::Result<()> {
+    pipe_impl(cx, args, event, &ShellBehavior::Replace)
+}
+
+fn pipe_impl(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+    behavior: &ShellBehavior,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    ensure!(!args.is_empty(), "Shell command required");
+    shell(cx, &args.join(" "), behavior);
     Ok(())
 }
 
-fn insert_output(
+/// Takes the complete lines read into `buf`, or everything once the output is `done`, decoding
+/// invalid UTF-8 lossily.
+fn take_output(buf: &mut Vec<u8>, done: bool) -> Option<String> {
+    let end = if done {
+        buf.len()
+    } else {
+        buf.iter().rposition(|&byte| byte == b'\n')? + 1
+    };
+    if end == 0 {
+        return None;
+    }
+    let mut text = String::from_utf8_lossy(&buf[..end]).into_owned();
+    buf.drain(..end);
+    if done && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    Some(text)
+}
+
+/// Runs the `name` command of the language of the current document in the background,
+/// streaming its output into a new scratch buffer, whose id is returned. The text received
+/// from `input` is written to its stdin, which is closed once all the senders are dropped.
+fn run_language_command(
     cx: &mut compositor::Context,
-    args: &[Cow<str>],
-    event: PromptEvent,
-) -> anyhow::Result<()> {
+    name: &str,
+    input: Option<tokio::sync::mpsc::UnboundedReceiver<String>>,
+) -> anyhow::Result<DocumentId> {
+    use std::process::Stdio;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let commands = &doc!(cx.editor)
+        .language_config()
+        .context("the current buffer has no language")?
+        .commands;
+    let command = match name {
+        "run" => commands.run.as_ref(),
+        "build" => commands.build.as_ref(),
+        "test" => commands.test.as_ref(),
+        "repl" => commands.repl.as_ref(),
+        _ => bail!(
+            "unknown command '{}', expected run, build, test or repl",
+            name
+        ),
+    }
+    .with_context(|| format!("no {} command configured for the language", name))?;
+    let command = expand_placeholders(cx.editor, command, shell_escape)?;
+    let shell = cx.editor.config().shell.clone();
+    ensure!(!shell.is_empty(), "No shell set");
+
+    let doc = Document::from(
+        Rope::from(format!("$ {}\n", command)),
+        None,
+        cx.editor.config.clone(),
+    );
+    let doc_id = cx
+        .editor
+        .new_file_from_document(Action::HorizontalSplit, doc);
+    let output = cx.editor.command_output.0.clone();
+    let send = move |text: String| {
+        // The receiver only goes away when the editor exits.
+        let _ = output.send(CommandOutput { doc_id, text });
+    };
+
+    let job_name = format!("{} `{}`", name, command);
+    let name = name.to_string();
+    // Relative paths in the output are relative to the directory the command runs in.
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let callback = async move {
+        let mut process = tokio::process::Command::new(&shell[0])
+            // Cancelled jobs stop their command.
+            .kill_on_drop(true)
+            .args(&shell[1..])
+            .arg(&command)
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let writer = match (process.stdin.take(), input) {
+            // Write concurrently to reading the output, which could otherwise fill up and block.
+            (Some(mut stdin), Some(mut input)) => Some(tokio::spawn(async move {
+                while let Some(text) = input.recv().await {
+                    if stdin.write_all(text.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+            })),
+            _ => None,
+        };
+        // The output is read in chunks of whatever is available, and sent a line at a time so
+        // that multi-byte characters aren't split. Invalid UTF-8 is replaced.
+        let mut stdout = process.stdout.take().unwrap();
+        let mut stderr = process.stderr.take().unwrap();
+        let (mut stdout_buf, mut stderr_buf) = (Vec::new(), Vec::new());
+        let (mut stdout_done, mut stderr_done) = (false, false);
+        // The locations printed by the command, collected into the quickfix list once it exits.
+        let mut entries = Vec::new();
+        let mut send_output = |text: String| {
+            entries.extend(
+                text.lines()
+                    .filter_map(|line| QuickfixEntry::parse(line, &cwd))
+                    .filter(|entry| entry.path.is_file()),
+            );
+            send(text);
+        };
+        while !(stdout_done && stderr_done) {
+            tokio::select! {
+                read = stdout.read_buf(&mut stdout_buf), if !stdout_done => {
+                    stdout_done = read? == 0;
+                    if let Some(text) = take_output(&mut stdout_buf, stdout_done) {
+                        send_output(text);
+                    }
+                },
+                read = stderr.read_buf(&mut stderr_buf), if !stderr_done => {
+                    stderr_done = read? == 0;
+                    if let Some(text) = take_output(&mut stderr_buf, stderr_done) {
+                        send_output(text);
+                    }
+                },
+            }
+        }
+        let status = process.wait().await?;
+        // Drops the input so that the senders see the process is gone.
+        if let Some(writer) = writer {
+            writer.abort();
+        }
+        send(format!("[{}]\n", status));
+
+        let call: job::Callback = Callback::Editor(Box::new(move |editor: &mut Editor| {
+            let locations = match entries.len() {
+                0 => String::new(),
+                count => format!(", {} location(s) in the quickfix list", count),
+            };
+            if !entries.is_empty() {
+                editor.quickfix = QuickfixList::new(name.clone(), entries);
+            }
+            if status.success() {
+                editor.set_status(format!("{} succeeded{}", name, locations));
+            } else {
+                editor.set_error(format!("{} failed: {}{}", name, status, locations));
+            }
+        }));
+        Ok(call)
+    };
+    cx.jobs.add(Job::with_callback(callback).named(job_name));
+
+    Ok(doc_id)
+}
+
+fn run(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
         return Ok(());
     }
 
-    ensure!(!args.is_empty(), "Shell command required");
-    shell(cx, &args.join(" "), &ShellBehavior::Insert);
+    let name = args.first().map_or("run", |name| name.as_ref());
+    run_language_command(cx, name, None)?;
     Ok(())
 }
 
-fn pipe_to(
+fn build(
     cx: &mut compositor::Context,
-    args: &[Cow<str>],
+    _args: &[Cow<str>],
     event: PromptEvent,
 ) -> anyhow::Result<()> {
-    pipe_impl(cx, args, event, &ShellBehavior::Ignore)
-}
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
 
-fn pipe(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) -> anyhow::Result<()> {
-    pipe_impl(cx, args, event, &ShellBehavior::Replace)
+    run_language_command(cx, "build", None)?;
+    Ok(())
 }
 
-fn pipe_impl(
+fn cell_run(
     cx: &mut compositor::Context,
-    args: &[Cow<str>],
+    _args: &[Cow<str>],
     event: PromptEvent,
-    behavior: &ShellBehavior,
 ) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
         return Ok(());
     }
 
-    ensure!(!args.is_empty(), "Shell command required");
-    shell(cx, &args.join(" "), behavior);
+    let (view, doc) = current_ref!(cx.editor);
+    let text = doc.text().slice(..);
+    let line = doc.selection(view.id).primary().cursor_line(text);
+    let cell = helix_core::cell::cell_lines(text, line, super::cell_comment_token(doc));
+    let code = text
+        .slice(text.line_to_char(cell.start)..text.line_to_char(cell.end))
+        .to_string();
+    let doc_id = doc.id();
+
+    // The REPL of the document keeps running between cells, so they share its state. A new one
+    // is started if it exited or its output buffer was closed.
+    let running = cx.editor.repls.get(&doc_id).map_or(false, |repl| {
+        !repl.input.is_closed() && cx.editor.documents.contains_key(&repl.doc_id)
+    });
+    if !running {
+        let (input, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let output_doc_id = run_language_command(cx, "repl", Some(receiver))?;
+        let repl = Repl {
+            doc_id: output_doc_id,
+            input,
+        };
+        cx.editor.repls.insert(doc_id, repl);
+    }
+    cx.editor.repls[&doc_id]
+        .input
+        .send(code)
+        .map_err(|_| anyhow!("The REPL exited"))?;
     Ok(())
 }
 
@@ -2081,7 +3553,8 @@ fn run_shell_command(
     }
 
     let shell = cx.editor.config().shell.clone();
-    let args = args.join(" ");
+    let args = expand_placeholders(cx.editor, &args.join(" "), shell_escape)?;
+    let name = format!("shell command `{}`", args);
 
     let callback = async move {
         let (output, success) = shell_impl_async(&shell, &args, None).await?;
@@ -2106,7 +3579,7 @@ fn run_shell_command(
         ));
         Ok(call)
     };
-    cx.jobs.callback(callback);
+    cx.jobs.add(Job::with_callback(callback).named(name));
 
     Ok(())
 }
@@ -2169,7 +3642,7 @@ fn reset_diff_change(
         TypableCommand {
             name: "quit",
             aliases: &["q"],
-            doc: "Close the current view.",
+            doc: "Close the current view. Offers to save, discard or diff the modified buffers when closing the last view.",
             fun: quit,
             signature: CommandSignature::none(),
         },
@@ -2185,7 +3658,7 @@ fn reset_diff_change(
             aliases: &["o"],
             doc: "Open a file from disk into the current view.",
             fun: open,
-            signature: CommandSignature::all(completers::filename),
+            signature: CommandSignature::all(completers::filename).args_required(),
         },
         TypableCommand {
             name: "buffer-close",
@@ -2280,6 +3753,34 @@ fn reset_diff_change(
             fun: set_indent_style,
             signature: CommandSignature::none(),
         },
+        TypableCommand {
+            name: "startuptime",
+            aliases: &[],
+            doc: "Open a scratch buffer reporting how long the steps of startup took.",
+            fun: startup_time,
+            signature: CommandSignature::none(),
+        },
+        TypableCommand {
+            name: "line-number",
+            aliases: &[],
+            doc: "Set the line numbers of the current view, overriding the `line-number` option. Options: absolute, relative, default.",
+            fun: set_view_line_number,
+            signature: CommandSignature::none(),
+        },
+        TypableCommand {
+            name: "gutters",
+            aliases: &[],
+            doc: "Set the gutters of the current view, overriding the `gutters` option. Takes the gutters to show, none or default.",
+            fun: set_view_gutters,
+            signature: CommandSignature::none(),
+        },
+        TypableCommand {
+            name: "grammar-rebuild",
+            aliases: &[],
+            doc: "Rebuild the tree-sitter grammar of a language (default: the current buffer's) and reload it.",
+            fun: grammar_rebuild,
+            signature: CommandSignature::positional(&[completers::language]),
+        },
         TypableCommand {
             name: "line-ending",
             aliases: &[],
@@ -2325,10 +3826,52 @@ fn reset_diff_change(
             fun: write_all,
             signature: CommandSignature::none(),
         },
+        TypableCommand {
+            name: "write-range",
+            aliases: &["wr"],
+            doc: "Write the selections, or the lines given as `first-last`, to a new file. e.g. `:write-range snippet.rs 10-20`",
+            fun: write_range,
+            signature: CommandSignature::positional(&[completers::filename]).args_required(),
+        },
+        TypableCommand {
+            name: "write-range!",
+            aliases: &["wr!"],
+            doc: "Write the selections, or the lines given as `first-last`, to a file, overwriting it if it exists.",
+            fun: force_write_range,
+            signature: CommandSignature::positional(&[completers::filename]).args_required(),
+        },
+        TypableCommand {
+            name: "append-range",
+            aliases: &[],
+            doc: "Append the selections, or the lines given as `first-last`, to a file.",
+            fun: append_range,
+            signature: CommandSignature::positional(&[completers::filename]).args_required(),
+        },
+        TypableCommand {
+            name: "rename-file",
+            aliases: &["move-file"],
+            doc: "Move the file of the current buffer to a new path, or into a directory. Language servers can update the references to the file.",
+            fun: rename_file,
+            signature: CommandSignature::positional(&[completers::filename]).args_required(),
+        },
+        TypableCommand {
+            name: "delete-file",
+            aliases: &[],
+            doc: "Move the file of the current buffer to the trash, or delete it with `editor.hard-delete`, and close the buffer.",
+            fun: delete_file,
+            signature: CommandSignature::none(),
+        },
+        TypableCommand {
+            name: "duplicate-file",
+            aliases: &[],
+            doc: "Copy the saved file of the current buffer to a new path, or into a directory, and open the copy.",
+            fun: duplicate_file,
+            signature: CommandSignature::positional(&[completers::filename]).args_required(),
+        },
         TypableCommand {
             name: "write-quit-all",
             aliases: &["wqa", "xa"],
-            doc: "Write changes from all buffers to disk and close all views.",
+            doc: "Write changes from all buffers to disk and close all views. Reports the buffers which failed to be written.",
             fun: write_all_quit,
             signature: CommandSignature::none(),
         },
@@ -2342,7 +3885,7 @@ fn reset_diff_change(
         TypableCommand {
             name: "quit-all",
             aliases: &["qa"],
-            doc: "Close all views.",
+            doc: "Close all views. Offers to save, discard or diff the modified buffers.",
             fun: quit_all,
             signature: CommandSignature::none(),
         },
@@ -2374,6 +3917,13 @@ fn reset_diff_change(
             fun: theme,
             signature: CommandSignature::positional(&[completers::theme]),
         },
+        TypableCommand {
+            name: "theme-set",
+            aliases: &[],
+            doc: "Override the style of a theme scope for this session, e.g. `:theme-set keyword '{ fg = \"red\", modifiers = [\"bold\"] }'`.",
+            fun: theme_set,
+            signature: CommandSignature::positional(&[completers::theme_scope]).args_required(),
+        },
         TypableCommand {
             name: "clipboard-yank",
             aliases: &[],
@@ -2456,8 +4006,22 @@ fn reset_diff_change(
             aliases: &["cd"],
             doc: "Change the current working directory.",
             fun: change_current_directory,
+            signature: CommandSignature::positional(&[completers::directory]).args_required(),
+        },
+        TypableCommand {
+            name: "workspace-add",
+            aliases: &[],
+            doc: "Add a directory to the workspace: to the workspace folders of language servers, the file picker and global search. Lists the workspace roots without argument.",
+            fun: workspace_add,
             signature: CommandSignature::positional(&[completers::directory]),
         },
+        TypableCommand {
+            name: "workspace-remove",
+            aliases: &[],
+            doc: "Remove a directory added with :workspace-add from the workspace.",
+            fun: workspace_remove,
+            signature: CommandSignature::positional(&[completers::directory]).args_required(),
+        },
         TypableCommand {
             name: "show-directory",
             aliases: &["pwd"],
@@ -2521,6 +4085,13 @@ fn reset_diff_change(
             fun: lsp_stop,
             signature: CommandSignature::none(),
         },
+        TypableCommand {
+            name: "rename-history",
+            aliases: &[],
+            doc: "Open a picker of the files changed by the symbol renames of the session, most recent first.",
+            fun: rename_history,
+            signature: CommandSignature::none(),
+        },
         TypableCommand {
             name: "tree-sitter-scopes",
             aliases: &[],
@@ -2547,7 +4118,7 @@ fn reset_diff_change(
             aliases: &[],
             doc: "Evaluate expression in current debug context.",
             fun: debug_eval,
-            signature: CommandSignature::none(),
+            signature: CommandSignature::none().args_required(),
         },
         TypableCommand {
             name: "vsplit",
@@ -2584,13 +4155,48 @@ fn reset_diff_change(
             fun: tutor,
             signature: CommandSignature::none(),
         },
+        TypableCommand {
+            name: "help",
+            aliases: &["h"],
+            doc: "Open the builtin documentation for a topic, command or config key.",
+            fun: help,
+            signature: CommandSignature::positional(&[completers::help_topic]),
+        },
         TypableCommand {
             name: "goto",
             aliases: &["g"],
             doc: "Goto line number.",
             fun: goto_line_number,
+            signature: CommandSignature::none().args_required(),
+        },
+        TypableCommand {
+            name: "cnext",
+            aliases: &["cn"],
+            doc: "Goto the next entry of the quickfix list, or the entry a given count after the current one.",
+            fun: quickfix_next,
+            signature: CommandSignature::none(),
+        },
+        TypableCommand {
+            name: "cprev",
+            aliases: &["cp"],
+            doc: "Goto the previous entry of the quickfix list, or the entry a given count before the current one.",
+            fun: quickfix_prev,
+            signature: CommandSignature::none(),
+        },
+        TypableCommand {
+            name: "copen",
+            aliases: &[],
+            doc: "Open a picker over the quickfix list, filled by the last global search, diagnostic picked in a diagnostics picker, references request or locations printed by `:run` and `:build`.",
+            fun: quickfix_open,
             signature: CommandSignature::none(),
         },
+        TypableCommand {
+            name: "goto-path",
+            aliases: &[],
+            doc: "Goto the key at a dotted path like `package.dependencies.serde` or `items[0].name` in JSON, YAML and TOML files.",
+            fun: goto_data_path,
+            signature: CommandSignature::none().args_required(),
+        },
         TypableCommand {
             name: "set-language",
             aliases: &["lang"],
@@ -2604,36 +4210,71 @@ fn reset_diff_change(
             doc: "Set a config option at runtime.\nFor example to disable smart case search, use `:set search.smart-case false`.",
             fun: set_option,
             // TODO: Add support for completion of the options value(s), when appropriate.
-            signature: CommandSignature::positional(&[completers::setting]),
+            signature: CommandSignature::positional(&[completers::setting]).args_required(),
         },
         TypableCommand {
             name: "toggle-option",
             aliases: &["toggle"],
             doc: "Toggle a boolean config option at runtime.\nFor example to toggle smart case search, use `:toggle search.smart-case`.",
             fun: toggle_option,
-            signature: CommandSignature::positional(&[completers::setting]),
+            signature: CommandSignature::positional(&[completers::setting]).args_required(),
+        },
+        TypableCommand {
+            name: "diagnostics-toggle-source",
+            aliases: &[],
+            doc: "Hide the diagnostics of a source like `clippy`, or show them again.\nTo hide diagnostics below a severity, use `:set diagnostic-filter.severity Warning`.",
+            fun: toggle_diagnostics_source,
+            signature: CommandSignature::none(),
         },
         TypableCommand {
             name: "get-option",
             aliases: &["get"],
             doc: "Get the current value of a config option.",
             fun: get_option,
-            signature: CommandSignature::positional(&[completers::setting]),
+            signature: CommandSignature::positional(&[completers::setting]).args_required(),
         },
         TypableCommand {
             name: "sort",
             aliases: &[],
-            doc: "Sort ranges in selection.",
+            doc: "Sort the lines of each selection spanning several lines, or else the selections among each other. Flags: -n numeric, -i ignore case, -r reverse, -- ends the flags; an optional regex sorts by its match or first capture group.",
             fun: sort,
             signature: CommandSignature::none(),
         },
         TypableCommand {
             name: "rsort",
             aliases: &[],
-            doc: "Sort ranges in selection in reverse order.",
+            doc: "Sort the lines of each selection, or else the selections, in reverse order. Takes the flags of :sort.",
             fun: sort_reverse,
             signature: CommandSignature::none(),
         },
+        TypableCommand {
+            name: "uniq",
+            aliases: &[],
+            doc: "Remove duplicate lines of each selection spanning several lines, or else the text of duplicate selections, keeping the first occurrences.",
+            fun: uniq,
+            signature: CommandSignature::none(),
+        },
+        TypableCommand {
+            name: "reverse",
+            aliases: &[],
+            doc: "Reverse the lines of each selection spanning several lines, or else the order of the selections.",
+            fun: reverse,
+            signature: CommandSignature::none(),
+        },
+        TypableCommand {
+            name: "keep-selections",
+            aliases: &[],
+            doc: "Keep the selections matching a predicate: `blank` (only blank lines), `longer <width>` (a line longer than the width) or `scope <name>` (inside a syntax node whose kind contains the name, e.g. `comment`).",
+            fun: keep_selections_matching,
+            signature: CommandSignature::none(),
+        },
+        TypableCommand {
+            name: "remove-selections",
+            aliases: &[],
+            doc: "Remove the selections matching a predicate: `blank` (only blank lines), `longer <width>` (a line longer than the width) or `scope <name>` (inside a syntax node whose kind contains the name, e.g. `comment`).",
+            fun: remove_selections_matching,
+            signature: CommandSignature::none(),
+        },
         TypableCommand {
             name: "reflow",
             aliases: &[],
@@ -2641,6 +4282,13 @@ fn reset_diff_change(
             fun: reflow,
             signature: CommandSignature::none(),
         },
+        TypableCommand {
+            name: "insert-sequence",
+            aliases: &["seq"],
+            doc: "Insert an incrementing sequence of numbers before the selections, in order. Takes the start (1), step (1), zero-padded width (0) and radix (10) of the numbers.",
+            fun: insert_sequence,
+            signature: CommandSignature::none(),
+        },
         TypableCommand {
             name: "tree-sitter-subtree",
             aliases: &["ts-subtree"],
@@ -2681,35 +4329,84 @@ fn reset_diff_change(
             aliases: &[],
             doc: "Run shell command, inserting output before each selection.",
             fun: insert_output,
-            signature: CommandSignature::none(),
+            signature: CommandSignature::none().args_required(),
         },
         TypableCommand {
             name: "append-output",
             aliases: &[],
             doc: "Run shell command, appending output after each selection.",
             fun: append_output,
-            signature: CommandSignature::none(),
+            signature: CommandSignature::none().args_required(),
         },
         TypableCommand {
             name: "pipe",
             aliases: &[],
             doc: "Pipe each selection to the shell command.",
             fun: pipe,
-            signature: CommandSignature::none(),
+            signature: CommandSignature::none().args_required(),
         },
         TypableCommand {
             name: "pipe-to",
             aliases: &[],
             doc: "Pipe each selection to the shell command, ignoring output.",
             fun: pipe_to,
-            signature: CommandSignature::none(),
+            signature: CommandSignature::none().args_required(),
         },
         TypableCommand {
             name: "run-shell-command",
             aliases: &["sh"],
             doc: "Run a shell command",
             fun: run_shell_command,
-            signature: CommandSignature::all(completers::filename)
+            signature: CommandSignature::all(completers::filename).args_required(),
+        },
+        TypableCommand {
+            name: "run",
+            aliases: &[],
+            doc: "Run the run command of the language of the current buffer, or its build or test command if given, showing the output in a scratch buffer and the locations it prints in the quickfix list.",
+            fun: run,
+            signature: CommandSignature::positional(&[completers::none]),
+        },
+        TypableCommand {
+            name: "build",
+            aliases: &[],
+            doc: "Run the build command of the language of the current buffer, showing the output in a scratch buffer and the locations it prints in the quickfix list.",
+            fun: build,
+            signature: CommandSignature::none(),
+        },
+        TypableCommand {
+            name: "cell-run",
+            aliases: &[],
+            doc: "Send the code cell around the cursor, delimited by comments like `# %%`, to the repl command of the language. The REPL keeps running for the buffer and shows its output in a scratch buffer.",
+            fun: cell_run,
+            signature: CommandSignature::none(),
+        },
+        TypableCommand {
+            name: "http-send",
+            aliases: &[],
+            doc: "Send the request under the cursor of a `.http` file with curl, showing the response in scratch buffers.",
+            fun: http::http_send,
+            signature: CommandSignature::none(),
+        },
+        TypableCommand {
+            name: "test-run-nearest",
+            aliases: &[],
+            doc: "Run the test around or before the cursor with the test runner of the language.",
+            fun: test_runner::test_run_nearest,
+            signature: CommandSignature::none(),
+        },
+        TypableCommand {
+            name: "test-run-file",
+            aliases: &[],
+            doc: "Run all tests of the current file with the test runner of the language.",
+            fun: test_runner::test_run_file,
+            signature: CommandSignature::none(),
+        },
+        TypableCommand {
+            name: "test-failures",
+            aliases: &[],
+            doc: "Open a picker over the tests that failed in their last run.",
+            fun: test_runner::test_failures,
+            signature: CommandSignature::none(),
         },
        TypableCommand {
             name: "reset-diff-change",
@@ -2731,8 +4428,180 @@ fn reset_diff_change(
             .collect()
     });
 
-#[allow(clippy::unnecessary_unwrap)]
+/// Commands whose arguments are run by the shell, which expands environment variables itself.
+const SHELL_COMMANDS: &[&str] = &[
+    "insert-output",
+    "append-output",
+    "pipe",
+    "pipe-to",
+    "run-shell-command",
+];
+
+/// Expands the environment variables (`${NAME}`) and leading tildes of the arguments of the
+/// typable command `name`. The arguments of shell commands are passed through unchanged.
+pub(super) fn expand_args<'a>(
+    name: &str,
+    args: &[Cow<'a, str>],
+) -> anyhow::Result<Vec<Cow<'a, str>>> {
+    let is_shell_command = TYPABLE_COMMAND_MAP
+        .get(name)
+        .map_or(false, |command| SHELL_COMMANDS.contains(&command.name));
+    if is_shell_command {
+        return Ok(args.to_vec());
+    }
+
+    args.iter()
+        .map(|arg| match helix_loader::expand_vars(arg) {
+            Ok(Cow::Owned(expanded)) => Ok(Cow::Owned(expanded)),
+            Ok(Cow::Borrowed(_)) => Ok(arg.clone()),
+            Err(err) => Err(anyhow!(err)),
+        })
+        .collect()
+}
+
+/// Replaces the `%{name}` placeholders of `input` for which `value` returns a value. Unknown
+/// placeholders are left unchanged.
+fn replace_placeholders(
+    input: &str,
+    mut value: impl FnMut(&str) -> anyhow::Result<Option<String>>,
+) -> anyhow::Result<String> {
+    let mut expanded = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find("%{") {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        expanded.push_str(&rest[..start]);
+        match value(&rest[start + 2..end])? {
+            Some(value) => expanded.push_str(&value),
+            None => expanded.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+/// Expands the placeholders of the current document in `input`: `%{filename}`, `%{dirname}`,
+/// `%{basename}`, `%{filestem}`, `%{cwd}`, `%{selection}` and `%{linenumber}`. The values are
+/// escaped with `escape` so they are read back as a single argument.
+pub(super) fn expand_placeholders(
+    editor: &Editor,
+    input: &str,
+    escape: fn(Cow<str>) -> Cow<str>,
+) -> anyhow::Result<String> {
+    let (view, doc) = current_ref!(editor);
+    let path = || doc.path().context("the current buffer has no path");
+
+    replace_placeholders(input, |placeholder| {
+        let value = match placeholder {
+            "filename" => path()?.to_string_lossy().into_owned(),
+            "dirname" => path()?
+                .parent()
+                .map(|dir| dir.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            "basename" => path()?
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            "filestem" => path()?
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            "cwd" => std::env::current_dir()?.to_string_lossy().into_owned(),
+            "selection" => {
+                let text = doc.text().slice(..);
+                doc.selection(view.id).primary().fragment(text).into_owned()
+            }
+            "linenumber" => {
+                let text = doc.text().slice(..);
+                let cursor = doc.selection(view.id).primary().cursor(text);
+                (text.char_to_line(cursor) + 1).to_string()
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(escape(value.into()).into_owned()))
+    })
+}
+
+/// Runs the command alias `name` from the config with the extra arguments `args`.
+pub(super) fn execute_alias(
+    cx: &mut compositor::Context,
+    name: &str,
+    args: &[Cow<str>],
+) -> anyhow::Result<()> {
+    let alias = match cx.editor.config().command_aliases.get(name) {
+        Some(alias) => alias.clone(),
+        None => bail!("no such command: '{}'", name),
+    };
+    let mut line = expand_placeholders(cx.editor, &alias, shellwords::escape)?;
+    for arg in args {
+        line.push(' ');
+        line.push_str(&shellwords::escape(arg.clone()));
+    }
+
+    let shellwords = Shellwords::from(line.as_str());
+    let words = shellwords.words();
+    let command = words
+        .first()
+        .and_then(|command| TYPABLE_COMMAND_MAP.get(command as &str))
+        .with_context(|| {
+            format!(
+                "alias '{}' doesn't run a typable command: '{}'",
+                name, alias
+            )
+        })?;
+
+    (command.fun)(cx, &words[1..], PromptEvent::Validate)
+}
+
+/// Executes a command line as it would be typed into command mode, e.g. `w` or `set-option
+/// mouse false`. A purely numeric input is interpreted as a line number to go to.
+pub(crate) fn execute_command_line(
+    cx: &mut compositor::Context,
+    input: &str,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    let parts = input.split_whitespace().collect::<Vec<&str>>();
+    if parts.is_empty() {
+        return Ok(());
+    }
+
+    // If command is numeric, interpret as line number and go there.
+    if parts.len() == 1 && parts[0].parse::<usize>().ok().is_some() {
+        return goto_line_number(cx, &[Cow::from(parts[0])], event);
+    }
+
+    // Handle typable commands
+    if let Some(cmd) = TYPABLE_COMMAND_MAP.get(parts[0]) {
+        let shellwords = Shellwords::from(input);
+        let args = match expand_args(cmd.name, &shellwords.words()[1..]) {
+            Ok(args) => args,
+            Err(err) if event == PromptEvent::Validate => return Err(err),
+            // The input may still be incomplete.
+            Err(_) => return Ok(()),
+        };
+
+        (cmd.fun)(cx, &args, event)
+    } else if event == PromptEvent::Validate {
+        let shellwords = Shellwords::from(input);
+        let args = expand_args(parts[0], &shellwords.words()[1..])?;
+        execute_alias(cx, parts[0], &args)
+    } else {
+        Ok(())
+    }
+}
+
 pub(super) fn command_mode(cx: &mut Context) {
+    let prompt = command_prompt(cx.editor, String::new());
+    cx.push_layer(Box::new(prompt));
+}
+
+/// Builds the command mode prompt with `line` as its initial input.
+#[allow(clippy::unnecessary_unwrap)]
+pub(super) fn command_prompt(editor: &Editor, line: String) -> Prompt {
     let mut prompt = Prompt::new(
         ":".into(),
         Some(':'),
@@ -2745,20 +4614,20 @@ pub(super) fn command_mode(cx: &mut Context) {
 
             if words.is_empty() || (words.len() == 1 && !shellwords.ends_with_whitespace()) {
                 // If the command has not been finished yet, complete commands.
+                let aliases = &editor.config().command_aliases;
                 let mut matches: Vec<_> = typed::TYPABLE_COMMAND_LIST
                     .iter()
-                    .filter_map(|command| {
+                    .map(|command| Cow::Borrowed(command.name))
+                    .chain(aliases.keys().map(|alias| Cow::Owned(alias.clone())))
+                    .filter_map(|name| {
                         FUZZY_MATCHER
-                            .fuzzy_match(command.name, input)
-                            .map(|score| (command.name, score))
+                            .fuzzy_match(&name, input)
+                            .map(|score| (name, score))
                     })
                     .collect();
 
                 matches.sort_unstable_by_key(|(_file, score)| std::cmp::Reverse(*score));
-                matches
-                    .into_iter()
-                    .map(|(name, _)| (0.., name.into()))
-                    .collect()
+                matches.into_iter().map(|(name, _)| (0.., name)).collect()
             } else {
                 // Otherwise, use the command's completer and the last shellword
                 // as completion input.
@@ -2794,30 +4663,8 @@ pub(super) fn command_mode(cx: &mut Context) {
             }
         }, // completion
         move |cx: &mut compositor::Context, input: &str, event: PromptEvent| {
-            let parts = input.split_whitespace().collect::<Vec<&str>>();
-            if parts.is_empty() {
-                return;
-            }
-
-            // If command is numeric, interpret as line number and go there.
-            if parts.len() == 1 && parts[0].parse::<usize>().ok().is_some() {
-                if let Err(e) = typed::goto_line_number(cx, &[Cow::from(parts[0])], event) {
-                    cx.editor.set_error(format!("{}", e));
-                }
-                return;
-            }
-
-            // Handle typable commands
-            if let Some(cmd) = typed::TYPABLE_COMMAND_MAP.get(parts[0]) {
-                let shellwords = Shellwords::from(input);
-                let args = shellwords.words();
-
-                if let Err(e) = (cmd.fun)(cx, &args[1..], event) {
-                    cx.editor.set_error(format!("{}", e));
-                }
-            } else if event == PromptEvent::Validate {
-                cx.editor
-                    .set_error(format!("no such command: '{}'", parts[0]));
+            if let Err(e) = execute_command_line(cx, input, event) {
+                cx.editor.set_error(format!("{}", e));
             }
         },
     );
@@ -2837,8 +4684,7 @@ pub(super) fn command_mode(cx: &mut Context) {
     });
 
     // Calculate initial completion
-    prompt.recalculate_completion(cx.editor);
-    cx.push_layer(Box::new(prompt));
+    prompt.with_line(line, editor)
 }
 
 fn argument_number_of(shellwords: &Shellwords) -> usize {
@@ -2866,3 +4712,40 @@ fn test_argument_number_of() {
         assert_eq!(case.1, argument_number_of(&Shellwords::from(case.0)));
     }
 }
+
+#[test]
+fn test_format_radix() {
+    assert_eq!(format_radix(0, 10, 0), "0");
+    assert_eq!(format_radix(42, 10, 0), "42");
+    assert_eq!(format_radix(42, 10, 4), "0042");
+    assert_eq!(format_radix(255, 16, 4), "00ff");
+    assert_eq!(format_radix(5, 2, 2), "101");
+    assert_eq!(format_radix(-42, 10, 0), "-42");
+    assert_eq!(format_radix(-42, 10, 4), "-0042");
+    assert_eq!(format_radix(-255, 16, 0), "-ff");
+    assert_eq!(format_radix(i64::MIN, 16, 0), "-8000000000000000");
+}
+
+#[test]
+fn test_replace_placeholders() {
+    let replace = |input| {
+        replace_placeholders(input, |placeholder| match placeholder {
+            "filename" => Ok(Some("a.rs".to_string())),
+            "linenumber" => Ok(Some("3".to_string())),
+            "error" => bail!("no value"),
+            _ => Ok(None),
+        })
+    };
+    assert_eq!(replace("cc %{filename}").unwrap(), "cc a.rs");
+    assert_eq!(
+        replace("%{filename}:%{linenumber}%{linenumber}").unwrap(),
+        "a.rs:33"
+    );
+    assert_eq!(
+        replace("echo %{unknown} %{filename}").unwrap(),
+        "echo %{unknown} a.rs"
+    );
+    assert_eq!(replace("printf '%s' %{").unwrap(), "printf '%s' %{");
+    assert_eq!(replace("awk '{print}' %").unwrap(), "awk '{print}' %");
+    assert!(replace("%{error}").is_err());
+}
@@ -1,5 +1,6 @@
+use crate::commands::{typed, MappableCommand};
 use crate::keymap;
-use crate::keymap::{merge_keys, Keymap};
+use crate::keymap::{merge_keys, KeyTrie, Keymap};
 use helix_loader::merge_toml_values;
 use helix_view::document::Mode;
 use serde::Deserialize;
@@ -24,6 +25,23 @@ pub struct ConfigRaw {
     pub editor: Option<toml::Value>,
 }
 
+impl ConfigRaw {
+    /// Parses a config file, expanding the environment variables and tildes of the theme and
+    /// editor options. The keymaps are left alone since `~` is a key.
+    fn parse(file: &str) -> Result<ConfigRaw, ConfigLoadError> {
+        let mut config: ConfigRaw = toml::from_str(file).map_err(ConfigLoadError::BadConfig)?;
+        if let Some(theme) = &mut config.theme {
+            *theme = helix_loader::expand_vars(theme)
+                .map_err(|err| ConfigLoadError::BadConfig(serde::de::Error::custom(err)))?
+                .into_owned();
+        }
+        if let Some(editor) = &mut config.editor {
+            helix_loader::expand_toml_vars(editor).map_err(ConfigLoadError::BadConfig)?;
+        }
+        Ok(config)
+    }
+}
+
 impl Default for Config {
     fn default() -> Config {
         Config {
@@ -60,10 +78,8 @@ pub fn load(
         global: Result<String, ConfigLoadError>,
         local: Result<String, ConfigLoadError>,
     ) -> Result<Config, ConfigLoadError> {
-        let global_config: Result<ConfigRaw, ConfigLoadError> =
-            global.and_then(|file| toml::from_str(&file).map_err(ConfigLoadError::BadConfig));
-        let local_config: Result<ConfigRaw, ConfigLoadError> =
-            local.and_then(|file| toml::from_str(&file).map_err(ConfigLoadError::BadConfig));
+        let global_config = global.and_then(|file| ConfigRaw::parse(&file));
+        let local_config = local.and_then(|file| ConfigRaw::parse(&file));
         let res = match (global_config, local_config) {
             (Ok(global), Ok(local)) => {
                 let mut keys = keymap::default();
@@ -113,9 +129,46 @@ pub fn load(
             (Err(err), Err(_)) => return Err(err),
         };
 
+        res.check_commands()
+            .map_err(|err| ConfigLoadError::BadConfig(serde::de::Error::custom(err)))?;
         Ok(res)
     }
 
+    /// Checks that the typable commands of the keymaps exist, either as builtin commands or as
+    /// command aliases, and that aliases don't shadow builtin commands.
+    fn check_commands(&self) -> Result<(), String> {
+        let aliases = &self.editor.command_aliases;
+        if let Some(alias) = aliases
+            .keys()
+            .find(|alias| typed::TYPABLE_COMMAND_MAP.contains_key(alias.as_str()))
+        {
+            return Err(format!(
+                "command alias '{}' shadows a typable command",
+                alias
+            ));
+        }
+
+        fn check(trie: &KeyTrie, aliases: &HashMap<String, String>) -> Result<(), String> {
+            let check_command = |command: &MappableCommand| match command {
+                MappableCommand::Typable { name, .. }
+                    if !typed::TYPABLE_COMMAND_MAP.contains_key(name.as_str())
+                        && !aliases.contains_key(name) =>
+                {
+                    Err(format!("No TypableCommand named '{}'", name))
+                }
+                _ => Ok(()),
+            };
+            match trie {
+                KeyTrie::Leaf(command) => check_command(command),
+                KeyTrie::Sequence(commands) => commands.iter().try_for_each(check_command),
+                KeyTrie::Node(node) => node.values().try_for_each(|trie| check(trie, aliases)),
+            }
+        }
+        self.keys
+            .values()
+            .try_for_each(|keymap| check(keymap.root(), aliases))
+    }
+
     pub fn load_default() -> Result<Config, ConfigLoadError> {
         let global_config =
             fs::read_to_string(helix_loader::config_file()).map_err(ConfigLoadError::Error);
@@ -184,4 +237,56 @@ fn keys_resolve_to_correct_defaults() {
         let default_keys = Config::default().keys;
         assert_eq!(default_keys, keymap::default());
     }
+
+    #[test]
+    fn expands_env_vars_in_editor_options() {
+        std::env::set_var("HELIX_TEST_SHELL", "/bin/test-shell");
+        let config = Config::load_test(
+            r#"
+            [editor]
+            shell = ["${HELIX_TEST_SHELL}", "-c"]
+            "#,
+        );
+        assert_eq!(config.editor.shell, ["/bin/test-shell", "-c"]);
+
+        let undefined = Config::load(
+            Ok(r#"theme = "${HELIX_TEST_UNDEFINED}""#.to_owned()),
+            Err(ConfigLoadError::default()),
+        );
+        assert!(matches!(undefined, Err(ConfigLoadError::BadConfig(_))));
+    }
+
+    #[test]
+    fn command_aliases_in_keymaps() {
+        let config = Config::load_test(
+            r#"
+            [keys.normal]
+            C-b = ":backup"
+
+            [editor.command-aliases]
+            backup = "write %{dirname}/backup_%{basename}"
+            "#,
+        );
+        assert_eq!(
+            config.editor.command_aliases["backup"],
+            "write %{dirname}/backup_%{basename}"
+        );
+
+        let load =
+            |config: &str| Config::load(Ok(config.to_owned()), Err(ConfigLoadError::default()));
+        let unknown = load(
+            r#"
+            [keys.normal]
+            C-b = ":backup"
+            "#,
+        );
+        assert!(matches!(unknown, Err(ConfigLoadError::BadConfig(_))));
+        let shadowing = load(
+            r#"
+            [editor.command-aliases]
+            w = "write-all"
+            "#,
+        );
+        assert!(matches!(shadowing, Err(ConfigLoadError::BadConfig(_))));
+    }
 }
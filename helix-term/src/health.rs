@@ -5,8 +5,11 @@
 use helix_core::config::{default_syntax_loader, user_syntax_loader};
 use helix_loader::grammar::load_runtime_file;
 use helix_view::clipboard::get_clipboard_provider;
+use helix_view::editor::ClipboardConfig;
 use std::io::Write;
 
+use crate::config::Config;
+
 #[derive(Copy, Clone)]
 pub enum TsFeature {
     Highlight,
@@ -44,6 +47,13 @@ pub fn short_title(&self) -> &'static str {
     }
 }
 
+/// The clipboard configuration of the user, or the default one if the config can't be loaded.
+fn clipboard_config() -> ClipboardConfig {
+    Config::load_default()
+        .map(|config| config.editor.clipboard)
+        .unwrap_or_default()
+}
+
 /// Display general diagnostics.
 pub fn general() -> std::io::Result<()> {
     let stdout = std::io::stdout();
@@ -53,7 +63,7 @@ pub fn general() -> std::io::Result<()> {
     let lang_file = helix_loader::lang_config_file();
     let log_file = helix_loader::log_file();
     let rt_dirs = helix_loader::runtime_dirs();
-    let clipboard_provider = get_clipboard_provider();
+    let clipboard_provider = get_clipboard_provider(&clipboard_config());
 
     if config_file.exists() {
         writeln!(stdout, "Config file: {}", config_file.display())?;
@@ -101,7 +111,7 @@ pub fn clipboard() -> std::io::Result<()> {
     let stdout = std::io::stdout();
     let mut stdout = stdout.lock();
 
-    let board = get_clipboard_provider();
+    let board = get_clipboard_provider(&clipboard_config());
     match board.name().as_ref() {
         "none" => {
             writeln!(
@@ -0,0 +1,286 @@
+//! Builtin documentation for the `:help` command.
+//!
+//! Help pages are generated from the command lists, the active keymap and the default editor
+//! configuration so that they always describe the running binary. Pages reference each other
+//! with `|topic|` links which can be followed with `goto_file` (`gf`).
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use anyhow::anyhow;
+use helix_core::Rope;
+use helix_view::{
+    document::Mode,
+    editor::{Action, Config},
+    input::KeyEvent,
+    Document, Editor,
+};
+
+use crate::{
+    commands::{typed::TYPABLE_COMMAND_MAP, MappableCommand, TYPABLE_COMMAND_LIST},
+    compositor::Compositor,
+    keymap::{Keymap, ReverseKeymap},
+    ui,
+};
+
+/// Help topics along with the summary shown for them on the index page.
+pub const TOPICS: &[(&str, &str)] = &[
+    ("index", "List of help topics"),
+    ("commands", "Commands that can be bound to keys"),
+    (
+        "typable-commands",
+        "Commands that can be run from command mode",
+    ),
+    ("keymap", "Active key bindings of each mode"),
+    (
+        "config",
+        "Editor configuration keys and their default values",
+    ),
+];
+
+const MODES: [Mode; 3] = [Mode::Normal, Mode::Select, Mode::Insert];
+
+/// Opens the help page for `topic` in a new read-only scratch buffer.
+pub fn open(editor: &mut Editor, compositor: &mut Compositor, topic: &str) -> anyhow::Result<()> {
+    let editor_view = compositor
+        .find::<ui::EditorView>()
+        .expect("the editor view is always present");
+    let contents = render(topic, &editor_view.keymaps.map())
+        .ok_or_else(|| anyhow!("no help for '{}'", topic))?;
+
+    let mut doc = Document::from(Rope::from(contents), None, editor.config.clone());
+    doc.readonly = true;
+    // Help pages are markdown, highlight them as such if the grammar is available.
+    let _ = doc.set_language_by_language_id("markdown", editor.syn_loader.clone());
+    editor.new_file_from_document(Action::Replace, doc);
+
+    Ok(())
+}
+
+/// Returns the topic of a `|topic|` help link, if `word` is one.
+pub fn link_target(word: &str) -> Option<&str> {
+    word.strip_prefix('|')?
+        .strip_suffix('|')
+        .filter(|topic| !topic.is_empty())
+}
+
+/// Renders the help page for `topic`.
+///
+/// Besides the names in [`TOPICS`], `topic` may be the name or alias of a command or a
+/// configuration key, in which case a page describing just that item is rendered.
+pub fn render(topic: &str, keymaps: &HashMap<Mode, Keymap>) -> Option<String> {
+    let reverse_keymaps: Vec<(Mode, ReverseKeymap)> = MODES
+        .iter()
+        .filter_map(|mode| Some((*mode, keymaps.get(mode)?.reverse_map())))
+        .collect();
+
+    let page = match topic {
+        "index" => index(),
+        "commands" => commands(&reverse_keymaps),
+        "typable-commands" => typable_commands(&reverse_keymaps),
+        "keymap" => keymap(&reverse_keymaps),
+        "config" => config(),
+        topic => command(topic, &reverse_keymaps).or_else(|| config_key(topic))?,
+    };
+    Some(page)
+}
+
+/// Names that can be passed to `:help`, used for completion.
+pub fn topic_names() -> impl Iterator<Item = &'static str> {
+    TOPICS
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(
+            MappableCommand::STATIC_COMMAND_LIST
+                .iter()
+                .map(|cmd| cmd.name()),
+        )
+        .chain(TYPABLE_COMMAND_LIST.iter().map(|cmd| cmd.name))
+}
+
+fn fmt_keys(keys: &[KeyEvent]) -> String {
+    keys.iter().map(|key| key.key_sequence_format()).collect()
+}
+
+/// Formats the bindings of `command` in every mode, e.g. "normal: `x`; select: `x`".
+fn fmt_bindings(command: &str, reverse_keymaps: &[(Mode, ReverseKeymap)]) -> Option<String> {
+    let bindings: Vec<_> = reverse_keymaps
+        .iter()
+        .filter_map(|(mode, keymap)| {
+            let keys = keymap.get(command)?;
+            let keys: Vec<_> = keys
+                .iter()
+                .map(|keys| format!("`{}`", fmt_keys(keys)))
+                .collect();
+            Some(format!("{}: {}", mode, keys.join(" ")))
+        })
+        .collect();
+
+    if bindings.is_empty() {
+        None
+    } else {
+        Some(bindings.join("; "))
+    }
+}
+
+fn index() -> String {
+    let mut page = String::from("# Helix help\n\n");
+    page.push_str(
+        "Follow a `|link|` with `gf` or open a page directly with `:help <topic>`.\n\
+         `:help <command>` and `:help <config-key>` describe a single command or setting.\n\n",
+    );
+    for (name, summary) in TOPICS.iter().skip(1) {
+        let _ = writeln!(page, "- |{}| {}", name, summary);
+    }
+    page
+}
+
+fn commands(reverse_keymaps: &[(Mode, ReverseKeymap)]) -> String {
+    let mut page = String::from("# Commands\n\nSee also: |typable-commands| |keymap|\n\n");
+    for command in MappableCommand::STATIC_COMMAND_LIST {
+        let _ = write!(page, "- `{}` {}", command.name(), command.doc());
+        if let Some(bindings) = fmt_bindings(command.name(), reverse_keymaps) {
+            let _ = write!(page, " ({})", bindings);
+        }
+        page.push('\n');
+    }
+    page
+}
+
+fn typable_commands(reverse_keymaps: &[(Mode, ReverseKeymap)]) -> String {
+    let mut page = String::from("# Typable commands\n\nSee also: |commands| |keymap|\n\n");
+    for command in TYPABLE_COMMAND_LIST {
+        let _ = write!(page, "- `:{}`", command.name);
+        for alias in command.aliases {
+            let _ = write!(page, ", `:{}`", alias);
+        }
+        let _ = write!(page, " {}", command.doc);
+        if let Some(bindings) = fmt_bindings(command.name, reverse_keymaps) {
+            let _ = write!(page, " ({})", bindings);
+        }
+        page.push('\n');
+    }
+    page
+}
+
+fn keymap(reverse_keymaps: &[(Mode, ReverseKeymap)]) -> String {
+    let mut page = String::from("# Keymap\n\nSee also: |commands| |typable-commands|\n");
+    for (mode, keymap) in reverse_keymaps {
+        let mut bindings: Vec<_> = keymap
+            .iter()
+            .flat_map(|(command, bindings)| {
+                bindings.iter().map(move |keys| (fmt_keys(keys), command))
+            })
+            .collect();
+        bindings.sort();
+
+        let _ = write!(page, "\n## {} mode\n\n", mode);
+        for (keys, command) in bindings {
+            let _ = writeln!(page, "- `{}` {}", keys, command);
+        }
+    }
+    page
+}
+
+/// Flattens the default configuration into `(key, value)` pairs such as `("lsp.enable", "true")`.
+fn config_entries() -> Vec<(String, String)> {
+    fn flatten(
+        value: &serde_json::Value,
+        scope: Option<&str>,
+        entries: &mut Vec<(String, String)>,
+    ) {
+        match value.as_object() {
+            Some(map) => {
+                for (key, value) in map {
+                    let key = match scope {
+                        Some(scope) => format!("{}.{}", scope, key),
+                        None => key.clone(),
+                    };
+                    flatten(value, Some(&key), entries);
+                }
+            }
+            None => entries.push((scope.unwrap_or_default().to_string(), value.to_string())),
+        }
+    }
+
+    let mut entries = Vec::new();
+    flatten(&serde_json::json!(Config::default()), None, &mut entries);
+    entries.sort();
+    entries
+}
+
+fn config() -> String {
+    let mut page = String::from(
+        "# Configuration\n\n\
+         Default values of the `[editor]` section of `config.toml`. Options can be changed at\n\
+         runtime with `:set-option` and `:toggle-option`.\n\n",
+    );
+    for (key, value) in config_entries() {
+        let _ = writeln!(page, "- `{}` = `{}`", key, value);
+    }
+    page
+}
+
+fn config_key(key: &str) -> Option<String> {
+    let (key, value) = config_entries().into_iter().find(|(name, _)| name == key)?;
+    Some(format!(
+        "# {}\n\nDefault: `{}`\n\nSet it with `:set-option {} <value>`.\n\nSee also: |config|\n",
+        key, value, key
+    ))
+}
+
+fn command(name: &str, reverse_keymaps: &[(Mode, ReverseKeymap)]) -> Option<String> {
+    let name = name.strip_prefix(':').unwrap_or(name);
+
+    let (name, doc, aliases, see_also) = match TYPABLE_COMMAND_MAP.get(name) {
+        Some(command) => (
+            command.name,
+            command.doc,
+            command.aliases,
+            "|typable-commands|",
+        ),
+        None => {
+            let command = MappableCommand::STATIC_COMMAND_LIST
+                .iter()
+                .find(|command| command.name() == name)?;
+            (command.name(), command.doc(), &[][..], "|commands|")
+        }
+    };
+
+    let mut page = format!("# {}\n\n{}\n", name, doc);
+    if !aliases.is_empty() {
+        let _ = write!(page, "\nAliases: {}\n", aliases.join(", "));
+    }
+    if let Some(bindings) = fmt_bindings(name, reverse_keymaps) {
+        let _ = write!(page, "\nBindings: {}\n", bindings);
+    }
+    let _ = write!(page, "\nSee also: {} |keymap|\n", see_also);
+    Some(page)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_link_target() {
+        assert_eq!(link_target("|keymap|"), Some("keymap"));
+        assert_eq!(link_target("||"), None);
+        assert_eq!(link_target("|keymap"), None);
+        assert_eq!(link_target("keymap"), None);
+    }
+
+    #[test]
+    fn render_topics() {
+        let keymaps = crate::keymap::default::default();
+
+        for (topic, _) in TOPICS {
+            assert!(render(topic, &keymaps).is_some(), "missing topic {}", topic);
+        }
+        let write = render("w", &keymaps).unwrap();
+        assert!(write.starts_with("# write\n"));
+        let scrolloff = render("scrolloff", &keymaps).unwrap();
+        assert!(scrolloff.contains("Default: `5`"));
+        assert!(render("not-a-topic", &keymaps).is_none());
+    }
+}
@@ -2,8 +2,13 @@
 
 use crate::compositor::Compositor;
 
-use futures_util::future::{BoxFuture, Future, FutureExt};
+use futures_util::future::{AbortHandle, Abortable, BoxFuture, Future, FutureExt};
 use futures_util::stream::{FuturesUnordered, StreamExt};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 
 pub type EditorCompositorCallback = Box<dyn FnOnce(&mut Editor, &mut Compositor) + Send>;
 pub type EditorCallback = Box<dyn FnOnce(&mut Editor) + Send>;
@@ -19,6 +24,32 @@ pub struct Job {
     pub future: BoxFuture<'static, anyhow::Result<Option<Callback>>>,
     /// Do we need to wait for this job to finish before exiting?
     pub wait: bool,
+    /// Name of the job in the list of running jobs. Unnamed jobs aren't listed.
+    pub name: Option<String>,
+    /// Run instead of the callback of the job when it is cancelled.
+    pub on_cancel: Option<Callback>,
+}
+
+/// A named job in flight, which can be cancelled.
+#[derive(Debug, Clone)]
+pub struct RunningJob {
+    pub id: usize,
+    pub name: String,
+    pub started: Instant,
+    abort: AbortHandle,
+}
+
+impl RunningJob {
+    /// Drops the future of the job. Its callback isn't run, its `on_cancel` callback is.
+    pub fn cancel(&self) {
+        self.abort.abort();
+    }
+}
+
+#[derive(Default)]
+struct RunningJobs {
+    next_id: usize,
+    jobs: BTreeMap<usize, RunningJob>,
 }
 
 #[derive(Default)]
@@ -26,6 +57,7 @@ pub struct Jobs {
     pub futures: FuturesUnordered<JobFuture>,
     /// These are the ones that need to complete before we exit.
     pub wait_futures: FuturesUnordered<JobFuture>,
+    running: Arc<Mutex<RunningJobs>>,
 }
 
 impl Job {
@@ -33,6 +65,8 @@ pub fn new<F: Future<Output = anyhow::Result<()>> + Send + 'static>(f: F) -> Sel
         Self {
             future: f.map(|r| r.map(|()| None)).boxed(),
             wait: false,
+            name: None,
+            on_cancel: None,
         }
     }
 
@@ -42,6 +76,8 @@ pub fn with_callback<F: Future<Output = anyhow::Result<Callback>> + Send + 'stat
         Self {
             future: f.map(|r| r.map(Some)).boxed(),
             wait: false,
+            name: None,
+            on_cancel: None,
         }
     }
 
@@ -49,6 +85,18 @@ pub fn wait_before_exiting(mut self) -> Self {
         self.wait = true;
         self
     }
+
+    /// Lists the job under `name` while it runs, so it can be cancelled.
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Runs `callback` instead of the callback of the job if the job is cancelled.
+    pub fn on_cancel(mut self, callback: Callback) -> Self {
+        self.on_cancel = Some(callback);
+        self
+    }
 }
 
 impl Jobs {
@@ -93,11 +141,65 @@ pub async fn next_job(&mut self) -> Option<anyhow::Result<Option<Callback>>> {
     }
 
     pub fn add(&self, j: Job) {
+        let future = match j.name {
+            Some(name) => self.track(name, j.future, j.on_cancel),
+            None => j.future,
+        };
         if j.wait {
-            self.wait_futures.push(j.future);
+            self.wait_futures.push(future);
         } else {
-            self.futures.push(j.future);
+            self.futures.push(future);
+        }
+    }
+
+    /// Adds the future to the running jobs until it completes or is cancelled.
+    fn track(&self, name: String, future: JobFuture, on_cancel: Option<Callback>) -> JobFuture {
+        let (abort, registration) = AbortHandle::new_pair();
+        let id = {
+            let mut running = self.running.lock().unwrap();
+            let id = running.next_id;
+            running.next_id += 1;
+            running.jobs.insert(
+                id,
+                RunningJob {
+                    id,
+                    name: name.clone(),
+                    started: Instant::now(),
+                    abort,
+                },
+            );
+            id
+        };
+
+        let running = self.running.clone();
+        async move {
+            let result = Abortable::new(future, registration).await;
+            running.lock().unwrap().jobs.remove(&id);
+            match result {
+                Ok(result) => result,
+                Err(_aborted) => match on_cancel {
+                    Some(on_cancel) => Ok(Some(on_cancel)),
+                    None => {
+                        let call: EditorCallback = Box::new(move |editor: &mut Editor| {
+                            editor.set_status(format!("Cancelled {}", name));
+                        });
+                        Ok(Some(Callback::Editor(call)))
+                    }
+                },
+            }
         }
+        .boxed()
+    }
+
+    /// The named jobs in flight, the oldest first.
+    pub fn running(&self) -> Vec<RunningJob> {
+        self.running
+            .lock()
+            .unwrap()
+            .jobs
+            .values()
+            .cloned()
+            .collect()
     }
 
     /// Blocks until all the jobs that need to be waited on are done.
@@ -58,6 +58,8 @@ pub fn default() -> HashMap<Mode, Keymap> {
             "k" => move_line_up,
             "j" => move_line_down,
             "." => goto_last_modification,
+            ";" => goto_last_change_location,
+            "x" => goto_link,
         },
         ":" => command_mode,
 
@@ -79,6 +81,7 @@ pub fn default() -> HashMap<Mode, Keymap> {
 
         "s" => select_regex,
         "A-s" => split_selection_on_newline,
+        "A-S" => split_selection_on_textobject,
         "A-_" => merge_consecutive_selections,
         "S" => split_selection,
         ";" => collapse_selection,
@@ -106,12 +109,16 @@ pub fn default() -> HashMap<Mode, Keymap> {
             "D" => goto_first_diag,
             "g" => goto_prev_change,
             "G" => goto_first_change,
+            ";" => goto_prev_change_location,
             "f" => goto_prev_function,
             "t" => goto_prev_class,
             "a" => goto_prev_parameter,
             "c" => goto_prev_comment,
             "T" => goto_prev_test,
+            "C" => goto_prev_cell,
+            "q" => goto_prev_quickfix,
             "p" => goto_prev_paragraph,
+            "P" => paste_before_reindent,
             "space" => add_newline_above,
         },
         "]" => { "Right bracket"
@@ -119,12 +126,16 @@ pub fn default() -> HashMap<Mode, Keymap> {
             "D" => goto_last_diag,
             "g" => goto_next_change,
             "G" => goto_last_change,
+            ";" => goto_next_change_location,
             "f" => goto_next_function,
             "t" => goto_next_class,
             "a" => goto_next_parameter,
             "c" => goto_next_comment,
             "T" => goto_next_test,
+            "C" => goto_next_cell,
+            "q" => goto_next_quickfix,
             "p" => goto_next_paragraph,
+            "P" => paste_after_reindent,
             "space" => add_newline_below,
         },
 
@@ -138,12 +149,15 @@ pub fn default() -> HashMap<Mode, Keymap> {
         "U" => redo,
         "A-u" => earlier,
         "A-U" => later,
+        "A-z" => undo_selection,
+        "A-Z" => redo_selection,
 
         "y" => yank,
         // yank_all
         "p" => paste_after,
         // paste_all
         "P" => paste_before,
+        "A-y" => cycle_paste,
 
         "Q" => record_macro,
         "q" => replay_macro,
@@ -214,7 +228,10 @@ pub fn default() -> HashMap<Mode, Keymap> {
             "f" => file_picker,
             "F" => file_picker_in_current_directory,
             "b" => buffer_picker,
+            "o" => recent_files_picker,
             "j" => jumplist_picker,
+            "J" => global_jumplist_picker,
+            "q" => quickfix_picker,
             "s" => symbol_picker,
             "S" => workspace_symbol_picker,
             "d" => diagnostics_picker,
@@ -269,7 +286,10 @@ pub fn default() -> HashMap<Mode, Keymap> {
             "p" => paste_clipboard_after,
             "P" => paste_clipboard_before,
             "R" => replace_selections_with_clipboard,
+            "\"" => yank_ring_picker,
+            "B" => jobs_picker,
             "/" => global_search,
+            "T" => todo_picker,
             "k" => hover,
             "r" => rename_symbol,
             "h" => select_references_to_symbol_under_cursor,
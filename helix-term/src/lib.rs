@@ -7,23 +7,46 @@
 pub mod compositor;
 pub mod config;
 pub mod health;
+pub mod help;
 pub mod job;
 pub mod keymap;
+pub mod startup;
+pub mod trash;
 pub mod ui;
-use std::path::Path;
+pub mod workspace_index;
+use std::path::{Path, PathBuf};
 
+use helix_view::theme::ColorSupport;
 use ignore::DirEntry;
 pub use keymap::macros::*;
 
+/// Detects the colors supported by the terminal. `true_color` overrides the detection
+/// in the event of a false negative.
 #[cfg(not(windows))]
-fn true_color() -> bool {
-    std::env::var("COLORTERM")
-        .map(|v| matches!(v.as_str(), "truecolor" | "24bit"))
-        .unwrap_or(false)
+fn color_support(true_color: bool) -> ColorSupport {
+    use std::env::var;
+
+    let term = var("TERM").unwrap_or_default();
+    if true_color
+        || matches!(var("COLORTERM").as_deref(), Ok("truecolor" | "24bit"))
+        || term.ends_with("-direct")
+        || term.contains("truecolor")
+        || matches!(
+            var("TERM_PROGRAM").as_deref(),
+            Ok("iTerm.app" | "WezTerm" | "vscode" | "ghostty")
+        )
+        || var("WT_SESSION").is_ok()
+    {
+        ColorSupport::TrueColor
+    } else if term.contains("256color") {
+        ColorSupport::Indexed256
+    } else {
+        ColorSupport::Ansi16
+    }
 }
 #[cfg(windows)]
-fn true_color() -> bool {
-    true
+fn color_support(_true_color: bool) -> ColorSupport {
+    ColorSupport::TrueColor
 }
 
 /// Function used for filtering dir entries in the various file pickers.
@@ -47,3 +70,13 @@ fn filter_picker_entry(entry: &DirEntry, root: &Path, dedup_symlinks: bool) -> b
 
     true
 }
+
+/// The roots added to the workspace which aren't already walked from `root`, for pickers and
+/// searches to cover the whole workspace.
+fn extra_walk_roots<'a>(root: &Path, workspace_roots: &'a [PathBuf]) -> Vec<&'a PathBuf> {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    workspace_roots
+        .iter()
+        .filter(|workspace_root| !workspace_root.starts_with(&root))
+        .collect()
+}
@@ -4,7 +4,9 @@
 use helix_term::application::Application;
 use helix_term::args::Args;
 use helix_term::config::{Config, ConfigLoadError};
+use helix_term::startup;
 use std::path::PathBuf;
+use std::time::Instant;
 
 fn setup_logging(logpath: PathBuf, verbosity: u64) -> Result<()> {
     let mut base_config = fern::Dispatch::new();
@@ -41,6 +43,8 @@ fn main() -> Result<()> {
 
 #[tokio::main]
 async fn main_impl() -> Result<i32> {
+    startup::start();
+
     let logpath = helix_loader::log_file();
     let parent = logpath.parent().unwrap();
     if !parent.exists() {
@@ -54,10 +58,12 @@ async fn main_impl() -> Result<i32> {
 {}
 
 USAGE:
-    hx [FLAGS] [files]...
+    hx [FLAGS] [files]... [+command]...
 
 ARGS:
     <files>...    Sets the input file to use, position can also be specified via file[:row[:col]]
+                  Pass `-` to read stdin into a scratch buffer
+    +<command>    Runs a typable command after startup, e.g. `+42` or `+'set-option mouse false'`
 
 FLAGS:
     -h, --help                     Prints help information
@@ -68,6 +74,7 @@ async fn main_impl() -> Result<i32> {
     -g, --grammar {{fetch|build}}    Fetches or builds tree-sitter grammars listed in languages.toml
     -c, --config <file>            Specifies a file to use for configuration
     -v                             Increases logging verbosity each use for up to 3 times
+    -e, --execute <command>        Runs a typable command after startup, may be given multiple times
     --log                          Specifies a file to use for logging
                                    (default file: {})
     -V, --version                  Prints version information
@@ -81,7 +88,8 @@ async fn main_impl() -> Result<i32> {
         logpath.display(),
     );
 
-    let args = Args::parse_args().context("could not parse arguments")?;
+    let args =
+        startup::time("parse arguments", Args::parse_args).context("could not parse arguments")?;
 
     // Help has a higher priority and should be handled separately.
     if args.display_help {
@@ -126,6 +134,10 @@ async fn main_impl() -> Result<i32> {
 
     helix_loader::initialize_config_file(args.config_file.clone());
 
+    // Gutters which can be named in the gutter layout of the config.
+    helix_view::gutter::register_gutter("breakpoints", helix_view::gutter::breakpoints);
+
+    let started = Instant::now();
     let config = match Config::load_default() {
         Ok(config) => config,
         Err(ConfigLoadError::Error(err)) if err.kind() == std::io::ErrorKind::NotFound => {
@@ -140,7 +152,9 @@ async fn main_impl() -> Result<i32> {
             Config::default()
         }
     };
+    startup::record("load config", started);
 
+    let started = Instant::now();
     let syn_loader_conf = helix_core::config::user_syntax_loader().unwrap_or_else(|err| {
         eprintln!("Bad language config: {}", err);
         eprintln!("Press <ENTER> to continue with default language config");
@@ -149,6 +163,7 @@ async fn main_impl() -> Result<i32> {
         let _ = std::io::stdin().read(&mut []);
         helix_core::config::default_syntax_loader()
     });
+    startup::record("load language config", started);
 
     // TODO: use the thread local executor to spawn the application task separately from the work pool
     let mut app = Application::new(args, config, syn_loader_conf)
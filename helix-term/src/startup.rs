@@ -0,0 +1,65 @@
+//! Timings of the steps of startup, reported by `:startuptime` to find slow components.
+
+use std::{
+    fmt::Write,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::{Lazy, OnceCell};
+
+/// When the process started, or close enough: set first thing in `main`.
+static START: OnceCell<Instant> = OnceCell::new();
+
+static STEPS: Lazy<Mutex<Vec<Step>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+struct Step {
+    name: &'static str,
+    /// Time since start when the step began.
+    start: Duration,
+    duration: Duration,
+}
+
+/// Marks the start of the process. Steps are reported relative to it.
+pub fn start() {
+    let _ = START.set(Instant::now());
+}
+
+/// Runs `f` as the startup step `name`, recording how long it took.
+pub fn time<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = f();
+    record(name, started);
+    result
+}
+
+/// Records the startup step `name`, which began at `started` and just completed.
+pub fn record(name: &'static str, started: Instant) {
+    let start = *START.get_or_init(|| started);
+    STEPS.lock().unwrap().push(Step {
+        name,
+        start: started.saturating_duration_since(start),
+        duration: started.elapsed(),
+    });
+}
+
+/// Renders the recorded steps in the order they completed, with the time since start they
+/// began at and how long they took.
+pub fn report() -> String {
+    let steps = STEPS.lock().unwrap();
+    let mut report = String::from("   start    duration  step\n");
+    for step in steps.iter() {
+        writeln!(
+            report,
+            "{:>8.3}ms {:>8.3}ms  {}",
+            step.start.as_secs_f64() * 1000.0,
+            step.duration.as_secs_f64() * 1000.0,
+            step.name
+        )
+        .unwrap();
+    }
+    if let Some(last) = steps.iter().map(|step| step.start + step.duration).max() {
+        writeln!(report, "\ntotal: {:.3}ms", last.as_secs_f64() * 1000.0).unwrap();
+    }
+    report
+}
@@ -0,0 +1,252 @@
+//! Deletion of files through the trash of the desktop so they can be recovered.
+//!
+//! The [FreeDesktop.org trash specification](https://specifications.freedesktop.org/trash-spec/trashspec-latest.html)
+//! is followed on Linux and the BSDs, `~/.Trash` is used on macOS. Other platforms have no
+//! trash and files are deleted permanently.
+
+use std::{fs, io, path::Path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deletion {
+    /// The file was moved to the trash.
+    Trashed,
+    /// The file was deleted permanently.
+    Deleted,
+}
+
+impl Deletion {
+    /// Describes the deletion of `path` for a status message.
+    pub fn describe(self, path: &Path) -> String {
+        let path = helix_core::path::get_relative_path(path);
+        match self {
+            Self::Trashed => format!("Moved '{}' to the trash", path.display()),
+            Self::Deleted => format!("Permanently deleted '{}'", path.display()),
+        }
+    }
+}
+
+/// Deletes the file or directory at `path`, moving it to the trash unless `hard_delete` is set
+/// or the platform has no trash. Directories are deleted with their contents.
+pub fn delete(path: &Path, hard_delete: bool) -> io::Result<Deletion> {
+    if !hard_delete {
+        match move_to_trash(path) {
+            Ok(()) => return Ok(Deletion::Trashed),
+            Err(err) if err.kind() == io::ErrorKind::Unsupported => (),
+            Err(err) => return Err(err),
+        }
+    }
+
+    if path.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+    Ok(Deletion::Deleted)
+}
+
+/// Moves the file or directory `from` to `to`. It's copied and removed if it's moved to
+/// another file system, which can't be done with a rename.
+pub fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(err) if crosses_devices(&err) => {
+            if let Err(err) = copy_all(from, to) {
+                // Don't leave a partial copy of a directory behind, it didn't exist before.
+                if from.is_dir() {
+                    let _ = fs::remove_dir_all(to);
+                }
+                return Err(err);
+            }
+            if from.is_dir() {
+                fs::remove_dir_all(from)
+            } else {
+                fs::remove_file(from)
+            }
+        }
+        result => result,
+    }
+}
+
+/// Whether `err` is the error of a rename to another file system.
+fn crosses_devices(err: &io::Error) -> bool {
+    #[cfg(not(windows))]
+    const EXDEV: i32 = libc::EXDEV;
+    // ERROR_NOT_SAME_DEVICE
+    #[cfg(windows)]
+    const EXDEV: i32 = 17;
+    err.raw_os_error() == Some(EXDEV)
+}
+
+/// Copies the file or directory `from` with its contents to `to`. Symbolic links are copied
+/// as links.
+fn copy_all(from: &Path, to: &Path) -> io::Result<()> {
+    let file_type = fs::symlink_metadata(from)?.file_type();
+    if file_type.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_all(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else if file_type.is_symlink() {
+        #[cfg(unix)]
+        return std::os::unix::fs::symlink(fs::read_link(from)?, to);
+        #[cfg(not(unix))]
+        return fs::copy(from, to).map(|_| ());
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn file_name(path: &Path) -> io::Result<&std::ffi::OsStr> {
+    path.file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid file name"))
+}
+
+/// Appends `.n` to `name` for all `n` but 0, to find a free name in the trash.
+#[cfg(unix)]
+fn numbered_name(name: &std::ffi::OsStr, n: usize) -> std::ffi::OsString {
+    let mut name = name.to_os_string();
+    if n > 0 {
+        name.push(format!(".{}", n));
+    }
+    name
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn move_to_trash(path: &Path) -> io::Result<()> {
+    use std::io::Write;
+
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(std::path::PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "no home directory"))?;
+    let trash = data_home.join("Trash");
+    let (files, info) = (trash.join("files"), trash.join("info"));
+    fs::create_dir_all(&files)?;
+    fs::create_dir_all(&info)?;
+
+    let path = path.canonicalize()?;
+    let name = file_name(&path)?;
+    let mut n = 0;
+    loop {
+        // The name is reserved by creating its info file.
+        let trashed_name = numbered_name(name, n);
+        let mut info_name = trashed_name.clone();
+        info_name.push(".trashinfo");
+        let info_path = info.join(info_name);
+        let mut info_file = match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_path)
+        {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                n += 1;
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+
+        let result = write!(
+            info_file,
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            percent_encode(&path),
+            chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+        )
+        .and_then(|_| move_path(&path, &files.join(&trashed_name)));
+        if result.is_err() {
+            let _ = fs::remove_file(&info_path);
+        }
+        return result;
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn move_to_trash(path: &Path) -> io::Result<()> {
+    let home = std::env::var_os("HOME")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "no home directory"))?;
+    let trash = Path::new(&home).join(".Trash");
+    let name = file_name(path)?;
+    let target = (0..)
+        .map(|n| trash.join(numbered_name(name, n)))
+        .find(|target| !target.exists())
+        .unwrap();
+    move_path(path, &target)
+}
+
+#[cfg(not(unix))]
+fn move_to_trash(_path: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "no trash on this platform",
+    ))
+}
+
+/// Percent encodes `path` for the `Path` key of a `.trashinfo` file.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn percent_encode(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut encoded = String::new();
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_all() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("from");
+        fs::create_dir_all(from.join("nested")).unwrap();
+        fs::write(from.join("a.txt"), "a").unwrap();
+        fs::write(from.join("nested/b.txt"), "b").unwrap();
+
+        let to = dir.path().join("to");
+        copy_all(&from, &to).unwrap();
+        assert_eq!(fs::read_to_string(to.join("a.txt")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(to.join("nested/b.txt")).unwrap(), "b");
+        // The source is left to the caller.
+        assert!(from.join("nested/b.txt").exists());
+    }
+
+    #[test]
+    fn test_move_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("from");
+        fs::create_dir(&from).unwrap();
+        fs::write(from.join("a.txt"), "a").unwrap();
+
+        let to = dir.path().join("to");
+        move_path(&from, &to).unwrap();
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(to.join("a.txt")).unwrap(), "a");
+    }
+
+    #[test]
+    fn test_crosses_devices() {
+        assert!(!crosses_devices(&io::Error::from(io::ErrorKind::NotFound)));
+        #[cfg(unix)]
+        assert!(crosses_devices(&io::Error::from_raw_os_error(libc::EXDEV)));
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(
+            percent_encode(Path::new("/home/user/my file#1.rs")),
+            "/home/user/my%20file%231.rs"
+        );
+        assert_eq!(percent_encode(Path::new("/tmp/é")), "/tmp/%C3%A9");
+    }
+}
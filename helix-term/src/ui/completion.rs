@@ -1,22 +1,29 @@
 use crate::compositor::{Component, Context, Event, EventResult};
 use helix_view::{
     document::SavePoint,
-    editor::CompleteAction,
+    editor::{CompleteAction, CompletionSort},
     theme::{Modifier, Style},
     ViewId,
 };
 use tui::{buffer::Buffer as Surface, text::Span};
 
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, cmp::Ordering, collections::HashMap, sync::Arc};
 
-use helix_core::{Change, Transaction};
+use helix_core::{chars::char_is_word, Assoc, Change, Transaction};
 use helix_view::{graphics::Rect, Document, Editor};
 
 use crate::commands;
 use crate::ui::{menu, Markdown, Menu, Popup, PromptEvent};
 
 use helix_lsp::{lsp, util};
-use lsp::CompletionItem;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletionItem {
+    pub item: lsp::CompletionItem,
+    /// Whether the item was resolved with `completionItem/resolve`, filling in lazily computed
+    /// properties like the documentation or additional text edits.
+    pub resolved: bool,
+}
 
 impl menu::Item for CompletionItem {
     type Data = ();
@@ -26,28 +33,29 @@ fn sort_text(&self, data: &Self::Data) -> Cow<str> {
 
     #[inline]
     fn filter_text(&self, _data: &Self::Data) -> Cow<str> {
-        self.filter_text
+        self.item
+            .filter_text
             .as_ref()
-            .unwrap_or(&self.label)
+            .unwrap_or(&self.item.label)
             .as_str()
             .into()
     }
 
     fn format(&self, _data: &Self::Data) -> menu::Row {
-        let deprecated = self.deprecated.unwrap_or_default()
-            || self.tags.as_ref().map_or(false, |tags| {
+        let deprecated = self.item.deprecated.unwrap_or_default()
+            || self.item.tags.as_ref().map_or(false, |tags| {
                 tags.contains(&lsp::CompletionItemTag::DEPRECATED)
             });
         menu::Row::new(vec![
             menu::Cell::from(Span::styled(
-                self.label.as_str(),
+                self.item.label.as_str(),
                 if deprecated {
                     Style::default().add_modifier(Modifier::CROSSED_OUT)
                 } else {
                     Style::default()
                 },
             )),
-            menu::Cell::from(match self.kind {
+            menu::Cell::from(match self.item.kind {
                 Some(lsp::CompletionItemKind::TEXT) => "text",
                 Some(lsp::CompletionItemKind::METHOD) => "method",
                 Some(lsp::CompletionItemKind::FUNCTION) => "function",
@@ -94,9 +102,64 @@ pub struct Completion {
     start_offset: usize,
     #[allow(dead_code)]
     trigger_offset: usize,
+    /// Whether the language server reported that further typing should requery completions.
+    is_incomplete: bool,
     // TODO: maintain a completioncontext with trigger kind & trigger char
 }
 
+/// Ranks completion item kinds for [`CompletionSort::Kind`], lower is better.
+fn kind_priority(kind: Option<lsp::CompletionItemKind>) -> u8 {
+    use lsp::CompletionItemKind as Kind;
+    match kind {
+        Some(
+            Kind::VARIABLE | Kind::FIELD | Kind::PROPERTY | Kind::ENUM_MEMBER | Kind::CONSTANT,
+        ) => 0,
+        Some(Kind::METHOD | Kind::FUNCTION | Kind::CONSTRUCTOR) => 1,
+        Some(
+            Kind::CLASS
+            | Kind::STRUCT
+            | Kind::INTERFACE
+            | Kind::ENUM
+            | Kind::MODULE
+            | Kind::TYPE_PARAMETER
+            | Kind::UNIT
+            | Kind::VALUE,
+        ) => 2,
+        Some(Kind::KEYWORD | Kind::SNIPPET | Kind::OPERATOR) => 3,
+        _ => 4,
+    }
+}
+
+/// Lines around the cursor searched for occurrences of completion items by
+/// [`CompletionSort::Locality`].
+const LOCALITY_LINES: usize = 200;
+
+/// Returns the distance in lines from the cursor to the closest occurrence of every word
+/// around the cursor.
+fn word_distances(doc: &Document, view_id: ViewId) -> HashMap<String, usize> {
+    let text = doc.text().slice(..);
+    let cursor = doc.selection(view_id).primary().cursor(text);
+    let cursor_line = text.char_to_line(cursor);
+    let start = cursor_line.saturating_sub(LOCALITY_LINES);
+    let end = (cursor_line + LOCALITY_LINES + 1).min(text.len_lines());
+
+    let mut distances = HashMap::new();
+    for line in start..end {
+        let distance = line.abs_diff(cursor_line);
+        let line = Cow::from(text.line(line));
+        for word in line.split(|ch: char| !char_is_word(ch)) {
+            if word.is_empty() {
+                continue;
+            }
+            distances
+                .entry(word.to_string())
+                .and_modify(|closest: &mut usize| *closest = (*closest).min(distance))
+                .or_insert(distance);
+        }
+    }
+    distances
+}
+
 impl Completion {
     pub const ID: &'static str = "completion";
 
@@ -107,17 +170,18 @@ pub fn new(
         offset_encoding: helix_lsp::OffsetEncoding,
         start_offset: usize,
         trigger_offset: usize,
+        is_incomplete: bool,
     ) -> Self {
         let replace_mode = editor.config().completion_replace;
         // Sort completion items according to their preselect status (given by the LSP server)
-        items.sort_by_key(|item| !item.preselect.unwrap_or(false));
+        items.sort_by_key(|item| !item.item.preselect.unwrap_or(false));
 
         // Then create the menu
         let menu = Menu::new(items, (), move |editor: &mut Editor, item, event| {
             fn item_to_transaction(
                 doc: &Document,
                 view_id: ViewId,
-                item: &CompletionItem,
+                item: &lsp::CompletionItem,
                 offset_encoding: helix_lsp::OffsetEncoding,
                 trigger_offset: usize,
                 include_placeholder: bool,
@@ -223,7 +287,7 @@ fn completion_changes(transaction: &Transaction, trigger_offset: usize) -> Vec<C
                     let transaction = item_to_transaction(
                         doc,
                         view.id,
-                        item,
+                        &item.item,
                         offset_encoding,
                         trigger_offset,
                         true,
@@ -242,6 +306,23 @@ fn completion_changes(transaction: &Transaction, trigger_offset: usize) -> Vec<C
                     // always present here
                     let item = item.unwrap();
 
+                    // Resolve the item if it wasn't yet, servers may only compute the additional
+                    // text edits (mostly used to auto import unqualified types) when resolving.
+                    let has_additional_edits = item
+                        .item
+                        .additional_text_edits
+                        .as_ref()
+                        .map_or(false, |edits| !edits.is_empty());
+                    let resolved_item;
+                    let item = if item.resolved || has_additional_edits {
+                        &item.item
+                    } else {
+                        resolved_item = Self::resolve_completion_item(doc, item.item.clone());
+                        resolved_item.as_ref().unwrap_or(&item.item)
+                    };
+
+                    // The edits of the item refer to the document before it is inserted.
+                    let text = doc.text().clone();
                     let transaction = item_to_transaction(
                         doc,
                         view.id,
@@ -259,35 +340,74 @@ fn completion_changes(transaction: &Transaction, trigger_offset: usize) -> Vec<C
                         changes: completion_changes(&transaction, trigger_offset),
                     });
 
-                    // apply additional edits, mostly used to auto import unqualified types
-                    let resolved_item = if item
+                    if let Some(additional_edits) = item
                         .additional_text_edits
                         .as_ref()
-                        .map(|edits| !edits.is_empty())
-                        .unwrap_or(false)
-                    {
-                        None
-                    } else {
-                        Self::resolve_completion_item(doc, item.clone())
-                    };
-
-                    if let Some(additional_edits) = resolved_item
-                        .as_ref()
-                        .and_then(|item| item.additional_text_edits.as_ref())
-                        .or(item.additional_text_edits.as_ref())
+                        .filter(|edits| !edits.is_empty())
                     {
-                        if !additional_edits.is_empty() {
-                            let transaction = util::generate_transaction_from_edits(
-                                doc.text(),
-                                additional_edits.clone(),
-                                offset_encoding, // TODO: should probably transcode in Client
-                            );
-                            doc.apply(&transaction, view.id);
-                        }
+                        let additional = util::generate_transaction_from_edits(
+                            &text,
+                            additional_edits.clone(),
+                            offset_encoding, // TODO: should probably transcode in Client
+                        );
+                        // Map the additional edits past the inserted completion. They may not
+                        // overlap with it, insertions next to it are placed before it.
+                        let changes = transaction.changes();
+                        let additional = Transaction::change(
+                            doc.text(),
+                            additional.changes_iter().map(|(from, to, text)| {
+                                let from_assoc = if from == to {
+                                    Assoc::Before
+                                } else {
+                                    Assoc::After
+                                };
+                                (
+                                    changes.map_pos(from, from_assoc),
+                                    changes.map_pos(to, Assoc::Before),
+                                    text,
+                                )
+                            }),
+                        );
+                        doc.apply(&additional, view.id);
                     }
                 }
             };
         });
+        let sort = editor.config().completion_sort.clone();
+        let distances = if sort.contains(&CompletionSort::Locality) {
+            let (view, doc) = current_ref!(editor);
+            word_distances(doc, view.id)
+        } else {
+            HashMap::new()
+        };
+        let distance = move |item: &CompletionItem| {
+            distances
+                .get(&item.item.label)
+                .copied()
+                .unwrap_or(usize::MAX)
+        };
+        let menu = menu.with_sort(move |(a, a_score), (b, b_score)| {
+            sort.iter()
+                .map(|criterion| match criterion {
+                    CompletionSort::Score => b_score.cmp(&a_score),
+                    CompletionSort::SortText => {
+                        let sort_text = |item: &CompletionItem| {
+                            item.item
+                                .sort_text
+                                .clone()
+                                .unwrap_or_else(|| item.item.label.clone())
+                        };
+                        sort_text(a).cmp(&sort_text(b))
+                    }
+                    CompletionSort::Kind => {
+                        kind_priority(a.item.kind).cmp(&kind_priority(b.item.kind))
+                    }
+                    CompletionSort::Locality => distance(a).cmp(&distance(b)),
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+
         let popup = Popup::new(Self::ID, menu)
             .with_scrollbar(false)
             .ignore_escape_key(true);
@@ -295,6 +415,7 @@ fn completion_changes(transaction: &Transaction, trigger_offset: usize) -> Vec<C
             popup,
             start_offset,
             trigger_offset,
+            is_incomplete,
         };
 
         // need to recompute immediately in case start_offset != trigger_offset
@@ -306,7 +427,7 @@ fn completion_changes(transaction: &Transaction, trigger_offset: usize) -> Vec<C
     fn resolve_completion_item(
         doc: &Document,
         completion_item: lsp::CompletionItem,
-    ) -> Option<CompletionItem> {
+    ) -> Option<lsp::CompletionItem> {
         let language_server = doc.language_server()?;
 
         let future = language_server.resolve_completion_item(completion_item)?;
@@ -355,11 +476,23 @@ pub fn update(&mut self, cx: &mut commands::Context) {
         self.recompute_filter(cx.editor)
     }
 
+    /// Accepts the only item of the completion. Returns false if there are several items.
+    pub fn accept_sole_item(&mut self, editor: &mut Editor) -> bool {
+        let menu = self.popup.contents_mut();
+        menu.select_sole().is_some() && menu.validate(editor)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.popup.contents().is_empty()
     }
 
-    fn replace_item(&mut self, old_item: lsp::CompletionItem, new_item: lsp::CompletionItem) {
+    /// Whether the items have to be requested again as the user keeps typing. Otherwise
+    /// they are only filtered.
+    pub fn is_incomplete(&self) -> bool {
+        self.is_incomplete
+    }
+
+    fn replace_item(&mut self, old_item: CompletionItem, new_item: CompletionItem) {
         self.popup.contents_mut().replace_option(old_item, new_item);
     }
 
@@ -375,7 +508,7 @@ pub fn ensure_item_resolved(&mut self, cx: &mut commands::Context) -> bool {
         // > The returned completion item should have the documentation property filled in.
         // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#textDocument_completion
         let current_item = match self.popup.contents().selection() {
-            Some(item) if item.documentation.is_none() => item.clone(),
+            Some(item) if !item.resolved => item.clone(),
             _ => return false,
         };
 
@@ -385,7 +518,7 @@ pub fn ensure_item_resolved(&mut self, cx: &mut commands::Context) -> bool {
         };
 
         // This method should not block the compositor so we handle the response asynchronously.
-        let future = match language_server.resolve_completion_item(current_item.clone()) {
+        let future = match language_server.resolve_completion_item(current_item.item.clone()) {
             Some(future) => future,
             None => return false,
         };
@@ -393,9 +526,10 @@ pub fn ensure_item_resolved(&mut self, cx: &mut commands::Context) -> bool {
         cx.callback(
             future,
             move |_editor, compositor, response: Option<lsp::CompletionItem>| {
-                let resolved_item = match response {
-                    Some(item) => item,
-                    None => return,
+                // Don't request the item again if the server couldn't resolve it.
+                let resolved_item = CompletionItem {
+                    item: response.unwrap_or_else(|| current_item.item.clone()),
+                    resolved: true,
                 };
 
                 if let Some(completion) = &mut compositor
@@ -430,7 +564,7 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
 
         // if we have a selection, render a markdown popup on top/below with info
         let option = match self.popup.contents().selection() {
-            Some(option) => option,
+            Some(option) => &option.item,
             None => return,
         };
         // need to render:
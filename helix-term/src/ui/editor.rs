@@ -6,7 +6,7 @@
     keymap::{KeymapResult, Keymaps},
     ui::{
         document::{render_document, LinePos, TextRenderer, TranslatedPosition},
-        Completion, ProgressSpinners,
+        Completion, CompletionItem, ProgressSpinners,
     },
 };
 
@@ -15,7 +15,9 @@
     graphemes::{
         ensure_grapheme_boundary_next_byte, next_grapheme_boundary, prev_grapheme_boundary,
     },
+    line_ending::line_end_char_index,
     movement::Direction,
+    pos_at_visual_coords,
     syntax::{self, HighlightEvent},
     text_annotations::TextAnnotations,
     unicode::width::UnicodeWidthStr,
@@ -23,7 +25,7 @@
 };
 use helix_view::{
     document::{Mode, SavePoint, SCRATCH_BUFFER_NAME},
-    editor::{CompleteAction, CursorShapeConfig},
+    editor::{CompleteAction, Config, CursorShapeConfig, IdleTask},
     graphics::{Color, CursorKind, Modifier, Rect, Style},
     input::{KeyEvent, MouseButton, MouseEvent, MouseEventKind},
     keyboard::{KeyCode, KeyModifiers},
@@ -139,6 +141,35 @@ pub fn render_view(
             highlights = Box::new(syntax::merge(highlights, diagnostic));
         }
 
+        if config.ruler_overflow {
+            let overflow = Self::doc_ruler_overflow_highlights(
+                doc,
+                view.offset.anchor,
+                inner.height,
+                &config.rulers,
+                theme,
+            );
+            if !overflow.is_empty() {
+                highlights = Box::new(syntax::merge(highlights, overflow));
+            }
+        }
+
+        let conflict_markers =
+            Self::doc_conflict_marker_highlights(doc, view.offset.anchor, inner.height, theme);
+        if !conflict_markers.is_empty() {
+            highlights = Box::new(syntax::merge(highlights, conflict_markers));
+        }
+
+        let links = Self::doc_link_highlights(doc, view.offset.anchor, inner.height, theme);
+        if !links.is_empty() {
+            highlights = Box::new(syntax::merge(highlights, links));
+        }
+
+        let document_highlights = Self::doc_document_highlights(doc, view, theme);
+        if !document_highlights.is_empty() {
+            highlights = Box::new(syntax::merge(highlights, document_highlights));
+        }
+
         let highlights: Box<dyn Iterator<Item = HighlightEvent>> = if is_focused {
             let highlights = syntax::merge(
                 highlights,
@@ -329,8 +360,8 @@ pub fn doc_syntax_highlights<'doc>(
     pub fn doc_diagnostics_highlights(
         doc: &Document,
         theme: &Theme,
-    ) -> [Vec<(usize, std::ops::Range<usize>)>; 5] {
-        use helix_core::diagnostic::Severity;
+    ) -> [Vec<(usize, std::ops::Range<usize>)>; 7] {
+        use helix_core::diagnostic::{DiagnosticTag, Severity};
         let get_scope_of = |scope| {
             theme
             .find_scope_index_exact(scope)
@@ -356,6 +387,30 @@ pub fn doc_diagnostics_highlights(
         let mut warning_vec = Vec::new();
         let mut error_vec = Vec::new();
 
+        // Tagged diagnostics are only styled if the theme defines a style for the tag.
+        let unnecessary = theme.find_scope_index_exact("diagnostic.unnecessary");
+        let deprecated = theme.find_scope_index_exact("diagnostic.deprecated");
+        let mut unnecessary_vec = Vec::new();
+        let mut deprecated_vec = Vec::new();
+
+        // If any diagnostic overlaps ranges with the prior diagnostic,
+        // merge the two together. Otherwise push a new span.
+        let push = |vec: &mut Vec<(usize, std::ops::Range<usize>)>,
+                    scope,
+                    diagnostic: &helix_core::Diagnostic| {
+            match vec.last_mut() {
+                Some((_, range)) if diagnostic.range.start <= range.end => {
+                    // This branch merges overlapping diagnostics, assuming that the current
+                    // diagnostic starts on range.start or later. If this assertion fails,
+                    // we will discard some part of `diagnostic`. This implies that
+                    // `doc.diagnostics()` is not sorted by `diagnostic.range`.
+                    debug_assert!(range.start <= diagnostic.range.start);
+                    range.end = diagnostic.range.end.max(range.end)
+                }
+                _ => vec.push((scope, diagnostic.range.start..diagnostic.range.end)),
+            }
+        };
+
         for diagnostic in doc.diagnostics() {
             // Separate diagnostics into different Vecs by severity.
             let (vec, scope) = match diagnostic.severity {
@@ -365,23 +420,170 @@ pub fn doc_diagnostics_highlights(
                 Some(Severity::Error) => (&mut error_vec, error),
                 _ => (&mut default_vec, r#default),
             };
+            push(vec, scope, diagnostic);
 
-            // If any diagnostic overlaps ranges with the prior diagnostic,
-            // merge the two together. Otherwise push a new span.
-            match vec.last_mut() {
-                Some((_, range)) if diagnostic.range.start <= range.end => {
-                    // This branch merges overlapping diagnostics, assuming that the current
-                    // diagnostic starts on range.start or later. If this assertion fails,
-                    // we will discard some part of `diagnostic`. This implies that
-                    // `doc.diagnostics()` is not sorted by `diagnostic.range`.
-                    debug_assert!(range.start <= diagnostic.range.start);
-                    range.end = diagnostic.range.end.max(range.end)
+            for tag in &diagnostic.tags {
+                match (tag, unnecessary, deprecated) {
+                    (DiagnosticTag::Unnecessary, Some(scope), _) => {
+                        push(&mut unnecessary_vec, scope, diagnostic)
+                    }
+                    (DiagnosticTag::Deprecated, _, Some(scope)) => {
+                        push(&mut deprecated_vec, scope, diagnostic)
+                    }
+                    _ => (),
                 }
-                _ => vec.push((scope, diagnostic.range.start..diagnostic.range.end)),
             }
         }
 
-        [default_vec, info_vec, hint_vec, warning_vec, error_vec]
+        [
+            default_vec,
+            unnecessary_vec,
+            deprecated_vec,
+            info_vec,
+            hint_vec,
+            warning_vec,
+            error_vec,
+        ]
+    }
+
+    /// Get highlight spans for git conflict markers (`<<<<<<<`, `|||||||`, `=======` and
+    /// `>>>>>>>`) on the lines visible in the view.
+    pub fn doc_conflict_marker_highlights(
+        doc: &Document,
+        anchor: usize,
+        height: u16,
+        theme: &Theme,
+    ) -> Vec<(usize, std::ops::Range<usize>)> {
+        let scope = match theme
+            .find_scope_index_exact("diff.conflict")
+            .or_else(|| theme.find_scope_index_exact("diff.delta"))
+        {
+            Some(scope) => scope,
+            None => return Vec::new(),
+        };
+
+        let text = doc.text().slice(..);
+        let start = text.char_to_line(anchor.min(text.len_chars()));
+        let end = (start + height as usize).min(text.len_lines());
+        (start..end)
+            .filter_map(|line| {
+                let line_start = text.line_to_char(line);
+                let line_end = line_end_char_index(&text, line);
+                let content = text.slice(line_start..line_end);
+                let marker = content.get_slice(..7)?;
+                let rest = content.slice(7..);
+                let is_marker = match marker.char(0) {
+                    '<' | '|' | '>' => rest.len_chars() == 0 || rest.char(0) == ' ',
+                    '=' => rest.len_chars() == 0,
+                    _ => false,
+                } && marker.chars().all(|ch| ch == marker.char(0));
+                is_marker.then_some((scope, line_start..line_end))
+            })
+            .collect()
+    }
+
+    /// Get highlight spans for the links in view, as reported by the language server or else
+    /// found in the text.
+    pub fn doc_link_highlights(
+        doc: &Document,
+        anchor: usize,
+        height: u16,
+        theme: &Theme,
+    ) -> Vec<(usize, std::ops::Range<usize>)> {
+        let scope = match theme.find_scope_index_exact("ui.link") {
+            Some(scope) => scope,
+            None => return Vec::new(),
+        };
+
+        let text = doc.text().slice(..);
+        let start_line = text.char_to_line(anchor.min(text.len_chars()));
+        let end_line = (start_line + height as usize).min(text.len_lines());
+        let mut spans: Vec<_> = match doc.document_links() {
+            Some(links) => {
+                let start = text.line_to_char(start_line);
+                let end = text.line_to_char(end_line);
+                links
+                    .iter()
+                    .filter(|link| link.range.start < end && link.range.end > start)
+                    .map(|link| (scope, link.range.clone()))
+                    .collect()
+            }
+            None => helix_core::link::find_links(text, start_line..end_line)
+                .into_iter()
+                .map(|link| (scope, link.range))
+                .collect(),
+        };
+        spans.sort_by_key(|(_, range)| range.start);
+        spans
+    }
+
+    /// Get highlight spans for the characters of the lines in view reaching past the first
+    /// ruler of the document.
+    pub fn doc_ruler_overflow_highlights(
+        doc: &Document,
+        anchor: usize,
+        height: u16,
+        editor_rulers: &[u16],
+        theme: &Theme,
+    ) -> Vec<(usize, std::ops::Range<usize>)> {
+        let scope = match theme
+            .find_scope_index_exact("ui.virtual.ruler.overflow")
+            .or_else(|| theme.find_scope_index_exact("ui.virtual.ruler"))
+        {
+            Some(scope) => scope,
+            None => return Vec::new(),
+        };
+        let rulers = doc
+            .language_config()
+            .and_then(|config| config.rulers.as_deref())
+            .unwrap_or(editor_rulers);
+        let column = match rulers.iter().min() {
+            Some(&ruler) => (ruler as usize).saturating_sub(1),
+            None => return Vec::new(),
+        };
+
+        let text = doc.text().slice(..);
+        let start = text.char_to_line(anchor.min(text.len_chars()));
+        let end = (start + height as usize).min(text.len_lines());
+        (start..end)
+            .filter_map(|line| {
+                let overflow_start =
+                    pos_at_visual_coords(text, Position::new(line, column), doc.tab_width());
+                let line_end = line_end_char_index(&text, line);
+                (overflow_start < line_end).then_some((scope, overflow_start..line_end))
+            })
+            .collect()
+    }
+
+    /// Get highlight spans for the occurrences of the symbol under the cursor reported by the
+    /// language server.
+    pub fn doc_document_highlights(
+        doc: &Document,
+        view: &View,
+        theme: &Theme,
+    ) -> Vec<(usize, std::ops::Range<usize>)> {
+        let highlights = match doc.document_highlights(view.id) {
+            Some(highlights) => highlights,
+            None => return Vec::new(),
+        };
+        let (read_scope, write_scope) = match (
+            theme.find_scope_index("ui.highlight.read"),
+            theme.find_scope_index("ui.highlight.write"),
+        ) {
+            (Some(read_scope), Some(write_scope)) => (read_scope, write_scope),
+            _ => return Vec::new(),
+        };
+
+        let mut spans: Vec<_> = highlights
+            .ranges
+            .iter()
+            .map(|(range, write)| {
+                let scope = if *write { write_scope } else { read_scope };
+                (scope, range.from()..range.to())
+            })
+            .collect();
+        spans.sort_by_key(|(_, range)| range.start);
+        spans
     }
 
     /// Get highlight spans for selections in a document view.
@@ -498,10 +700,13 @@ pub fn highlight_focused_view_elements(
             use helix_core::match_brackets;
             let pos = doc.selection(view.id).primary().cursor(text);
 
-            if let Some(pos) = match_brackets::find_matching_bracket(syntax, doc.text(), pos) {
+            let matching = match_brackets::find_matching_bracket(syntax, doc.text(), pos)
+                .map(|pos| pos..pos + 1)
+                .or_else(|| match_brackets::find_matching_delimiter(syntax, doc.text(), pos));
+            if let Some(range) = matching {
                 // ensure col is on screen
                 if let Some(highlight) = theme.find_scope_index_exact("ui.cursor.match") {
-                    return vec![(highlight, pos..pos + 1)];
+                    return vec![(highlight, range)];
                 }
             }
         }
@@ -797,7 +1002,27 @@ fn handle_keymap_event(
         cxt.editor.autoinfo = self.keymaps.sticky().map(|node| node.infobox());
 
         let mut execute_command = |command: &commands::MappableCommand| {
+            // Selections changed by commands outside of insert mode are remembered in the
+            // selection history of the view.
+            let remember_selection = last_mode != Mode::Insert
+                && !matches!(command.name(), "undo_selection" | "redo_selection");
+            let before = remember_selection.then(|| {
+                let (view, doc) = current_ref!(cxt.editor);
+                (view.id, doc.id(), doc.selection(view.id).clone())
+            });
+
             command.execute(cxt);
+
+            if let Some((view_id, doc_id, selection)) = before {
+                let editor = &mut *cxt.editor;
+                let changed = editor.tree.contains(view_id)
+                    && editor.tree.get(view_id).doc == doc_id
+                    && editor.documents[&doc_id].selection(view_id) != &selection;
+                if changed {
+                    let view = editor.tree.get_mut(view_id);
+                    view.selection_history.push(doc_id, selection);
+                }
+            }
             let current_mode = cxt.editor.mode();
             match (last_mode, current_mode) {
                 (Mode::Normal, Mode::Insert) => {
@@ -846,7 +1071,16 @@ fn handle_keymap_event(
     }
 
     fn insert_mode(&mut self, cx: &mut commands::Context, event: KeyEvent) {
-        if let Some(keyresult) = self.handle_keymap_event(Mode::Insert, cx, event) {
+        // Typed text isn't translated, only the keys of insert mode bindings like `C-w`.
+        let key = if event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            translate_key(&cx.editor.config(), event)
+        } else {
+            event
+        };
+        if let Some(keyresult) = self.handle_keymap_event(Mode::Insert, cx, key) {
             match keyresult {
                 KeymapResult::NotFound => {
                     if let Some(ch) = event.char() {
@@ -947,10 +1181,11 @@ pub fn set_completion(
         &mut self,
         editor: &mut Editor,
         savepoint: Arc<SavePoint>,
-        items: Vec<helix_lsp::lsp::CompletionItem>,
+        items: Vec<CompletionItem>,
         offset_encoding: helix_lsp::OffsetEncoding,
         start_offset: usize,
         trigger_offset: usize,
+        is_incomplete: bool,
         size: Rect,
     ) -> Option<Rect> {
         let mut completion = Completion::new(
@@ -960,6 +1195,7 @@ pub fn set_completion(
             offset_encoding,
             start_offset,
             trigger_offset,
+            is_incomplete,
         );
 
         if completion.is_empty() {
@@ -977,6 +1213,18 @@ pub fn set_completion(
         Some(area)
     }
 
+    /// Accepts the completion if it only has one item. Returns whether it was accepted.
+    pub fn accept_sole_completion(&mut self, editor: &mut Editor) -> bool {
+        let accepted = self
+            .completion
+            .as_mut()
+            .map_or(false, |completion| completion.accept_sole_item(editor));
+        if accepted {
+            self.clear_completion(editor);
+        }
+        accepted
+    }
+
     pub fn clear_completion(&mut self, editor: &mut Editor) {
         self.completion = None;
 
@@ -985,9 +1233,39 @@ pub fn clear_completion(&mut self, editor: &mut Editor) {
     }
 
     pub fn handle_idle_timeout(&mut self, cx: &mut commands::Context) -> EventResult {
-        commands::compute_inlay_hints_for_all_views(cx.editor, cx.jobs);
+        if cx.editor.idle_task_due(IdleTask::InlayHints) {
+            commands::compute_inlay_hints_for_all_views(cx.editor, cx.jobs);
+        }
+        if cx.editor.idle_task_due(IdleTask::LanguageServers) {
+            cx.editor.start_idle_language_servers();
+        }
+        if cx.editor.idle_task_due(IdleTask::DocumentHighlight) {
+            commands::compute_document_highlights(cx.editor, cx.jobs);
+        }
+        if cx.editor.idle_task_due(IdleTask::DocumentLinks) {
+            commands::compute_document_links(cx.editor, cx.jobs);
+        }
+        // Saving would interrupt the edits of insert mode, which are committed when leaving it.
+        if cx.editor.idle_task_due(IdleTask::AutoSave) && cx.editor.mode != Mode::Insert {
+            let mut cx = crate::compositor::Context {
+                editor: cx.editor,
+                jobs: cx.jobs,
+                scroll: None,
+            };
+            if let Err(e) = commands::typed::write_all_impl(&mut cx, false, false) {
+                cx.editor.set_error(format!("{}", e));
+            }
+        }
+        if !cx.editor.idle_task_due(IdleTask::Completion) {
+            return EventResult::Ignored(None);
+        }
 
         if let Some(completion) = &mut self.completion {
+            // Request the items again for the text typed since they were received.
+            if completion.is_incomplete() && cx.editor.mode == Mode::Insert {
+                commands::completion_impl(cx, commands::CompletionInvoked::Automatic);
+                return EventResult::Consumed(None);
+            }
             return if completion.ensure_item_resolved(cx) {
                 EventResult::Consumed(None)
             } else {
@@ -1231,6 +1509,9 @@ fn handle_event(
                     doc.append_changes_to_history(view);
                 }
 
+                // Large pastes wait for a confirmation key.
+                self.on_next_key = cx.on_next_key_callback.take();
+
                 EventResult::Consumed(None)
             }
             Event::Resize(_width, _height) => {
@@ -1291,6 +1572,7 @@ fn handle_event(
                                 }
 
                                 self.insert_mode(&mut cx, key);
+                                commands::sync_linked_editing_ranges(&mut cx);
 
                                 // record last_insert key
                                 self.last_insert.1.push(InsertEvent::Key(key));
@@ -1304,7 +1586,10 @@ fn handle_event(
                                 }
                             }
                         }
-                        mode => self.command_mode(mode, &mut cx, key),
+                        mode => {
+                            let key = translate_key(&cx.editor.config(), key);
+                            self.command_mode(mode, &mut cx, key)
+                        }
                     }
                 }
 
@@ -1460,6 +1745,12 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
 
     fn cursor(&self, _area: Rect, editor: &Editor) -> (Option<Position>, CursorKind) {
         match editor.cursor() {
+            // Input methods draw at the terminal cursor, which they need visible.
+            cursor @ (_, CursorKind::Block)
+                if editor.mode == Mode::Insert && editor.config().ime_cursor =>
+            {
+                cursor
+            }
             // All block cursors are drawn manually
             (pos, CursorKind::Block) => (pos, CursorKind::Hidden),
             cursor => cursor,
@@ -1467,6 +1758,16 @@ fn cursor(&self, _area: Rect, editor: &Editor) -> (Option<Position>, CursorKind)
     }
 }
 
+/// Translates the character of `key` typed with the configured keyboard layout and key
+/// remappings to the character key bindings are looked up with.
+fn translate_key(config: &Config, mut key: KeyEvent) -> KeyEvent {
+    if let KeyCode::Char(ch) = key.code {
+        let ch = config.keyboard_layout.translate(ch);
+        key.code = KeyCode::Char(config.key_remap.get(&ch).copied().unwrap_or(ch));
+    }
+    key
+}
+
 fn canonicalize_key(key: &mut KeyEvent) {
     if let KeyEvent {
         code: KeyCode::Char(_),
@@ -0,0 +1,49 @@
+use crate::compositor::{Component, Context};
+use tui::buffer::Buffer as Surface;
+
+use helix_view::graphics::{Image, Rect};
+
+/// Displays an image, or a description of it in terminals which can't display images.
+pub struct ImageView {
+    image: Image,
+    supported: bool,
+    placeholder: String,
+}
+
+impl ImageView {
+    pub fn new(image: Image, supported: bool) -> Self {
+        let placeholder = format!("<Image {}×{}>", image.width, image.height);
+        Self {
+            image,
+            supported,
+            placeholder,
+        }
+    }
+}
+
+impl Component for ImageView {
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        if self.supported {
+            surface.set_image(self.image.fit(area), self.image.clone());
+        } else {
+            let style = cx.editor.theme.get("ui.text");
+            surface.set_stringn(
+                area.x,
+                area.y,
+                &self.placeholder,
+                area.width as usize,
+                style,
+            );
+        }
+    }
+
+    fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
+        if self.supported {
+            let area = self.image.fit(Rect::new(0, 0, viewport.0, viewport.1));
+            Some((area.width, area.height))
+        } else {
+            let width = self.placeholder.chars().count() as u16;
+            Some((width.min(viewport.0), 1))
+        }
+    }
+}
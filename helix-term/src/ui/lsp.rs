@@ -1,15 +1,25 @@
-use std::sync::Arc;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
-use helix_core::syntax;
-use helix_view::graphics::{Margin, Rect, Style};
+use helix_core::{path, syntax, text_annotations::TextAnnotations};
+use helix_lsp::{lsp, util::lsp_range_to_range, OffsetEncoding};
+use helix_view::{
+    editor::Action,
+    graphics::{Margin, Rect, Style},
+    view::ViewPosition,
+    Document, Editor,
+};
 use tui::buffer::Buffer;
 use tui::widgets::{BorderType, Paragraph, Widget, Wrap};
 
-use crate::compositor::{Component, Compositor, Context};
+use crate::compositor::{Callback, Component, Compositor, Context, Event, EventResult};
+use crate::{ctrl, key, shift};
 
 use crate::ui::Markdown;
 
-use super::Popup;
+use super::{
+    document::{render_document, LineDecoration, LinePos, TextRenderer},
+    EditorView, Popup,
+};
 
 pub struct SignatureHelp {
     signature: String,
@@ -135,3 +145,189 @@ fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
         Some((width + PADDING, height + PADDING))
     }
 }
+
+/// An embedded read-only view of the locations returned by a goto request, shown without
+/// leaving the current buffer.
+pub struct Peek {
+    locations: Vec<lsp::Location>,
+    index: usize,
+    offset_encoding: OffsetEncoding,
+    /// Documents which aren't open in the editor, loaded the first time they are peeked at.
+    documents: HashMap<PathBuf, Option<Document>>,
+}
+
+impl Peek {
+    pub const ID: &'static str = "peek";
+    const HEIGHT: u16 = 16;
+
+    pub fn new(
+        editor: &Editor,
+        locations: Vec<lsp::Location>,
+        offset_encoding: OffsetEncoding,
+    ) -> Self {
+        let mut peek = Self {
+            locations,
+            index: 0,
+            offset_encoding,
+            documents: HashMap::new(),
+        };
+        peek.load_document(editor);
+        peek
+    }
+
+    /// Loads the document of the current location, unless it is open in the editor or was
+    /// loaded already.
+    fn load_document(&mut self, editor: &Editor) {
+        let path = match self.locations[self.index].uri.to_file_path() {
+            Ok(path) => path,
+            Err(_) => return,
+        };
+        if editor.document_by_path(&path).is_some() || self.documents.contains_key(&path) {
+            return;
+        }
+        let loader = editor.syn_loader.clone();
+        let doc = Document::open(&path, None, Some(loader), editor.config.clone()).ok();
+        self.documents.insert(path, doc);
+    }
+
+    fn cycle(&mut self, editor: &Editor, forward: bool) {
+        let len = self.locations.len();
+        self.index = if forward {
+            (self.index + 1) % len
+        } else {
+            (self.index + len - 1) % len
+        };
+        self.load_document(editor);
+    }
+
+    /// Opens the peeked location in a real view, closing the peek.
+    fn promote(&self, cx: &mut Context, action: Action) -> EventResult {
+        let location = &self.locations[self.index];
+        crate::commands::jump_to_location(cx.editor, location, self.offset_encoding, action);
+        let close_fn: Callback = Box::new(|compositor, _| {
+            compositor.remove(Self::ID);
+        });
+        EventResult::Consumed(Some(close_fn))
+    }
+}
+
+impl Component for Peek {
+    fn render(&mut self, area: Rect, surface: &mut Buffer, cx: &mut Context) {
+        let text_style = cx.editor.theme.get("ui.text");
+        let location = &self.locations[self.index];
+        let path = match location.uri.to_file_path() {
+            Ok(path) => path,
+            Err(_) => {
+                surface.set_stringn(
+                    area.x,
+                    area.y,
+                    location.uri.as_str(),
+                    area.width as usize,
+                    text_style,
+                );
+                return;
+            }
+        };
+
+        let title = format!(
+            "{}:{} ({}/{})",
+            path::get_relative_path(&path).display(),
+            location.range.start.line + 1,
+            self.index + 1,
+            self.locations.len()
+        );
+        let title_style = cx.editor.theme.get("ui.text.focus");
+        surface.set_stringn(area.x, area.y, title, area.width as usize, title_style);
+
+        let doc = match cx
+            .editor
+            .document_by_path(&path)
+            .or_else(|| self.documents.get(&path)?.as_ref())
+        {
+            Some(doc) => doc,
+            None => return,
+        };
+
+        let inner = area.clip_top(1);
+        let (start, end) =
+            match lsp_range_to_range(doc.text(), location.range, self.offset_encoding) {
+                Some(range) => (
+                    doc.text().char_to_line(range.from()),
+                    doc.text().char_to_line(range.to()),
+                ),
+                None => (0, 0),
+            };
+        // show the location in the upper third of the view
+        let first_line = start.saturating_sub(inner.height as usize / 3);
+        let offset = ViewPosition {
+            anchor: doc.text().line_to_char(first_line),
+            horizontal_offset: 0,
+            vertical_offset: 0,
+        };
+
+        let mut highlights =
+            EditorView::doc_syntax_highlights(doc, offset.anchor, inner.height, &cx.editor.theme);
+        for spans in EditorView::doc_diagnostics_highlights(doc, &cx.editor.theme) {
+            if spans.is_empty() {
+                continue;
+            }
+            highlights = Box::new(helix_core::syntax::merge(highlights, spans));
+        }
+
+        let style = cx
+            .editor
+            .theme
+            .try_get("ui.highlight")
+            .unwrap_or_else(|| cx.editor.theme.get("ui.selection"));
+        let draw_highlight = move |renderer: &mut TextRenderer, pos: LinePos| {
+            if (start..=end).contains(&pos.doc_line) {
+                let area = Rect::new(
+                    renderer.viewport.x,
+                    renderer.viewport.y + pos.visual_line,
+                    renderer.viewport.width,
+                    1,
+                );
+                renderer.surface.set_style(area, style)
+            }
+        };
+        let mut decorations: Vec<Box<dyn LineDecoration>> = vec![Box::new(draw_highlight)];
+
+        render_document(
+            surface,
+            inner,
+            doc,
+            offset,
+            &TextAnnotations::default(),
+            highlights,
+            &cx.editor.theme,
+            &mut decorations,
+            &mut [],
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let key = match event {
+            Event::Key(key) => *key,
+            _ => return EventResult::Ignored(None),
+        };
+
+        match key {
+            key!(Tab) | key!('n') | ctrl!('n') => {
+                self.cycle(cx.editor, true);
+                EventResult::Consumed(None)
+            }
+            shift!(Tab) | key!('N') | ctrl!('p') => {
+                self.cycle(cx.editor, false);
+                EventResult::Consumed(None)
+            }
+            key!(Enter) => self.promote(cx, Action::Replace),
+            ctrl!('s') => self.promote(cx, Action::HorizontalSplit),
+            ctrl!('v') => self.promote(cx, Action::VerticalSplit),
+            _ => EventResult::Ignored(None),
+        }
+    }
+
+    fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
+        Some((viewport.0, Self::HEIGHT.min(viewport.1)))
+    }
+}
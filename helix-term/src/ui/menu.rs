@@ -1,4 +1,4 @@
-use std::{borrow::Cow, path::PathBuf};
+use std::{borrow::Cow, cmp::Ordering, path::PathBuf};
 
 use crate::{
     compositor::{Callback, Component, Compositor, Context, Event, EventResult},
@@ -29,6 +29,12 @@ fn filter_text(&self, data: &Self::Data) -> Cow<str> {
         let label: String = self.format(data).cell_text().collect();
         label.into()
     }
+
+    /// Added to the score of every fuzzy match of this item in a [`Picker`](super::Picker).
+    /// Used to rank some items above others that match equally well.
+    fn score_bonus(&self, _data: &Self::Data) -> i64 {
+        0
+    }
 }
 
 impl Item for PathBuf {
@@ -43,7 +49,17 @@ fn format(&self, root_path: &Self::Data) -> Row {
     }
 }
 
+impl Item for String {
+    type Data = ();
+
+    fn format(&self, _data: &Self::Data) -> Row {
+        self.as_str().into()
+    }
+}
+
 pub type MenuCallback<T> = Box<dyn Fn(&mut Editor, Option<&T>, MenuEvent)>;
+/// Compares two matching options along with their fuzzy match scores.
+pub type MenuSortFn<T> = Box<dyn Fn((&T, i64), (&T, i64)) -> Ordering>;
 
 pub struct Menu<T: Item> {
     options: Vec<T>,
@@ -58,6 +74,7 @@ pub struct Menu<T: Item> {
     widths: Vec<Constraint>,
 
     callback_fn: MenuCallback<T>,
+    sort_fn: Option<MenuSortFn<T>>,
 
     scroll: usize,
     size: (u16, u16),
@@ -84,6 +101,7 @@ pub fn new(
             cursor: None,
             widths: Vec::new(),
             callback_fn: Box::new(callback_fn),
+            sort_fn: None,
             scroll: 0,
             size: (0, 0),
             viewport: (0, 0),
@@ -91,6 +109,15 @@ pub fn new(
         }
     }
 
+    /// Sorts the matching options with `sort_fn` instead of by their score.
+    pub fn with_sort(
+        mut self,
+        sort_fn: impl Fn((&T, i64), (&T, i64)) -> Ordering + 'static,
+    ) -> Self {
+        self.sort_fn = Some(Box::new(sort_fn));
+        self
+    }
+
     pub fn score(&mut self, pattern: &str) {
         // reuse the matches allocation
         self.matches.clear();
@@ -107,7 +134,15 @@ pub fn score(&mut self, pattern: &str) {
                 }),
         );
         // Order of equal elements needs to be preserved as LSP preselected items come in order of high to low priority
-        self.matches.sort_by_key(|(_, score)| -score);
+        match &self.sort_fn {
+            Some(sort_fn) => {
+                let options = &self.options;
+                self.matches.sort_by(|(a, a_score), (b, b_score)| {
+                    sort_fn((&options[*a], *a_score), (&options[*b], *b_score))
+                })
+            }
+            None => self.matches.sort_by_key(|(_, score)| -score),
+        }
 
         // reset cursor position
         self.cursor = None;
@@ -213,6 +248,27 @@ pub fn selection_mut(&mut self) -> Option<&mut T> {
         })
     }
 
+    /// Accepts the selected option as if enter was pressed. Returns whether an option was
+    /// selected.
+    pub fn validate(&self, editor: &mut Editor) -> bool {
+        match self.selection() {
+            Some(selection) => {
+                (self.callback_fn)(editor, Some(selection), MenuEvent::Validate);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Selects the only matching option, returning it. Returns `None` if there are several.
+    pub fn select_sole(&mut self) -> Option<&T> {
+        if self.matches.len() != 1 {
+            return None;
+        }
+        self.cursor = Some(0);
+        self.selection()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.matches.is_empty()
     }
@@ -386,3 +442,28 @@ const fn div_ceil(a: usize, b: usize) -> usize {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn menu(options: &[&str]) -> Menu<String> {
+        let options = options.iter().map(|option| option.to_string()).collect();
+        Menu::new(options, (), |_, _, _| {})
+    }
+
+    #[test]
+    fn select_sole() {
+        let mut single = menu(&["foo"]);
+        assert_eq!(single.selection(), None);
+        assert_eq!(single.select_sole().map(String::as_str), Some("foo"));
+        assert_eq!(single.selection().map(String::as_str), Some("foo"));
+
+        let mut several = menu(&["foo", "bar"]);
+        assert_eq!(several.select_sole(), None);
+        assert_eq!(several.selection(), None);
+
+        several.score("ba");
+        assert_eq!(several.select_sole().map(String::as_str), Some("bar"));
+    }
+}
@@ -2,6 +2,7 @@
 mod document;
 pub(crate) mod editor;
 mod fuzzy_match;
+mod image;
 mod info;
 pub mod lsp;
 mod markdown;
@@ -15,10 +16,11 @@
 mod text;
 
 use crate::compositor::{Component, Compositor};
-use crate::filter_picker_entry;
 use crate::job::{self, Callback};
-pub use completion::Completion;
+use crate::{extra_walk_roots, filter_picker_entry};
+pub use completion::{Completion, CompletionItem};
 pub use editor::EditorView;
+pub use image::ImageView;
 pub use markdown::Markdown;
 pub use menu::Menu;
 pub use picker::{DynamicPicker, FileLocation, FilePicker, Picker};
@@ -108,6 +110,7 @@ pub fn regex_prompt(
 
                             if event == PromptEvent::Validate {
                                 // Equivalent to push_jump to store selection just before jump
+                                cx.editor.global_jumps.push(doc, &snapshot);
                                 view.jumps.push((doc_id, snapshot.clone()));
                             }
 
@@ -159,15 +162,29 @@ pub fn regex_prompt(
 }
 
 pub fn file_picker(root: PathBuf, config: &helix_view::editor::Config) -> FilePicker<PathBuf> {
+    workspace_file_picker(root, &[], config)
+}
+
+/// A file picker over `root` and the roots added to the workspace.
+pub fn workspace_file_picker(
+    root: PathBuf,
+    workspace_roots: &[PathBuf],
+    config: &helix_view::editor::Config,
+) -> FilePicker<PathBuf> {
     use ignore::{types::TypesBuilder, WalkBuilder};
     use std::time::Instant;
 
     let now = Instant::now();
 
     let dedup_symlinks = config.file_picker.deduplicate_links;
-    let absolute_root = root.canonicalize().unwrap_or_else(|_| root.clone());
+    let extra_roots = extra_walk_roots(&root, workspace_roots);
+    let mut absolute_roots = vec![root.canonicalize().unwrap_or_else(|_| root.clone())];
+    absolute_roots.extend(extra_roots.iter().map(|root| root.to_path_buf()));
 
     let mut walk_builder = WalkBuilder::new(&root);
+    for root in extra_roots {
+        walk_builder.add(root);
+    }
     walk_builder
         .hidden(config.file_picker.hidden)
         .parents(config.file_picker.parents)
@@ -177,7 +194,11 @@ pub fn file_picker(root: PathBuf, config: &helix_view::editor::Config) -> FilePi
         .git_global(config.file_picker.git_global)
         .git_exclude(config.file_picker.git_exclude)
         .max_depth(config.file_picker.max_depth)
-        .filter_entry(move |entry| filter_picker_entry(entry, &absolute_root, dedup_symlinks));
+        .filter_entry(move |entry| {
+            absolute_roots
+                .iter()
+                .all(|root| filter_picker_entry(entry, root, dedup_symlinks))
+        });
 
     // We want to exclude files that the editor can't handle yet
     let mut type_builder = TypesBuilder::new();
@@ -239,7 +260,6 @@ pub mod completers {
     use fuzzy_matcher::skim::SkimMatcherV2 as Matcher;
     use fuzzy_matcher::FuzzyMatcher;
     use helix_view::document::SCRATCH_BUFFER_NAME;
-    use helix_view::theme;
     use helix_view::{editor::Config, Editor};
     use once_cell::sync::Lazy;
     use std::borrow::Cow;
@@ -279,17 +299,33 @@ pub fn buffer(editor: &Editor, input: &str) -> Vec<Completion> {
         names
     }
 
-    pub fn theme(_editor: &Editor, input: &str) -> Vec<Completion> {
-        let mut names = theme::Loader::read_names(&helix_loader::config_dir().join("themes"));
-        for rt_dir in helix_loader::runtime_dirs() {
-            names.extend(theme::Loader::read_names(&rt_dir.join("themes")));
-        }
-        names.push("default".into());
-        names.push("base16_default".into());
-        names.sort();
-        names.dedup();
+    pub fn theme_scope(editor: &Editor, input: &str) -> Vec<Completion> {
+        let matcher = Matcher::default();
+
+        let mut matches: Vec<_> = editor
+            .theme
+            .scopes()
+            .iter()
+            .filter_map(|scope| {
+                matcher
+                    .fuzzy_match(scope, input)
+                    .map(|score| (scope, score))
+            })
+            .collect();
+
+        matches.sort_unstable_by(|(name1, score1), (name2, score2)| {
+            (Reverse(*score1), name1).cmp(&(Reverse(*score2), name2))
+        });
+        matches
+            .into_iter()
+            .map(|(name, _)| ((0..), name.clone().into()))
+            .collect()
+    }
 
-        let mut names: Vec<_> = names
+    pub fn theme(editor: &Editor, input: &str) -> Vec<Completion> {
+        let mut names: Vec<_> = editor
+            .theme_loader
+            .names()
             .into_iter()
             .map(|name| ((0..), Cow::from(name)))
             .collect();
@@ -311,6 +347,22 @@ pub fn theme(_editor: &Editor, input: &str) -> Vec<Completion> {
         names
     }
 
+    pub fn help_topic(_editor: &Editor, input: &str) -> Vec<Completion> {
+        let matcher = Matcher::default();
+
+        let mut matches: Vec<_> = crate::help::topic_names()
+            .filter_map(|name| matcher.fuzzy_match(name, input).map(|score| (name, score)))
+            .collect();
+
+        matches.sort_unstable_by(|(name1, score1), (name2, score2)| {
+            (Reverse(*score1), name1).cmp(&(Reverse(*score2), name2))
+        });
+        matches
+            .into_iter()
+            .map(|(name, _)| ((0..), name.into()))
+            .collect()
+    }
+
     /// Recursive function to get all keys from this value and add them to vec
     fn get_keys(value: &serde_json::Value, vec: &mut Vec<String>, scope: Option<&str>) {
         if let Some(map) = value.as_object() {
@@ -462,8 +514,9 @@ fn filename_impl<F>(_editor: &Editor, input: &str, filter_fn: F) -> Vec<Completi
         use ignore::WalkBuilder;
         use std::path::Path;
 
-        let is_tilde = input == "~";
-        let path = helix_core::path::expand_tilde(Path::new(input));
+        let path = helix_core::path::expand(Path::new(input));
+        // A lone `~` or environment variable expands to an absolute path which replaces it.
+        let is_tilde = !input.contains(std::path::MAIN_SEPARATOR) && path != Path::new(input);
 
         let (dir, file_name) = if input.ends_with(std::path::MAIN_SEPARATOR) {
             (path, None)
@@ -545,7 +598,11 @@ fn filename_impl<F>(_editor: &Editor, input: &str, filter_fn: F) -> Vec<Completi
                 })
                 .collect();
 
-            let range = (input.len().saturating_sub(file_name.len()))..;
+            let range = if is_tilde {
+                0..
+            } else {
+                (input.len().saturating_sub(file_name.len()))..
+            };
 
             matches.sort_unstable_by(|(file1, score1), (file2, score2)| {
                 (Reverse(*score1), file1).cmp(&(Reverse(*score2), file2))
@@ -10,6 +10,7 @@
     },
 };
 use futures_util::future::BoxFuture;
+use tokio::sync::mpsc::{error::TryRecvError, UnboundedReceiver};
 use tui::{
     buffer::Buffer as Surface,
     layout::Constraint,
@@ -21,6 +22,7 @@
 use tui::widgets::Widget;
 
 use std::cmp::{self, Ordering};
+use std::time::{Duration, Instant};
 use std::{collections::HashMap, io::Read, path::PathBuf};
 
 use crate::ui::{Prompt, PromptEvent};
@@ -30,7 +32,7 @@
 };
 use helix_view::{
     editor::Action,
-    graphics::{CursorKind, Margin, Modifier, Rect},
+    graphics::{CursorKind, Image, Margin, Modifier, Rect},
     theme::Style,
     view::ViewPosition,
     Document, DocumentId, Editor,
@@ -87,6 +89,7 @@ pub struct FilePicker<T: Item> {
 
 pub enum CachedPreview {
     Document(Box<Document>),
+    Image(Image),
     Binary,
     LargeFile,
     NotFound,
@@ -114,6 +117,7 @@ fn placeholder(&self) -> &str {
             Self::EditorDocument(_) => "<File preview>",
             Self::Cached(preview) => match preview {
                 CachedPreview::Document(_) => "<File preview>",
+                CachedPreview::Image(_) => "<Image file>",
                 CachedPreview::Binary => "<Binary file>",
                 CachedPreview::LargeFile => "<File too large to preview>",
                 CachedPreview::NotFound => "<File not found>",
@@ -148,6 +152,12 @@ pub fn truncate_start(mut self, truncate_start: bool) -> Self {
         self
     }
 
+    /// See [`Picker::with_option_stream`].
+    pub fn with_option_stream(mut self, options: UnboundedReceiver<T>) -> Self {
+        self.picker = self.picker.with_option_stream(options);
+        self
+    }
+
     fn current_file(&self, editor: &Editor) -> Option<FileLocation> {
         self.picker
             .selection()
@@ -178,12 +188,22 @@ fn get_preview<'picker, 'editor>(
                     // Read up to 1kb to detect the content type
                     let n = file.take(1024).read_to_end(&mut self.read_buffer)?;
                     let content_type = content_inspector::inspect(&self.read_buffer[..n]);
+                    let is_png = self.read_buffer.starts_with(b"\x89PNG");
                     self.read_buffer.clear();
-                    Ok((metadata, content_type))
+                    Ok((metadata, content_type, is_png))
                 });
                 let preview = data
                     .map(
-                        |(metadata, content_type)| match (metadata.len(), content_type) {
+                        |(metadata, content_type, is_png)| match (metadata.len(), content_type) {
+                            (size, content_inspector::ContentType::BINARY)
+                                if is_png && size <= MAX_FILE_SIZE_FOR_PREVIEW =>
+                            {
+                                std::fs::read(path)
+                                    .ok()
+                                    .and_then(Image::from_png)
+                                    .map(CachedPreview::Image)
+                                    .unwrap_or(CachedPreview::Binary)
+                            }
                             (_, content_inspector::ContentType::BINARY) => CachedPreview::Binary,
                             (size, _) if size > MAX_FILE_SIZE_FOR_PREVIEW => {
                                 CachedPreview::LargeFile
@@ -276,7 +296,14 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
         block.render(preview_area, surface);
 
         if let Some((path, range)) = self.current_file(cx.editor) {
+            let supports_images = cx.editor.supports_images();
             let preview = self.get_preview(path, cx.editor);
+            if let Preview::Cached(CachedPreview::Image(image)) = &preview {
+                if supports_images {
+                    surface.set_image(image.fit(inner), image.clone());
+                    return;
+                }
+            }
             let doc = match preview.document() {
                 Some(doc) => doc,
                 None => {
@@ -375,7 +402,7 @@ fn required_size(&mut self, (width, height): (u16, u16)) -> Option<(u16, u16)> {
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
 struct PickerMatch {
     score: i64,
     index: usize,
@@ -400,14 +427,41 @@ fn cmp(&self, other: &Self) -> Ordering {
     }
 }
 
+/// The matches of an earlier pattern, kept while the current pattern refines it.
+struct Refinement {
+    pattern: String,
+    matches: Vec<PickerMatch>,
+    unscored: Vec<PickerMatch>,
+    scored: usize,
+}
+
+/// How long the picker scores options at once before showing the matches so far, so that
+/// scoring huge option lists doesn't block input.
+const SCORE_BUDGET: Duration = Duration::from_millis(20);
+/// Number of options scored between checks of the budget.
+const SCORE_BATCH_SIZE: usize = 1024;
+
 type PickerCallback<T> = Box<dyn Fn(&mut Context, &T, Action)>;
+type SelectionChangeCallback<T> = Box<dyn Fn(&mut Context, &T)>;
+type AbortCallback = Box<dyn Fn(&mut Context)>;
 
 pub struct Picker<T: Item> {
     options: Vec<T>,
     editor_data: T::Data,
     // filter: String,
     matcher: Box<Matcher>,
+    /// Sorted matches of the options scored so far against the current pattern.
     matches: Vec<PickerMatch>,
+    /// Matches of a previous pattern the current one refines, yet to be scored again.
+    unscored: Vec<PickerMatch>,
+    /// Number of options scored against the current pattern (or the one it refines). Options
+    /// past it, such as the ones added by [`Picker::push_options`], are yet to be scored.
+    scored: usize,
+    /// The matches of the shorter patterns the current one refines, restored when the pattern
+    /// shrinks back to one of them.
+    refinements: Vec<Refinement>,
+    /// Source options are streamed in from, drained whenever the picker is rendered.
+    option_stream: Option<UnboundedReceiver<T>>,
 
     /// Current height of the completions box
     completion_height: u16,
@@ -424,6 +478,10 @@ pub struct Picker<T: Item> {
     widths: Vec<Constraint>,
 
     callback_fn: PickerCallback<T>,
+    /// Called whenever a different option is selected
+    selection_change_fn: Option<SelectionChangeCallback<T>>,
+    /// Called when the picker is closed without picking an option
+    abort_fn: Option<AbortCallback>,
 }
 
 impl<T: Item> Picker<T> {
@@ -444,60 +502,123 @@ pub fn new(
             editor_data,
             matcher: Box::default(),
             matches: Vec::new(),
+            unscored: Vec::new(),
+            scored: 0,
+            refinements: Vec::new(),
+            option_stream: None,
             cursor: 0,
             prompt,
             previous_pattern: (String::new(), FuzzyQuery::default()),
             truncate_start: true,
             show_preview: true,
             callback_fn: Box::new(callback_fn),
+            selection_change_fn: None,
+            abort_fn: None,
             completion_height: 0,
             widths: Vec::new(),
         };
 
-        picker.calculate_column_widths();
+        picker.calculate_column_widths(0);
+        picker.score_pending();
 
-        // scoring on empty input
-        // TODO: just reuse score()
         picker
-            .matches
-            .extend(picker.options.iter().enumerate().map(|(index, option)| {
-                let text = option.filter_text(&picker.editor_data);
-                PickerMatch {
-                    index,
-                    score: 0,
-                    len: text.chars().count(),
-                }
-            }));
+    }
 
-        picker
+    /// Calls `f` with the newly selected option whenever the selection changes,
+    /// for example to preview it.
+    pub fn on_selection_change(mut self, f: impl Fn(&mut Context, &T) + 'static) -> Self {
+        self.selection_change_fn = Some(Box::new(f));
+        self
+    }
+
+    /// Calls `f` when the picker is closed without picking an option.
+    pub fn on_abort(mut self, f: impl Fn(&mut Context) + 'static) -> Self {
+        self.abort_fn = Some(Box::new(f));
+        self
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        self.matches.get(self.cursor).map(|pmatch| pmatch.index)
     }
 
     pub fn set_options(&mut self, new_options: Vec<T>) {
         self.options = new_options;
         self.cursor = 0;
         self.force_score();
-        self.calculate_column_widths();
+        self.calculate_column_widths(0);
+    }
+
+    /// Adds options to the picker, for sources that stream them in. Only the new options are
+    /// scored, and the selected option stays selected.
+    pub fn push_options(&mut self, new_options: impl IntoIterator<Item = T>) {
+        let start = self.options.len();
+        self.options.extend(new_options);
+        self.calculate_column_widths(start);
+        self.score_pending();
+    }
+
+    /// Streams options in as they are sent on `options`, for sources that take a while to
+    /// produce them. The senders should notify the redraw handle of the editor so the picker
+    /// is rendered with the new options.
+    pub fn with_option_stream(mut self, options: UnboundedReceiver<T>) -> Self {
+        self.option_stream = Some(options);
+        self
+    }
+
+    /// Adds the options streamed in since the picker was last rendered.
+    fn drain_option_stream(&mut self) {
+        let stream = match &mut self.option_stream {
+            Some(stream) => stream,
+            None => return,
+        };
+        let mut new_options = Vec::new();
+        let closed = loop {
+            match stream.try_recv() {
+                Ok(option) => new_options.push(option),
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => break true,
+            }
+        };
+        if closed {
+            self.option_stream = None;
+        }
+        if !new_options.is_empty() {
+            self.push_options(new_options);
+        }
     }
 
     /// Calculate the width constraints using the maximum widths of each column
-    /// for the current options.
-    fn calculate_column_widths(&mut self) {
+    /// for the options from `start` on, widening the current ones.
+    fn calculate_column_widths(&mut self, start: usize) {
         let n = self
             .options
             .first()
             .map(|option| option.format(&self.editor_data).cells.len())
             .unwrap_or_default();
-        let max_lens = self.options.iter().fold(vec![0; n], |mut acc, option| {
-            let row = option.format(&self.editor_data);
-            // maintain max for each column
-            for (acc, cell) in acc.iter_mut().zip(row.cells.iter()) {
-                let width = cell.content.width();
-                if width > *acc {
-                    *acc = width;
+        let widths = if start == 0 || self.widths.is_empty() {
+            vec![0; n]
+        } else {
+            self.widths
+                .iter()
+                .map(|width| match width {
+                    Constraint::Length(len) => *len as usize,
+                    _ => 0,
+                })
+                .collect()
+        };
+        let max_lens = self.options[start..]
+            .iter()
+            .fold(widths, |mut acc, option| {
+                let row = option.format(&self.editor_data);
+                // maintain max for each column
+                for (acc, cell) in acc.iter_mut().zip(row.cells.iter()) {
+                    let width = cell.content.width();
+                    if width > *acc {
+                        *acc = width;
+                    }
                 }
-            }
-            acc
-        });
+                acc
+            });
         self.widths = max_lens
             .into_iter()
             .map(|len| Constraint::Length(len as u16))
@@ -505,81 +626,132 @@ fn calculate_column_widths(&mut self) {
     }
 
     pub fn score(&mut self) {
-        let pattern = self.prompt.line();
+        let pattern = self.prompt.line().clone();
 
-        if pattern == &self.previous_pattern.0 {
-            return;
+        if pattern != self.previous_pattern.0 {
+            self.refine(pattern);
+            // reset cursor position
+            self.cursor = 0;
         }
 
+        self.score_pending();
+    }
+
+    /// Starts scoring `pattern`, from the matches of an earlier pattern it refines if possible.
+    fn refine(&mut self, pattern: String) {
         let (query, is_refined) = self
             .previous_pattern
             .1
-            .refine(pattern, &self.previous_pattern.0);
-
-        if pattern.is_empty() {
-            // Fast path for no pattern.
-            self.matches.clear();
-            self.matches
-                .extend(self.options.iter().enumerate().map(|(index, option)| {
-                    let text = option.filter_text(&self.editor_data);
-                    PickerMatch {
-                        index,
-                        score: 0,
-                        len: text.chars().count(),
-                    }
-                }));
-        } else if is_refined {
-            // optimization: if the pattern is a more specific version of the previous one
-            // then we can score the filtered set.
-            self.matches.retain_mut(|pmatch| {
-                let option = &self.options[pmatch.index];
-                let text = option.sort_text(&self.editor_data);
-
-                match query.fuzzy_match(&text, &self.matcher) {
-                    Some(s) => {
-                        // Update the score
-                        pmatch.score = s;
-                        true
-                    }
-                    None => false,
-                }
+            .refine(&pattern, &self.previous_pattern.0);
+
+        if is_refined {
+            // Keep the matches of the previous pattern, in case the pattern shrinks back to it.
+            self.refinements.push(Refinement {
+                pattern: std::mem::take(&mut self.previous_pattern.0),
+                matches: self.matches.clone(),
+                unscored: self.unscored.clone(),
+                scored: self.scored,
             });
-
-            self.matches.sort_unstable();
+            self.unscored.append(&mut self.matches);
         } else {
-            self.force_score();
+            self.matches.clear();
+            self.unscored.clear();
+            self.scored = 0;
+
+            // Fall back to the longest earlier pattern the new one is or refines.
+            while let Some(refinement) = self.refinements.pop() {
+                if refinement.pattern == pattern {
+                    self.matches = refinement.matches;
+                    self.unscored = refinement.unscored;
+                    self.scored = refinement.scored;
+                    self.previous_pattern = (pattern, query);
+                    return;
+                }
+                let (_, is_refined) =
+                    FuzzyQuery::new(&refinement.pattern).refine(&pattern, &refinement.pattern);
+                if is_refined {
+                    self.unscored.extend(&refinement.matches);
+                    self.unscored.extend(&refinement.unscored);
+                    self.scored = refinement.scored;
+                    self.refinements.push(refinement);
+                    break;
+                }
+            }
         }
 
-        // reset cursor position
-        self.cursor = 0;
-        let pattern = self.prompt.line();
-        self.previous_pattern.0.clone_from(pattern);
-        self.previous_pattern.1 = query;
+        self.previous_pattern = (pattern, query);
     }
 
     pub fn force_score(&mut self) {
         let pattern = self.prompt.line();
 
-        let query = FuzzyQuery::new(pattern);
+        self.previous_pattern = (pattern.clone(), FuzzyQuery::new(pattern));
+        self.refinements.clear();
         self.matches.clear();
-        self.matches.extend(
-            self.options
-                .iter()
-                .enumerate()
-                .filter_map(|(index, option)| {
-                    let text = option.filter_text(&self.editor_data);
-
-                    query
-                        .fuzzy_match(&text, &self.matcher)
-                        .map(|score| PickerMatch {
-                            index,
-                            score,
-                            len: text.chars().count(),
-                        })
-                }),
-        );
+        self.unscored.clear();
+        self.scored = 0;
+        self.score_pending();
+    }
+
+    /// Scores the matches of the refined pattern and the options not yet scored for at most
+    /// [`SCORE_BUDGET`], merging the new matches in. Returns whether everything was scored.
+    fn score_pending(&mut self) -> bool {
+        if self.unscored.is_empty() && self.scored == self.options.len() {
+            return true;
+        }
+
+        // Follow the selected option as matches are added above it, unless the best match is
+        // selected.
+        let selected = self
+            .matches
+            .get(self.cursor)
+            .copied()
+            .filter(|_| self.cursor > 0);
+
+        let query = &self.previous_pattern.1;
+        let score = |index: usize| {
+            let option = &self.options[index];
+            let text = option.filter_text(&self.editor_data);
+
+            query
+                .fuzzy_match(&text, &self.matcher)
+                .map(|score| PickerMatch {
+                    index,
+                    score: score + option.score_bonus(&self.editor_data),
+                    len: text.chars().count(),
+                })
+        };
+
+        let started = Instant::now();
+        let mut new_matches = Vec::new();
+        while started.elapsed() < SCORE_BUDGET {
+            if !self.unscored.is_empty() {
+                let batch = self
+                    .unscored
+                    .split_off(self.unscored.len().saturating_sub(SCORE_BATCH_SIZE));
+                new_matches.extend(batch.into_iter().filter_map(|pmatch| score(pmatch.index)));
+            } else if self.scored < self.options.len() {
+                let end = self.options.len().min(self.scored + SCORE_BATCH_SIZE);
+                new_matches.extend((self.scored..end).filter_map(&score));
+                self.scored = end;
+            } else {
+                break;
+            }
+        }
+
+        // The matches and the new matches are both sorted runs, which the stable sort merges in
+        // linear time.
+        new_matches.sort_unstable();
+        self.matches.append(&mut new_matches);
+        self.matches.sort();
+
+        if let Some(selected) = selected {
+            if let Ok(cursor) = self.matches.binary_search(&selected) {
+                self.cursor = cursor;
+            }
+        }
 
-        self.matches.sort_unstable();
+        self.unscored.is_empty() && self.scored == self.options.len()
     }
 
     /// Move the cursor by a number of lines, either down (`Forward`) or up (`Backward`)
@@ -667,6 +839,8 @@ fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
         // So that idle timeout retriggers
         cx.editor.reset_idle_timer();
 
+        let previous_selection = self.selected_index();
+
         match key_event {
             shift!(Tab) | key!(Up) | ctrl!('p') => {
                 self.move_by(1, Direction::Backward);
@@ -687,6 +861,9 @@ fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
                 self.to_end();
             }
             key!(Esc) | ctrl!('c') => {
+                if let Some(abort_fn) = &self.abort_fn {
+                    abort_fn(cx);
+                }
                 return close_fn;
             }
             alt!(Enter) => {
@@ -720,10 +897,24 @@ fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
             }
         }
 
+        if let Some(selection_change_fn) = &self.selection_change_fn {
+            if self.selected_index() != previous_selection {
+                if let Some(option) = self.selection() {
+                    selection_change_fn(cx, option);
+                }
+            }
+        }
+
         EventResult::Consumed(None)
     }
 
     fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        self.drain_option_stream();
+        if !self.score_pending() {
+            // Render again to show the rest of the matches once they're scored.
+            cx.editor.redraw_handle.0.notify_one();
+        }
+
         let text_style = cx.editor.theme.get("ui.text");
         let selected = cx.editor.theme.get("ui.text.focus");
         let highlight_style = cx.editor.theme.get("special").add_modifier(Modifier::BOLD);
@@ -973,3 +1164,100 @@ fn id(&self) -> Option<&'static str> {
         Some(Self::ID)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn picker(options: &[&str]) -> Picker<PathBuf> {
+        let options = options.iter().map(PathBuf::from).collect();
+        Picker::new(options, PathBuf::new(), |_, _, _| {})
+    }
+
+    fn matches(picker: &Picker<PathBuf>) -> Vec<&str> {
+        let mut matches: Vec<_> = picker
+            .matches
+            .iter()
+            .map(|pmatch| picker.options[pmatch.index].to_str().unwrap())
+            .collect();
+        matches.sort_unstable();
+        matches
+    }
+
+    fn set_pattern(picker: &mut Picker<PathBuf>, pattern: &str) {
+        picker.refine(pattern.to_string());
+        assert!(picker.score_pending());
+    }
+
+    #[test]
+    fn refinements() {
+        let mut picker = picker(&["foo", "food", "bar"]);
+        assert_eq!(matches(&picker), ["bar", "foo", "food"]);
+
+        set_pattern(&mut picker, "f");
+        assert_eq!(matches(&picker), ["foo", "food"]);
+        set_pattern(&mut picker, "fo");
+        set_pattern(&mut picker, "fod");
+        assert_eq!(matches(&picker), ["food"]);
+        assert_eq!(picker.refinements.len(), 3);
+
+        // Shrinking the pattern back restores the matches of the earlier pattern.
+        picker.refine("fo".to_string());
+        assert!(picker.unscored.is_empty());
+        assert_eq!(matches(&picker), ["foo", "food"]);
+        assert_eq!(picker.refinements.len(), 2);
+
+        // A pattern that refines an earlier one rescores the matches of that one.
+        picker.refine("b".to_string());
+        assert_eq!(picker.unscored.len(), 3);
+        assert!(picker.score_pending());
+        assert_eq!(matches(&picker), ["bar"]);
+        assert_eq!(picker.refinements.len(), 1);
+
+        picker.refine("x".to_string());
+        picker.refine(String::new());
+        assert!(picker.score_pending());
+        assert_eq!(matches(&picker), ["bar", "foo", "food"]);
+    }
+
+    #[test]
+    fn score_pending() {
+        let mut picker = picker(&["foo", "bar"]);
+        set_pattern(&mut picker, "fo");
+        assert_eq!(matches(&picker), ["foo"]);
+
+        // Only the pushed options are scored.
+        picker.push_options([PathBuf::from("food"), PathBuf::from("baz")]);
+        assert_eq!(picker.scored, 4);
+        assert_eq!(matches(&picker), ["foo", "food"]);
+
+        // The selected option stays selected as better matches are added above it.
+        picker.cursor = 1;
+        let selected = picker.selection().cloned();
+        picker.push_options([PathBuf::from("fo")]);
+        assert_eq!(picker.selection().cloned(), selected);
+        assert_eq!(picker.cursor, 2);
+
+        // Options are scored in batches until everything is.
+        let many: Vec<_> = (0..3 * SCORE_BATCH_SIZE)
+            .map(|i| PathBuf::from(format!("fo{}", i)))
+            .collect();
+        picker.push_options(many);
+        while !picker.score_pending() {}
+        assert_eq!(picker.scored, picker.options.len());
+        assert_eq!(picker.matches.len(), 3 + 3 * SCORE_BATCH_SIZE);
+    }
+
+    #[test]
+    fn option_stream() {
+        let (options_sx, options_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut picker = picker(&["foo"]).with_option_stream(options_rx);
+        options_sx.send(PathBuf::from("bar")).unwrap();
+        picker.drain_option_stream();
+        assert_eq!(matches(&picker), ["bar", "foo"]);
+
+        drop(options_sx);
+        picker.drain_option_stream();
+        assert!(picker.option_stream.is_none());
+    }
+}
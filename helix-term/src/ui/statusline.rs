@@ -1,4 +1,4 @@
-use helix_core::{coords_at_pos, encoding, Position};
+use helix_core::{coords_at_pos, data_path, diagnostic::Severity, encoding, Position};
 use helix_lsp::lsp::DiagnosticSeverity;
 use helix_view::document::DEFAULT_LANGUAGE_NAME;
 use helix_view::{
@@ -160,6 +160,8 @@ fn get_render_function<F>(element_id: StatusLineElementID) -> impl Fn(&mut Rende
         helix_view::editor::StatusLineElement::Separator => render_separator,
         helix_view::editor::StatusLineElement::Spacer => render_spacer,
         helix_view::editor::StatusLineElement::VersionControl => render_version_control,
+        helix_view::editor::StatusLineElement::DataPath => render_data_path,
+        helix_view::editor::StatusLineElement::TodoCounts => render_todo_counts,
     }
 }
 
@@ -223,78 +225,82 @@ fn render_diagnostics<F>(context: &mut RenderContext, write: F)
 where
     F: Fn(&mut RenderContext, String, Option<Style>) + Copy,
 {
-    let (warnings, errors) = context
-        .doc
-        .diagnostics()
-        .iter()
-        .fold((0, 0), |mut counts, diag| {
-            use helix_core::diagnostic::Severity;
-            match diag.severity {
-                Some(Severity::Warning) => counts.0 += 1,
-                Some(Severity::Error) | None => counts.1 += 1,
-                _ => {}
-            }
-            counts
-        });
-
-    if warnings > 0 {
-        write(
-            context,
-            "●".to_string(),
-            Some(context.editor.theme.get("warning")),
-        );
-        write(context, format!(" {} ", warnings), None);
-    }
-
-    if errors > 0 {
-        write(
-            context,
-            "●".to_string(),
-            Some(context.editor.theme.get("error")),
-        );
-        write(context, format!(" {} ", errors), None);
-    }
+    let doc = context.doc;
+    let diagnostics = doc.diagnostics();
+    render_diagnostic_counts(context, write, |severity| {
+        diagnostics
+            .iter()
+            .filter(|diag| diag.severity.unwrap_or(Severity::Error) == severity)
+            .count()
+    });
 }
 
 fn render_workspace_diagnostics<F>(context: &mut RenderContext, write: F)
 where
     F: Fn(&mut RenderContext, String, Option<Style>) + Copy,
 {
-    let (warnings, errors) =
-        context
-            .editor
-            .diagnostics
+    let editor = context.editor;
+    let diagnostics = &editor.diagnostics;
+    let config = editor.config();
+    let count = |severity| {
+        diagnostics
             .values()
             .flatten()
-            .fold((0, 0), |mut counts, diag| {
-                match diag.severity {
-                    Some(DiagnosticSeverity::WARNING) => counts.0 += 1,
-                    Some(DiagnosticSeverity::ERROR) | None => counts.1 += 1,
-                    _ => {}
-                }
-                counts
-            });
+            .filter(|diag| config.diagnostic_filter.allows_lsp(diag))
+            .filter(|diag| {
+                let diag_severity = match diag.severity {
+                    Some(DiagnosticSeverity::WARNING) => Severity::Warning,
+                    Some(DiagnosticSeverity::INFORMATION) => Severity::Info,
+                    Some(DiagnosticSeverity::HINT) => Severity::Hint,
+                    _ => Severity::Error,
+                };
+                diag_severity == severity
+            })
+            .count()
+    };
 
-    if warnings > 0 || errors > 0 {
+    if config.statusline.show_zero_diagnostics
+        || config
+            .statusline
+            .diagnostics
+            .iter()
+            .any(|severity| count(*severity) > 0)
+    {
         write(context, format!(" {} ", "W"), None);
     }
 
-    if warnings > 0 {
-        write(
-            context,
-            "●".to_string(),
-            Some(context.editor.theme.get("warning")),
-        );
-        write(context, format!(" {} ", warnings), None);
-    }
+    render_diagnostic_counts(context, write, count);
+}
+
+/// Writes the symbol and number of diagnostics of each severity configured in
+/// `statusline.diagnostics`.
+fn render_diagnostic_counts<F>(
+    context: &mut RenderContext,
+    write: F,
+    count: impl Fn(Severity) -> usize,
+) where
+    F: Fn(&mut RenderContext, String, Option<Style>) + Copy,
+{
+    let editor = context.editor;
+    let config = editor.config();
+    for &severity in &config.statusline.diagnostics {
+        let count = count(severity);
+        if count == 0 && !config.statusline.show_zero_diagnostics {
+            continue;
+        }
 
-    if errors > 0 {
+        let scope = match severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Hint => "hint",
+        };
         write(
             context,
-            "●".to_string(),
-            Some(context.editor.theme.get("error")),
+            config.diagnostic_symbols.get(severity).to_string(),
+            Some(context.editor.theme.get(scope)),
         );
-        write(context, format!(" {} ", errors), None);
+        write(context, format!(" {} ", count), None);
     }
 }
 
@@ -490,3 +496,39 @@ fn render_version_control<F>(context: &mut RenderContext, write: F)
 
     write(context, head, None);
 }
+
+fn render_data_path<F>(context: &mut RenderContext, write: F)
+where
+    F: Fn(&mut RenderContext, String, Option<Style>) + Copy,
+{
+    let doc = context.doc;
+    let path = match (doc.language_name(), doc.syntax()) {
+        (Some(language), Some(syntax)) if data_path::LANGUAGES.contains(&language) => {
+            let text = doc.text().slice(..);
+            let pos = doc.selection(context.view.id).primary().cursor(text);
+            data_path::path_at(text, syntax.tree().root_node(), pos)
+        }
+        _ => return,
+    };
+
+    if !path.is_empty() {
+        write(context, format!(" {} ", path), None);
+    }
+}
+
+fn render_todo_counts<F>(context: &mut RenderContext, write: F)
+where
+    F: Fn(&mut RenderContext, String, Option<Style>) + Copy,
+{
+    let counts = &context.editor.todo_counts;
+    if counts.is_empty() {
+        return;
+    }
+
+    let counts = counts
+        .iter()
+        .map(|(tag, count)| format!("{} {}", count, tag))
+        .collect::<Vec<_>>()
+        .join(", ");
+    write(context, format!(" {} ", counts), None);
+}
@@ -0,0 +1,149 @@
+//! Background indexing of the files of the workspace. The tag and trigram indexes of the
+//! editor are persisted in the state directory so they only need to be updated with the
+//! files changed since the previous session.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use helix_core::{syntax, tags::TagIndex, trigram::TrigramIndex};
+use helix_view::{editor::FilePickerConfig, state, Editor};
+
+use crate::filter_picker_entry;
+
+/// Returns the files below `root` which aren't ignored by the file picker configuration.
+pub fn workspace_files(root: &Path, config: &FilePickerConfig) -> HashSet<PathBuf> {
+    let dedup_symlinks = config.deduplicate_links;
+    let absolute_root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+
+    ignore::WalkBuilder::new(root)
+        .hidden(config.hidden)
+        .parents(config.parents)
+        .ignore(config.ignore)
+        .follow_links(config.follow_symlinks)
+        .git_ignore(config.git_ignore)
+        .git_global(config.git_global)
+        .git_exclude(config.git_exclude)
+        .max_depth(config.max_depth)
+        .filter_entry(move |entry| filter_picker_entry(entry, &absolute_root, dedup_symlinks))
+        .build()
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            entry.file_type()?.is_file().then(|| entry.into_path())
+        })
+        .collect()
+}
+
+/// Updates `index` with the files of the workspace that changed since the last update and
+/// marks it complete. Returns whether the index changed.
+pub fn refresh_tags(
+    index: &mut TagIndex,
+    files: &HashSet<PathBuf>,
+    loader: &Arc<syntax::Loader>,
+) -> bool {
+    let mut changed = index.retain_files(|path| files.contains(path));
+    for path in files {
+        if loader.language_config_for_file_name(path).is_some() {
+            changed |= index.update_file(path, loader);
+        }
+    }
+    index.mark_complete();
+    changed
+}
+
+/// Updates `index` with the files of the workspace that changed since the last update.
+/// Returns whether the index changed.
+pub fn refresh_trigrams(index: &mut TrigramIndex, files: &HashSet<PathBuf>) -> bool {
+    let mut changed = index.retain_files(|path| files.contains(path));
+    for path in files {
+        changed |= index.update_file(path);
+    }
+    changed
+}
+
+const TAGS_FILE: &str = "tags.json";
+const TRIGRAMS_FILE: &str = "trigrams.json";
+
+/// Directory the indexes of the workspace at `root` are stored in.
+fn index_dir(root: &Path) -> PathBuf {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let name = root.to_string_lossy().replace(['/', '\\', ':'], "%");
+    helix_loader::state_dir().join("index").join(name)
+}
+
+/// Saves the trigram index of the workspace at `root` to the state directory if it changed
+/// since it was loaded or last saved.
+pub fn save_trigrams(root: &Path, index: &mut TrigramIndex) {
+    if index.take_unsaved() {
+        state::save_path(&index_dir(root).join(TRIGRAMS_FILE), index);
+    }
+}
+
+/// Saves the tag index of the workspace at `root` to the state directory if it changed
+/// since it was loaded or last saved.
+pub fn save_tags(root: &Path, index: &mut TagIndex) {
+    if index.take_unsaved() {
+        state::save_path(&index_dir(root).join(TAGS_FILE), index);
+    }
+}
+
+/// Indexes the tags of the saved file at `path` in the background, if the tag index of the
+/// workspace was already built so that the next lookup doesn't have to walk the workspace.
+pub fn update_saved_file(editor: &Editor, path: &Path) {
+    let in_workspace = std::env::current_dir().map_or(false, |root| path.starts_with(root));
+    if !in_workspace {
+        return;
+    }
+    let tag_index = editor.tag_index.clone();
+    let loader = editor.syn_loader.clone();
+    let path = path.to_path_buf();
+
+    tokio::task::spawn_blocking(move || {
+        let mut tag_index = tag_index.lock();
+        if tag_index.is_complete() && loader.language_config_for_file_name(&path).is_some() {
+            tag_index.update_file(&path, &loader);
+        }
+    });
+}
+
+/// Indexes the workspace in the background if `search.index` is enabled: the persisted
+/// indexes are loaded, brought up to date and saved again.
+pub fn spawn_indexer(editor: &Editor) {
+    let config = editor.config();
+    if !config.search.index {
+        return;
+    }
+    let file_picker_config = config.file_picker.clone();
+    let tag_index = editor.tag_index.clone();
+    let trigram_index = editor.trigram_index.clone();
+    let loader = editor.syn_loader.clone();
+    let root = match std::env::current_dir() {
+        Ok(root) => root,
+        Err(_) => return,
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let dir = index_dir(&root);
+        let tags_path = dir.join(TAGS_FILE);
+        let trigrams_path = dir.join(TRIGRAMS_FILE);
+        let files = workspace_files(&root, &file_picker_config);
+
+        // Indexes built during this session are more recent than the persisted ones.
+        let mut trigram_index = trigram_index.lock();
+        if trigram_index.is_empty() {
+            *trigram_index = state::load_path(&trigrams_path);
+        }
+        refresh_trigrams(&mut trigram_index, &files);
+        save_trigrams(&root, &mut trigram_index);
+        drop(trigram_index);
+
+        let mut tag_index = tag_index.lock();
+        if tag_index.is_empty() {
+            *tag_index = state::load_path(&tags_path);
+        }
+        refresh_tags(&mut tag_index, &files, &loader);
+        save_tags(&root, &mut tag_index);
+    });
+}
@@ -2,6 +2,12 @@
 
 use super::*;
 
+mod completion;
+mod file_operations;
+mod lines;
+mod linked_editing;
+mod run;
+mod test_runner;
 mod write;
 
 #[tokio::test(flavor = "multi_thread")]
@@ -257,6 +263,38 @@ async fn test_multi_selection_shell_commands() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_shell_commands_leave_variables_to_the_shell() -> anyhow::Result<()> {
+    // Undefined variables expand to nothing in the shell instead of being an error.
+    test((
+        platform_line("#[|lorem]#\n").as_str(),
+        ":insert-output echo ${HELIX_TEST_UNDEFINED}foo<ret>",
+        platform_line("#[|foo\n]#lorem\n").as_str(),
+    ))
+    .await?;
+
+    test_key_sequences(
+        &mut helpers::AppBuilder::new().build()?,
+        vec![
+            (Some(":sh for f in a b; do echo ${f}; done<ret>"), None),
+            (
+                None,
+                Some(&|app| {
+                    helpers::assert_status_not_error(&app.editor);
+                    assert_eq!(
+                        app.editor.get_status().map(|(status, _)| status.as_ref()),
+                        Some("Command succeeded")
+                    );
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_undo_redo() -> anyhow::Result<()> {
     // A jumplist selection is created at a point which is undone.
@@ -385,3 +423,151 @@ async fn test_character_info() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_selection_history() -> anyhow::Result<()> {
+    // Undo keep_primary_selection
+    test((
+        platform_line(indoc! {"\
+            #[lo|]#rem
+            ipsum
+            dolor
+            "})
+        .as_str(),
+        "CC,<A-z>",
+        platform_line(indoc! {"\
+            #(lo|)#rem
+            #(ip|)#sum
+            #[do|]#lor
+            "})
+        .as_str(),
+    ))
+    .await?;
+
+    // Undo twice, then redo once
+    test((
+        platform_line(indoc! {"\
+            #[lo|]#rem
+            ipsum
+            dolor
+            "})
+        .as_str(),
+        "CC,<A-z><A-z><A-Z>",
+        platform_line(indoc! {"\
+            #(lo|)#rem
+            #(ip|)#sum
+            #[do|]#lor
+            "})
+        .as_str(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_keep_and_remove_selections_by_predicate() -> anyhow::Result<()> {
+    test((
+        platform_line(indoc! {"\
+            #[lorem|]#
+            #(   |)#
+            #(ipsum dolor|)#
+            "})
+        .as_str(),
+        ":keep-selections longer 5<ret>",
+        platform_line(indoc! {"\
+            lorem
+               
+            #[ipsum dolor|]#
+            "})
+        .as_str(),
+    ))
+    .await?;
+
+    test((
+        platform_line(indoc! {"\
+            #[lorem|]#
+            #(   |)#
+            #(ipsum dolor|)#
+            "})
+        .as_str(),
+        ":remove-selections blank<ret>",
+        platform_line(indoc! {"\
+            #[lorem|]#
+               
+            #(ipsum dolor|)#
+            "})
+        .as_str(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_cycle_paste() -> anyhow::Result<()> {
+    test((
+        platform_line(indoc! {"\
+            #[one|]#
+            "})
+        .as_str(),
+        // Yank the word, then the line, paste the line and replace it with the word.
+        "yxyP<A-y>",
+        platform_line(indoc! {"\
+            #[one|]#one
+            "})
+        .as_str(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_paste_reindent() -> anyhow::Result<()> {
+    test((
+        platform_line(indoc! {"\
+            #[one|]#
+              two
+                three
+            "})
+        .as_str(),
+        // Yank the indented lines and paste them below the unindented one.
+        "jxxykk]P",
+        platform_line(indoc! {"\
+            one
+            #[two
+              three
+            |]#  two
+                three
+            "})
+        .as_str(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_insert_sequence() -> anyhow::Result<()> {
+    test((
+        platform_line(indoc! {"\
+            #[a|]#
+            #(b|)#
+            #(c|)#
+            "})
+        .as_str(),
+        ":insert-sequence 8 2 2<ret>",
+        platform_line(indoc! {"\
+            08#[a|]#
+            10#(b|)#
+            12#(c|)#
+            "})
+        .as_str(),
+    ))
+    .await?;
+
+    test(("#[a|]# #(b|)#", ":seq 9 1 0 16<ret>", "9#[a|]# a#(b|)#")).await?;
+
+    Ok(())
+}
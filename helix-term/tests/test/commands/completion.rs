@@ -0,0 +1,32 @@
+use helix_view::doc;
+
+use super::*;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_word_completion() -> anyhow::Result<()> {
+    // Without a language server, the words of the open documents are completed.
+    let mut app = helpers::AppBuilder::new()
+        .with_input_text(platform_line("#[h|]#ello_world help\n"))
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (Some("ohello_<C-x>"), None),
+            (
+                Some("<tab>"),
+                Some(&|app| {
+                    helpers::assert_status_not_error(&app.editor);
+                    assert_eq!(
+                        platform_line("hello_world help\nhello_world\n"),
+                        doc!(app.editor).text().to_string()
+                    );
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::fs;
+
+use helix_core::path::get_normalized_path;
+use helix_view::doc;
+
+use super::*;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rename_file() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let old_path = dir.path().join("old.txt");
+    let new_path = dir.path().join("nested/new.txt");
+    fs::write(&old_path, "hello")?;
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(&old_path, None)
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some(&format!(":rename-file {}<ret>", new_path.display())),
+        Some(&|app| {
+            helpers::assert_status_not_error(&app.editor);
+            let doc = doc!(app.editor);
+            assert_eq!(Some(&get_normalized_path(&new_path)), doc.path());
+        }),
+        false,
+    )
+    .await?;
+
+    assert!(!old_path.exists());
+    assert_eq!(fs::read_to_string(&new_path)?, "hello");
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_duplicate_file() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let source = dir.path().join("source.txt");
+    let copy = dir.path().join("copy.txt");
+    fs::write(&source, "hello")?;
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(&source, None)
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some(&format!(":duplicate-file {}<ret>", copy.display())),
+        Some(&|app| {
+            helpers::assert_status_not_error(&app.editor);
+            let doc = doc!(app.editor);
+            assert_eq!(Some(&get_normalized_path(&copy)), doc.path());
+        }),
+        false,
+    )
+    .await?;
+
+    assert_eq!(fs::read_to_string(&source)?, "hello");
+    assert_eq!(fs::read_to_string(&copy)?, "hello");
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_delete_file() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("file.txt");
+    fs::write(&path, "hello")?;
+
+    let mut config = helpers::test_config();
+    config.editor.hard_delete = true;
+    let mut app = helpers::AppBuilder::new()
+        .with_config(config)
+        .with_file(&path, None)
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some(":delete-file<ret>"),
+        Some(&|app| {
+            helpers::assert_status_not_error(&app.editor);
+            assert!(app.editor.document_by_path(&path).is_none());
+        }),
+        false,
+    )
+    .await?;
+
+    assert!(!path.exists());
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use helix_core::diagnostic::Severity;
+use helix_view::doc;
+
+use super::*;
+
+/// Runs `keys` on `input` and checks the text of the document is `expected`.
+async fn test_lines(input: &str, keys: &str, expected: &str) -> anyhow::Result<()> {
+    let mut app = helpers::AppBuilder::new().with_input_text(input).build()?;
+    let expected = platform_line(expected);
+
+    test_key_sequence(
+        &mut app,
+        Some(keys),
+        Some(&|app| {
+            helpers::assert_status_not_error(&app.editor);
+            assert_eq!(expected, doc!(app.editor).text().to_string());
+        }),
+        false,
+    )
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sort() -> anyhow::Result<()> {
+    let input = platform_line("#[b\nC\na\n|]#");
+    test_lines(&input, ":sort<ret>", "C\na\nb\n").await?;
+    test_lines(&input, ":sort -i<ret>", "a\nb\nC\n").await?;
+    test_lines(&input, ":sort -r<ret>", "b\na\nC\n").await?;
+    test_lines(&input, ":sort --reverse --ignore-case<ret>", "C\nb\na\n").await?;
+    test_lines(&input, ":rsort<ret>", "b\na\nC\n").await?;
+
+    // keys without numbers come first
+    let input = platform_line("#[10\n9\nx\n-1.5\n|]#");
+    test_lines(&input, ":sort -n<ret>", "x\n-1.5\n9\n10\n").await?;
+
+    // sorting by the match of a regex
+    let input = platform_line("#[a 2\nb 10\nc 1\n|]#");
+    test_lines(&input, ":sort -n [0-9]+<ret>", "c 1\na 2\nb 10\n").await?;
+    test_lines(&input, ":sort [a-z].([0-9])<ret>", "b 10\nc 1\na 2\n").await?;
+    // a regex starting with a dash follows `--`
+    let input = platform_line("#[1 -b\n2 -a\n|]#");
+    test_lines(&input, ":sort -- -[a-z]<ret>", "2 -a\n1 -b\n").await?;
+
+    // selections on a single line are sorted among each other
+    let input = platform_line("#[b|]#\n#(a|)#\n#(c|)#\n");
+    test_lines(&input, ":sort<ret>", "a\nb\nc\n").await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sort_unknown_flag() -> anyhow::Result<()> {
+    let mut app = helpers::AppBuilder::new()
+        .with_input_text(platform_line("#[b\na\n|]#"))
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some(":sort -x<ret>"),
+        Some(&|app| {
+            let (status, severity) = app.editor.get_status().unwrap();
+            assert_eq!(&Severity::Error, severity);
+            assert!(status.contains("unknown flag '-x'"), "{}", status);
+            assert_eq!(platform_line("b\na\n"), doc!(app.editor).text().to_string());
+        }),
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_uniq() -> anyhow::Result<()> {
+    let input = platform_line("#[a\nb\na\nb\nc\n|]#");
+    test_lines(&input, ":uniq<ret>", "a\nb\nc\n").await?;
+
+    // duplicate selections are emptied
+    let input = platform_line("#[a|]#\n#(b|)#\n#(a|)#\n");
+    test_lines(&input, ":uniq<ret>", "a\nb\n\n").await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reverse() -> anyhow::Result<()> {
+    let input = platform_line("#[a\nb\nc\n|]#");
+    test_lines(&input, ":reverse<ret>", "c\nb\na\n").await?;
+
+    let input = platform_line("#[a|]#\n#(b|)#\n#(c|)#\n");
+    test_lines(&input, ":reverse<ret>", "c\nb\na\n").await?;
+
+    Ok(())
+}
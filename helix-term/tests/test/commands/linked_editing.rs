@@ -0,0 +1,95 @@
+use helix_view::doc;
+
+use super::*;
+
+fn linked_editing_config() -> Config {
+    Config {
+        editor: helix_view::editor::Config {
+            linked_editing: true,
+            ..helpers::test_editor_config()
+        },
+        ..helpers::test_config()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_linked_editing_html_tags() -> anyhow::Result<()> {
+    // editing the opening tag renames the closing one
+    test_with_config(
+        AppBuilder::new()
+            .with_file("foo.html", None)
+            .with_config(linked_editing_config()),
+        (
+            platform_line("<#[d|]#iv>hi</div>").as_str(),
+            "ix",
+            platform_line("<x#[|d]#iv>hi</xdiv>").as_str(),
+        ),
+    )
+    .await?;
+
+    // and the other way around
+    test_with_config(
+        AppBuilder::new()
+            .with_file("foo.html", None)
+            .with_config(linked_editing_config()),
+        (
+            platform_line("<div>hi</#[d|]#iv>").as_str(),
+            "ix",
+            platform_line("<xdiv>hi</x#[|d]#iv>").as_str(),
+        ),
+    )
+    .await?;
+
+    // nested elements link the tags of the element under the cursor
+    test_with_config(
+        AppBuilder::new()
+            .with_file("foo.html", None)
+            .with_config(linked_editing_config()),
+        (
+            platform_line("<div><#[p|]#>hi</p></div>").as_str(),
+            "ix",
+            platform_line("<div><x#[|p]#>hi</xp></div>").as_str(),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_linked_editing_append() -> anyhow::Result<()> {
+    // the tags are looked up where the cursor ends up, here right after the tag name
+    let mut app = helpers::AppBuilder::new()
+        .with_file("foo.html", None)
+        .with_config(linked_editing_config())
+        .with_input_text(platform_line("<di#[v|]#>hi</div>").as_str())
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some("ax"),
+        Some(&|app| {
+            let doc = doc!(app.editor);
+            assert_eq!(platform_line("<divx>hi</divx>"), doc.text().to_string());
+        }),
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_linked_editing_disabled() -> anyhow::Result<()> {
+    test_with_config(
+        AppBuilder::new().with_file("foo.html", None),
+        (
+            platform_line("<#[d|]#iv>hi</div>").as_str(),
+            "ix",
+            platform_line("<x#[|d]#iv>hi</div>").as_str(),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use helix_view::doc;
+
+use super::*;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_run_output() -> anyhow::Result<()> {
+    let file = tempfile::Builder::new().suffix(".rs").tempfile()?;
+    // The output has invalid UTF-8 and doesn't end with a newline.
+    let lang_conf = indoc! {r#"
+        [[language]]
+        name = "rust"
+        commands = { run = "printf 'one\\ntwo \\377\\nthree'" }
+    "#};
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file.path(), None)
+        .with_lang_config(helpers::test_syntax_conf(Some(lang_conf.into())))
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (Some(":run<ret>"), None),
+            (
+                None,
+                Some(&|app| {
+                    let doc = doc!(app.editor);
+                    let text = doc.text().to_string();
+                    assert!(
+                        text.ends_with("one\ntwo \u{FFFD}\nthree\n[exit status: 0]\n"),
+                        "{:?}",
+                        text
+                    );
+                    assert!(!doc.is_modified());
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_build_quickfix() -> anyhow::Result<()> {
+    let file = tempfile::Builder::new().suffix(".rs").tempfile()?;
+    std::fs::write(file.path(), "fn main() {\n    let x = 1\n}\n")?;
+    let lang_conf = format!(
+        indoc! {r#"
+            [[language]]
+            name = "rust"
+            commands = {{ build = "echo 'Compiling'; echo '{}:2:5: error: expected `;`' >&2" }}
+        "#},
+        file.path().display()
+    );
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file.path(), None)
+        .with_lang_config(helpers::test_syntax_conf(Some(lang_conf)))
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (Some(":build<ret>"), None),
+            (
+                None,
+                Some(&|app| {
+                    let entries = app.editor.quickfix.entries();
+                    assert_eq!(app.editor.quickfix.title, "build");
+                    assert_eq!(entries.len(), 1);
+                    assert_eq!((entries[0].line, entries[0].column), (1, 4));
+                    assert_eq!(entries[0].text, "error: expected `;`");
+                }),
+            ),
+            (
+                Some(":cnext<ret>"),
+                Some(&|app| {
+                    let (view, doc) = helix_view::current_ref!(app.editor);
+                    assert_eq!(
+                        doc.path(),
+                        Some(&helix_core::path::get_normalized_path(file.path()))
+                    );
+                    let text = doc.text().slice(..);
+                    let cursor = doc.selection(view.id).primary().cursor(text);
+                    assert_eq!(cursor, text.line_to_char(1) + 4);
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
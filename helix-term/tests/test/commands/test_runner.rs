@@ -0,0 +1,198 @@
+use helix_term::application::Application;
+use helix_view::{doc, document::TestStatus};
+
+use super::*;
+
+const TESTS: &str = indoc! {"\
+    #[l|]#et x = 1;
+
+    #[test]
+    fn a() {}
+
+    mod nested {
+        #[test]
+        fn a() {}
+    }
+
+    // not a test
+    fn helper() {}
+
+    #[test]
+    #[should_panic]
+    fn b() {}
+"};
+
+fn test_results(app: &Application) -> Vec<(String, usize, TestStatus)> {
+    let doc = doc!(app.editor);
+    let text = doc.text();
+    let mut results: Vec<_> = doc
+        .test_results()
+        .iter()
+        .map(|result| {
+            let line = text.char_to_line(result.pos);
+            (result.name.clone(), line, result.status)
+        })
+        .collect();
+    results.sort_by_key(|(_, line, _)| *line);
+    results
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_run_file_runs_tests_at_once() -> anyhow::Result<()> {
+    let file = tempfile::Builder::new().suffix(".rs").tempfile()?;
+    // The file command reports `b` as failed, and would fail if it was run per test.
+    let lang_conf = indoc! {r#"
+        [[language]]
+        name = "rust"
+        test-runner = { command = "exit 1", file-command = "echo %{tests}; echo 'test b ... FAILED'; exit 1", failure-regex = 'test (?P<test>\S+) \.\.\. FAILED' }
+    "#};
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file.path(), None)
+        .with_input_text(TESTS)
+        .with_lang_config(helpers::test_syntax_conf(Some(lang_conf.into())))
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (Some(":test-run-file<ret>"), None),
+            (
+                None,
+                Some(&|app| {
+                    assert_eq!(
+                        test_results(app),
+                        [
+                            ("a".to_string(), 3, TestStatus::Passed),
+                            ("a".to_string(), 7, TestStatus::Passed),
+                            ("b".to_string(), 15, TestStatus::Failed),
+                        ]
+                    );
+                    let doc = doc!(app.editor);
+                    let output = &doc.test_results()[0].output;
+                    assert!(output.starts_with("a a b\n"), "{:?}", output);
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_run_file_without_file_command() -> anyhow::Result<()> {
+    let file = tempfile::Builder::new().suffix(".rs").tempfile()?;
+    // Without a file command, the tests run one by one.
+    let lang_conf = indoc! {r#"
+        [[language]]
+        name = "rust"
+        test-runner = { command = "test %{test} = a" }
+    "#};
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file.path(), None)
+        .with_input_text(TESTS)
+        .with_lang_config(helpers::test_syntax_conf(Some(lang_conf.into())))
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (Some(":test-run-file<ret>"), None),
+            (
+                None,
+                Some(&|app| {
+                    assert_eq!(
+                        test_results(app),
+                        [
+                            ("a".to_string(), 3, TestStatus::Passed),
+                            ("a".to_string(), 7, TestStatus::Passed),
+                            ("b".to_string(), 15, TestStatus::Failed),
+                        ]
+                    );
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_run_file_compile_error() -> anyhow::Result<()> {
+    let file = tempfile::Builder::new().suffix(".rs").tempfile()?;
+    // When no failed test is found in the output, all the tests failed.
+    let lang_conf = indoc! {r#"
+        [[language]]
+        name = "rust"
+        test-runner = { command = "true", file-command = "echo 'error: could not compile'; exit 1", failure-regex = 'test (?P<test>\S+) \.\.\. FAILED' }
+    "#};
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file.path(), None)
+        .with_input_text(TESTS)
+        .with_lang_config(helpers::test_syntax_conf(Some(lang_conf.into())))
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (Some(":test-run-file<ret>"), None),
+            (
+                None,
+                Some(&|app| {
+                    let statuses: Vec<_> = test_results(app)
+                        .into_iter()
+                        .map(|(_, _, status)| status)
+                        .collect();
+                    assert_eq!(statuses, [TestStatus::Failed; 3]);
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_run_nearest() -> anyhow::Result<()> {
+    let file = tempfile::Builder::new().suffix(".rs").tempfile()?;
+    let lang_conf = indoc! {r#"
+        [[language]]
+        name = "rust"
+        test-runner = { command = "true", file-command = "exit 1" }
+    "#};
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file.path(), None)
+        .with_input_text(TESTS)
+        .with_lang_config(helpers::test_syntax_conf(Some(lang_conf.into())))
+        .build()?;
+
+    // The closest test before the cursor, the one of the nested module, is the one run.
+    test_key_sequences(
+        &mut app,
+        vec![
+            (Some("9gg:test-run-nearest<ret>"), None),
+            (
+                None,
+                Some(&|app| {
+                    assert_eq!(
+                        test_results(app),
+                        [("a".to_string(), 7, TestStatus::Passed)]
+                    );
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
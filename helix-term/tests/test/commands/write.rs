@@ -407,3 +407,45 @@ async fn test_write_fail_new_path() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_write_range() -> anyhow::Result<()> {
+    let mut file = tempfile::NamedTempFile::new()?;
+    let path = file.path().to_string_lossy().to_string();
+    let mut app = helpers::AppBuilder::new()
+        .with_input_text("#[l|]#ine one\nline two\nline three\n")
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some(&format!(":write-range {} 2-3<ret>", path)),
+                Some(&|app| {
+                    assert_eq!(&Severity::Error, app.editor.get_status().unwrap().1);
+                }),
+            ),
+            (
+                Some(&format!(":write-range! {} 2-3<ret>", path)),
+                Some(&|app| {
+                    assert!(!app.editor.is_err());
+                }),
+            ),
+            (
+                Some(&format!(":append-range {}<ret>", path)),
+                Some(&|app| {
+                    assert!(!app.editor.is_err());
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    helpers::assert_file_has_content(
+        file.as_file_mut(),
+        &helpers::platform_line("line two\nline three\nl\n"),
+    )?;
+
+    Ok(())
+}
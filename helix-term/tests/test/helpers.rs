@@ -236,6 +236,8 @@ pub fn test_editor_config() -> helix_view::editor::Config {
             enable: false,
             ..Default::default()
         },
+        persist_old_files: false,
+        persist_jumplist: false,
         ..Default::default()
     }
 }
@@ -513,3 +513,22 @@ fn dec(x: usize) -> usize { x - 1 }
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn split_selection_on_tree_sitter_parameters() -> anyhow::Result<()> {
+    test_with_config(
+        AppBuilder::new().with_file("foo.rs", None),
+        (
+            helpers::platform_line(indoc! {"\
+                fn inc(#[x: usize, y: usize|]#) -> usize { x + y }
+            "}),
+            "<A-S>a",
+            helpers::platform_line(indoc! {"\
+                fn inc(#[x: usize|]#, #(y: usize|)#) -> usize { x + y }
+            "}),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
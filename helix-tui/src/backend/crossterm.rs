@@ -1,6 +1,6 @@
 use crate::{backend::Backend, buffer::Cell, terminal::Config};
 use crossterm::{
-    cursor::{Hide, MoveTo, SetCursorStyle, Show},
+    cursor::{Hide, MoveRight, MoveTo, SetCursorStyle, Show},
     event::{
         DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
         EnableFocusChange, EnableMouseCapture, KeyboardEnhancementFlags,
@@ -14,12 +14,14 @@
     terminal::{self, Clear, ClearType},
     Command,
 };
+use helix_core::unicode::width::UnicodeWidthStr;
 use helix_view::{
-    editor::Config as EditorConfig,
-    graphics::{Color, CursorKind, Modifier, Rect, UnderlineStyle},
+    editor::{Config as EditorConfig, GraphicsProtocol, KittyKeyboardProtocolConfig},
+    graphics::{Color, CursorKind, Image, Modifier, Rect, UnderlineStyle},
 };
 use once_cell::sync::OnceCell;
 use std::{
+    collections::HashSet,
     fmt,
     io::{self, Write},
 };
@@ -63,6 +65,12 @@ pub struct CrosstermBackend<W: Write> {
     buffer: W,
     capabilities: Capabilities,
     supports_keyboard_enhancement_protocol: OnceCell<bool>,
+    /// Whether keyboard enhancement flags were pushed when claiming the terminal and need to
+    /// be popped when restoring it.
+    keyboard_enhancement_enabled: bool,
+    /// Ids of the images whose data was already sent to the terminal.
+    transmitted_images: HashSet<u32>,
+    supports_kitty_graphics: OnceCell<bool>,
 }
 
 impl<W> CrosstermBackend<W>
@@ -74,6 +82,9 @@ pub fn new(buffer: W, config: &EditorConfig) -> CrosstermBackend<W> {
             buffer,
             capabilities: Capabilities::from_env_or_default(config),
             supports_keyboard_enhancement_protocol: OnceCell::new(),
+            keyboard_enhancement_enabled: false,
+            transmitted_images: HashSet::new(),
+            supports_kitty_graphics: OnceCell::new(),
         }
     }
 
@@ -93,6 +104,102 @@ fn supports_keyboard_enhancement_protocol(&self) -> bool {
                 supported
             })
     }
+
+    /// Asks the terminal whether it implements the kitty graphics protocol. Must be called in
+    /// raw mode, before the terminal input is read by crossterm.
+    fn supports_kitty_graphics(&mut self) -> bool {
+        if let Some(&supported) = self.supports_kitty_graphics.get() {
+            return supported;
+        }
+
+        let now = std::time::Instant::now();
+        let supported = self.query_kitty_graphics().unwrap_or_else(|err| {
+            log::warn!("Failed to query the terminal for image support: {}", err);
+            false
+        });
+        log::debug!(
+            "The kitty graphics protocol is {}supported in this terminal (checked in {:?})",
+            if supported { "" } else { "not " },
+            now.elapsed()
+        );
+        let _ = self.supports_kitty_graphics.set(supported);
+        supported
+    }
+
+    /// Sends a kitty graphics query followed by a request for the primary device attributes,
+    /// which all terminals answer, and reads the replies from the terminal. Terminals without
+    /// support for the protocol only answer the second request.
+    #[cfg(unix)]
+    fn query_kitty_graphics(&mut self) -> io::Result<bool> {
+        use std::{
+            io::Read,
+            os::unix::io::AsRawFd,
+            time::{Duration, Instant},
+        };
+
+        let mut tty = std::fs::File::open("/dev/tty")?;
+        write!(
+            self.buffer,
+            "\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\\x1b[c"
+        )?;
+        self.buffer.flush()?;
+
+        let deadline = Instant::now() + Duration::from_secs(1);
+        let mut reply = Vec::new();
+        loop {
+            if let Some(supported) = parse_kitty_graphics_reply(&reply) {
+                return Ok(supported);
+            }
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let mut pollfd = libc::pollfd {
+                fd: tty.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: `pollfd` is a single valid pollfd struct for the duration of the call.
+            let ready = unsafe { libc::poll(&mut pollfd, 1, timeout.as_millis() as libc::c_int) };
+            if timeout.is_zero() || ready <= 0 {
+                return Ok(false);
+            }
+            let mut buf = [0; 64];
+            let n = tty.read(&mut buf)?;
+            if n == 0 {
+                return Ok(false);
+            }
+            reply.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    /// Terminals can't be queried from Windows consoles, known terminals are detected from
+    /// their environment instead.
+    #[cfg(not(unix))]
+    fn query_kitty_graphics(&mut self) -> io::Result<bool> {
+        Ok(helix_view::env::supports_kitty_graphics())
+    }
+}
+
+/// Reads the reply to the kitty graphics query sent by `query_kitty_graphics`. Returns `None`
+/// until the primary device attributes (`CSI ? ... c`) sent after it were received.
+fn parse_kitty_graphics_reply(reply: &[u8]) -> Option<bool> {
+    let find = |haystack: &[u8], needle: &[u8]| {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    };
+
+    let mut start = 0;
+    while let Some(offset) = find(&reply[start..], b"\x1b[?") {
+        let attributes_start = start + offset;
+        let attributes = &reply[attributes_start + 3..];
+        let end = attributes
+            .iter()
+            .position(|&byte| !(byte.is_ascii_digit() || byte == b';'))?;
+        if attributes[end] == b'c' {
+            return Some(find(&reply[..attributes_start], b"\x1b_Gi=31;OK").is_some());
+        }
+        start = attributes_start + 3;
+    }
+    None
 }
 
 impl<W> Write for CrosstermBackend<W>
@@ -121,10 +228,18 @@ fn claim(&mut self, config: Config) -> io::Result<()> {
             EnableFocusChange
         )?;
         execute!(self.buffer, terminal::Clear(terminal::ClearType::All))?;
+        if config.graphics_protocol == GraphicsProtocol::Auto {
+            self.supports_kitty_graphics();
+        }
         if config.enable_mouse_capture {
             execute!(self.buffer, EnableMouseCapture)?;
         }
-        if self.supports_keyboard_enhancement_protocol() {
+        self.keyboard_enhancement_enabled = match config.kitty_keyboard_protocol {
+            KittyKeyboardProtocolConfig::Auto => self.supports_keyboard_enhancement_protocol(),
+            KittyKeyboardProtocolConfig::Disabled => false,
+            KittyKeyboardProtocolConfig::Enabled => true,
+        };
+        if self.keyboard_enhancement_enabled {
             execute!(
                 self.buffer,
                 PushKeyboardEnhancementFlags(
@@ -142,8 +257,14 @@ fn restore(&mut self, config: Config) -> io::Result<()> {
         if config.enable_mouse_capture {
             execute!(self.buffer, DisableMouseCapture)?;
         }
-        if self.supports_keyboard_enhancement_protocol() {
+        if self.keyboard_enhancement_enabled {
             execute!(self.buffer, PopKeyboardEnhancementFlags)?;
+            self.keyboard_enhancement_enabled = false;
+        }
+        if !self.transmitted_images.is_empty() {
+            // Free the memory used by the images in the terminal
+            write!(self.buffer, "\x1b_Ga=d,d=A,q=2\x1b\\")?;
+            self.transmitted_images.clear();
         }
         execute!(
             self.buffer,
@@ -181,13 +302,25 @@ fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
         let mut underline_color = Color::Reset;
         let mut underline_style = UnderlineStyle::Reset;
         let mut modifier = Modifier::empty();
-        let mut last_pos: Option<(u16, u16)> = None;
+        // Where the terminal cursor is after printing the last cell.
+        let mut cursor: Option<(u16, u16)> = None;
         for (x, y, cell) in content {
-            // Move the cursor if the previous location was not (x - 1, y)
-            if !matches!(last_pos, Some(p) if x == p.0 + 1 && y == p.1) {
-                map_error(queue!(self.buffer, MoveTo(x, y)))?;
+            // Jump to the cell with the shortest sequence, if it's not where the cursor is.
+            match cursor {
+                Some(cursor) if cursor == (x, y) => (),
+                Some((cursor_x, cursor_y)) if cursor_y == y && cursor_x < x => {
+                    map_error(queue!(self.buffer, MoveRight(x - cursor_x)))?;
+                }
+                Some((_, cursor_y)) if cursor_y + 1 == y && x == 0 => {
+                    self.buffer.write_all(b"\r\n")?;
+                }
+                _ => map_error(queue!(self.buffer, MoveTo(x, y)))?,
             }
-            last_pos = Some((x, y));
+            // Terminals don't agree on the width of some wide graphemes (emoji sequences for
+            // example), so the cursor position is only trusted after narrow cells and the cell
+            // after a wide one is moved to absolutely.
+            let width = cell.symbol.width();
+            cursor = (width <= 1).then(|| (x + 1, y));
             if cell.modifier != modifier {
                 let diff = ModifierDiff {
                     from: modifier,
@@ -239,6 +372,46 @@ fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
         ))
     }
 
+    fn draw_images(&mut self, images: &[(Rect, Image)]) -> io::Result<()> {
+        // Images are displayed with the kitty graphics protocol:
+        // https://sw.kovidgoyal.net/kitty/graphics-protocol/
+        // Delete all placements but keep the image data around for later placements.
+        write!(self.buffer, "\x1b_Ga=d,d=a,q=2\x1b\\")?;
+        for (area, image) in images {
+            map_error(queue!(self.buffer, MoveTo(area.x, area.y)))?;
+            let placement = format!("i={},c={},r={},C=1,q=2", image.id, area.width, area.height);
+            if !self.transmitted_images.insert(image.id) {
+                write!(self.buffer, "\x1b_Ga=p,{}\x1b\\", placement)?;
+                continue;
+            }
+
+            // Transmit the PNG data and display it in one go. The payload is split into chunks
+            // since terminals limit the size of escape sequences.
+            const CHUNK_SIZE: usize = 4096;
+            let data = helix_view::base64::encode(&image.data);
+            let chunks = data.as_bytes().chunks(CHUNK_SIZE);
+            let count = chunks.len();
+            for (i, chunk) in chunks.enumerate() {
+                let more = u8::from(i + 1 < count);
+                let chunk = std::str::from_utf8(chunk).expect("base64 is ascii");
+                if i == 0 {
+                    write!(
+                        self.buffer,
+                        "\x1b_Ga=T,f=100,{},m={};{}\x1b\\",
+                        placement, more, chunk
+                    )?;
+                } else {
+                    write!(self.buffer, "\x1b_Gm={};{}\x1b\\", more, chunk)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn supports_images(&self) -> bool {
+        self.supports_kitty_graphics.get() == Some(&true)
+    }
+
     fn hide_cursor(&mut self) -> io::Result<()> {
         map_error(execute!(self.buffer, Hide))
     }
@@ -398,3 +571,27 @@ fn execute_winapi(&self) -> crossterm::Result<()> {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_kitty_graphics_reply() {
+        assert_eq!(parse_kitty_graphics_reply(b""), None);
+        assert_eq!(parse_kitty_graphics_reply(b"\x1b_Gi=31;OK\x1b\\"), None);
+        assert_eq!(
+            parse_kitty_graphics_reply(b"\x1b_Gi=31;OK\x1b\\\x1b[?62;"),
+            None
+        );
+        assert_eq!(
+            parse_kitty_graphics_reply(b"\x1b_Gi=31;OK\x1b\\\x1b[?62;22c"),
+            Some(true)
+        );
+        assert_eq!(
+            parse_kitty_graphics_reply(b"\x1b_Gi=31;ENODATA:unsupported\x1b\\\x1b[?62c"),
+            Some(false)
+        );
+        assert_eq!(parse_kitty_graphics_reply(b"\x1b[?1;2c"), Some(false));
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::{buffer::Cell, terminal::Config};
 
-use helix_view::graphics::{CursorKind, Rect};
+use helix_view::graphics::{CursorKind, Image, Rect};
 
 #[cfg(feature = "crossterm")]
 mod crossterm;
@@ -19,6 +19,10 @@ pub trait Backend {
     fn draw<'a, I>(&mut self, content: I) -> Result<(), io::Error>
     where
         I: Iterator<Item = (u16, u16, &'a Cell)>;
+    /// Replaces all images currently displayed with `images`.
+    fn draw_images(&mut self, images: &[(Rect, Image)]) -> Result<(), io::Error>;
+    /// Whether the terminal can display images, as detected when it was claimed.
+    fn supports_images(&self) -> bool;
     fn hide_cursor(&mut self) -> Result<(), io::Error>;
     fn show_cursor(&mut self, kind: CursorKind) -> Result<(), io::Error>;
     fn get_cursor(&mut self) -> Result<(u16, u16), io::Error>;
@@ -4,7 +4,7 @@
     terminal::Config,
 };
 use helix_core::unicode::width::UnicodeWidthStr;
-use helix_view::graphics::{CursorKind, Rect};
+use helix_view::graphics::{CursorKind, Image, Rect};
 use std::{fmt::Write, io};
 
 /// A backend used for the integration tests.
@@ -129,6 +129,15 @@ fn draw<'a, I>(&mut self, content: I) -> Result<(), io::Error>
         Ok(())
     }
 
+    fn draw_images(&mut self, images: &[(Rect, Image)]) -> Result<(), io::Error> {
+        self.buffer.images = images.to_vec();
+        Ok(())
+    }
+
+    fn supports_images(&self) -> bool {
+        false
+    }
+
     fn hide_cursor(&mut self) -> Result<(), io::Error> {
         self.cursor = false;
         Ok(())
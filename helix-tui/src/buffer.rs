@@ -3,7 +3,7 @@
 use std::cmp::min;
 use unicode_segmentation::UnicodeSegmentation;
 
-use helix_view::graphics::{Color, Modifier, Rect, Style, UnderlineStyle};
+use helix_view::graphics::{Color, Image, Modifier, Rect, Style, UnderlineStyle};
 
 /// A buffer cell
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -126,6 +126,8 @@ pub struct Buffer {
     /// The content of the buffer. The length of this Vec should always be equal to area.width *
     /// area.height
     pub content: Vec<Cell>,
+    /// Images drawn on top of the content, see [`Buffer::set_image`].
+    pub images: Vec<(Rect, Image)>,
 }
 
 impl Buffer {
@@ -142,7 +144,11 @@ pub fn filled(area: Rect, cell: &Cell) -> Buffer {
         for _ in 0..size {
             content.push(cell.clone());
         }
-        Buffer { area, content }
+        Buffer {
+            area,
+            content,
+            images: Vec::new(),
+        }
     }
 
     /// Returns a Buffer containing the given lines
@@ -531,15 +537,29 @@ pub fn resize(&mut self, area: Rect) {
         self.area = area;
     }
 
-    /// Reset all cells in the buffer
+    /// Reset all cells in the buffer and remove all images
     pub fn reset(&mut self) {
         for c in &mut self.content {
             c.reset();
         }
+        self.images.clear();
+    }
+
+    /// Draw `image` over `area`, replacing any image previously drawn there.
+    ///
+    /// Images are only displayed by backends supporting an image protocol, callers are expected
+    /// to check for support and render a textual fallback otherwise.
+    pub fn set_image(&mut self, area: Rect, image: Image) {
+        let area = area.intersection(self.area);
+        self.images.retain(|(rect, _)| !rect.intersects(area));
+        if area.area() > 0 {
+            self.images.push((area, image));
+        }
     }
 
     /// Clear an area in the buffer
     pub fn clear(&mut self, area: Rect) {
+        self.images.retain(|(rect, _)| !rect.intersects(area));
         for x in area.left()..area.right() {
             for y in area.top()..area.bottom() {
                 self[(x, y)].reset();
@@ -549,6 +569,7 @@ pub fn clear(&mut self, area: Rect) {
 
     /// Clear an area in the buffer with a default style.
     pub fn clear_with(&mut self, area: Rect, style: Style) {
+        self.images.retain(|(rect, _)| !rect.intersects(area));
         for x in area.left()..area.right() {
             for y in area.top()..area.bottom() {
                 let cell = &mut self[(x, y)];
@@ -585,6 +606,8 @@ pub fn merge(&mut self, other: &Buffer) {
             let k = ((y - area.y) * area.width + x - area.x) as usize;
             self.content[k] = other.content[i].clone();
         }
+        self.images.retain(|(rect, _)| !rect.intersects(other.area));
+        self.images.extend(other.images.iter().cloned());
         self.area = area;
     }
 
@@ -622,23 +645,37 @@ pub fn diff<'a>(&self, other: &'a Buffer) -> Vec<(u16, u16, &'a Cell)> {
         let width = self.area.width;
 
         let mut updates: Vec<(u16, u16, &Cell)> = vec![];
+        if width == 0 {
+            return updates;
+        }
         // Cells invalidated by drawing/replacing preceding multi-width characters:
         let mut invalidated: usize = 0;
         // Cells from the current buffer to skip due to preceding multi-width characters taking their
         // place (the skipped cells should be blank anyway):
         let mut to_skip: usize = 0;
-        for (i, (current, previous)) in next_buffer.iter().zip(previous_buffer.iter()).enumerate() {
-            if (current != previous || invalidated > 0) && to_skip == 0 {
-                let x = (i % width as usize) as u16;
-                let y = (i / width as usize) as u16;
-                updates.push((x, y, &next_buffer[i]));
+        let rows = next_buffer
+            .chunks(width as usize)
+            .zip(previous_buffer.chunks(width as usize));
+        for (y, (next_row, previous_row)) in rows.enumerate() {
+            // Most rows of a frame are unchanged: skip them without comparing cell by cell.
+            if invalidated == 0 && to_skip == 0 && next_row == previous_row {
+                let last_width = next_row.last().map_or(1, |cell| cell.symbol.width());
+                to_skip = last_width.saturating_sub(1);
+                invalidated = to_skip;
+                continue;
             }
 
-            let current_width = current.symbol.width();
-            to_skip = current_width.saturating_sub(1);
+            for (x, (current, previous)) in next_row.iter().zip(previous_row).enumerate() {
+                if (current != previous || invalidated > 0) && to_skip == 0 {
+                    updates.push((x as u16, y as u16, current));
+                }
 
-            let affected_width = std::cmp::max(current_width, previous.symbol.width());
-            invalidated = std::cmp::max(affected_width, invalidated).saturating_sub(1);
+                let current_width = current.symbol.width();
+                to_skip = current_width.saturating_sub(1);
+
+                let affected_width = std::cmp::max(current_width, previous.symbol.width());
+                invalidated = std::cmp::max(affected_width, invalidated).saturating_sub(1);
+            }
         }
         updates
     }
@@ -847,6 +884,15 @@ fn buffer_diffing_multi_width() {
         );
     }
 
+    #[test]
+    fn buffer_diffing_unchanged_rows() {
+        let prev = Buffer::with_lines(vec!["称号──", "abcdef", "┌──┐  "]);
+        let next = Buffer::with_lines(vec!["称号──", "abcdef", "┌─x┐  "]);
+
+        let diff = prev.diff(&next);
+        assert_eq!(diff, vec![(2, 2, &cell("x"))]);
+    }
+
     #[test]
     fn buffer_diffing_multi_width_offset() {
         let prev = Buffer::with_lines(vec!["┌称号──┐"]);
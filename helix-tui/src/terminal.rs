@@ -1,5 +1,5 @@
 use crate::{backend::Backend, buffer::Buffer};
-use helix_view::editor::Config as EditorConfig;
+use helix_view::editor::{Config as EditorConfig, GraphicsProtocol, KittyKeyboardProtocolConfig};
 use helix_view::graphics::{CursorKind, Rect};
 use std::io;
 
@@ -20,12 +20,16 @@ pub struct Viewport {
 #[derive(Debug)]
 pub struct Config {
     pub enable_mouse_capture: bool,
+    pub kitty_keyboard_protocol: KittyKeyboardProtocolConfig,
+    pub graphics_protocol: GraphicsProtocol,
 }
 
 impl From<EditorConfig> for Config {
     fn from(config: EditorConfig) -> Self {
         Self {
             enable_mouse_capture: config.mouse,
+            kitty_keyboard_protocol: config.kitty_keyboard_protocol,
+            graphics_protocol: config.graphics_protocol,
         }
     }
 }
@@ -146,7 +150,11 @@ pub fn flush(&mut self) -> io::Result<()> {
         let previous_buffer = &self.buffers[1 - self.current];
         let current_buffer = &self.buffers[self.current];
         let updates = previous_buffer.diff(current_buffer);
-        self.backend.draw(updates.into_iter())
+        self.backend.draw(updates.into_iter())?;
+        if previous_buffer.images != current_buffer.images {
+            self.backend.draw_images(&current_buffer.images)?;
+        }
+        Ok(())
     }
 
     /// Updates the Terminal so that internal buffers match the requested size. Requested size will
@@ -0,0 +1,120 @@
+//! The locations of the recent edits of a document, which can be revisited like a jumplist.
+
+use std::{collections::VecDeque, time::Instant};
+
+use helix_core::{Assoc, ChangeSet, RopeSlice};
+
+/// The maximum number of locations remembered per document.
+const CHANGE_LIST_CAPACITY: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct ChangeList {
+    /// Character positions of the edits, the oldest first.
+    locations: VecDeque<usize>,
+    /// The location navigated to last, or `locations.len()` while not navigating.
+    current: usize,
+    /// When the document was last edited.
+    last_change: Option<Instant>,
+}
+
+impl Default for ChangeList {
+    fn default() -> Self {
+        Self {
+            locations: VecDeque::with_capacity(CHANGE_LIST_CAPACITY),
+            current: 0,
+            last_change: None,
+        }
+    }
+}
+
+impl ChangeList {
+    /// Records an edit at `pos`. An edit on the same line as the latest one replaces it, so
+    /// typing a word doesn't fill the list with a location per character.
+    pub fn push(&mut self, text: RopeSlice, pos: usize) {
+        let line = text.char_to_line(pos);
+        if self.locations.back().map_or(false, |&last| {
+            text.char_to_line(last.min(text.len_chars())) == line
+        }) {
+            self.locations.pop_back();
+        }
+        while self.locations.len() >= CHANGE_LIST_CAPACITY {
+            self.locations.pop_front();
+        }
+        self.locations.push_back(pos);
+        self.current = self.locations.len();
+        self.last_change = Some(Instant::now());
+    }
+
+    /// Maps the locations through `changes` so they keep pointing to the same text.
+    pub fn apply(&mut self, changes: &ChangeSet) {
+        for location in &mut self.locations {
+            *location = changes.map_pos(*location, Assoc::After);
+        }
+    }
+
+    /// Moves `count` locations back to an older edit.
+    pub fn backward(&mut self, count: usize) -> Option<usize> {
+        let current = self.current.checked_sub(count)?;
+        self.current = current;
+        self.locations.get(current).copied()
+    }
+
+    /// Moves `count` locations forward to a newer edit.
+    pub fn forward(&mut self, count: usize) -> Option<usize> {
+        let current = self.current + count;
+        let location = self.locations.get(current).copied()?;
+        self.current = current;
+        Some(location)
+    }
+
+    /// Location of the latest edit. Navigation continues from there.
+    pub fn last(&mut self) -> Option<usize> {
+        self.current = self.locations.len().checked_sub(1)?;
+        self.locations.back().copied()
+    }
+
+    /// When the document was last edited, if it was.
+    pub fn last_change(&self) -> Option<Instant> {
+        self.last_change
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helix_core::{Rope, Transaction};
+
+    #[test]
+    fn navigate_change_list() {
+        let text = Rope::from("one\ntwo\nthree\n");
+        let text = text.slice(..);
+        let mut changes = ChangeList::default();
+        assert_eq!(changes.backward(1), None);
+
+        changes.push(text, 0);
+        changes.push(text, 5);
+        // Same line as the previous edit.
+        changes.push(text, 6);
+        changes.push(text, 9);
+
+        assert_eq!(changes.backward(1), Some(9));
+        assert_eq!(changes.backward(1), Some(6));
+        assert_eq!(changes.backward(1), Some(0));
+        assert_eq!(changes.backward(1), None);
+        assert_eq!(changes.forward(2), Some(9));
+        assert_eq!(changes.forward(1), None);
+        assert_eq!(changes.last(), Some(9));
+        assert_eq!(changes.backward(2), Some(0));
+    }
+
+    #[test]
+    fn apply_maps_locations() {
+        let doc = Rope::from("one\ntwo\n");
+        let mut changes = ChangeList::default();
+        changes.push(doc.slice(..), 4);
+
+        let transaction = Transaction::change(&doc, [(0, 0, Some("zero\n".into()))].into_iter());
+        changes.apply(transaction.changes());
+        assert_eq!(changes.last(), Some(9));
+    }
+}
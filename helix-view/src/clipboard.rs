@@ -1,5 +1,6 @@
 // Implementation reference: https://github.com/neovim/neovim/blob/f2906a4669a2eef6d7bf86a29648793d63c98949/runtime/autoload/provider/clipboard.vim#L68-L152
 
+use crate::editor::ClipboardConfig;
 use anyhow::Result;
 use std::borrow::Cow;
 
@@ -22,7 +23,7 @@ macro_rules! command_provider {
             "Using {} to interact with the system clipboard",
             if $set_prg != $get_prg { format!("{}+{}", $set_prg, $get_prg)} else { $set_prg.to_string() }
         );
-        Box::new(provider::command::Provider {
+        Some(Box::new(provider::command::Provider {
             get_cmd: provider::command::Config {
                 prg: $get_prg,
                 args: &[ $( $get_arg ),* ],
@@ -33,7 +34,7 @@ macro_rules! command_provider {
             },
             get_primary_cmd: None,
             set_primary_cmd: None,
-        })
+        }))
     }};
 
     (paste => $get_prg:literal $( , $get_arg:literal )* ;
@@ -45,7 +46,7 @@ macro_rules! command_provider {
             "Using {} to interact with the system and selection (primary) clipboard",
             if $set_prg != $get_prg { format!("{}+{}", $set_prg, $get_prg)} else { $set_prg.to_string() }
         );
-        Box::new(provider::command::Provider {
+        Some(Box::new(provider::command::Provider {
             get_cmd: provider::command::Config {
                 prg: $get_prg,
                 args: &[ $( $get_arg ),* ],
@@ -62,17 +63,53 @@ macro_rules! command_provider {
                 prg: $pr_set_prg,
                 args: &[ $( $pr_set_arg ),* ],
             }),
-        })
+        }))
     }};
 }
 
 #[cfg(windows)]
-pub fn get_clipboard_provider() -> Box<dyn ClipboardProvider> {
+pub fn get_clipboard_provider(_config: &ClipboardConfig) -> Box<dyn ClipboardProvider> {
     Box::new(provider::WindowsProvider::default())
 }
 
+#[cfg(not(windows))]
+pub fn get_clipboard_provider(config: &ClipboardConfig) -> Box<dyn ClipboardProvider> {
+    use crate::editor::ClipboardProviderKind;
+
+    match config.provider {
+        ClipboardProviderKind::Auto if is_remote_session() => osc52_provider(config),
+        ClipboardProviderKind::Auto => {
+            native_clipboard_provider().unwrap_or_else(|| osc52_provider(config))
+        }
+        ClipboardProviderKind::Osc52 => osc52_provider(config),
+        ClipboardProviderKind::None => Box::new(provider::FallbackProvider::new()),
+    }
+}
+
+/// Whether Helix runs in an SSH session without a forwarded display server, where native
+/// clipboard tools would only reach the clipboard of the remote machine.
+#[cfg(not(windows))]
+fn is_remote_session() -> bool {
+    use crate::env::env_var_is_set;
+
+    (env_var_is_set("SSH_TTY") || env_var_is_set("SSH_CONNECTION"))
+        && !env_var_is_set("DISPLAY")
+        && !env_var_is_set("WAYLAND_DISPLAY")
+}
+
+#[cfg(all(not(windows), feature = "term"))]
+fn osc52_provider(config: &ClipboardConfig) -> Box<dyn ClipboardProvider> {
+    log::debug!("Using OSC 52 to interact with the system clipboard");
+    Box::new(provider::osc52::Provider::new(config.osc52_max_size))
+}
+
+#[cfg(all(not(windows), not(feature = "term")))]
+fn osc52_provider(_config: &ClipboardConfig) -> Box<dyn ClipboardProvider> {
+    Box::new(provider::FallbackProvider::new())
+}
+
 #[cfg(target_os = "macos")]
-pub fn get_clipboard_provider() -> Box<dyn ClipboardProvider> {
+fn native_clipboard_provider() -> Option<Box<dyn ClipboardProvider>> {
     use crate::env::binary_exists;
 
     if binary_exists("pbcopy") && binary_exists("pbpaste") {
@@ -81,18 +118,18 @@ pub fn get_clipboard_provider() -> Box<dyn ClipboardProvider> {
             copy => "pbcopy";
         }
     } else {
-        Box::new(provider::FallbackProvider::new())
+        None
     }
 }
 
 #[cfg(target_os = "wasm32")]
-pub fn get_clipboard_provider() -> Box<dyn ClipboardProvider> {
+fn native_clipboard_provider() -> Option<Box<dyn ClipboardProvider>> {
     // TODO:
-    Box::new(provider::FallbackProvider::new())
+    None
 }
 
 #[cfg(not(any(windows, target_os = "wasm32", target_os = "macos")))]
-pub fn get_clipboard_provider() -> Box<dyn ClipboardProvider> {
+fn native_clipboard_provider() -> Option<Box<dyn ClipboardProvider>> {
     use crate::env::{binary_exists, env_var_is_set};
     use provider::command::is_exit_success;
     // TODO: support for user-defined provider, probably when we have plugin support by setting a
@@ -139,7 +176,7 @@ pub fn get_clipboard_provider() -> Box<dyn ClipboardProvider> {
             copy => "tmux", "load-buffer", "-w", "-";
         }
     } else {
-        Box::new(provider::FallbackProvider::new())
+        None
     }
 }
 
@@ -150,8 +187,11 @@ pub mod provider {
     use std::borrow::Cow;
 
     #[cfg(feature = "term")]
-    mod osc52 {
-        use {super::ClipboardType, crate::base64, crossterm};
+    pub mod osc52 {
+        use super::{ClipboardProvider, ClipboardType};
+        use crate::base64;
+        use anyhow::{bail, Result};
+        use std::borrow::Cow;
 
         #[derive(Debug)]
         pub struct SetClipboardCommand {
@@ -178,8 +218,72 @@ fn write_ansi(&self, f: &mut impl std::fmt::Write) -> std::fmt::Result {
                 write!(f, "\x1b]52;{};{}\x1b\\", kind, &self.encoded_content)
             }
         }
+
+        /// Sets the system clipboard through the terminal emulator with OSC 52 escape
+        /// sequences, which also works over SSH and inside terminal multiplexers that forward
+        /// them (tmux needs `set-clipboard on`).
+        #[derive(Debug)]
+        pub struct Provider {
+            buf: String,
+            primary_buf: String,
+            /// Maximum size of the encoded content, terminals drop or truncate larger sequences.
+            max_size: usize,
+        }
+
+        impl Provider {
+            pub fn new(max_size: usize) -> Self {
+                Self {
+                    buf: String::new(),
+                    primary_buf: String::new(),
+                    max_size,
+                }
+            }
+        }
+
+        impl ClipboardProvider for Provider {
+            fn name(&self) -> Cow<str> {
+                Cow::Borrowed("termcode")
+            }
+
+            fn get_contents(&self, clipboard_type: ClipboardType) -> Result<String> {
+                // We don't use the get side of OSC 52 as it isn't often enabled, it's a security
+                // hole, and it would require this to be async to listen for the response
+                let value = match clipboard_type {
+                    ClipboardType::Clipboard => self.buf.clone(),
+                    ClipboardType::Selection => self.primary_buf.clone(),
+                };
+
+                Ok(value)
+            }
+
+            fn set_contents(
+                &mut self,
+                content: String,
+                clipboard_type: ClipboardType,
+            ) -> Result<()> {
+                let command = SetClipboardCommand::new(&content, clipboard_type);
+                let size = command.encoded_content.len();
+                if size <= self.max_size {
+                    crossterm::execute!(std::io::stdout(), command)?;
+                }
+                // Set our internal variables to use in get_content regardless of using OSC 52
+                match clipboard_type {
+                    ClipboardType::Clipboard => self.buf = content,
+                    ClipboardType::Selection => self.primary_buf = content,
+                }
+                if size > self.max_size {
+                    bail!(
+                        "yank of {} bytes exceeds the OSC 52 limit of {} bytes, kept internally",
+                        size,
+                        self.max_size
+                    );
+                }
+                Ok(())
+            }
+        }
     }
 
+    /// Keeps yanked text internal to Helix.
     #[derive(Debug)]
     pub struct FallbackProvider {
         buf: String,
@@ -188,13 +292,8 @@ pub struct FallbackProvider {
 
     impl FallbackProvider {
         pub fn new() -> Self {
-            #[cfg(feature = "term")]
-            log::debug!(
-                "No native clipboard provider found. Yanking by OSC 52 and pasting will be internal to Helix"
-            );
-            #[cfg(not(feature = "term"))]
             log::warn!(
-                "No native clipboard provider found! Yanking and pasting will be internal to Helix"
+                "No system clipboard provider in use! Yanking and pasting will be internal to Helix"
             );
             Self {
                 buf: String::new(),
@@ -210,20 +309,11 @@ fn default() -> Self {
     }
 
     impl ClipboardProvider for FallbackProvider {
-        #[cfg(feature = "term")]
-        fn name(&self) -> Cow<str> {
-            Cow::Borrowed("termcode")
-        }
-
-        #[cfg(not(feature = "term"))]
         fn name(&self) -> Cow<str> {
             Cow::Borrowed("none")
         }
 
         fn get_contents(&self, clipboard_type: ClipboardType) -> Result<String> {
-            // This is the same noop if term is enabled or not.
-            // We don't use the get side of OSC 52 as it isn't often enabled, it's a security hole,
-            // and it would require this to be async to listen for the response
             let value = match clipboard_type {
                 ClipboardType::Clipboard => self.buf.clone(),
                 ClipboardType::Selection => self.primary_buf.clone(),
@@ -233,12 +323,6 @@ fn get_contents(&self, clipboard_type: ClipboardType) -> Result<String> {
         }
 
         fn set_contents(&mut self, content: String, clipboard_type: ClipboardType) -> Result<()> {
-            #[cfg(feature = "term")]
-            crossterm::execute!(
-                std::io::stdout(),
-                osc52::SetClipboardCommand::new(&content, clipboard_type)
-            )?;
-            // Set our internal variables to use in get_content regardless of using OSC 52
             match clipboard_type {
                 ClipboardType::Clipboard => self.buf = content,
                 ClipboardType::Selection => self.primary_buf = content,
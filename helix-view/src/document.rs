@@ -5,6 +5,7 @@
 use futures_util::FutureExt;
 use helix_core::auto_pairs::AutoPairs;
 use helix_core::doc_formatter::TextFormat;
+use helix_core::link::Link;
 use helix_core::syntax::Highlight;
 use helix_core::text_annotations::{InlineAnnotation, TextAnnotations};
 use helix_core::Range;
@@ -34,6 +35,7 @@
     DEFAULT_LINE_ENDING,
 };
 
+use crate::change_list::ChangeList;
 use crate::editor::{Config, RedrawHandle};
 use crate::{DocumentId, Editor, Theme, View, ViewId};
 
@@ -127,12 +129,23 @@ pub struct Document {
     /// Set to `true` when the document is updated, reset to `false` on the next inlay hints
     /// update from the LSP
     pub inlay_hints_oudated: bool,
+    /// Occurrences of the symbol under the cursor, by view.
+    pub(crate) document_highlights: HashMap<ViewId, DocumentHighlights>,
+    /// Ranges edited together in insert mode, like the names of an opening and closing tag.
+    linked_editing_ranges: Vec<Range>,
+    /// Links reported by the language server.
+    document_links: Option<DocumentLinks>,
+    /// Results of the last runs of the tests of the document.
+    test_results: Vec<TestResult>,
 
     path: Option<PathBuf>,
     encoding: &'static encoding::Encoding,
 
     pub restore_cursor: bool,
 
+    /// Whether changes to the text are rejected, e.g. for `:help` pages.
+    pub readonly: bool,
+
     /// Current indent style.
     pub indent_style: IndentStyle,
 
@@ -152,6 +165,8 @@ pub struct Document {
     // it back as it separated from the edits. We could split out the parts manually but that will
     // be more troublesome.
     pub history: Cell<History>,
+    /// Locations of the recent edits.
+    pub change_list: ChangeList,
     pub config: Arc<dyn DynAccess<Config>>,
 
     savepoints: Vec<Weak<SavePoint>>,
@@ -165,6 +180,8 @@ pub struct Document {
     pub(crate) modified_since_accessed: bool,
 
     diagnostics: Vec<Diagnostic>,
+    /// Diagnostics hidden by the `diagnostic-filter`, kept to be shown again if it changes.
+    hidden_diagnostics: Vec<Diagnostic>,
     language_server: Option<Arc<helix_lsp::Client>>,
 
     diff_handle: Option<DiffHandle>,
@@ -224,6 +241,45 @@ pub fn empty_with_id(id: DocumentInlayHintsId) -> Self {
     }
 }
 
+/// Occurrences of the symbol under the cursor of a view, as reported by the LSP
+/// `textDocument/documentHighlight` request.
+#[derive(Debug, Clone)]
+pub struct DocumentHighlights {
+    /// Version of the document the highlights were requested for.
+    pub version: i32,
+    /// Position of the primary cursor the highlights were requested for. The highlights are
+    /// stale as soon as it moves.
+    pub cursor: usize,
+    /// Ranges of the occurrences, along with whether the symbol is written to.
+    pub ranges: Vec<(Range, bool)>,
+}
+
+/// Links of a document, as reported by the LSP `textDocument/documentLink` request.
+#[derive(Debug, Clone)]
+pub struct DocumentLinks {
+    /// Version of the document the links were requested for.
+    pub version: i32,
+    pub links: Vec<Link>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestStatus {
+    Running,
+    Passed,
+    Failed,
+}
+
+/// The result of running a test of a document with the test runner of its language.
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    pub name: String,
+    /// Start of the test, mapped through the changes to the document.
+    pub pos: usize,
+    pub status: TestStatus,
+    /// Output of the test runner once the test finished.
+    pub output: String,
+}
+
 /// Associated with a [`Document`] and [`ViewId`], uniquely identifies the state of inlay hints for
 /// for that document and view: if this changed since the last save, the inlay hints for the view
 /// should be recomputed.
@@ -478,16 +534,23 @@ pub fn from(
             selections: HashMap::default(),
             inlay_hints: HashMap::default(),
             inlay_hints_oudated: false,
+            document_highlights: HashMap::default(),
             indent_style: DEFAULT_INDENT,
             line_ending: DEFAULT_LINE_ENDING,
             restore_cursor: false,
+            linked_editing_ranges: Vec::new(),
+            document_links: None,
+            test_results: Vec::new(),
+            readonly: false,
             syntax: None,
             language: None,
             changes,
             old_state,
             diagnostics: Vec::new(),
+            hidden_diagnostics: Vec::new(),
             version: 0,
             history: Cell::new(History::default()),
+            change_list: ChangeList::default(),
             savepoints: Vec::new(),
             last_saved_time: SystemTime::now(),
             last_saved_revision: 0,
@@ -908,10 +971,11 @@ pub fn ensure_view_init(&mut self, view_id: ViewId) {
         }
     }
 
-    /// Remove a view's selection and inlay hints from this document.
+    /// Remove a view's selection, inlay hints and document highlights from this document.
     pub fn remove_view(&mut self, view_id: ViewId) {
         self.selections.remove(&view_id);
         self.inlay_hints.remove(&view_id);
+        self.document_highlights.remove(&view_id);
     }
 
     /// Apply a [`Transaction`] to the [`Document`] to change its text.
@@ -919,6 +983,7 @@ fn apply_impl(&mut self, transaction: &Transaction, view_id: ViewId) -> bool {
         use helix_core::Assoc;
 
         let old_doc = self.text().clone();
+        let old_primary = self.selections.get(&view_id).map(Selection::primary);
 
         let success = transaction.changes().apply(&mut self.text);
 
@@ -975,21 +1040,54 @@ fn apply_impl(&mut self, transaction: &Transaction, view_id: ViewId) -> bool {
 
             let changes = transaction.changes();
 
-            // map state.diagnostics over changes::map_pos too
-            for diagnostic in &mut self.diagnostics {
-                diagnostic.range.start = changes.map_pos(diagnostic.range.start, Assoc::After);
-                diagnostic.range.end = changes.map_pos(diagnostic.range.end, Assoc::After);
-                diagnostic.line = self.text.char_to_line(diagnostic.range.start);
+            // remember the location of the change made at the primary cursor
+            self.change_list.apply(changes);
+            let (_from, to, _fragment) = transaction
+                .changes_iter()
+                .find(|(from, to, _fragment)| {
+                    old_primary.map_or(false, |primary| Range::new(*from, *to).overlaps(&primary))
+                })
+                .or_else(|| transaction.changes_iter().next())
+                .unwrap();
+            let pos = changes.map_pos(to, Assoc::After);
+            self.change_list.push(self.text.slice(..), pos);
+
+            for result in &mut self.test_results {
+                result.pos = changes.map_pos(result.pos, Assoc::Before);
+            }
+
+            // grow the linked ranges when typing at their edges
+            for range in &mut self.linked_editing_ranges {
+                range.anchor = changes.map_pos(range.anchor, Assoc::Before);
+                range.head = changes.map_pos(range.head, Assoc::After);
+            }
+
+            // map state.diagnostics over changes::map_pos too. They are sorted by range, so
+            // the starts are mapped in a single pass over the changes.
+            for diagnostics in [&mut self.diagnostics, &mut self.hidden_diagnostics] {
+                changes.update_positions(
+                    diagnostics
+                        .iter_mut()
+                        .map(|diagnostic| (&mut diagnostic.range.start, Assoc::After)),
+                );
+                changes.update_positions(
+                    diagnostics
+                        .iter_mut()
+                        .map(|diagnostic| (&mut diagnostic.range.end, Assoc::After)),
+                );
+                for diagnostic in diagnostics.iter_mut() {
+                    diagnostic.line = self.text.char_to_line(diagnostic.range.start);
+                }
+                diagnostics.sort_unstable_by_key(|diagnostic| diagnostic.range);
             }
-            self.diagnostics
-                .sort_unstable_by_key(|diagnostic| diagnostic.range);
 
             // Update the inlay hint annotations' positions, helping ensure they are displayed in the proper place
             let apply_inlay_hint_changes = |annotations: &mut Rc<[InlineAnnotation]>| {
                 if let Some(data) = Rc::get_mut(annotations) {
-                    for inline in data.iter_mut() {
-                        inline.char_idx = changes.map_pos(inline.char_idx, Assoc::After);
-                    }
+                    changes.update_positions(
+                        data.iter_mut()
+                            .map(|inline| (&mut inline.char_idx, Assoc::After)),
+                    );
                 }
             };
 
@@ -1029,7 +1127,12 @@ fn apply_impl(&mut self, transaction: &Transaction, view_id: ViewId) -> bool {
     }
 
     /// Apply a [`Transaction`] to the [`Document`] to change its text.
+    /// Changes to [`readonly`](Self::readonly) documents are rejected.
     pub fn apply(&mut self, transaction: &Transaction, view_id: ViewId) -> bool {
+        if self.readonly && !transaction.changes().is_empty() {
+            return false;
+        }
+
         // store the state just before any changes are made. This allows us to undo to the
         // state just before a transaction was applied.
         if self.changes.is_empty() && !transaction.changes().is_empty() {
@@ -1380,15 +1483,79 @@ pub fn position(
         )
     }
 
+    pub fn linked_editing_ranges(&self) -> &[Range] {
+        &self.linked_editing_ranges
+    }
+
+    /// Sets the ranges mirrored into each other while editing in insert mode, sorted by position.
+    pub fn set_linked_editing_ranges(&mut self, mut ranges: Vec<Range>) {
+        ranges.sort_unstable_by_key(|range| range.from());
+        self.linked_editing_ranges = ranges;
+    }
+
+    pub fn set_document_links(&mut self, links: DocumentLinks) {
+        self.document_links = Some(links);
+    }
+
+    /// Get the links reported by the language server, unless the document was modified since
+    /// they were requested.
+    pub fn document_links(&self) -> Option<&[Link]> {
+        let links = self.document_links.as_ref()?;
+        (links.version == self.version).then_some(&links.links[..])
+    }
+
+    pub fn test_results(&self) -> &[TestResult] {
+        &self.test_results
+    }
+
+    /// Records the result of a test, replacing the previous result of the test starting at the
+    /// same position. Returns the index of the result, which [`Document::finish_test`] takes.
+    pub fn set_test_result(&mut self, result: TestResult) -> usize {
+        match self
+            .test_results
+            .iter()
+            .position(|previous| previous.pos == result.pos)
+        {
+            Some(index) => {
+                self.test_results[index] = result;
+                index
+            }
+            None => {
+                self.test_results.push(result);
+                self.test_results.len() - 1
+            }
+        }
+    }
+
+    /// Records the outcome of the test whose result is at `index`, which keeps its position.
+    pub fn finish_test(&mut self, index: usize, status: TestStatus, output: String) {
+        if let Some(result) = self.test_results.get_mut(index) {
+            result.status = status;
+            result.output = output;
+        }
+    }
+
     #[inline]
     pub fn diagnostics(&self) -> &[Diagnostic] {
         &self.diagnostics
     }
 
     pub fn set_diagnostics(&mut self, diagnostics: Vec<Diagnostic>) {
+        let config = self.config.load();
+        let (mut diagnostics, mut hidden_diagnostics): (Vec<_>, Vec<_>) = diagnostics
+            .into_iter()
+            .partition(|diagnostic| config.diagnostic_filter.allows(diagnostic));
+        diagnostics.sort_unstable_by_key(|diagnostic| diagnostic.range);
+        hidden_diagnostics.sort_unstable_by_key(|diagnostic| diagnostic.range);
         self.diagnostics = diagnostics;
-        self.diagnostics
-            .sort_unstable_by_key(|diagnostic| diagnostic.range);
+        self.hidden_diagnostics = hidden_diagnostics;
+    }
+
+    /// Applies a changed `diagnostic-filter` to the diagnostics of this document.
+    pub fn refilter_diagnostics(&mut self) {
+        let mut diagnostics = std::mem::take(&mut self.diagnostics);
+        diagnostics.append(&mut self.hidden_diagnostics);
+        self.set_diagnostics(diagnostics);
     }
 
     /// Get the document's auto pairs. If the document has a recognized
@@ -1496,6 +1663,23 @@ pub fn inlay_hints(&self, view_id: ViewId) -> Option<&DocumentInlayHints> {
     pub fn reset_all_inlay_hints(&mut self) {
         self.inlay_hints = Default::default();
     }
+
+    /// Set the document highlights for this document and `view_id`.
+    pub fn set_document_highlights(&mut self, view_id: ViewId, highlights: DocumentHighlights) {
+        self.document_highlights.insert(view_id, highlights);
+    }
+
+    /// Get the document highlights for `view_id`, unless the document was modified or the
+    /// cursor moved since they were requested.
+    pub fn document_highlights(&self, view_id: ViewId) -> Option<&DocumentHighlights> {
+        let highlights = self.document_highlights.get(&view_id)?;
+        let cursor = self
+            .selections
+            .get(&view_id)?
+            .primary()
+            .cursor(self.text.slice(..));
+        (highlights.version == self.version && highlights.cursor == cursor).then_some(highlights)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -1575,6 +1759,51 @@ fn changeset_to_changes_ignore_line_endings() {
         );
     }
 
+    #[test]
+    fn diagnostic_filter() {
+        use crate::editor::{DiagnosticFilter, Severity};
+        use helix_core::diagnostic::{Diagnostic, Range};
+
+        let diagnostic = |start, severity, source: &str| Diagnostic {
+            range: Range {
+                start,
+                end: start + 1,
+            },
+            line: 0,
+            message: String::new(),
+            severity: Some(severity),
+            code: None,
+            tags: Vec::new(),
+            source: Some(source.to_string()),
+            data: None,
+        };
+        let config = Arc::new(ArcSwap::new(Arc::new(Config {
+            diagnostic_filter: DiagnosticFilter {
+                severity: Severity::Warning,
+                disabled_sources: vec!["cspell".to_string()],
+            },
+            ..Default::default()
+        })));
+        let mut doc = Document::from(Rope::from("hello world"), None, config.clone());
+
+        doc.set_diagnostics(vec![
+            diagnostic(0, Severity::Error, "rustc"),
+            diagnostic(2, Severity::Hint, "rustc"),
+            diagnostic(4, Severity::Error, "cspell"),
+        ]);
+        let starts = |doc: &Document| -> Vec<_> {
+            doc.diagnostics()
+                .iter()
+                .map(|diagnostic| diagnostic.range.start)
+                .collect()
+        };
+        assert_eq!(starts(&doc), [0]);
+
+        config.store(Arc::new(Config::default()));
+        doc.refilter_diagnostics();
+        assert_eq!(starts(&doc), [0, 2, 4]);
+    }
+
     #[test]
     fn changeset_to_changes() {
         use helix_lsp::{lsp, Client, OffsetEncoding};
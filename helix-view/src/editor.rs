@@ -2,9 +2,13 @@
     align_view,
     clipboard::{get_clipboard_provider, ClipboardProvider},
     document::{DocumentSavedEventFuture, DocumentSavedEventResult, Mode},
+    file_watcher::FileWatcher,
+    global_jumplist::GlobalJumpList,
     graphics::{CursorKind, Rect},
     info::Info,
     input::KeyEvent,
+    quickfix::QuickfixList,
+    recent_files::RecentFiles,
     theme::{self, Theme},
     tree::{self, Tree},
     view::ViewPosition,
@@ -21,7 +25,7 @@
 use std::{
     borrow::Cow,
     cell::Cell,
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     io::stdin,
     num::NonZeroUsize,
     path::{Path, PathBuf},
@@ -39,11 +43,15 @@
 
 use anyhow::{anyhow, bail, Error};
 
+use helix_core::diagnostic::Diagnostic;
 pub use helix_core::diagnostic::Severity;
 pub use helix_core::register::Registers;
 use helix_core::{
     auto_pairs::AutoPairs,
-    syntax::{self, AutoPairConfig, SoftWrap},
+    coords_at_pos, pos_at_coords,
+    syntax::{self, AutoPairConfig, LanguageServerStart, SoftWrap},
+    tags::TagIndex,
+    trigram::TrigramIndex,
     Change,
 };
 use helix_core::{Position, Selection};
@@ -81,6 +89,8 @@ pub struct GutterConfig {
     pub layout: Vec<GutterType>,
     /// Options specific to the "line-numbers" gutter
     pub line_numbers: GutterLineNumbersConfig,
+    /// Widths of gutters by name, overriding the width the gutter needs.
+    pub widths: HashMap<String, usize>,
 }
 
 impl Default for GutterConfig {
@@ -94,6 +104,7 @@ fn default() -> Self {
                 GutterType::Diff,
             ],
             line_numbers: GutterLineNumbersConfig::default(),
+            widths: HashMap::new(),
         }
     }
 }
@@ -242,6 +253,16 @@ pub struct Config {
     pub auto_format: bool,
     /// Automatic save on focus lost. Defaults to false.
     pub auto_save: bool,
+    /// Permanently delete the files deleted by the editor instead of moving them to the trash.
+    /// Defaults to false.
+    pub hard_delete: bool,
+    /// Persist the recently edited files and the cursor positions in them across sessions.
+    /// Defaults to true.
+    pub persist_old_files: bool,
+    /// Persist the jumps made across files between sessions. Defaults to true.
+    pub persist_jumplist: bool,
+    /// Re-indent linewise pastes to match the indentation where they are pasted. Defaults to false.
+    pub reindent_paste: bool,
     /// Set a global text_width
     pub text_width: usize,
     /// Time in milliseconds since last keypress before idle timers trigger.
@@ -251,10 +272,34 @@ pub struct Config {
         deserialize_with = "deserialize_duration_millis"
     )]
     pub idle_timeout: Duration,
+    /// Delays of the tasks run when the editor is idle.
+    pub idle_timeouts: IdleTimeoutsConfig,
     pub completion_trigger_len: u8,
     /// Whether to instruct the LSP to replace the entire word when applying a completion
     /// or to only insert new text
     pub completion_replace: bool,
+    /// Criteria completion items matching the typed text are sorted by, in order of
+    /// precedence. Defaults to `["score"]`.
+    pub completion_sort: Vec<CompletionSort>,
+    /// Completion of the words of the open documents.
+    pub word_completion: WordCompletionConfig,
+    /// Whether to complete filesystem paths typed inside strings. Defaults to true.
+    pub path_completion: bool,
+    /// Comment tags collected by the todo picker, in the order they are grouped in.
+    pub todo_tags: Vec<String>,
+    /// Typable command aliases, mapping their name to the command line they run. The command
+    /// line may contain placeholders for the current document such as `%{filename}`.
+    pub command_aliases: HashMap<String, String>,
+    /// Keyboard layout whose keys are translated to the keys at the same positions of a QWERTY
+    /// keyboard when looking up key bindings. Defaults to `qwerty`, translating nothing.
+    pub keyboard_layout: KeyboardLayout,
+    /// Characters translated to other characters when looking up key bindings, after the
+    /// translation of `keyboard-layout`.
+    pub key_remap: HashMap<char, char>,
+    /// Whether to show the terminal cursor at the primary cursor in insert mode, even with a
+    /// block cursor shape, so that input methods place their candidate window and preedit
+    /// text at it. Defaults to false.
+    pub ime_cursor: bool,
     /// Whether to display infoboxes. Defaults to true.
     pub auto_info: bool,
     pub file_picker: FilePickerConfig,
@@ -273,6 +318,8 @@ pub struct Config {
     pub terminal: Option<TerminalConfig>,
     /// Column numbers at which to draw the rulers. Default to `[]`, meaning no rulers.
     pub rulers: Vec<u16>,
+    /// Highlight the characters of lines reaching past the first ruler. Defaults to false.
+    pub ruler_overflow: bool,
     #[serde(default)]
     pub whitespace: WhitespaceConfig,
     /// Persistently display open buffers along the top
@@ -284,6 +331,236 @@ pub struct Config {
     pub soft_wrap: SoftWrap,
     /// Workspace specific lsp ceiling dirs
     pub workspace_lsp_roots: Vec<PathBuf>,
+    /// System clipboard configuration.
+    pub clipboard: ClipboardConfig,
+    /// Whether to enable the kitty keyboard protocol, which makes keys like `C-i` and `tab`
+    /// distinguishable. Defaults to `auto`, enabling it if the terminal reports support.
+    pub kitty_keyboard_protocol: KittyKeyboardProtocolConfig,
+    /// Protocol used to display image previews. Defaults to `auto`, using the kitty graphics
+    /// protocol if the terminal reports support.
+    pub graphics_protocol: GraphicsProtocol,
+    /// Size in bytes above which bracketed pastes are stored in the `"` register and only
+    /// inserted after confirmation. `0` disables the check. Defaults to 1 MiB.
+    pub large_paste_threshold: usize,
+    /// Maximum number of renders per second. Events arriving faster, like held-down keys or
+    /// bursts of language server messages, are drawn together. Defaults to 60.
+    pub max_fps: u32,
+    /// Symbols marking diagnostics in the gutter and statusline.
+    pub diagnostic_symbols: DiagnosticSymbols,
+    /// Where goto commands and symbol pickers open their target. Defaults to `replace`.
+    pub goto_action: GotoAction,
+    /// Diagnostics hidden everywhere in the editor.
+    pub diagnostic_filter: DiagnosticFilter,
+    /// Mirror edits of a tag name into the matching tag while in insert mode. Defaults to false.
+    pub linked_editing: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct DiagnosticFilter {
+    /// Minimal severity of the diagnostics to display. Defaults to `Hint`, showing all of them.
+    pub severity: Severity,
+    /// Sources (like `clippy` or `cspell`) whose diagnostics are hidden.
+    pub disabled_sources: Vec<String>,
+}
+
+impl DiagnosticFilter {
+    pub fn allows(&self, diagnostic: &Diagnostic) -> bool {
+        self.allows_parts(diagnostic.severity, diagnostic.source.as_deref())
+    }
+
+    pub fn allows_lsp(&self, diagnostic: &lsp::Diagnostic) -> bool {
+        let severity = diagnostic.severity.map(|severity| match severity {
+            lsp::DiagnosticSeverity::WARNING => Severity::Warning,
+            lsp::DiagnosticSeverity::INFORMATION => Severity::Info,
+            lsp::DiagnosticSeverity::HINT => Severity::Hint,
+            _ => Severity::Error,
+        });
+        self.allows_parts(severity, diagnostic.source.as_deref())
+    }
+
+    fn allows_parts(&self, severity: Option<Severity>, source: Option<&str>) -> bool {
+        severity.map_or(true, |severity| severity >= self.severity)
+            && source.map_or(true, |source| {
+                !self
+                    .disabled_sources
+                    .iter()
+                    .any(|disabled| disabled == source)
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct DiagnosticSymbols {
+    pub error: String,
+    pub warning: String,
+    pub info: String,
+    pub hint: String,
+}
+
+impl DiagnosticSymbols {
+    pub fn get(&self, severity: Severity) -> &str {
+        match severity {
+            Severity::Error => &self.error,
+            Severity::Warning => &self.warning,
+            Severity::Info => &self.info,
+            Severity::Hint => &self.hint,
+        }
+    }
+}
+
+impl Default for DiagnosticSymbols {
+    fn default() -> Self {
+        Self {
+            error: String::from("●"),
+            warning: String::from("●"),
+            info: String::from("●"),
+            hint: String::from("●"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct WordCompletionConfig {
+    /// Whether to complete the words of the open documents, in addition to the items of the
+    /// language server. Defaults to true.
+    pub enable: bool,
+    /// Minimum length of the completed words. Defaults to 4.
+    pub min_word_length: usize,
+    /// Whether to also complete the names of the definitions of the workspace found by
+    /// tree-sitter tag queries. Defaults to false.
+    pub workspace: bool,
+}
+
+impl Default for WordCompletionConfig {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            min_word_length: 4,
+            workspace: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CompletionSort {
+    /// How well the item matches the typed text.
+    Score,
+    /// The `sortText` of the item provided by the language server.
+    SortText,
+    /// The kind of the item: variables and fields, then functions, then types, then
+    /// keywords and snippets.
+    Kind,
+    /// How close to the cursor the item occurs in the document.
+    Locality,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GraphicsProtocol {
+    #[default]
+    Auto,
+    Kitty,
+    None,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KittyKeyboardProtocolConfig {
+    #[default]
+    Auto,
+    Disabled,
+    Enabled,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Dvorak,
+    Colemak,
+    /// The Russian ЙЦУКЕН layout.
+    Jcuken,
+}
+
+impl KeyboardLayout {
+    /// Pairs of the characters typed with the layout and the QWERTY characters at the same
+    /// positions.
+    fn translations(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Qwerty => &[],
+            Self::Dvorak => &[
+                (
+                    "[]',.pyfgcrl/=aoeuidhtns-;qjkxbmwvz",
+                    "-=qwertyuiop[]asdfghjkl;'zxcvbnm,./",
+                ),
+                (
+                    "{}\"<>PYFGCRL?+AOEUIDHTNS_:QJKXBMWVZ",
+                    "_+QWERTYUIOP{}ASDFGHJKL:\"ZXCVBNM<>?",
+                ),
+            ],
+            Self::Colemak => &[
+                ("qwfpgjluy;arstdhneiozxcvbkm", "qwertyuiopasdfghjkl;zxcvbnm"),
+                ("QWFPGJLUY:ARSTDHNEIOZXCVBKM", "QWERTYUIOPASDFGHJKL:ZXCVBNM"),
+            ],
+            Self::Jcuken => &[
+                (
+                    "ёйцукенгшщзхъфывапролджэячсмитьбю.",
+                    "`qwertyuiop[]asdfghjkl;'zxcvbnm,./",
+                ),
+                (
+                    "ЁЙЦУКЕНГШЩЗХЪФЫВАПРОЛДЖЭЯЧСМИТЬБЮ,\"№;:?",
+                    "~QWERTYUIOP{}ASDFGHJKL:\"ZXCVBNM<>?@#$^&",
+                ),
+            ],
+        }
+    }
+
+    /// Translates `ch` typed with the layout to the QWERTY character at the same position.
+    pub fn translate(self, ch: char) -> char {
+        self.translations()
+            .iter()
+            .find_map(|(typed, qwerty)| {
+                let idx = typed.chars().position(|typed| typed == ch)?;
+                qwerty.chars().nth(idx)
+            })
+            .unwrap_or(ch)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct ClipboardConfig {
+    /// Which provider to use for the system clipboard. Defaults to `auto`.
+    pub provider: ClipboardProviderKind,
+    /// Maximum size in bytes of the encoded text sent to the terminal by the OSC 52 provider.
+    /// Larger yanks are only kept internally. Defaults to 100000.
+    pub osc52_max_size: usize,
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self {
+            provider: ClipboardProviderKind::Auto,
+            osc52_max_size: 100_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClipboardProviderKind {
+    /// Use OSC 52 in remote sessions without a display server, otherwise detect a native
+    /// provider and fall back to OSC 52 if there is none.
+    Auto,
+    /// Always use OSC 52 escape sequences, handled by the terminal emulator.
+    Osc52,
+    /// Keep yanked text internal to Helix.
+    None,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -352,8 +629,14 @@ pub struct LspConfig {
     pub display_signature_help_docs: bool,
     /// Display inlay hints
     pub display_inlay_hints: bool,
+    /// Highlight the occurrences of the symbol under the cursor
+    pub display_document_highlights: bool,
     /// Whether to enable snippet support
     pub snippets: bool,
+    /// Apply the code action without opening the menu when it is the only preferred quickfix
+    pub auto_apply_quickfix: bool,
+    /// Let the language server reformat the code after typing its trigger characters
+    pub format_on_type: bool,
 }
 
 impl Default for LspConfig {
@@ -364,11 +647,56 @@ fn default() -> Self {
             auto_signature_help: true,
             display_signature_help_docs: true,
             display_inlay_hints: false,
+            display_document_highlights: false,
             snippets: true,
+            auto_apply_quickfix: false,
+            format_on_type: false,
         }
     }
 }
 
+/// Work done once the editor has been idle for a while, each task after its own delay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdleTask {
+    Completion,
+    DocumentHighlight,
+    DocumentLinks,
+    InlayHints,
+    AutoSave,
+    /// Start the language servers configured to start once the editor is idle.
+    LanguageServers,
+}
+
+impl IdleTask {
+    pub const ALL: [IdleTask; 6] = [
+        IdleTask::Completion,
+        IdleTask::DocumentHighlight,
+        IdleTask::DocumentLinks,
+        IdleTask::InlayHints,
+        IdleTask::AutoSave,
+        IdleTask::LanguageServers,
+    ];
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct IdleTimeoutsConfig {
+    /// Milliseconds before requesting completions. Defaults to `idle-timeout`.
+    pub completion: Option<u64>,
+    /// Milliseconds before highlighting the references of the symbol under the cursor.
+    /// Defaults to `idle-timeout`.
+    pub document_highlight: Option<u64>,
+    /// Milliseconds before requesting the links of the document. Defaults to `idle-timeout`.
+    pub document_links: Option<u64>,
+    /// Milliseconds before refreshing the inlay hints. Defaults to `idle-timeout`.
+    pub inlay_hints: Option<u64>,
+    /// Milliseconds before saving the modified documents. Defaults to never.
+    pub auto_save: Option<u64>,
+    /// Milliseconds before starting the language servers which start once the editor is
+    /// idle. Defaults to `idle-timeout`.
+    pub language_servers: Option<u64>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
 pub struct SearchConfig {
@@ -376,6 +704,9 @@ pub struct SearchConfig {
     pub smart_case: bool,
     /// Whether the search should wrap after depleting the matches. Default to true.
     pub wrap_around: bool,
+    /// Whether to keep an index of the workspace in the state directory to speed up
+    /// global search and the workspace symbol picker. Defaults to false.
+    pub index: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -386,6 +717,11 @@ pub struct StatusLineConfig {
     pub right: Vec<StatusLineElement>,
     pub separator: String,
     pub mode: ModeConfig,
+    /// Severities counted by the `diagnostics` and `workspace-diagnostics` elements, in the
+    /// order they are displayed. Defaults to `["Warning", "Error"]`.
+    pub diagnostics: Vec<Severity>,
+    /// Whether to display severities without any diagnostics. Defaults to `false`.
+    pub show_zero_diagnostics: bool,
 }
 
 impl Default for StatusLineConfig {
@@ -403,6 +739,8 @@ fn default() -> Self {
             right: vec![E::Diagnostics, E::Selections, E::Position, E::FileEncoding],
             separator: String::from("│"),
             mode: ModeConfig::default(),
+            diagnostics: vec![Severity::Warning, Severity::Error],
+            show_zero_diagnostics: false,
         }
     }
 }
@@ -481,6 +819,12 @@ pub enum StatusLineElement {
 
     /// Current version control information
     VersionControl,
+
+    /// The structural path of the cursor in JSON, YAML and TOML files
+    DataPath,
+
+    /// The number of comments of each tag found by the last todo search
+    TodoCounts,
 }
 
 // Cursor shape is read and used on every rendered frame and so needs
@@ -537,6 +881,29 @@ fn default() -> Self {
     }
 }
 
+/// Where goto commands open their target
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GotoAction {
+    /// Replace the current view
+    #[default]
+    Replace,
+    /// Open a horizontal split
+    Hsplit,
+    /// Open a vertical split
+    Vsplit,
+}
+
+impl From<GotoAction> for Action {
+    fn from(action: GotoAction) -> Self {
+        match action {
+            GotoAction::Replace => Action::Replace,
+            GotoAction::Hsplit => Action::HorizontalSplit,
+            GotoAction::Vsplit => Action::VerticalSplit,
+        }
+    }
+}
+
 /// bufferline render modes
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -573,8 +940,17 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
+impl std::fmt::Display for LineNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Absolute => f.write_str("absolute"),
+            Self::Relative => f.write_str("relative"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum GutterType {
     /// Show diagnostics and other features like breakpoints
     Diagnostics,
@@ -584,6 +960,9 @@ pub enum GutterType {
     Spacer,
     /// Highlight local changes
     Diff,
+    /// A gutter registered with [`register_gutter`](crate::gutter::register_gutter) under
+    /// this name
+    Custom(String),
 }
 
 impl std::str::FromStr for GutterType {
@@ -595,11 +974,40 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
             "spacer" => Ok(Self::Spacer),
             "line-numbers" => Ok(Self::LineNumbers),
             "diff" => Ok(Self::Diff),
-            _ => anyhow::bail!("Gutter type can only be `diagnostics` or `line-numbers`."),
+            "" => anyhow::bail!("Gutter type can not be empty."),
+            _ if crate::gutter::custom_gutter(s).is_some() => Ok(Self::Custom(s.to_string())),
+            _ => anyhow::bail!("Unknown gutter type: '{}'", s),
         }
     }
 }
 
+impl TryFrom<String> for GutterType {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl GutterType {
+    /// The name of the gutter in the gutter layout.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Diagnostics => "diagnostics",
+            Self::LineNumbers => "line-numbers",
+            Self::Spacer => "spacer",
+            Self::Diff => "diff",
+            Self::Custom(name) => name,
+        }
+    }
+}
+
+impl From<GutterType> for String {
+    fn from(gutter: GutterType) -> Self {
+        gutter.name().to_string()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(default)]
 pub struct WhitespaceConfig {
@@ -733,7 +1141,12 @@ fn default() -> Self {
             auto_completion: true,
             auto_format: true,
             auto_save: false,
+            hard_delete: false,
+            persist_old_files: true,
+            persist_jumplist: true,
+            reindent_paste: false,
             idle_timeout: Duration::from_millis(400),
+            idle_timeouts: IdleTimeoutsConfig::default(),
             completion_trigger_len: 2,
             auto_info: true,
             file_picker: FilePickerConfig::default(),
@@ -745,6 +1158,7 @@ fn default() -> Self {
             lsp: LspConfig::default(),
             terminal: get_terminal_provider(),
             rulers: Vec::new(),
+            ruler_overflow: false,
             whitespace: WhitespaceConfig::default(),
             bufferline: BufferLine::default(),
             indent_guides: IndentGuidesConfig::default(),
@@ -752,7 +1166,27 @@ fn default() -> Self {
             soft_wrap: SoftWrap::default(),
             text_width: 80,
             completion_replace: false,
+            completion_sort: vec![CompletionSort::Score],
+            word_completion: WordCompletionConfig::default(),
+            path_completion: true,
+            todo_tags: ["TODO", "FIXME", "HACK", "XXX"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            command_aliases: HashMap::new(),
+            keyboard_layout: KeyboardLayout::default(),
+            key_remap: HashMap::new(),
+            ime_cursor: false,
             workspace_lsp_roots: Vec::new(),
+            clipboard: ClipboardConfig::default(),
+            kitty_keyboard_protocol: KittyKeyboardProtocolConfig::default(),
+            graphics_protocol: GraphicsProtocol::default(),
+            large_paste_threshold: 1024 * 1024,
+            max_fps: 60,
+            diagnostic_symbols: DiagnosticSymbols::default(),
+            goto_action: GotoAction::default(),
+            diagnostic_filter: DiagnosticFilter::default(),
+            linked_editing: false,
         }
     }
 }
@@ -762,10 +1196,23 @@ fn default() -> Self {
         Self {
             wrap_around: true,
             smart_case: true,
+            index: false,
         }
     }
 }
 
+/// The last paste from a register, which can be replaced by the other entries of the yank ring.
+pub struct LastPaste {
+    pub doc_id: DocumentId,
+    pub view_id: ViewId,
+    /// Version of the document right after the paste, to detect later changes.
+    pub version: i32,
+    /// Index of the pasted values in the yank ring, if they are in it.
+    pub ring_index: Option<usize>,
+    /// Pastes other values in the same way, after the paste was undone.
+    pub repaste: Box<dyn Fn(&mut Editor, &[String])>,
+}
+
 pub struct Motion(pub Box<dyn Fn(&mut Editor)>);
 impl Motion {
     pub fn run(&self, e: &mut Editor) {
@@ -842,18 +1289,60 @@ pub struct Editor {
     pub auto_pairs: Option<AutoPairs>,
 
     pub idle_timer: Pin<Box<Sleep>>,
+    /// Deadlines of the idle tasks waiting for the idle timer.
+    idle_tasks: HashMap<IdleTask, Instant>,
+    /// Idle tasks whose deadline passed when the idle timer last fired.
+    due_idle_tasks: HashSet<IdleTask>,
+    /// Documents waiting for their language server to start, see [`LanguageServerStart`].
+    deferred_language_servers: HashSet<DocumentId>,
+    /// Number of times the language server of each language scope was restarted after
+    /// exiting unexpectedly, reset by `:lsp-restart`.
+    pub language_server_restarts: HashMap<String, usize>,
     pub last_motion: Option<Motion>,
+    pub last_paste: Option<LastPaste>,
 
     pub last_completion: Option<CompleteAction>,
 
+    /// Names of the commands most recently run from the command palette, most recent first.
+    pub recent_commands: Vec<String>,
+    /// Files recently edited, with the last cursor position in them.
+    pub recent_files: RecentFiles,
+    /// Jumps made in all views, by file path.
+    pub global_jumps: GlobalJumpList,
+    /// Locations found by the last global search, diagnostics picker or references request.
+    pub quickfix: QuickfixList,
+
+    /// Definitions found by tree-sitter tag queries, used when no language server is available.
+    pub tag_index: Arc<parking_lot::Mutex<TagIndex>>,
+    /// Trigrams of the files of the workspace, used to narrow down global searches.
+    pub trigram_index: Arc<parking_lot::Mutex<TrigramIndex>>,
+    /// Roots added to the workspace with `:workspace-add`, besides the one found from the
+    /// current directory.
+    pub workspace_roots: Vec<PathBuf>,
+    /// Watches the workspace once a language server registers file watchers.
+    pub file_watcher: FileWatcher,
+
     pub exit_code: i32,
 
     pub config_events: (UnboundedSender<ConfigEvent>, UnboundedReceiver<ConfigEvent>),
+    /// Output of the commands started with `:run`, appended to their scratch buffers.
+    pub command_output: (
+        UnboundedSender<CommandOutput>,
+        UnboundedReceiver<CommandOutput>,
+    ),
+    /// The REPLs started with `:cell-run`, by the document whose cells they run.
+    pub repls: HashMap<DocumentId, Repl>,
+    /// The symbol renames applied during the session, oldest first.
+    pub renames: Vec<Rename>,
+    /// The comment tags found by the last todo search, with their number of comments.
+    pub todo_counts: Vec<(String, usize)>,
     /// Allows asynchronous tasks to control the rendering
     /// The `Notify` allows asynchronous tasks to request the editor to perform a redraw
     /// The `RwLock` blocks the editor from performing the render until an exclusive lock can be aquired
     pub redraw_handle: RedrawHandle,
     pub needs_redraw: bool,
+    /// Whether the terminal reported support for the kitty graphics protocol.
+    pub terminal_supports_images: bool,
     /// Cached position of the cursor calculated during rendering.
     /// The content of `cursor_cache` is returned by `Editor::cursor` if
     /// set to `Some(_)`. The value will be cleared after it's used.
@@ -885,9 +1374,35 @@ pub enum EditorEvent {
     ConfigEvent(ConfigEvent),
     LanguageServerMessage((usize, Call)),
     DebuggerEvent(dap::Payload),
+    CommandOutput(CommandOutput),
     IdleTimer,
 }
 
+/// Text printed by a command started with `:run`, for the scratch buffer `doc_id`.
+#[derive(Debug)]
+pub struct CommandOutput {
+    pub doc_id: DocumentId,
+    pub text: String,
+}
+
+/// A REPL process kept running to run code cells, whose output is appended to the scratch
+/// buffer `doc_id`.
+#[derive(Debug)]
+pub struct Repl {
+    pub doc_id: DocumentId,
+    /// Code written to the stdin of the process.
+    pub input: UnboundedSender<String>,
+}
+
+/// A symbol rename applied from a language server, listed by `:rename-history`.
+#[derive(Debug, Clone)]
+pub struct Rename {
+    pub old_name: String,
+    pub new_name: String,
+    /// The files changed by the rename.
+    pub files: Vec<PathBuf>,
+}
+
 #[derive(Debug, Clone)]
 pub enum ConfigEvent {
     Refresh,
@@ -960,18 +1475,36 @@ pub fn new(
             last_theme: None,
             last_selection: None,
             registers: Registers::default(),
-            clipboard_provider: get_clipboard_provider(),
+            clipboard_provider: get_clipboard_provider(&conf.clipboard),
             status_msg: None,
             autoinfo: None,
             idle_timer: Box::pin(sleep(conf.idle_timeout)),
+            idle_tasks: HashMap::new(),
+            due_idle_tasks: HashSet::new(),
+            deferred_language_servers: HashSet::new(),
+            language_server_restarts: HashMap::new(),
             last_motion: None,
+            last_paste: None,
             last_completion: None,
+            recent_commands: Vec::new(),
+            recent_files: RecentFiles::default(),
+            global_jumps: GlobalJumpList::default(),
+            quickfix: QuickfixList::default(),
+            tag_index: Arc::default(),
+            trigram_index: Arc::default(),
+            workspace_roots: Vec::new(),
+            file_watcher: FileWatcher::default(),
             config,
             auto_pairs,
             exit_code: 0,
             config_events: unbounded_channel(),
+            command_output: unbounded_channel(),
+            repls: HashMap::new(),
+            renames: Vec::new(),
+            todo_counts: Vec::new(),
             redraw_handle: Default::default(),
             needs_redraw: false,
+            terminal_supports_images: false,
             cursor_cache: Cell::new(None),
             completion_request_handle: None,
         }
@@ -986,6 +1519,15 @@ pub fn config(&self) -> DynGuard<Config> {
         self.config.load()
     }
 
+    /// Whether images can be displayed in the terminal.
+    pub fn supports_images(&self) -> bool {
+        match self.config().graphics_protocol {
+            GraphicsProtocol::Auto => self.terminal_supports_images,
+            GraphicsProtocol::Kitty => true,
+            GraphicsProtocol::None => false,
+        }
+    }
+
     /// Call if the config has changed to let the editor update all
     /// relevant members.
     pub fn refresh_config(&mut self) {
@@ -996,17 +1538,80 @@ pub fn refresh_config(&mut self) {
     }
 
     pub fn clear_idle_timer(&mut self) {
-        // equivalent to internal Instant::far_future() (30 years)
-        self.idle_timer
-            .as_mut()
-            .reset(Instant::now() + Duration::from_secs(86400 * 365 * 30));
+        self.idle_tasks.clear();
+        self.schedule_idle_timer();
     }
 
+    /// Schedules all idle tasks after their delays from now.
     pub fn reset_idle_timer(&mut self) {
+        let now = Instant::now();
+        for task in IdleTask::ALL {
+            match self.idle_task_delay(task) {
+                Some(delay) => self.idle_tasks.insert(task, now + delay),
+                None => self.idle_tasks.remove(&task),
+            };
+        }
+        self.schedule_idle_timer();
+    }
+
+    fn idle_task_delay(&self, task: IdleTask) -> Option<Duration> {
         let config = self.config();
-        self.idle_timer
-            .as_mut()
-            .reset(Instant::now() + config.idle_timeout);
+        let timeouts = &config.idle_timeouts;
+        let delay = match task {
+            IdleTask::Completion => self
+                .tree
+                .try_get(self.tree.focus)
+                .and_then(|view| self.documents.get(&view.doc))
+                .and_then(|doc| doc.language_config()?.completion.as_ref()?.idle_timeout)
+                .or(timeouts.completion),
+            IdleTask::DocumentHighlight => timeouts.document_highlight,
+            IdleTask::DocumentLinks => timeouts.document_links,
+            IdleTask::InlayHints => timeouts.inlay_hints,
+            IdleTask::LanguageServers if self.deferred_language_servers.is_empty() => return None,
+            IdleTask::LanguageServers => timeouts.language_servers,
+            IdleTask::AutoSave => return timeouts.auto_save.map(Duration::from_millis),
+        };
+        Some(delay.map_or(config.idle_timeout, Duration::from_millis))
+    }
+
+    /// Schedules `task` after its delay from now, unless it is already pending.
+    pub fn schedule_idle_task(&mut self, task: IdleTask) {
+        if self.idle_tasks.contains_key(&task) {
+            return;
+        }
+        if let Some(delay) = self.idle_task_delay(task) {
+            self.idle_tasks.insert(task, Instant::now() + delay);
+            self.schedule_idle_timer();
+        }
+    }
+
+    /// Sets the idle timer to the earliest deadline of the pending idle tasks.
+    fn schedule_idle_timer(&mut self) {
+        let deadline = self.idle_tasks.values().min().copied().unwrap_or_else(|| {
+            // equivalent to internal Instant::far_future() (30 years)
+            Instant::now() + Duration::from_secs(86400 * 365 * 30)
+        });
+        self.idle_timer.as_mut().reset(deadline);
+    }
+
+    /// Marks the idle tasks whose deadline passed as due, and schedules the idle timer for
+    /// the remaining ones. Called when the idle timer fires.
+    pub fn start_due_idle_tasks(&mut self) {
+        let now = Instant::now();
+        self.due_idle_tasks.clear();
+        self.idle_tasks.retain(|&task, &mut deadline| {
+            let due = deadline <= now;
+            if due {
+                self.due_idle_tasks.insert(task);
+            }
+            !due
+        });
+        self.schedule_idle_timer();
+    }
+
+    /// Whether `task` should run while handling the current idle timeout.
+    pub fn idle_task_due(&self, task: IdleTask) -> bool {
+        self.due_idle_tasks.contains(&task)
     }
 
     pub fn clear_status(&mut self) {
@@ -1083,11 +1688,74 @@ fn set_theme_impl(&mut self, theme: Theme, preview: ThemeAction) {
 
     /// Refreshes the language server for a given document
     pub fn refresh_language_server(&mut self, doc_id: DocumentId) -> Option<()> {
-        self.launch_language_server(doc_id)
+        self.launch_language_server(doc_id, true)
+    }
+
+    /// Starts the language server of a document whose start was deferred by the `start`
+    /// setting of the server. Returns whether the document was waiting for it.
+    pub fn start_deferred_language_server(&mut self, doc_id: DocumentId) -> bool {
+        if !self.deferred_language_servers.remove(&doc_id) {
+            return false;
+        }
+        self.launch_language_server(doc_id, false);
+
+        // The other documents of the language can use the server now that it runs.
+        let scope = self
+            .document(doc_id)
+            .and_then(|doc| Some(doc.language_config()?.scope.clone()));
+        let doc_ids: Vec<_> = self
+            .deferred_language_servers
+            .iter()
+            .copied()
+            .filter(|doc_id| {
+                self.document(*doc_id)
+                    .and_then(|doc| doc.language_config())
+                    .map(|config| &config.scope)
+                    == scope.as_ref()
+            })
+            .collect();
+        for doc_id in doc_ids {
+            self.launch_language_server(doc_id, true);
+        }
+        true
+    }
+
+    /// Starts the deferred language servers which start once the editor is idle.
+    pub fn start_idle_language_servers(&mut self) {
+        let doc_ids: Vec<_> = self
+            .deferred_language_servers
+            .iter()
+            .copied()
+            .filter(|doc_id| {
+                self.document(*doc_id)
+                    .and_then(|doc| doc.language_config()?.language_server.as_ref())
+                    .map_or(false, |config| config.start == LanguageServerStart::Idle)
+            })
+            .collect();
+        for doc_id in doc_ids {
+            self.start_deferred_language_server(doc_id);
+        }
+    }
+
+    /// Starts the language server of the documents of the language `scope` again, after it
+    /// exited unexpectedly.
+    pub fn restart_language_servers(&mut self, scope: &str) {
+        let doc_ids: Vec<_> = self
+            .documents()
+            .filter(|doc| {
+                doc.language_config()
+                    .map_or(false, |config| config.scope == scope)
+            })
+            .map(|doc| doc.id())
+            .collect();
+        for doc_id in doc_ids {
+            self.launch_language_server(doc_id, false);
+        }
     }
 
-    /// Launch a language server for a given document
-    fn launch_language_server(&mut self, doc_id: DocumentId) -> Option<()> {
+    /// Launch a language server for a given document. With `defer`, servers that don't start
+    /// when a document is opened are only started once one of their language is running.
+    fn launch_language_server(&mut self, doc_id: DocumentId, defer: bool) -> Option<()> {
         if !self.config().lsp.enable {
             return None;
         }
@@ -1098,6 +1766,24 @@ fn launch_language_server(&mut self, doc_id: DocumentId) -> Option<()> {
         let config = doc.config.load();
         let root_dirs = &config.workspace_lsp_roots;
 
+        let start = lang
+            .as_ref()
+            .and_then(|language| language.language_server.as_ref())
+            .map(|server| server.start);
+        if let (true, Some(language), Some(start)) = (defer, &lang, start) {
+            if start != LanguageServerStart::Open
+                && doc.language_server().is_none()
+                && !self.language_servers.is_running(language)
+            {
+                self.deferred_language_servers.insert(doc_id);
+                if start == LanguageServerStart::Idle {
+                    self.schedule_idle_task(IdleTask::LanguageServers);
+                }
+                return Some(());
+            }
+        }
+        self.deferred_language_servers.remove(&doc_id);
+
         // try to find a language server based on the language name
         let language_server = lang.as_ref().and_then(|language| {
             self.language_servers
@@ -1133,12 +1819,73 @@ fn launch_language_server(&mut self, doc_id: DocumentId) -> Option<()> {
                     language_id,
                 ));
 
-                doc.set_language_server(Some(language_server));
+                doc.set_language_server(Some(language_server.clone()));
+            }
+
+            for root in &self.workspace_roots {
+                language_server.add_workspace_root(root);
             }
         }
         Some(())
     }
 
+    /// Adds `root` to the workspace, and to the workspace folders of the running language
+    /// servers.
+    pub fn add_workspace_root(&mut self, root: PathBuf) {
+        if self.workspace_roots.contains(&root) {
+            return;
+        }
+        for language_server in self.language_servers.iter_clients() {
+            language_server.add_workspace_root(&root);
+        }
+        if self.file_watcher.is_watching() {
+            self.file_watcher.watch(&root);
+        }
+        self.workspace_roots.push(root);
+    }
+
+    /// Removes a root added with [`Editor::add_workspace_root`]. Returns whether it was one.
+    pub fn remove_workspace_root(&mut self, root: &Path) -> bool {
+        let index = match self.workspace_roots.iter().position(|r| r == root) {
+            Some(index) => index,
+            None => return false,
+        };
+        self.workspace_roots.remove(index);
+        for language_server in self.language_servers.iter_clients() {
+            language_server.remove_workspace_root(root);
+        }
+        self.file_watcher.unwatch(root);
+        true
+    }
+
+    /// Starts watching the workspace roots for changes of files on disk, reported to the
+    /// language servers that registered file watchers.
+    pub fn watch_workspace_files(&mut self) {
+        if self.file_watcher.is_watching() {
+            return;
+        }
+        self.file_watcher.watch(&helix_loader::find_workspace().0);
+        for root in &self.workspace_roots {
+            self.file_watcher.watch(root);
+        }
+    }
+
+    /// Notifies the language servers watching `path` that the editor changed it on disk,
+    /// unless the file watcher reports the change itself.
+    pub fn file_changed(&self, path: &Path, typ: lsp::FileChangeType) {
+        if !self.file_watcher.watches(path) {
+            self.notify_file_changed(path, typ);
+        }
+    }
+
+    fn notify_file_changed(&self, path: &Path, typ: lsp::FileChangeType) {
+        for language_server in self.language_servers.iter_clients() {
+            if let Some(notification) = language_server.did_change_watched_file(path, typ) {
+                tokio::spawn(notification);
+            }
+        }
+    }
+
     fn _refresh(&mut self) {
         let config = self.config();
 
@@ -1154,6 +1901,10 @@ fn _refresh(&mut self) {
             }
         }
 
+        for doc in self.documents_mut() {
+            doc.refilter_diagnostics();
+        }
+
         for (view, _) in self.tree.views_mut() {
             let doc = doc_mut!(self, &view.doc);
             view.sync_changes(doc);
@@ -1219,6 +1970,7 @@ pub fn switch(&mut self, id: DocumentId, action: Action) {
                     }
                 } else {
                     let jump = (view.doc, doc.selection(view.id).clone());
+                    self.global_jumps.push(doc, &jump.1);
                     view.jumps.push(jump);
                     // Set last accessed doc if it is a different document
                     if doc.id != id {
@@ -1285,7 +2037,7 @@ fn new_document(&mut self, mut doc: Document) -> DocumentId {
         id
     }
 
-    fn new_file_from_document(&mut self, action: Action, doc: Document) -> DocumentId {
+    pub fn new_file_from_document(&mut self, action: Action, doc: Document) -> DocumentId {
         let id = self.new_document(doc);
         self.switch(id, action);
         id
@@ -1307,6 +2059,7 @@ pub fn new_file_from_stdin(&mut self, action: Action) -> Result<DocumentId, Erro
     pub fn open(&mut self, path: &Path, action: Action) -> Result<DocumentId, Error> {
         let path = helix_core::path::get_canonicalized_path(path)?;
         let id = self.document_by_path(&path).map(|doc| doc.id);
+        let is_new = id.is_none();
 
         let id = if let Some(id) = id {
             id
@@ -1323,16 +2076,64 @@ pub fn open(&mut self, path: &Path, action: Action) -> Result<DocumentId, Error>
             }
             doc.set_version_control_head(self.diff_providers.get_current_head_name(&path));
 
+            // Opening the document loaded its grammar: highlighting is silently missing when
+            // that failed, so say why.
+            if let Some(err) = doc
+                .language_config()
+                .and_then(|config| config.grammar_error())
+            {
+                self.set_error(err.to_string());
+            }
+
             let id = self.new_document(doc);
-            let _ = self.launch_language_server(id);
+            let _ = self.launch_language_server(id, true);
 
             id
         };
 
         self.switch(id, action);
+
+        // Restore the last cursor position of a recently edited file.
+        if is_new {
+            let position = self.recent_files.position(&path);
+            if let Some(pos) = position {
+                // `switch` initialized the document for the focused view, even when it is
+                // only loaded in the background and the view shows another document.
+                let view = view_mut!(self);
+                let doc = doc_mut!(self, &id);
+                let text = doc.text().slice(..);
+                doc.set_selection(view.id, Selection::point(pos_at_coords(text, pos, true)));
+                if view.doc == id {
+                    align_view(doc, view, Align::Center);
+                }
+            }
+            self.recent_files
+                .record(&path, position.unwrap_or_default());
+        }
         Ok(id)
     }
 
+    /// Remembers the cursor position in the document `doc_id` in the recent files.
+    pub fn record_recent_file(&mut self, doc_id: DocumentId) {
+        let doc = match self.documents.get(&doc_id) {
+            Some(doc) => doc,
+            None => return,
+        };
+        let path = match doc.path() {
+            Some(path) => path,
+            None => return,
+        };
+        let selection = doc
+            .selections()
+            .get(&self.tree.focus)
+            .or_else(|| doc.selections().values().next());
+        let text = doc.text().slice(..);
+        let pos = selection.map_or_else(Position::default, |selection| {
+            coords_at_pos(text, selection.primary().cursor(text))
+        });
+        self.recent_files.record(path, pos);
+    }
+
     pub fn close(&mut self, id: ViewId) {
         // Remove selections for the closed view on all documents.
         for doc in self.documents_mut() {
@@ -1353,12 +2154,17 @@ pub fn close_document(&mut self, doc_id: DocumentId, force: bool) -> Result<(),
 
         // This will also disallow any follow-up writes
         self.saves.remove(&doc_id);
+        self.deferred_language_servers.remove(&doc_id);
+        // Closing the input of the REPL of the document stops it.
+        self.repls.remove(&doc_id);
 
         if let Some(language_server) = doc.language_server() {
             // TODO: track error
             tokio::spawn(language_server.text_document_did_close(doc.identifier()));
         }
 
+        self.record_recent_file(doc_id);
+
         enum Action {
             Close(ViewId),
             ReplaceDoc(ViewId, DocumentId),
@@ -1501,6 +2307,44 @@ pub fn ensure_cursor_in_view(&mut self, id: ViewId) {
         view.ensure_cursor_in_view(doc, config.scrolloff)
     }
 
+    /// Appends the output of a command to the end of its scratch buffer, unless the buffer was
+    /// closed. The buffer isn't marked as modified by it. The output received since is appended
+    /// along with it, so that a chatty command adds few revisions to the history.
+    pub fn append_command_output(&mut self, output: CommandOutput) {
+        let mut outputs = vec![output];
+        while let Ok(output) = self.command_output.1.try_recv() {
+            match outputs
+                .iter_mut()
+                .find(|previous| previous.doc_id == output.doc_id)
+            {
+                Some(previous) => previous.text.push_str(&output.text),
+                None => outputs.push(output),
+            }
+        }
+        for output in outputs {
+            self.append_to_scratch(output);
+        }
+    }
+
+    fn append_to_scratch(&mut self, output: CommandOutput) {
+        let doc = match self.documents.get_mut(&output.doc_id) {
+            Some(doc) => doc,
+            None => return,
+        };
+        let view_id = match doc.selections().keys().next() {
+            Some(&view_id) => view_id,
+            None => return,
+        };
+        let end = doc.text().len_chars();
+        let transaction = helix_core::Transaction::change(
+            doc.text(),
+            std::iter::once((end, end, Some(output.text.into()))),
+        );
+        doc.apply(&transaction, view_id);
+        doc.append_changes_to_history(self.tree.get_mut(view_id));
+        doc.reset_modified();
+    }
+
     #[inline]
     pub fn document(&self, id: DocumentId) -> Option<&Document> {
         self.documents.get(&id)
@@ -1593,6 +2437,12 @@ pub async fn wait_event(&mut self) -> EditorEvent {
                 Some(event) = self.debugger_events.next() => {
                     return EditorEvent::DebuggerEvent(event)
                 }
+                Some(output) = self.command_output.1.recv() => {
+                    return EditorEvent::CommandOutput(output)
+                }
+                Some((path, typ)) = self.file_watcher.events.recv() => {
+                    self.notify_file_changed(&path, typ);
+                }
 
                 _ = self.redraw_handle.0.notified() => {
                     if  !self.needs_redraw{
@@ -1644,6 +2494,7 @@ pub fn enter_normal_mode(&mut self) {
         let (view, doc) = current!(self);
 
         try_restore_indent(doc, view);
+        doc.set_linked_editing_ranges(Vec::new());
 
         // if leaving append mode, move cursor back by 1
         if doc.restore_cursor {
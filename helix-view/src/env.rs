@@ -6,3 +6,20 @@ pub fn binary_exists(binary_name: &str) -> bool {
 pub fn env_var_is_set(env_var_name: &str) -> bool {
     std::env::var_os(env_var_name).is_some()
 }
+
+/// Detects terminals implementing the kitty graphics protocol from their environment, for
+/// platforms where the terminal can't be queried.
+///
+/// Terminal multiplexers don't pass the protocol through, so images are disabled inside tmux.
+pub fn supports_kitty_graphics() -> bool {
+    use std::env::var;
+
+    if var("TMUX").is_ok() {
+        return false;
+    }
+    var("KITTY_WINDOW_ID").is_ok()
+        || var("TERM").map_or(false, |term| {
+            term == "xterm-kitty" || term == "xterm-ghostty"
+        })
+        || matches!(var("TERM_PROGRAM").as_deref(), Ok("WezTerm" | "ghostty"))
+}
@@ -0,0 +1,171 @@
+//! Watches the workspace roots for changes of files on disk, including the ones made outside
+//! of the editor, so they can be forwarded to the language servers that registered file
+//! watchers with `workspace/didChangeWatchedFiles`.
+
+use std::path::{Path, PathBuf};
+
+use helix_lsp::lsp::FileChangeType;
+use notify::{
+    event::{ModifyKind, RenameMode},
+    EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// A file created, changed or deleted on disk.
+pub type FileEvent = (PathBuf, FileChangeType);
+
+pub struct FileWatcher {
+    /// Created when the first root is watched, as watching a large workspace is not free.
+    watcher: Option<RecommendedWatcher>,
+    roots: Vec<PathBuf>,
+    sender: UnboundedSender<FileEvent>,
+    pub(crate) events: UnboundedReceiver<FileEvent>,
+}
+
+impl Default for FileWatcher {
+    fn default() -> Self {
+        let (sender, events) = unbounded_channel();
+        Self {
+            watcher: None,
+            roots: Vec::new(),
+            sender,
+            events,
+        }
+    }
+}
+
+impl FileWatcher {
+    /// Starts watching `root` recursively. Failures are logged.
+    pub fn watch(&mut self, root: &Path) {
+        if self.roots.iter().any(|watched| watched == root) {
+            return;
+        }
+        if self.watcher.is_none() {
+            match start_watcher(self.sender.clone()) {
+                Ok(watcher) => self.watcher = Some(watcher),
+                Err(err) => {
+                    log::error!("Failed to start the file watcher: {}", err);
+                    return;
+                }
+            }
+        }
+        if let Some(watcher) = &mut self.watcher {
+            if let Err(err) = watcher.watch(root, RecursiveMode::Recursive) {
+                log::error!("Failed to watch {}: {}", root.display(), err);
+                return;
+            }
+            self.roots.push(root.to_path_buf());
+        }
+    }
+
+    /// Stops watching `root`.
+    pub fn unwatch(&mut self, root: &Path) {
+        let index = match self.roots.iter().position(|watched| watched == root) {
+            Some(index) => index,
+            None => return,
+        };
+        self.roots.remove(index);
+        if let Some(watcher) = &mut self.watcher {
+            if let Err(err) = watcher.unwatch(root) {
+                log::warn!("Failed to stop watching {}: {}", root.display(), err);
+            }
+        }
+    }
+
+    /// Whether any root is watched.
+    pub fn is_watching(&self) -> bool {
+        !self.roots.is_empty()
+    }
+
+    /// Whether changes of `path` are reported by the watcher.
+    pub fn watches(&self, path: &Path) -> bool {
+        self.roots.iter().any(|root| path.starts_with(root))
+    }
+}
+
+fn start_watcher(sender: UnboundedSender<FileEvent>) -> notify::Result<RecommendedWatcher> {
+    notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+        Ok(event) => {
+            for change in changes(event) {
+                // The receiver only goes away when the editor exits.
+                let _ = sender.send(change);
+            }
+        }
+        Err(err) => log::warn!("File watcher error: {}", err),
+    })
+}
+
+/// The file changes described by `event`. Changes inside `.git` directories and of metadata
+/// only are left out.
+fn changes(event: notify::Event) -> Vec<FileEvent> {
+    let mut paths = event.paths.into_iter().filter(|path| {
+        !path
+            .components()
+            .any(|component| component.as_os_str() == ".git")
+    });
+    let typ = match event.kind {
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+            FileChangeType::CREATED
+        }
+        EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+            FileChangeType::DELETED
+        }
+        // The old path is given first, the new one second.
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            return paths
+                .zip([FileChangeType::DELETED, FileChangeType::CREATED])
+                .collect();
+        }
+        EventKind::Modify(ModifyKind::Metadata(_)) => return Vec::new(),
+        EventKind::Modify(_) => FileChangeType::CHANGED,
+        _ => return Vec::new(),
+    };
+    paths.map(|path| (path, typ)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange, MetadataKind};
+
+    fn event(kind: EventKind, paths: &[&str]) -> notify::Event {
+        paths.iter().fold(notify::Event::new(kind), |event, path| {
+            event.add_path(PathBuf::from(*path))
+        })
+    }
+
+    #[test]
+    fn event_changes() {
+        assert_eq!(
+            changes(event(EventKind::Create(CreateKind::File), &["/a.rs"])),
+            [(PathBuf::from("/a.rs"), FileChangeType::CREATED)]
+        );
+        assert_eq!(
+            changes(event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                &["/a.rs"]
+            )),
+            [(PathBuf::from("/a.rs"), FileChangeType::CHANGED)]
+        );
+        assert_eq!(
+            changes(event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &["/a.rs", "/b.rs"]
+            )),
+            [
+                (PathBuf::from("/a.rs"), FileChangeType::DELETED),
+                (PathBuf::from("/b.rs"), FileChangeType::CREATED)
+            ]
+        );
+        assert!(changes(event(
+            EventKind::Modify(ModifyKind::Metadata(MetadataKind::Permissions)),
+            &["/a.rs"]
+        ))
+        .is_empty());
+        assert!(changes(event(
+            EventKind::Create(CreateKind::File),
+            &["/project/.git/index.lock"]
+        ))
+        .is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+//! A jumplist shared by all views which remembers jumps by file path, so it can be persisted in
+//! the state directory and used to return to earlier locations in later sessions.
+
+use std::{collections::VecDeque, path::PathBuf};
+
+use crate::{state, Document};
+use helix_core::{coords_at_pos, Position, Selection};
+use serde::{Deserialize, Serialize};
+
+/// The maximum number of jumps remembered.
+const GLOBAL_JUMP_LIST_CAPACITY: usize = 100;
+
+const STATE_FILE: &str = "jumplist.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlobalJump {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    /// The trimmed contents of the line at the time of the jump.
+    pub text: String,
+}
+
+impl GlobalJump {
+    pub fn position(&self) -> Position {
+        Position::new(self.line, self.column)
+    }
+}
+
+/// Jumps made in any view, the most recent first.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GlobalJumpList {
+    jumps: VecDeque<GlobalJump>,
+    /// The jumps made during this session, oldest first, replayed onto the jumps saved by
+    /// other sessions when saving.
+    #[serde(skip)]
+    session_jumps: Vec<GlobalJump>,
+}
+
+impl GlobalJumpList {
+    /// Loads the jumps of previous sessions from the state directory.
+    pub fn load() -> Self {
+        state::load(STATE_FILE)
+    }
+
+    /// Saves the jumps to the state directory, on top of the jumps saved by other sessions
+    /// since this one started.
+    pub fn save(&self) {
+        state::update(STATE_FILE, |saved: &mut Self| self.merge_into(saved))
+    }
+
+    /// Pushes the jumps made during this session onto `saved`.
+    fn merge_into(&self, saved: &mut Self) {
+        for jump in &self.session_jumps {
+            saved.push_jump(jump.clone());
+        }
+    }
+
+    /// Remembers a jump from the primary cursor of `selection` in `doc`. Jumps in documents
+    /// without a path are ignored, and a jump to the same line as the last one replaces it.
+    pub fn push(&mut self, doc: &Document, selection: &Selection) {
+        let path = match doc.path() {
+            Some(path) => path,
+            None => return,
+        };
+        let text = doc.text().slice(..);
+        let pos = coords_at_pos(text, selection.primary().cursor(text));
+        let jump = GlobalJump {
+            path: path.clone(),
+            line: pos.row,
+            column: pos.col,
+            text: text.line(pos.row).to_string().trim().to_string(),
+        };
+
+        if self.session_jumps.len() == GLOBAL_JUMP_LIST_CAPACITY {
+            self.session_jumps.remove(0);
+        }
+        self.session_jumps.push(jump.clone());
+        self.push_jump(jump);
+    }
+
+    fn push_jump(&mut self, jump: GlobalJump) {
+        if self.jumps.front().map_or(false, |last| {
+            last.path == jump.path && last.line == jump.line
+        }) {
+            self.jumps.pop_front();
+        }
+        self.jumps.push_front(jump);
+        self.jumps.truncate(GLOBAL_JUMP_LIST_CAPACITY);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &GlobalJump> {
+        self.jumps.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::Config;
+    use arc_swap::ArcSwap;
+    use helix_core::Rope;
+    use std::{path::Path, sync::Arc};
+
+    #[test]
+    fn push_replaces_jumps_to_the_same_line() {
+        let mut doc = Document::from(
+            Rope::from("first\n  second\nthird\n"),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+        );
+        let mut jumps = GlobalJumpList::default();
+        jumps.push(&doc, &Selection::point(0));
+        assert_eq!(jumps.iter().count(), 0);
+
+        doc.set_path(Some(Path::new("/file.txt"))).unwrap();
+        jumps.push(&doc, &Selection::point(0));
+        jumps.push(&doc, &Selection::point(8));
+        jumps.push(&doc, &Selection::point(10));
+
+        let jumps: Vec<_> = jumps
+            .iter()
+            .map(|jump| (jump.position(), &*jump.text))
+            .collect();
+        assert_eq!(
+            jumps,
+            [
+                (Position::new(1, 4), "second"),
+                (Position::new(0, 0), "first")
+            ]
+        );
+    }
+    #[test]
+    fn save_keeps_jumps_of_other_sessions() {
+        let mut doc = Document::from(
+            Rope::from("first\nsecond\n"),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+        );
+        doc.set_path(Some(Path::new("/file.txt"))).unwrap();
+        let mut saved = GlobalJumpList::default();
+        saved.push(&doc, &Selection::point(0));
+
+        let mut session = GlobalJumpList::default();
+        session.push(&doc, &Selection::point(6));
+
+        let mut merged = saved;
+        session.merge_into(&mut merged);
+        let lines: Vec<_> = merged.iter().map(|jump| jump.line).collect();
+        assert_eq!(lines, [1, 0]);
+    }
+}
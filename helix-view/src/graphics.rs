@@ -3,6 +3,10 @@
 use std::{
     cmp::{max, min},
     str::FromStr,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
@@ -273,6 +277,93 @@ pub enum Color {
     Indexed(u8),
 }
 
+impl Color {
+    /// The 16 ANSI colors in palette order along with their xterm default values.
+    const ANSI: [(Color, (u8, u8, u8)); 16] = [
+        (Color::Black, (0, 0, 0)),
+        (Color::Red, (205, 0, 0)),
+        (Color::Green, (0, 205, 0)),
+        (Color::Yellow, (205, 205, 0)),
+        (Color::Blue, (0, 0, 238)),
+        (Color::Magenta, (205, 0, 205)),
+        (Color::Cyan, (0, 205, 205)),
+        (Color::LightGray, (229, 229, 229)),
+        (Color::Gray, (127, 127, 127)),
+        (Color::LightRed, (255, 0, 0)),
+        (Color::LightGreen, (0, 255, 0)),
+        (Color::LightYellow, (255, 255, 0)),
+        (Color::LightBlue, (92, 92, 255)),
+        (Color::LightMagenta, (255, 0, 255)),
+        (Color::LightCyan, (0, 255, 255)),
+        (Color::White, (255, 255, 255)),
+    ];
+    /// Channel values of the 6x6x6 color cube of the 256 color palette.
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    /// Returns the RGB value of an entry of the xterm 256 color palette.
+    fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+        match index {
+            0..=15 => Self::ANSI[index as usize].1,
+            16..=231 => {
+                let index = (index - 16) as usize;
+                (
+                    Self::CUBE_LEVELS[index / 36],
+                    Self::CUBE_LEVELS[index / 6 % 6],
+                    Self::CUBE_LEVELS[index % 6],
+                )
+            }
+            232..=255 => {
+                let level = 8 + 10 * (index - 232);
+                (level, level, level)
+            }
+        }
+    }
+
+    fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+        d(r1, r2) + d(g1, g2) + d(b1, b2)
+    }
+
+    /// Maps an RGB color to the closest color of the xterm 256 color palette.
+    /// Other colors are returned unchanged.
+    pub fn to_indexed(self) -> Self {
+        let Color::Rgb(r, g, b) = self else {
+            return self;
+        };
+        // Only the color cube and the grayscale ramp are considered since the 16 ANSI colors
+        // are commonly redefined by terminal color schemes.
+        let nearest_level = |c: u8| {
+            (0..6)
+                .min_by_key(|&i| (Self::CUBE_LEVELS[i] as i32 - c as i32).abs())
+                .unwrap() as u8
+        };
+        let cube = 16 + 36 * nearest_level(r) + 6 * nearest_level(g) + nearest_level(b);
+        let average = (r as u32 + g as u32 + b as u32) / 3;
+        let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+        let index = [cube, gray]
+            .into_iter()
+            .min_by_key(|&i| Self::distance((r, g, b), Self::indexed_to_rgb(i)))
+            .unwrap();
+        Color::Indexed(index)
+    }
+
+    /// Maps an RGB or 256 palette color to the closest of the 16 ANSI colors.
+    /// Other colors are returned unchanged.
+    pub fn to_ansi(self) -> Self {
+        let rgb = match self {
+            Color::Rgb(r, g, b) => (r, g, b),
+            Color::Indexed(index) => Self::indexed_to_rgb(index),
+            _ => return self,
+        };
+        Self::ANSI
+            .iter()
+            .min_by_key(|(_, value)| Self::distance(rgb, *value))
+            .map(|(color, _)| *color)
+            .unwrap()
+    }
+}
+
 #[cfg(feature = "term")]
 impl From<Color> for crossterm::style::Color {
     fn from(color: Color) -> Self {
@@ -612,6 +703,76 @@ pub fn patch(mut self, other: Style) -> Style {
     }
 }
 
+/// A PNG image that can be displayed by terminals implementing an image protocol.
+///
+/// Images are compared by their id, which is unique for every decoded image, so that a backend
+/// only has to transmit the image data once.
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub id: u32,
+    pub data: Arc<[u8]>,
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+}
+
+impl Image {
+    const PNG_SIGNATURE: &'static [u8] = b"\x89PNG\r\n\x1a\n";
+
+    /// Reads the dimensions from the header of a PNG file. Returns `None` if `data` is not a PNG.
+    pub fn from_png(data: Vec<u8>) -> Option<Self> {
+        static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+
+        // The signature is followed by the IHDR chunk: length, type, width, height.
+        if !data.starts_with(Self::PNG_SIGNATURE) || data.get(12..16)? != b"IHDR" {
+            return None;
+        }
+        let width = u32::from_be_bytes(data.get(16..20)?.try_into().ok()?);
+        let height = u32::from_be_bytes(data.get(20..24)?.try_into().ok()?);
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        Some(Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            data: data.into(),
+            width,
+            height,
+        })
+    }
+
+    /// Returns the largest area inside `area` that displays the image without distorting it,
+    /// assuming terminal cells are twice as high as they are wide. Images are never scaled up.
+    pub fn fit(&self, area: Rect) -> Rect {
+        const CELL_WIDTH: u32 = 8;
+        const CELL_HEIGHT: u32 = 16;
+
+        let cols = (self.width + CELL_WIDTH - 1) / CELL_WIDTH;
+        let rows = (self.height + CELL_HEIGHT - 1) / CELL_HEIGHT;
+        let (cols, rows) = (cols.max(1) as u64, rows.max(1) as u64);
+        let (max_cols, max_rows) = (area.width as u64, area.height as u64);
+
+        let (width, height) = if cols <= max_cols && rows <= max_rows {
+            (cols, rows)
+        } else if cols * max_rows > rows * max_cols {
+            (max_cols, (rows * max_cols / cols).max(1))
+        } else {
+            ((cols * max_rows / rows).max(1), max_rows)
+        };
+
+        Rect::new(area.x, area.y, width as u16, height as u16)
+    }
+}
+
+impl PartialEq for Image {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Image {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -699,4 +860,53 @@ fn combined_patch_gives_same_result_as_individual_patch() {
             }
         }
     }
+
+    fn png_header(width: u32, height: u32) -> Vec<u8> {
+        let mut data = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        data.extend_from_slice(&width.to_be_bytes());
+        data.extend_from_slice(&height.to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn image_from_png() {
+        let image = Image::from_png(png_header(640, 480)).unwrap();
+        assert_eq!((image.width, image.height), (640, 480));
+        assert_ne!(image, Image::from_png(png_header(640, 480)).unwrap());
+
+        assert!(Image::from_png(b"GIF89a".to_vec()).is_none());
+        assert!(Image::from_png(png_header(0, 10)).is_none());
+    }
+
+    #[test]
+    fn image_fit() {
+        let area = Rect::new(2, 3, 40, 20);
+        let small = Image::from_png(png_header(80, 80)).unwrap();
+        assert_eq!(small.fit(area), Rect::new(2, 3, 10, 5));
+        let wide = Image::from_png(png_header(1600, 160)).unwrap();
+        assert_eq!(wide.fit(area), Rect::new(2, 3, 40, 2));
+        let tall = Image::from_png(png_header(160, 1600)).unwrap();
+        assert_eq!(tall.fit(area), Rect::new(2, 3, 4, 20));
+    }
+
+    #[test]
+    fn color_to_indexed() {
+        assert_eq!(Color::Rgb(0, 0, 0).to_indexed(), Color::Indexed(16));
+        assert_eq!(Color::Rgb(255, 255, 255).to_indexed(), Color::Indexed(231));
+        assert_eq!(Color::Rgb(255, 0, 0).to_indexed(), Color::Indexed(196));
+        assert_eq!(Color::Rgb(128, 128, 128).to_indexed(), Color::Indexed(244));
+        assert_eq!(
+            Color::Rgb(0x5f, 0x87, 0xaf).to_indexed(),
+            Color::Indexed(67)
+        );
+        assert_eq!(Color::Red.to_indexed(), Color::Red);
+    }
+
+    #[test]
+    fn color_to_ansi() {
+        assert_eq!(Color::Rgb(250, 10, 10).to_ansi(), Color::LightRed);
+        assert_eq!(Color::Rgb(20, 20, 20).to_ansi(), Color::Black);
+        assert_eq!(Color::Indexed(231).to_ansi(), Color::White);
+        assert_eq!(Color::Blue.to_ansi(), Color::Blue);
+    }
 }
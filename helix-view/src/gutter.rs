@@ -1,6 +1,10 @@
-use std::fmt::Write;
+use std::{collections::HashMap, fmt::Write};
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
 
 use crate::{
+    document::TestStatus,
     editor::GutterType,
     graphics::{Style, UnderlineStyle},
     Document, Editor, Theme, View,
@@ -12,12 +16,24 @@ fn count_digits(n: usize) -> usize {
 }
 
 pub type GutterFn<'doc> = Box<dyn FnMut(usize, bool, bool, &mut String) -> Option<Style> + 'doc>;
-pub type Gutter =
-    for<'doc> fn(&'doc Editor, &'doc Document, &View, &Theme, bool, usize) -> GutterFn<'doc>;
+pub type Gutter = for<'doc> fn(&'doc Editor, &'doc Document, &View, &Theme, bool) -> GutterFn<'doc>;
+
+static CUSTOM_GUTTERS: Lazy<RwLock<HashMap<String, Gutter>>> = Lazy::new(Default::default);
+
+/// Registers `gutter` under `name`, which the gutter layout can then use. Gutters have to be
+/// registered before the configuration naming them is loaded.
+pub fn register_gutter(name: impl Into<String>, gutter: Gutter) {
+    CUSTOM_GUTTERS.write().insert(name.into(), gutter);
+}
+
+/// Returns the gutter registered under `name`.
+pub fn custom_gutter(name: &str) -> Option<Gutter> {
+    CUSTOM_GUTTERS.read().get(name).copied()
+}
 
 impl GutterType {
     pub fn style<'doc>(
-        self,
+        &self,
         editor: &'doc Editor,
         doc: &'doc Document,
         view: &View,
@@ -31,21 +47,29 @@ pub fn style<'doc>(
             GutterType::LineNumbers => line_numbers(editor, doc, view, theme, is_focused),
             GutterType::Spacer => padding(editor, doc, view, theme, is_focused),
             GutterType::Diff => diff(editor, doc, view, theme, is_focused),
+            GutterType::Custom(name) => match custom_gutter(name) {
+                Some(gutter) => gutter(editor, doc, view, theme, is_focused),
+                None => padding(editor, doc, view, theme, is_focused),
+            },
         }
     }
 
-    pub fn width(self, view: &View, doc: &Document) -> usize {
+    pub fn width(&self, view: &View, doc: &Document) -> usize {
+        if let Some(&width) = view.gutters.widths.get(self.name()) {
+            return width;
+        }
         match self {
             GutterType::Diagnostics => 1,
             GutterType::LineNumbers => line_numbers_width(view, doc),
             GutterType::Spacer => 1,
             GutterType::Diff => 1,
+            GutterType::Custom(_) => 1,
         }
     }
 }
 
 pub fn diagnostic<'doc>(
-    _editor: &'doc Editor,
+    editor: &'doc Editor,
     doc: &'doc Document,
     _view: &View,
     theme: &Theme,
@@ -56,6 +80,18 @@ pub fn diagnostic<'doc>(
     let info = theme.get("info");
     let hint = theme.get("hint");
     let diagnostics = doc.diagnostics();
+    let symbols = editor.config().diagnostic_symbols.clone();
+    let text = doc.text().slice(..);
+    let tests: Vec<_> = doc
+        .test_results()
+        .iter()
+        .map(|result| {
+            (
+                text.char_to_line(result.pos.min(text.len_chars())),
+                result.status,
+            )
+        })
+        .collect();
 
     Box::new(
         move |line: usize, _selected: bool, first_visual_line: bool, out: &mut String| {
@@ -76,14 +112,25 @@ pub fn diagnostic<'doc>(
                 // This unwrap is safe because the iterator cannot be empty as it contains at least the item found by the binary search.
                 let diagnostic = diagnostics_on_line.max_by_key(|d| d.severity).unwrap();
 
-                write!(out, "●").unwrap();
-                return Some(match diagnostic.severity {
-                    Some(Severity::Error) => error,
-                    Some(Severity::Warning) | None => warning,
-                    Some(Severity::Info) => info,
-                    Some(Severity::Hint) => hint,
+                let severity = diagnostic.severity.unwrap_or(Severity::Warning);
+                write!(out, "{}", symbols.get(severity)).unwrap();
+                return Some(match severity {
+                    Severity::Error => error,
+                    Severity::Warning => warning,
+                    Severity::Info => info,
+                    Severity::Hint => hint,
                 });
             }
+            // Diagnostics take precedence over the results of tests.
+            if let Some((_, status)) = tests.iter().find(|(test_line, _)| *test_line == line) {
+                let (symbol, style) = match status {
+                    TestStatus::Running => ("…", hint),
+                    TestStatus::Passed => ("✔", info),
+                    TestStatus::Failed => ("✘", error),
+                };
+                out.push_str(symbol);
+                return Some(style);
+            }
             None
         },
     )
@@ -149,7 +196,7 @@ pub fn line_numbers<'doc>(
     is_focused: bool,
 ) -> GutterFn<'doc> {
     let text = doc.text().slice(..);
-    let width = line_numbers_width(view, doc);
+    let width = GutterType::LineNumbers.width(view, doc);
 
     let last_line_in_view = view.estimate_last_doc_line(doc);
 
@@ -164,7 +211,7 @@ pub fn line_numbers<'doc>(
         .text()
         .char_to_line(doc.selection(view.id).primary().cursor(text));
 
-    let line_number = editor.config().line_number;
+    let line_number = view.line_number.unwrap_or(editor.config().line_number);
     let mode = editor.mode;
 
     Box::new(
@@ -390,6 +437,7 @@ fn test_configured_gutter_widths() {
         let gutters = GutterConfig {
             layout: vec![GutterType::Diagnostics, GutterType::LineNumbers],
             line_numbers: GutterLineNumbersConfig { min_width: 10 },
+            ..Default::default()
         };
 
         let mut view = View::new(DocumentId::default(), gutters);
@@ -407,11 +455,49 @@ fn test_configured_gutter_widths() {
         assert_eq!(view.gutters.layout[1].width(&view, &doc), 10);
     }
 
+    #[test]
+    fn test_custom_gutter_widths() {
+        register_gutter("bookmarks", padding);
+        assert!("unregistered".parse::<GutterType>().is_err());
+
+        let gutters = GutterConfig {
+            layout: vec![
+                "bookmarks".parse().unwrap(),
+                GutterType::Spacer,
+                GutterType::LineNumbers,
+            ],
+            widths: [("bookmarks".to_string(), 2), ("spacer".to_string(), 3)]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut view = View::new(DocumentId::default(), gutters);
+        view.area = Rect::new(40, 40, 40, 40);
+
+        let rope = Rope::from_str("abc\n\tdef");
+        let doc = Document::from(
+            rope,
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+        );
+
+        assert_eq!(
+            view.gutters.layout[0],
+            GutterType::Custom("bookmarks".to_string())
+        );
+        assert_eq!(view.gutters.layout[0].width(&view, &doc), 2);
+        assert_eq!(view.gutters.layout[1].width(&view, &doc), 3);
+        assert_eq!(view.gutters.layout[2].width(&view, &doc), 3);
+        assert_eq!(view.gutter_offset(&doc), 8);
+    }
+
     #[test]
     fn test_line_numbers_gutter_width_resizes() {
         let gutters = GutterConfig {
             layout: vec![GutterType::Diagnostics, GutterType::LineNumbers],
             line_numbers: GutterLineNumbersConfig { min_width: 1 },
+            ..Default::default()
         };
 
         let mut view = View::new(DocumentId::default(), gutters);
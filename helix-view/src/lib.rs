@@ -1,10 +1,12 @@
 #[macro_use]
 pub mod macros;
 
+pub mod change_list;
 pub mod clipboard;
 pub mod document;
 pub mod editor;
 pub mod env;
+pub mod file_watcher;
 pub mod graphics;
 pub mod gutter;
 pub mod handlers {
@@ -12,9 +14,13 @@ pub mod handlers {
     pub mod lsp;
 }
 pub mod base64;
+pub mod global_jumplist;
 pub mod info;
 pub mod input;
 pub mod keyboard;
+pub mod quickfix;
+pub mod recent_files;
+pub mod state;
 pub mod theme;
 pub mod tree;
 pub mod view;
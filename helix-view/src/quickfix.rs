@@ -0,0 +1,208 @@
+//! The quickfix list: locations in files found by a global search, the diagnostics of the
+//! workspace, the references of a symbol or the errors printed by a language command, which
+//! can be stepped through with `:cnext` and `:cprev` after the picker showing them is closed.
+
+use std::path::{Path, PathBuf};
+
+use helix_core::{pos_at_coords, Position, Rope};
+use helix_lsp::{lsp, util::lsp_pos_to_pos, OffsetEncoding};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuickfixEntry {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    /// The matched line or the diagnostic message.
+    pub text: String,
+    /// The encoding of language server positions, whose `column` counts code units of the
+    /// encoding rather than chars.
+    pub offset_encoding: Option<OffsetEncoding>,
+}
+
+impl QuickfixEntry {
+    /// Parses a `path:line[:column][: message]` location printed by a compiler or linter,
+    /// like `src/main.rs:3:5: error: ...` or rustc's `--> src/main.rs:3:5`. Relative paths
+    /// are resolved against `dir`.
+    pub fn parse(line: &str, dir: &Path) -> Option<Self> {
+        let line = line.trim();
+        let location = line.strip_prefix("-->").map_or(line, str::trim_start);
+        let (path, rest) = location.split_once(':')?;
+        if path.is_empty() || path.contains(char::is_whitespace) {
+            return None;
+        }
+        let (line_num, rest) = rest.split_once(':').unwrap_or((rest, ""));
+        let line_num = line_num.parse::<usize>().ok()?.checked_sub(1)?;
+        let (column, message) = {
+            let (column, message) = rest.split_once(':').unwrap_or((rest, ""));
+            match column.parse::<usize>() {
+                Ok(column) => (column.saturating_sub(1), message),
+                Err(_) => (0, rest),
+            }
+        };
+        let message = message.trim();
+
+        Some(Self {
+            path: dir.join(path),
+            line: line_num,
+            column,
+            text: if message.is_empty() { line } else { message }.to_string(),
+            offset_encoding: None,
+        })
+    }
+
+    /// Returns the char index of the entry in `text`, the contents of its file.
+    pub fn pos(&self, text: &Rope) -> usize {
+        self.offset_encoding
+            .and_then(|offset_encoding| {
+                let position = lsp::Position::new(self.line as u32, self.column as u32);
+                lsp_pos_to_pos(text, position, offset_encoding)
+            })
+            .unwrap_or_else(|| {
+                let position = Position::new(self.line, self.column);
+                pos_at_coords(text.slice(..), position, true)
+            })
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct QuickfixList {
+    /// What filled the list, like `global search`.
+    pub title: String,
+    entries: Vec<QuickfixEntry>,
+    /// Index of the current entry, `None` before stepping to the first one.
+    index: Option<usize>,
+}
+
+impl QuickfixList {
+    pub fn new(title: impl Into<String>, entries: Vec<QuickfixEntry>) -> Self {
+        Self {
+            title: title.into(),
+            entries,
+            index: None,
+        }
+    }
+
+    pub fn entries(&self) -> &[QuickfixEntry] {
+        &self.entries
+    }
+
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// Makes the entry at `index` the current one, returning it.
+    pub fn select(&mut self, index: usize) -> Option<&QuickfixEntry> {
+        let entry = self.entries.get(index)?;
+        self.index = Some(index);
+        Some(entry)
+    }
+
+    /// Steps `count` entries forward, stopping at the last one. The first step goes to the
+    /// first entry.
+    pub fn next(&mut self, count: usize) -> Option<&QuickfixEntry> {
+        let last = self.entries.len().checked_sub(1)?;
+        let index = match self.index {
+            Some(index) => index.saturating_add(count),
+            None => count.saturating_sub(1),
+        };
+        self.select(index.min(last))
+    }
+
+    /// Steps `count` entries backward, stopping at the first one.
+    pub fn prev(&mut self, count: usize) -> Option<&QuickfixEntry> {
+        let index = self.index.unwrap_or_default().saturating_sub(count);
+        self.select(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(line: usize) -> QuickfixEntry {
+        QuickfixEntry {
+            path: PathBuf::from("/file.txt"),
+            line,
+            column: 0,
+            text: String::new(),
+            offset_encoding: None,
+        }
+    }
+
+    #[test]
+    fn stepping() {
+        let mut list = QuickfixList::new("test", vec![entry(1), entry(2), entry(3)]);
+        assert_eq!(list.next(1).map(|entry| entry.line), Some(1));
+        assert_eq!(list.next(1).map(|entry| entry.line), Some(2));
+        assert_eq!(list.next(5).map(|entry| entry.line), Some(3));
+        assert_eq!(list.prev(1).map(|entry| entry.line), Some(2));
+        assert_eq!(list.prev(5).map(|entry| entry.line), Some(1));
+        assert_eq!(list.index(), Some(0));
+
+        let mut empty = QuickfixList::default();
+        assert_eq!(empty.next(1), None);
+        assert_eq!(empty.prev(1), None);
+    }
+
+    #[test]
+    fn parse() {
+        let dir = Path::new("/project");
+        let parse = |line| {
+            QuickfixEntry::parse(line, dir).map(|entry| {
+                let QuickfixEntry {
+                    path,
+                    line,
+                    column,
+                    text,
+                    ..
+                } = entry;
+                (path, line, column, text)
+            })
+        };
+        assert_eq!(
+            parse("src/main.rs:3:5: error: expected `;`"),
+            Some((
+                PathBuf::from("/project/src/main.rs"),
+                2,
+                4,
+                "error: expected `;`".to_string()
+            ))
+        );
+        assert_eq!(
+            parse("  --> /tmp/lib.rs:10:1"),
+            Some((
+                PathBuf::from("/tmp/lib.rs"),
+                9,
+                0,
+                "--> /tmp/lib.rs:10:1".to_string()
+            ))
+        );
+        assert_eq!(
+            parse("main.go:7: undefined: x"),
+            Some((
+                PathBuf::from("/project/main.go"),
+                6,
+                0,
+                "undefined: x".to_string()
+            ))
+        );
+        assert_eq!(parse("error[E0425]: cannot find value `x`"), None);
+        assert_eq!(parse("test result: ok. 1 passed"), None);
+        assert_eq!(parse("see https://example.com:443/docs"), None);
+    }
+
+    #[test]
+    fn pos() {
+        let text = Rope::from("a\n😀b\n");
+        let mut entry = QuickfixEntry {
+            line: 1,
+            column: 1,
+            ..entry(1)
+        };
+        assert_eq!(entry.pos(&text), 3);
+        // The emoji is two UTF-16 code units.
+        entry.column = 2;
+        entry.offset_encoding = Some(OffsetEncoding::Utf16);
+        assert_eq!(entry.pos(&text), 3);
+    }
+}
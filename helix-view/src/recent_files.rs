@@ -0,0 +1,136 @@
+//! Files recently edited in the editor together with the last position of the cursor in them.
+//! The list is persisted in the state directory so files can be reopened at the same position
+//! in later sessions.
+
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+};
+
+use crate::state;
+use helix_core::Position;
+use serde::{Deserialize, Serialize};
+
+/// The maximum number of files remembered.
+const MAX_RECENT_FILES: usize = 100;
+
+const STATE_FILE: &str = "recent_files.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl RecentFile {
+    pub fn position(&self) -> Position {
+        Position::new(self.line, self.column)
+    }
+}
+
+/// Recently edited files, the most recent first.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RecentFiles {
+    files: VecDeque<RecentFile>,
+    /// The files recorded during this session, oldest first, replayed onto the files saved by
+    /// other sessions when saving.
+    #[serde(skip)]
+    recorded: Vec<RecentFile>,
+}
+
+impl RecentFiles {
+    /// Loads the recent files of previous sessions from the state directory.
+    pub fn load() -> Self {
+        state::load(STATE_FILE)
+    }
+
+    /// Saves the recent files to the state directory, on top of the files saved by other
+    /// sessions since this one started.
+    pub fn save(&self) {
+        state::update(STATE_FILE, |saved: &mut Self| self.merge_into(saved))
+    }
+
+    /// Moves the files recorded during this session to the front of `saved`.
+    fn merge_into(&self, saved: &mut Self) {
+        for file in &self.recorded {
+            saved.push(file.clone());
+        }
+    }
+
+    /// Moves `path` to the front of the list, remembering `pos` as its last cursor position.
+    pub fn record(&mut self, path: &Path, pos: Position) {
+        let file = RecentFile {
+            path: path.to_path_buf(),
+            line: pos.row,
+            column: pos.col,
+        };
+        self.recorded.retain(|recorded| recorded.path != path);
+        self.recorded.push(file.clone());
+        self.push(file);
+    }
+
+    fn push(&mut self, file: RecentFile) {
+        self.files.retain(|recent| recent.path != file.path);
+        self.files.push_front(file);
+        self.files.truncate(MAX_RECENT_FILES);
+    }
+
+    /// The last cursor position in `path`, if it was edited recently.
+    pub fn position(&self, path: &Path) -> Option<Position> {
+        self.files
+            .iter()
+            .find(|file| file.path == path)
+            .map(RecentFile::position)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RecentFile> {
+        self.files.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_moves_files_to_the_front() {
+        let mut recent = RecentFiles::default();
+        recent.record(Path::new("/a"), Position::new(1, 2));
+        recent.record(Path::new("/b"), Position::new(3, 4));
+        recent.record(Path::new("/a"), Position::new(5, 6));
+
+        let paths: Vec<_> = recent.iter().map(|file| file.path.as_path()).collect();
+        assert_eq!(paths, [Path::new("/a"), Path::new("/b")]);
+        assert_eq!(recent.position(Path::new("/a")), Some(Position::new(5, 6)));
+        assert_eq!(recent.position(Path::new("/c")), None);
+    }
+
+    #[test]
+    fn record_forgets_the_oldest_files() {
+        let mut recent = RecentFiles::default();
+        for i in 0..=MAX_RECENT_FILES {
+            recent.record(&PathBuf::from(format!("/{}", i)), Position::default());
+        }
+        assert_eq!(recent.iter().count(), MAX_RECENT_FILES);
+        assert_eq!(recent.position(Path::new("/0")), None);
+    }
+
+    #[test]
+    fn save_keeps_files_of_other_sessions() {
+        let mut saved = RecentFiles::default();
+        saved.record(Path::new("/a"), Position::new(1, 0));
+        saved.record(Path::new("/b"), Position::new(2, 0));
+
+        let mut session = RecentFiles::default();
+        session.record(Path::new("/c"), Position::new(3, 0));
+        session.record(Path::new("/a"), Position::new(4, 0));
+
+        let mut merged = saved;
+        session.merge_into(&mut merged);
+        let paths: Vec<_> = merged.iter().map(|file| file.path.as_path()).collect();
+        assert_eq!(paths, [Path::new("/a"), Path::new("/c"), Path::new("/b")]);
+        assert_eq!(merged.position(Path::new("/a")), Some(Position::new(4, 0)));
+    }
+}
@@ -0,0 +1,66 @@
+//! Editor state persisted across sessions as JSON files in the state directory.
+//!
+//! Several sessions may share the state directory, so files are replaced atomically and
+//! sessions [`update`] the latest saved state instead of overwriting it with their own.
+
+use std::path::{Path, PathBuf};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+fn state_file(name: &str) -> PathBuf {
+    helix_loader::state_dir().join(name)
+}
+
+/// Loads the state saved in the file `name`, or the default state if there is none or it's
+/// invalid.
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    load_path(&state_file(name))
+}
+
+/// Saves `state` to the file `name`. Failures are logged.
+pub fn save<T: Serialize>(name: &str, state: &T) {
+    save_path(&state_file(name), state)
+}
+
+/// Like [`load`], for state saved at `path` rather than directly in the state directory.
+pub fn load_path<T: DeserializeOwned + Default>(path: &Path) -> T {
+    let contents = match std::fs::read(path) {
+        Ok(contents) => contents,
+        Err(_) => return T::default(),
+    };
+    serde_json::from_slice(&contents).unwrap_or_else(|err| {
+        log::warn!("Discarding invalid state {}: {}", path.display(), err);
+        T::default()
+    })
+}
+
+/// Like [`save`], for state saved at `path` rather than directly in the state directory.
+pub fn save_path<T: Serialize>(path: &Path, state: &T) {
+    if let Err(err) = write_atomic(path, state) {
+        log::error!("Failed to save state {}: {}", path.display(), err);
+    }
+}
+
+/// Applies `f` to the state currently saved in the file `name` and saves the result, so that
+/// the changes saved by other sessions in the meantime are kept.
+pub fn update<T: Serialize + DeserializeOwned + Default>(name: &str, f: impl FnOnce(&mut T)) {
+    let mut state = load(name);
+    f(&mut state);
+    save(name, &state);
+}
+
+/// Writes `state` to a temporary file next to `path` and renames it over `path`, so that
+/// other sessions never read a partially written file.
+fn write_atomic<T: Serialize>(path: &Path, state: &T) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = PathBuf::from(tmp_path);
+    std::fs::write(&tmp_path, serde_json::to_vec(state)?)?;
+    std::fs::rename(&tmp_path, path).map_err(|err| {
+        let _ = std::fs::remove_file(&tmp_path);
+        err
+    })
+}
@@ -108,6 +108,20 @@ fn load_theme(&self, name: &str, visited_paths: &mut HashSet<PathBuf>) -> Result
         Ok(theme_toml)
     }
 
+    /// Returns the sorted names of all themes that can be loaded.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self
+            .theme_dirs
+            .iter()
+            .flat_map(|dir| Self::read_names(dir))
+            .collect();
+        names.push("default".into());
+        names.push("base16_default".into());
+        names.sort();
+        names.dedup();
+        names
+    }
+
     pub fn read_names(path: &Path) -> Vec<String> {
         std::fs::read_dir(path)
             .map(|entries| {
@@ -207,6 +221,16 @@ pub fn base16_default(&self) -> Theme {
     }
 }
 
+/// Colors a terminal is able to display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSupport {
+    TrueColor,
+    /// The xterm 256 color palette
+    Indexed256,
+    /// The 16 ANSI colors
+    Ansi16,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Theme {
     name: String,
@@ -320,6 +344,24 @@ pub fn try_get_exact(&self, scope: &str) -> Option<Style> {
         self.styles.get(scope).copied()
     }
 
+    /// Overrides the style of `scope` with a style parsed from a theme TOML value, e.g. a color
+    /// name or a table like `{ fg = "red", modifiers = ["bold"] }`. Colors may not refer to the
+    /// theme's palette.
+    pub fn set_style(&mut self, scope: &str, value: Value) -> Result<(), String> {
+        let mut style = Style::default();
+        ThemePalette::default().parse_style(&mut style, value)?;
+
+        self.styles.insert(scope.to_string(), style);
+        match self.find_scope_index_exact(scope) {
+            Some(index) => self.highlights[index] = style,
+            None => {
+                self.scopes.push(scope.to_string());
+                self.highlights.push(style);
+            }
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn scopes(&self) -> &[String] {
         &self.scopes
@@ -342,6 +384,21 @@ pub fn find_scope_index(&self, mut scope: &str) -> Option<usize> {
         }
     }
 
+    /// Maps the colors of the theme to ones the terminal is able to display.
+    pub fn degrade(mut self, color_support: ColorSupport) -> Self {
+        let map: fn(Color) -> Color = match color_support {
+            ColorSupport::TrueColor => return self,
+            ColorSupport::Indexed256 => Color::to_indexed,
+            ColorSupport::Ansi16 => Color::to_ansi,
+        };
+        for style in self.styles.values_mut().chain(self.highlights.iter_mut()) {
+            style.fg = style.fg.map(map);
+            style.bg = style.bg.map(map);
+            style.underline_color = style.underline_color.map(map);
+        }
+        self
+    }
+
     pub fn is_16_color(&self) -> bool {
         self.styles.iter().all(|(_, style)| {
             [style.fg, style.bg]
@@ -553,4 +610,30 @@ fn test_parse_style_table() {
                 .add_modifier(Modifier::BOLD)
         );
     }
+
+    #[test]
+    fn test_set_style() {
+        let mut theme = Theme::from(Value::Table(toml::toml! {
+            "keyword" = "#ffffff"
+        }));
+
+        theme
+            .set_style("keyword", Value::String("red".into()))
+            .unwrap();
+        assert_eq!(theme.get("keyword"), Style::default().fg(Color::Red));
+        let index = theme.find_scope_index_exact("keyword").unwrap();
+        assert_eq!(theme.highlight(index), Style::default().fg(Color::Red));
+
+        let table = toml::toml! { modifiers = ["bold"] };
+        theme.set_style("function", Value::Table(table)).unwrap();
+        let index = theme.find_scope_index_exact("function").unwrap();
+        assert_eq!(
+            theme.highlight(index),
+            Style::default().add_modifier(Modifier::BOLD)
+        );
+
+        assert!(theme
+            .set_style("keyword", Value::String("not-a-color".into()))
+            .is_err());
+    }
 }
@@ -1,7 +1,7 @@
 use crate::{
     align_view,
     document::DocumentInlayHints,
-    editor::{GutterConfig, GutterType},
+    editor::{GutterConfig, GutterType, LineNumber},
     graphics::Rect,
     Align, Document, DocumentId, Theme, ViewId,
 };
@@ -23,6 +23,7 @@
 };
 
 const JUMP_LIST_CAPACITY: usize = 30;
+const SELECTION_HISTORY_CAPACITY: usize = 100;
 
 type Jump = (DocumentId, Selection);
 
@@ -101,6 +102,65 @@ fn apply(&mut self, transaction: &Transaction, doc: &Document) {
     }
 }
 
+/// Previous selections of the documents shown in a view, so selection changes can be undone
+/// and redone independently of the changes to the text.
+#[derive(Debug, Clone, Default)]
+pub struct SelectionHistory {
+    undo: VecDeque<Jump>,
+    redo: Vec<Jump>,
+}
+
+impl SelectionHistory {
+    /// Remembers `selection` as the selection of `doc_id` before it was changed.
+    pub fn push(&mut self, doc_id: DocumentId, selection: Selection) {
+        let entry = (doc_id, selection);
+        if self.undo.back() != Some(&entry) {
+            while self.undo.len() >= SELECTION_HISTORY_CAPACITY {
+                self.undo.pop_front();
+            }
+            self.undo.push_back(entry);
+        }
+        self.redo.clear();
+    }
+
+    /// Returns the selection of `doc_id` before its latest change and remembers `current` to
+    /// redo the change.
+    pub fn undo(&mut self, doc_id: DocumentId, current: Selection) -> Option<Selection> {
+        let index = self.undo.iter().rposition(|(id, _)| *id == doc_id)?;
+        let (_, selection) = self.undo.remove(index)?;
+        self.redo.push((doc_id, current));
+        Some(selection)
+    }
+
+    /// Returns the selection of `doc_id` of the latest undone change and remembers `current` to
+    /// undo it again.
+    pub fn redo(&mut self, doc_id: DocumentId, current: Selection) -> Option<Selection> {
+        let index = self.redo.iter().rposition(|(id, _)| *id == doc_id)?;
+        let (_, selection) = self.redo.remove(index);
+        self.undo.push_back((doc_id, current));
+        Some(selection)
+    }
+
+    fn remove(&mut self, doc_id: &DocumentId) {
+        self.undo.retain(|(id, _)| id != doc_id);
+        self.redo.retain(|(id, _)| id != doc_id);
+    }
+
+    /// Maps the selections of `doc` through the changes of a [`Transaction`].
+    fn apply(&mut self, transaction: &Transaction, doc: &Document) {
+        let text = doc.text().slice(..);
+
+        for (doc_id, selection) in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            if doc.id() == *doc_id {
+                *selection = selection
+                    .clone()
+                    .map(transaction.changes())
+                    .ensure_invariants(text);
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Copy, Default)]
 pub struct ViewPosition {
     pub anchor: usize,
@@ -115,6 +175,8 @@ pub struct View {
     pub area: Rect,
     pub doc: DocumentId,
     pub jumps: JumpList,
+    /// Previous selections of the documents, to undo and redo selection changes.
+    pub selection_history: SelectionHistory,
     // documents accessed from this view from the oldest one to last viewed one
     pub docs_access_history: Vec<DocumentId>,
     /// the last modified files before the current one
@@ -126,6 +188,10 @@ pub struct View {
     pub object_selections: Vec<Selection>,
     /// all gutter-related configuration settings, used primarily for gutter rendering
     pub gutters: GutterConfig,
+    /// Overrides the `line-number` setting of the editor for this view
+    pub line_number: Option<LineNumber>,
+    /// Overrides the gutter layout of the editor for this view
+    pub gutter_layout: Option<Vec<GutterType>>,
     /// A mapping between documents and the last history revision the view was updated at.
     /// Changes between documents and views are synced lazily when switching windows. This
     /// mapping keeps track of the last applied history revision so that only new changes
@@ -155,10 +221,13 @@ pub fn new(doc: DocumentId, gutters: GutterConfig) -> Self {
             },
             area: Rect::default(), // will get calculated upon inserting into tree
             jumps: JumpList::new((doc, Selection::point(0))), // TODO: use actual sel
+            selection_history: SelectionHistory::default(),
             docs_access_history: Vec::new(),
             last_modified_docs: [None, None],
             object_selections: Vec::new(),
             gutters,
+            line_number: None,
+            gutter_layout: None,
             doc_revisions: HashMap::new(),
         }
     }
@@ -183,12 +252,11 @@ pub fn inner_width(&self, doc: &Document) -> u16 {
     }
 
     pub fn gutters(&self) -> &[GutterType] {
-        &self.gutters.layout
+        self.gutter_layout.as_ref().unwrap_or(&self.gutters.layout)
     }
 
     pub fn gutter_offset(&self, doc: &Document) -> u16 {
-        self.gutters
-            .layout
+        self.gutters()
             .iter()
             .map(|gutter| gutter.width(self, doc) as u16)
             .sum()
@@ -564,6 +632,7 @@ pub fn gutter_coords_at_screen_coords(&self, row: u16, column: u16) -> Option<Po
 
     pub fn remove_document(&mut self, doc_id: &DocumentId) {
         self.jumps.remove(doc_id);
+        self.selection_history.remove(doc_id);
         self.docs_access_history.retain(|doc| doc != doc_id);
     }
 
@@ -591,6 +660,7 @@ pub fn remove_document(&mut self, doc_id: &DocumentId) {
     /// Applies a [`Transaction`] to the view.
     pub fn apply(&mut self, transaction: &Transaction, doc: &mut Document) {
         self.jumps.apply(transaction, doc);
+        self.selection_history.apply(transaction, doc);
         self.doc_revisions
             .insert(doc.id(), doc.get_current_revision());
     }
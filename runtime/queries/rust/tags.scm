@@ -0,0 +1,32 @@
+(struct_item
+  name: (type_identifier) @name) @definition.class
+
+(enum_item
+  name: (type_identifier) @name) @definition.class
+
+(union_item
+  name: (type_identifier) @name) @definition.class
+
+(type_item
+  name: (type_identifier) @name) @definition.class
+
+(trait_item
+  name: (type_identifier) @name) @definition.interface
+
+(function_item
+  name: (identifier) @name) @definition.function
+
+(function_signature_item
+  name: (identifier) @name) @definition.function
+
+(mod_item
+  name: (identifier) @name) @definition.module
+
+(macro_definition
+  name: (identifier) @name) @definition.macro
+
+(const_item
+  name: (identifier) @name) @definition.constant
+
+(static_item
+  name: (identifier) @name) @definition.constant